@@ -92,7 +92,7 @@ pub struct BarPlugin;
 impl Plugin for BarPlugin {
     fn build(&self, app: &mut App) {
         let url = "sqlite:db/sqlite.db";
-        app.add_plugins(SqlxPlugin::<Sqlite, Bar>::from_url(&url));
+        app.add_plugins(SqlxPlugin::<Sqlite, Bar>::from_url(url));
         app.add_systems(Update, Self::send_bar_events);
     }
 }
@@ -155,16 +155,10 @@ fn watch_status(
     mut bar_statuses: EventReader<SqlxEventStatus<Sqlite, Bar>>,
 ) {
     for foo_status in foo_statuses.read() {
-        dbg!({
-            "Foo status";
-            foo_status
-        });
+        dbg!("Foo status", foo_status);
     }
     for bar_status in bar_statuses.read() {
-        dbg!({
-            "Bar status";
-            bar_status
-        });
+        dbg!("Bar status", bar_status);
     }
 }
 
@@ -173,16 +167,10 @@ fn detect_changed(
     bar_query: Query<(Entity, &Bar), Changed<Bar>>,
 ) {
     for foo in &foo_query {
-        dbg!({
-            "Foo changed";
-            &foo
-        });
+        dbg!("Foo changed", &foo);
     }
     for bar in &bar_query {
-        dbg!({
-            "Bar changed";
-            &bar
-        });
+        dbg!("Bar changed", &bar);
     }
 }
 
@@ -191,15 +179,9 @@ fn detect_removals(
     mut bar_removals: RemovedComponents<Bar>,
 ) {
     for entity in foo_removals.read() {
-        dbg!({
-            "foo removed";
-            entity
-        });
+        dbg!("foo removed", entity);
     }
     for entity in bar_removals.read() {
-        dbg!({
-            "bar removed";
-            entity
-        });
+        dbg!("bar removed", entity);
     }
 }
@@ -39,13 +39,12 @@ fn reset(db: Res<SqlxDatabase<Sqlite>>) {
 fn insert(mut events: EventWriter<SqlxEvent<Sqlite, Foo>>) {
     let sql = "INSERT INTO foos(text) VALUES ('insert') RETURNING *";
     events.send(SqlxEvent::<Sqlite, Foo>::query(sql));
+    let sql = "SELECT COUNT(*) FROM foos";
+    events.send(SqlxEvent::<Sqlite, Foo>::aggregate(sql));
 }
 
 fn watch_status(mut statuses: EventReader<SqlxEventStatus<Sqlite, Foo>>) {
     for status in statuses.read() {
-        dbg!({
-            "status";
-            status
-        });
+        dbg!("status", status);
     }
 }
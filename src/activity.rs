@@ -0,0 +1,358 @@
+use crate::*;
+use bevy::prelude::*;
+use bevy::utils::{HashMap, Instant};
+use sqlx::{Database, Executor, IntoArguments};
+use std::fmt;
+use std::marker::PhantomData;
+
+/// A reflectable summary of a [`SqlxEvent`], for inspectors and editors
+#[derive(Reflect, Clone, Debug, PartialEq)]
+pub struct SqlxEventSummary {
+    /// The event's id, unless it wasn't handled yet
+    pub id: Option<SqlxEventId>,
+    pub label: Option<String>,
+    /// The [`Database::NAME`] of the event's database
+    pub database: String,
+    /// The type name of the event's component
+    pub component: String,
+    /// What the event does, like `query` or `insert`
+    pub kind: String,
+    /// The event's SQL, unless it calls a function
+    pub sql: Option<String>,
+    pub will_sync: bool,
+    pub target: Option<Entity>,
+}
+
+/// A reflectable summary of a [`SqlxEventStatus`], for inspectors and
+/// editors
+#[derive(Reflect, Clone, Debug, PartialEq)]
+pub struct SqlxStatusSummary {
+    pub id: SqlxEventId,
+    /// The name of the status, like `start` or `error`
+    pub kind: String,
+    /// The status' data, like the error message or the number of rows
+    pub detail: Option<String>,
+}
+
+/// A reflectable [`Resource`] of the events which haven't finished yet, of
+/// every [`SqlxPlugin`]
+///
+/// The events of each component type are refreshed every frame, after its
+/// [`SqlxTasks::handle_tasks`].
+#[derive(Resource, Reflect, Default, Debug)]
+#[reflect(Resource)]
+pub struct SqlxActivity {
+    pub events: Vec<SqlxEventSummary>,
+}
+
+impl SqlxActivity {
+    /// A [`System`] refreshing the summaries of `C`'s pending events
+    pub fn handle_activity<DB, C>(
+        mut activity: ResMut<Self>,
+        tasks: Res<SqlxTasks<DB, C>>,
+    ) where
+        DB: Database + Sync,
+        C: SqlxComponent<DB::Row>,
+    {
+        let component = std::any::type_name::<C>();
+        activity.events.retain(|event| {
+            event.database != DB::NAME || event.component != component
+        });
+        let pending = tasks
+            .components
+            .iter()
+            .map(|(event, _, _)| event)
+            .chain(&tasks.throttled)
+            .chain(&tasks.lost);
+        activity.events.extend(pending.map(SqlxEvent::summary));
+    }
+}
+
+/// An event in the [`SqlxEventRegistry`]
+#[derive(Clone, Debug)]
+pub struct SqlxEventEntry {
+    /// What the event is, including its label, SQL and sync flag
+    pub summary: SqlxEventSummary,
+    /// When the event was first handled, in the frame it was sent
+    pub sent: Instant,
+}
+
+/// A [`Resource`] of `C`'s events which haven't finished yet, by id
+///
+/// Unlike [`SqlxActivity`], it's kept for a single component type, and
+/// remembers when each event was sent, so a [`SqlxEventStatus`] can be
+/// resolved into something readable:
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::Sqlite;
+/// # use bevy_sqlx::{SqlxEventRegistry, SqlxEventStatus, SqlxDummy};
+/// fn log(
+///     mut statuses: EventReader<SqlxEventStatus<Sqlite, SqlxDummy>>,
+///     registry: Res<SqlxEventRegistry<Sqlite, SqlxDummy>>,
+/// ) {
+///     for status in statuses.read() {
+///         if let Some(entry) = registry.get(status.id()) {
+///             let elapsed = entry.sent.elapsed();
+///             info!("{:?} after {elapsed:?}", entry.summary.label);
+///         }
+///     }
+/// }
+/// ```
+#[derive(Resource)]
+pub struct SqlxEventRegistry<DB: Database, C: SqlxComponent<DB::Row>> {
+    events: HashMap<SqlxEventId, SqlxEventEntry>,
+    _db: PhantomData<fn() -> (DB, C)>,
+}
+
+impl<DB: Database, C: SqlxComponent<DB::Row>> Default
+    for SqlxEventRegistry<DB, C>
+{
+    fn default() -> Self {
+        SqlxEventRegistry { events: HashMap::new(), _db: PhantomData }
+    }
+}
+
+impl<DB: Database, C: SqlxComponent<DB::Row>> SqlxEventRegistry<DB, C> {
+    /// The entry of the event `id`, while it hasn't finished
+    pub fn get(&self, id: SqlxEventId) -> Option<&SqlxEventEntry> {
+        self.events.get(&id)
+    }
+
+    /// The entries of every event which hasn't finished, in no order
+    pub fn iter(&self) -> impl Iterator<Item = &SqlxEventEntry> {
+        self.events.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Add `events`, sent now
+    pub(crate) fn insert(&mut self, events: &[SqlxEvent<DB, C>]) {
+        let sent = Instant::now();
+        for event in events {
+            let summary = event.summary();
+            self.events
+                .insert(event.assigned_id(), SqlxEventEntry { summary, sent });
+        }
+    }
+}
+
+impl<DB: Database + Sync, C: SqlxComponent<DB::Row>> SqlxEventRegistry<DB, C>
+where
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    /// A [`System`] removing the events which are no longer pending in the
+    /// [`SqlxTasks`]
+    ///
+    /// Events are added by [`SqlxEvent::handle_events`].
+    pub fn handle_registry(
+        mut registry: ResMut<Self>,
+        tasks: Res<SqlxTasks<DB, C>>,
+    ) {
+        registry.events.retain(|id, _| tasks.is_pending(*id));
+    }
+}
+
+impl<DB: Database, C: SqlxComponent<DB::Row>> SqlxEvent<DB, C> {
+    /// Summarize this event, see [`SqlxEventSummary`]
+    pub fn summary(&self) -> SqlxEventSummary {
+        let (kind, sql) = self.describe();
+        SqlxEventSummary {
+            id: self.id(),
+            label: self.label().map(String::from),
+            database: DB::NAME.into(),
+            component: std::any::type_name::<C>().into(),
+            kind,
+            sql,
+            will_sync: self.will_sync(),
+            target: self.target(),
+        }
+    }
+
+    /// What this event does, and its SQL unless it calls a function
+    pub(crate) fn describe(&self) -> (String, Option<String>) {
+        match &self.op {
+            SqlxEventOp::Query(sql, ..) => {
+                ("query".into(), Some(sql.to_string()))
+            }
+            SqlxEventOp::Call(_) => ("call".into(), None),
+            SqlxEventOp::Statement(stmt, _) => (
+                format!("{:?}", stmt.kind()).to_lowercase(),
+                Some(stmt.sql::<DB>()),
+            ),
+            SqlxEventOp::Aggregate(sql) => {
+                ("aggregate".into(), Some(sql.to_string()))
+            }
+            SqlxEventOp::Export(sql, ..) => {
+                ("export".into(), Some(sql.to_string()))
+            }
+            SqlxEventOp::Import(..) => ("import".into(), None),
+            SqlxEventOp::Backup(_) => ("backup".into(), None),
+            SqlxEventOp::Ddl(sql) => ("ddl".into(), Some(sql.to_string())),
+        }
+    }
+}
+
+impl<DB: Database, C: SqlxComponent<DB::Row>> SqlxEventStatus<DB, C> {
+    /// Summarize this status, see [`SqlxStatusSummary`]
+    pub fn summary(&self) -> SqlxStatusSummary
+    where
+        C::Column: fmt::Debug,
+    {
+        let (kind, detail) = match self {
+            SqlxEventStatus::Throttled(_) => ("throttled", None),
+            SqlxEventStatus::Start(_) => ("start", None),
+            SqlxEventStatus::Queued(_) => ("queued", None),
+            SqlxEventStatus::Executing(_) => ("executing", None),
+            SqlxEventStatus::Decoded(_, rows) => {
+                ("decoded", Some(rows.to_string()))
+            }
+            SqlxEventStatus::Return(_, components) => {
+                ("return", Some(components.len().to_string()))
+            }
+            SqlxEventStatus::Optional(_, component) => {
+                ("optional", Some(usize::from(component.is_some()).to_string()))
+            }
+            SqlxEventStatus::Scalar(_, value) => {
+                ("scalar", Some(format!("{value:?}")))
+            }
+            SqlxEventStatus::Progress(_, rows) => {
+                ("progress", Some(rows.to_string()))
+            }
+            SqlxEventStatus::Done(_, rows) => ("done", Some(rows.to_string())),
+            SqlxEventStatus::Inserted(_, key) => {
+                ("inserted", Some(key.to_string()))
+            }
+            SqlxEventStatus::Spawn(_, pk, _) => {
+                ("spawn", Some(format!("{pk:?}")))
+            }
+            SqlxEventStatus::Update(_, pk, _) => {
+                ("update", Some(format!("{pk:?}")))
+            }
+            SqlxEventStatus::Constraint(_, violation) => (
+                "constraint",
+                violation
+                    .constraint
+                    .clone()
+                    .or_else(|| Some(violation.columns.join(", "))),
+            ),
+            SqlxEventStatus::Error(_, err, _) => {
+                ("error", Some(err.to_string()))
+            }
+            SqlxEventStatus::Complete(_, summary) => {
+                ("complete", Some(format!("{summary:?}")))
+            }
+            SqlxEventStatus::SchemaChanged(_) => ("schema_changed", None),
+        };
+        SqlxStatusSummary { id: self.id(), kind: kind.into(), detail }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{AsyncComputeTaskPool, TaskPool};
+    use sqlx::{FromRow, Sqlite};
+    use std::any::TypeId;
+
+    #[derive(Component, FromRow, Debug)]
+    struct Foo {
+        id: u32,
+    }
+
+    impl PrimaryKey for Foo {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    #[test]
+    fn test_activity() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let mut app = App::new();
+        app.add_plugins(
+            SqlxPlugin::<Sqlite, Foo>::from_url(url).with_rate_limit(0.01, 1),
+        );
+        let registry = app.world().resource::<AppTypeRegistry>().read();
+        assert!(registry.get(TypeId::of::<SqlxActivity>()).is_some());
+        assert!(registry.get(TypeId::of::<SqlxStatusSummary>()).is_some());
+        drop(registry);
+
+        let sql = "SELECT * FROM foos";
+        let first = SqlxEvent::<Sqlite, Foo>::query(sql);
+        let second = SqlxEvent::<Sqlite, Foo>::query(sql).with_label("second");
+        let id = app.world().resource::<SqlxEventIds>().next();
+        let second = second.with_id(id);
+        app.world_mut().send_event(first);
+        app.world_mut().send_event(second);
+        app.update();
+        app.update();
+
+        let activity = app.world().resource::<SqlxActivity>();
+        let summary =
+            activity.events.iter().find(|event| event.id == Some(id)).unwrap();
+        assert_eq!(Some("second".into()), summary.label);
+        assert_eq!("SQLite", summary.database);
+        assert_eq!("query", summary.kind);
+        assert_eq!(Some(sql.into()), summary.sql);
+
+        let status = SqlxEventStatus::<Sqlite, Foo>::Done(id, 3).summary();
+        assert_eq!(
+            SqlxStatusSummary {
+                id,
+                kind: "done".into(),
+                detail: Some("3".into())
+            },
+            status
+        );
+    }
+
+    #[test]
+    fn test_event_registry() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let mut app = App::new();
+        app.add_plugins(
+            SqlxPlugin::<Sqlite, Foo>::from_url(url).with_rate_limit(0.01, 1),
+        );
+
+        let sql = "SELECT * FROM foos";
+        let first = SqlxEvent::<Sqlite, Foo>::query(sql);
+        let second = SqlxEvent::<Sqlite, Foo>::query(sql).with_label("second");
+        let ids = app.world().resource::<SqlxEventIds>();
+        let (first_id, second_id) = (ids.next(), ids.next());
+        let (first, second) =
+            (first.with_id(first_id), second.with_id(second_id));
+        app.world_mut().send_event(first);
+        app.world_mut().send_event(second);
+
+        let mut tries = 0;
+        app.update();
+        while tries < 1000 {
+            let registry =
+                app.world().resource::<SqlxEventRegistry<Sqlite, Foo>>();
+            if registry.get(first_id).is_none() {
+                break;
+            }
+            app.update();
+            tries += 1;
+        }
+
+        let registry = app.world().resource::<SqlxEventRegistry<Sqlite, Foo>>();
+        assert_eq!(1, registry.len());
+        let entry = registry.get(second_id).unwrap();
+        assert_eq!(Some("second".into()), entry.summary.label);
+        assert_eq!(Some(sql.into()), entry.summary.sql);
+        assert!(!entry.summary.will_sync);
+    }
+}
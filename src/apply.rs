@@ -0,0 +1,212 @@
+use crate::*;
+use bevy::prelude::*;
+use std::fmt;
+
+/// A strategy deciding what [`SqlxTasks::handle_tasks`] does with each
+/// synced component
+///
+/// The default, [`SqlxDefaultApply`], inserts the component onto the entity
+/// found by the plugin's [`SqlxMatch`], the spawned one with the same primary
+/// key by default, or spawns a new entity with it.
+/// Implement this to match components differently, merge them into the
+/// existing ones, or ignore some rows, and build the plugin
+/// [`SqlxPlugin::with_apply`]. [`SqlxGroupApply`] merges rows into a
+/// [`SqlxFromRows`] component of their group instead.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::FromRow;
+/// # use bevy_sqlx::*;
+/// #[derive(Component, FromRow)]
+/// struct Foo {
+///     id: u32,
+///     flag: bool,
+/// }
+/// # impl PrimaryKey for Foo {
+/// #     type Column = u32;
+/// #     fn primary_key(&self) -> Self::Column { self.id }
+/// # }
+///
+/// /// Only apply flagged rows
+/// struct Flagged;
+///
+/// impl SqlxApply<Foo> for Flagged {
+///     fn apply(
+///         &self,
+///         foo: Foo,
+///         ctx: &mut SqlxApplyContext<Foo>,
+///     ) -> SqlxApplied {
+///         if foo.flag {
+///             SqlxDefaultApply.apply(foo, ctx)
+///         } else {
+///             SqlxApplied::Ignored
+///         }
+///     }
+/// }
+/// ```
+pub trait SqlxApply<C: Component + PrimaryKey>: Send + Sync + 'static {
+    /// Apply the synced `component`, returning what was done with it
+    fn apply(&self, component: C, ctx: &mut SqlxApplyContext<C>)
+        -> SqlxApplied;
+}
+
+impl<C: Component + PrimaryKey> fmt::Debug for dyn SqlxApply<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SqlxApply").finish_non_exhaustive()
+    }
+}
+
+/// What a [`SqlxApply`] did with a synced component
+///
+/// The matching [`SqlxEventStatus::Spawn`] or [`SqlxEventStatus::Update`] is
+/// sent for it, or nothing when it was ignored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SqlxApplied {
+    Spawned,
+    Updated,
+    Ignored,
+}
+
+/// The access a [`SqlxApply`] has to the world
+pub struct SqlxApplyContext<'a, 'w, 's, C: Component + PrimaryKey> {
+    pub commands: &'a mut Commands<'w, 's>,
+    /// Every spawned `C`, with its entity, except those marked
+    /// [`SqlxSkipSync`]
+    pub spawned:
+        &'a Query<'w, 's, (Entity, Ref<'static, C>), Without<SqlxSkipSync>>,
+    /// Every [`SqlxKey<C>`], with its entity, except those marked
+    /// [`SqlxSkipSync`]
+    pub keys:
+        &'a Query<'w, 's, (Entity, &'static SqlxKey<C>), Without<SqlxSkipSync>>,
+    pub(crate) matcher: &'a dyn SqlxMatch<C>,
+    pub(crate) target: &'a SqlxSpawnTarget,
+    pub(crate) parent: Option<Entity>,
+}
+
+impl<C: Component + PrimaryKey> SqlxApplyContext<'_, '_, '_, C> {
+    /// The entity of the spawned `C` with the primary key `pk`
+    pub fn find(&self, pk: &C::Column) -> Option<Entity> {
+        self.spawned
+            .iter()
+            .find(|(_, spawned)| spawned.primary_key() == *pk)
+            .map(|(entity, _)| entity)
+    }
+
+    /// The entity `component` belongs to, found by the plugin's
+    /// [`SqlxMatch`]
+    pub fn find_match(&self, component: &C) -> Option<Entity> {
+        self.matcher.find(component, self)
+    }
+
+    /// Spawn a new entity with `component`, where the plugin's
+    /// [`SqlxSpawnTarget`] says
+    pub fn spawn(&mut self, component: C) {
+        self.target.spawn(self.commands, self.parent, component);
+    }
+}
+
+/// The default [`SqlxApply`], spawning new components and updating the
+/// existing ones
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SqlxDefaultApply;
+
+impl<C: Component + PrimaryKey> SqlxApply<C> for SqlxDefaultApply {
+    fn apply(
+        &self,
+        component: C,
+        ctx: &mut SqlxApplyContext<C>,
+    ) -> SqlxApplied {
+        // Check if the task's component is already spawned.
+        if let Some(entity) = ctx.find_match(&component) {
+            ctx.commands.entity(entity).insert(component);
+            SqlxApplied::Updated
+        } else {
+            // TODO: Look into world.spawn_batch after taking set disjunction
+            // of ids.
+            ctx.spawn(component);
+            SqlxApplied::Spawned
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{AsyncComputeTaskPool, TaskPool};
+    use sqlx::{FromRow, Sqlite};
+
+    #[derive(Component, FromRow, Debug)]
+    struct Foo {
+        id: u32,
+        count: u32,
+    }
+
+    impl PrimaryKey for Foo {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    /// Add counts to the existing components, ignoring new ones
+    struct Merge;
+
+    impl SqlxApply<Foo> for Merge {
+        fn apply(
+            &self,
+            foo: Foo,
+            ctx: &mut SqlxApplyContext<Foo>,
+        ) -> SqlxApplied {
+            let existing =
+                ctx.spawned.iter().find(|(_, spawned)| spawned.id == foo.id);
+            let Some((entity, existing)) = existing else {
+                return SqlxApplied::Ignored;
+            };
+            let count = existing.count + foo.count;
+            ctx.commands.entity(entity).insert(Foo { id: foo.id, count });
+            SqlxApplied::Updated
+        }
+    }
+
+    #[test]
+    fn test_apply() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let mut app = App::new();
+        app.add_plugins(
+            SqlxPlugin::<Sqlite, Foo>::from_url(url).with_apply(Merge),
+        );
+        let entity = app.world_mut().spawn(Foo { id: 1, count: 1 }).id();
+
+        let sql = "SELECT 1 AS id, 2 AS count UNION SELECT 2 AS id, 2 AS count";
+        let event = SqlxEvent::<Sqlite, Foo>::query_sync(sql);
+        let id = app.world().resource::<SqlxEventIds>().next();
+        let event = event.with_id(id);
+        app.world_mut().send_event(event);
+        let mut statuses = Vec::new();
+        let mut tries = 0;
+        while !statuses.iter().any(|s: &SqlxStatusSummary| s.kind == "update")
+            && tries < 1000
+        {
+            app.update();
+            let events =
+                app.world().resource::<Events<SqlxEventStatus<Sqlite, Foo>>>();
+            statuses.extend(
+                events
+                    .iter_current_update_events()
+                    .map(|status| status.summary()),
+            );
+            tries += 1;
+        }
+
+        let kinds: Vec<_> = statuses
+            .iter()
+            .filter(|status| status.id == id && status.kind != "start")
+            .map(|status| status.kind.as_str())
+            .collect();
+        assert_eq!(vec!["update", "complete"], kinds);
+        assert_eq!(3, app.world().get::<Foo>(entity).unwrap().count);
+        assert_eq!(1, app.world_mut().query::<&Foo>().iter(app.world()).len());
+    }
+}
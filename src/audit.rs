@@ -0,0 +1,179 @@
+//! An opt-in log of every generated write
+//!
+//! When a [`SqlxPlugin`] is built [`SqlxPlugin::with_audit`], every generated
+//! insert, update, upsert and delete (see [`SqlxStatement`]) is mirrored into
+//! the [`AUDIT_TABLE`] within the same transaction as the write itself:
+//!
+//! | column       | value                                             |
+//! | ------------ | ------------------------------------------------- |
+//! | `event_id`   | the [`SqlxEventId`] of the write                  |
+//! | `label`      | the [`SqlxEvent::label`] of the write, if any     |
+//! | `table_name` | the table written to                              |
+//! | `operation`  | `INSERT`, `UPDATE`, `UPSERT` or `DELETE`          |
+//! | `old_values` | JSON array of the rows before the write           |
+//! | `new_values` | JSON array of the rows returned by the write      |
+//! | `created_at` | the time of the write                             |
+//!
+//! Rows are rendered with the component's [`ToRow`] implementation. Events
+//! built directly with [`SqlxEvent::statement`] don't know about `ToRow`, so
+//! only the values they write are logged, as `new_values`.
+//!
+//! Raw SQL from [`SqlxEvent::query`] and [`SqlxEvent::call`] is never
+//! audited.
+use crate::*;
+use sqlx::Type;
+use sqlx::{Connection, Database, Encode, Error, Executor, FromRow};
+use sqlx::{IntoArguments, Pool};
+use std::fmt::Write;
+use std::sync::Arc;
+
+/// The name of the table audited writes are logged to
+pub const AUDIT_TABLE: &str = "_bevy_sqlx_audit";
+
+/// The columns and values of a component, i.e. [`ToRow::to_row`]
+pub(crate) type SqlxRowFn<C> = fn(&C) -> Vec<(&'static str, SqlxValue)>;
+
+/// Create the [`AUDIT_TABLE`] unless it already exists
+pub(crate) async fn create_audit_table<DB>(pool: &Pool<DB>) -> Result<(), Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    let sql = format!(
+        "CREATE TABLE IF NOT EXISTS {AUDIT_TABLE} (
+            event_id    BIGINT      NOT NULL,
+            label       TEXT,
+            table_name  TEXT        NOT NULL,
+            operation   TEXT        NOT NULL,
+            old_values  TEXT,
+            new_values  TEXT,
+            created_at  TIMESTAMP   NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )"
+    );
+    sqlx::query(&sql).execute(pool).await.map(|_| ())
+}
+
+/// Execute a generated write, logging it to the [`AUDIT_TABLE`]
+pub(crate) async fn audited<DB, C>(
+    stmt: SqlxStatement,
+    to_row: Option<SqlxRowFn<C>>,
+    id: SqlxEventId,
+    label: Option<Arc<str>>,
+    conn: &mut DB::Connection,
+) -> Result<Vec<C>, Error>
+where
+    DB: Database,
+    C: for<'r> FromRow<'r, DB::Row> + Send + Unpin,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+{
+    let mut tx = conn.begin().await?;
+
+    let old_values = match (stmt.selection(), to_row) {
+        (Some(select), Some(to_row)) => {
+            let old: Vec<C> = select.fetch_all(&mut *tx).await?;
+            Some(json_rows(old.iter().map(to_row)))
+        }
+        _ => None,
+    };
+
+    let kind = stmt.kind();
+    let table = stmt.table_name();
+    let written = stmt.columns().to_vec();
+    let components: Vec<C> = stmt.fetch_all(&mut *tx).await?;
+    let new_values = match (kind, to_row) {
+        (SqlxStatementKind::Delete, _) => None,
+        (_, Some(to_row)) => Some(json_rows(components.iter().map(to_row))),
+        (_, None) => Some(json_rows(std::iter::once(written))),
+    };
+
+    let operation = match kind {
+        SqlxStatementKind::Select => "SELECT",
+        SqlxStatementKind::Insert => "INSERT",
+        SqlxStatementKind::Update => "UPDATE",
+        SqlxStatementKind::Upsert => "UPSERT",
+        SqlxStatementKind::Delete => "DELETE",
+    };
+    let log: [(&str, SqlxValue); 6] = [
+        ("event_id", id.into()),
+        ("label", label.as_deref().into()),
+        ("table_name", table.into()),
+        ("operation", operation.into()),
+        ("old_values", old_values.into()),
+        ("new_values", new_values.into()),
+    ];
+    let names: Vec<_> = log.iter().map(|(name, _)| *name).collect();
+    let placeholders: Vec<_> = (1..=log.len()).map(placeholder::<DB>).collect();
+    let sql = format!(
+        "INSERT INTO {AUDIT_TABLE} ({}) VALUES ({})",
+        names.join(", "),
+        placeholders.join(", "),
+    );
+    let mut query = sqlx::query(&sql);
+    for (_, value) in log {
+        query = query.bind(value);
+    }
+    query.execute(&mut *tx).await?;
+
+    tx.commit().await?;
+    Ok(components)
+}
+
+/// Render rows as a JSON array of objects
+fn json_rows(
+    rows: impl Iterator<Item = Vec<(&'static str, SqlxValue)>>,
+) -> String {
+    let rows: Vec<_> = rows
+        .map(|row| {
+            let columns: Vec<_> = row
+                .iter()
+                .map(|(name, value)| {
+                    format!("{}:{}", json_string(name), json_value(value))
+                })
+                .collect();
+            format!("{{{}}}", columns.join(","))
+        })
+        .collect();
+    format!("[{}]", rows.join(","))
+}
+
+pub(crate) fn json_value(value: &SqlxValue) -> String {
+    match value {
+        SqlxValue::Null => "null".into(),
+        SqlxValue::Bool(value) => value.to_string(),
+        SqlxValue::Int(value) => value.to_string(),
+        SqlxValue::Float(value) if value.is_finite() => value.to_string(),
+        SqlxValue::Float(_) => "null".into(),
+        SqlxValue::Text(value) => json_string(value),
+        SqlxValue::Bytes(value) => {
+            let bytes: Vec<_> = value.iter().map(u8::to_string).collect();
+            format!("[{}]", bytes.join(","))
+        }
+        SqlxValue::Timestamp(micros) => json_string(&rfc3339(*micros)),
+        SqlxValue::Array(array) => {
+            let values: Vec<_> =
+                array.values().iter().map(json_value).collect();
+            format!("[{}]", values.join(","))
+        }
+    }
+}
+
+pub(crate) fn json_string(value: &str) -> String {
+    let mut json = String::with_capacity(value.len() + 2);
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
@@ -0,0 +1,523 @@
+use crate::*;
+use bevy::ecs::system::BoxedSystem;
+use bevy::prelude::*;
+use bevy::tasks::futures_lite::future;
+use bevy::tasks::{block_on, AsyncComputeTaskPool, Task};
+use bevy::utils::{Duration, Instant};
+use sqlx::{Database, Encode, Error, Executor, IntoArguments, Pool, Type};
+use std::fmt;
+use std::marker::PhantomData;
+
+/// A marker [`Component`] for entities saved by the [`SqlxAutosavePlugin`]
+#[derive(Component, Clone, Copy, Default, Debug)]
+pub struct Persisted;
+
+/// A [`System`] returning the upserts of a component type's dirty
+/// components
+type SqlxCollector = BoxedSystem<(), Vec<SqlxStatement>>;
+
+/// Add the systems saving on a state transition, see
+/// [`SqlxAutosavePlugin::with_save_on_exit`]
+type SqlxSaveOn = Box<dyn Fn(&mut App) + Send + Sync>;
+
+/// A [`Plugin`] periodically upserting the dirty components of every
+/// [`Persisted`] entity
+///
+/// Each component type is registered with [`Self::with`]. Every `interval`,
+/// the registered components which changed since the last save, or whose
+/// entity was just marked [`Persisted`], are upserted in a single
+/// transaction, and a [`SqlxAutosaved`] is sent. If the last save hasn't
+/// finished yet, the next one waits for it.
+///
+/// Saves can also be made on state transitions, like leaving a level, with
+/// [`Self::with_save_on_exit`] and [`Self::with_save_on_enter`].
+///
+/// The upserts bypass the events of the [`SqlxPlugin`], so they aren't
+/// throttled, audited or scoped to a tenant.
+///
+/// ### Example
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy::utils::Duration;
+/// # use sqlx::{FromRow, Sqlite};
+/// # use bevy_sqlx::*;
+/// # #[derive(Component, FromRow)]
+/// # struct Foo { id: u32, text: String }
+/// # impl PrimaryKey for Foo {
+/// #     type Column = u32;
+/// #     fn primary_key(&self) -> Self::Column { self.id }
+/// # }
+/// # impl ToRow for Foo {
+/// #     fn table() -> &'static str { "foos" }
+/// #     fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+/// #         vec![("id", self.id.into()), ("text", self.text.clone().into())]
+/// #     }
+/// # }
+/// let url = "sqlite:db/sqlite.db";
+/// App::new()
+///     .add_plugins(SqlxPlugin::<Sqlite, Foo>::from_url(url))
+///     .add_plugins(
+///         SqlxAutosavePlugin::<Sqlite>::every(Duration::from_secs(30))
+///             .with::<Foo>(),
+///     );
+/// ```
+pub struct SqlxAutosavePlugin<DB> {
+    interval: Duration,
+    collectors: Vec<fn(&mut World) -> SqlxCollector>,
+    transitions: Vec<SqlxSaveOn>,
+    _db: PhantomData<fn() -> DB>,
+}
+
+impl<DB> SqlxAutosavePlugin<DB> {
+    /// Save every `interval`
+    pub fn every(interval: Duration) -> Self {
+        SqlxAutosavePlugin {
+            interval,
+            collectors: Vec::new(),
+            transitions: Vec::new(),
+            _db: PhantomData,
+        }
+    }
+
+    /// Save the component `C` of [`Persisted`] entities
+    pub fn with<C: Component + PrimaryKey + ToRow>(mut self) -> Self {
+        self.collectors.push(|world| {
+            let mut system = Box::new(IntoSystem::into_system(dirty::<C>));
+            system.initialize(world);
+            system
+        });
+        self
+    }
+}
+
+impl<DB> SqlxAutosavePlugin<DB>
+where
+    DB: Database + Sync,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+{
+    /// Also save whenever `state` is exited, e.g. when leaving a level
+    ///
+    /// The dirty components are collected in [`OnExit`], so the entities
+    /// of the level can be despawned by systems ordered after
+    /// [`SqlxAutosave::save_dirty`], and saved on the next frame.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy::utils::Duration;
+    /// # use sqlx::{FromRow, Sqlite};
+    /// # use bevy_sqlx::*;
+    /// # #[derive(Component, FromRow)]
+    /// # struct Foo { id: u32, text: String }
+    /// # impl PrimaryKey for Foo {
+    /// #     type Column = u32;
+    /// #     fn primary_key(&self) -> Self::Column { self.id }
+    /// # }
+    /// # impl ToRow for Foo {
+    /// #     fn table() -> &'static str { "foos" }
+    /// #     fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+    /// #         vec![("id", self.id.into()), ("text", self.text.clone().into())]
+    /// #     }
+    /// # }
+    /// #[derive(States, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+    /// enum Game {
+    ///     #[default]
+    ///     MainMenu,
+    ///     InGame,
+    /// }
+    ///
+    /// SqlxAutosavePlugin::<Sqlite>::every(Duration::from_secs(300))
+    ///     .with::<Foo>()
+    ///     .with_save_on_exit(Game::InGame);
+    /// ```
+    pub fn with_save_on_exit<S: States>(mut self, state: S) -> Self {
+        self.transitions.push(Box::new(move |app| {
+            app.add_systems(
+                OnExit(state.clone()),
+                SqlxAutosave::<DB>::save_dirty,
+            );
+        }));
+        self
+    }
+
+    /// Also save whenever `state` is entered, e.g. when returning to the
+    /// main menu
+    ///
+    /// Like [`Self::with_save_on_exit`], but the components are collected in
+    /// [`OnEnter`].
+    pub fn with_save_on_enter<S: States>(mut self, state: S) -> Self {
+        self.transitions.push(Box::new(move |app| {
+            app.add_systems(
+                OnEnter(state.clone()),
+                SqlxAutosave::<DB>::save_dirty,
+            );
+        }));
+        self
+    }
+}
+
+impl<DB> Plugin for SqlxAutosavePlugin<DB>
+where
+    DB: Database + Sync,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+{
+    fn build(&self, app: &mut App) {
+        let collectors = self
+            .collectors
+            .iter()
+            .map(|collector| collector(app.world_mut()))
+            .collect();
+        app.insert_resource(SqlxAutosave::<DB> {
+            interval: self.interval,
+            saved: Instant::now(),
+            collectors,
+            pending: Vec::new(),
+            task: None,
+            _db: PhantomData,
+        });
+        app.add_event::<SqlxAutosaved>();
+        // Client builds never write, see `SqlxPlugin::with_client`.
+        if cfg!(feature = "client") {
+            return;
+        }
+        app.add_systems(
+            Update,
+            SqlxAutosave::<DB>::handle_autosave
+                .after(SqlxRegistry::<DB>::handle_tasks),
+        );
+        for transition in &self.transitions {
+            transition(app);
+        }
+    }
+}
+
+/// A [`Resource`] holding the state of the [`SqlxAutosavePlugin`] of `DB`
+#[derive(Resource)]
+pub struct SqlxAutosave<DB> {
+    interval: Duration,
+    saved: Instant,
+    collectors: Vec<SqlxCollector>,
+    /// Upserts collected on a state transition, saved with the next save
+    pending: Vec<SqlxStatement>,
+    task: Option<Task<Result<u64, Error>>>,
+    _db: PhantomData<fn() -> DB>,
+}
+
+impl<DB> SqlxAutosave<DB> {
+    /// Return true while a save is running
+    pub fn is_saving(&self) -> bool {
+        self.task.is_some()
+    }
+
+    /// Save on the next frame, instead of waiting for the interval
+    pub fn save_now(&mut self) {
+        self.saved = Instant::now() - self.interval;
+    }
+}
+
+impl<DB> SqlxAutosave<DB>
+where
+    DB: Database + Sync,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+{
+    /// An exclusive [`System`] finishing the running save, and starting the
+    /// next one once the interval elapsed
+    pub fn handle_autosave(world: &mut World) {
+        world.resource_scope(|world, mut autosave: Mut<Self>| {
+            if let Some(task) = &mut autosave.task {
+                let Some(result) = block_on(future::poll_once(task)) else {
+                    return;
+                };
+                autosave.task = None;
+                world.send_event(SqlxAutosaved { database: DB::NAME, result });
+            }
+            let due = autosave.saved.elapsed() >= autosave.interval;
+            if !due && autosave.pending.is_empty() {
+                return;
+            }
+
+            let mut stmts = std::mem::take(&mut autosave.pending);
+            if due {
+                autosave.saved = Instant::now();
+                stmts.extend(autosave.collect(world));
+            }
+            if stmts.is_empty() {
+                return;
+            }
+            let pool = world.resource::<SqlxDatabase<DB>>().pool.clone();
+            let task = AsyncComputeTaskPool::get().spawn(save(stmts, pool));
+            autosave.task = Some(task);
+        });
+    }
+
+    /// An exclusive [`System`] collecting the upserts of the dirty
+    /// components right away, to be saved on the next frame
+    ///
+    /// See [`SqlxAutosavePlugin::with_save_on_exit`].
+    pub fn save_dirty(world: &mut World) {
+        world.resource_scope(|world, mut autosave: Mut<Self>| {
+            let stmts = autosave.collect(world);
+            autosave.pending.extend(stmts);
+        });
+    }
+
+    /// Run every collector, returning the upserts of the dirty components
+    fn collect(&mut self, world: &mut World) -> Vec<SqlxStatement> {
+        let mut stmts = Vec::new();
+        for collector in &mut self.collectors {
+            stmts.extend(collector.run((), world));
+        }
+        stmts
+    }
+}
+
+impl<DB> fmt::Debug for SqlxAutosave<DB> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SqlxAutosave")
+            .field("interval", &self.interval)
+            .field("saved", &self.saved)
+            .field("pending", &self.pending.len())
+            .field("saving", &self.is_saving())
+            .finish_non_exhaustive()
+    }
+}
+
+/// An [`Event`] sent when a save of a [`SqlxAutosavePlugin`] finishes
+///
+/// The result is the number of components saved, or the error which rolled
+/// the save back.
+#[derive(Event, Debug)]
+pub struct SqlxAutosaved {
+    /// The [`Database::NAME`] of the saved database
+    pub database: &'static str,
+    pub result: Result<u64, Error>,
+}
+
+/// The upserts of `C`'s dirty [`Persisted`] components
+fn dirty<C: Component + PrimaryKey + ToRow>(
+    components: Query<(Ref<C>, Ref<Persisted>), Without<SqlxSkipSync>>,
+    prefix: Option<Res<SqlxTablePrefix>>,
+) -> Vec<SqlxStatement> {
+    let prefix = prefix.as_ref().map_or("", |prefix| &prefix.0);
+    components
+        .iter()
+        .filter(|(component, persisted)| {
+            component.is_changed() || persisted.is_added()
+        })
+        .map(|(component, _)| {
+            SqlxStatement::upsert(
+                C::table(),
+                C::primary_key_name(),
+                component.to_row(),
+            )
+            .prefixed(prefix)
+        })
+        .collect()
+}
+
+/// Execute `stmts` in one transaction, returning how many were executed
+async fn save<DB>(
+    stmts: Vec<SqlxStatement>,
+    pool: Pool<DB>,
+) -> Result<u64, Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+{
+    let mut tx = pool.begin().await?;
+    for stmt in &stmts {
+        let sql = stmt.sql::<DB>();
+        let mut query = sqlx::query(&sql);
+        for value in stmt.binds() {
+            query = query.bind(value);
+        }
+        query.execute(&mut *tx).await?;
+    }
+    tx.commit().await?;
+    Ok(stmts.len() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::state::app::StatesPlugin;
+    use bevy::tasks::{block_on, AsyncComputeTaskPool, TaskPool};
+    use bevy::utils::Duration;
+    use sqlx::{FromRow, Sqlite};
+
+    #[derive(Component, FromRow, Debug)]
+    struct Foo {
+        id: u32,
+        text: String,
+    }
+
+    impl PrimaryKey for Foo {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    impl ToRow for Foo {
+        fn table() -> &'static str {
+            "foos"
+        }
+
+        fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+            vec![("id", self.id.into()), ("text", self.text.clone().into())]
+        }
+    }
+
+    #[derive(Component, FromRow, Debug)]
+    struct Bar {
+        id: u32,
+        foo_id: u32,
+    }
+
+    impl PrimaryKey for Bar {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    impl ToRow for Bar {
+        fn table() -> &'static str {
+            "bars"
+        }
+
+        fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+            vec![("id", self.id.into()), ("foo_id", self.foo_id.into())]
+        }
+    }
+
+    fn wait_for_save(app: &mut App) -> u64 {
+        let mut tries = 0;
+        loop {
+            app.update();
+            let events = app.world().resource::<Events<SqlxAutosaved>>();
+            if let Some(saved) = events.iter_current_update_events().next() {
+                return *saved.result.as_ref().unwrap();
+            }
+            tries += 1;
+            assert!(tries < 1000);
+        }
+    }
+
+    #[test]
+    fn test_autosave() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let mut app = App::new();
+        app.add_plugins(SqlxPlugin::<Sqlite, Foo>::from_url(url));
+        app.add_plugins(
+            SqlxAutosavePlugin::<Sqlite>::every(Duration::ZERO)
+                .with::<Foo>()
+                .with::<Bar>(),
+        );
+
+        let pool = app.world().resource::<SqlxDatabase<Sqlite>>().pool.clone();
+        let (foo_id, bar_id): (u32, u32) = block_on(async {
+            let foo = sqlx::query_scalar(
+                "INSERT INTO foos (text) VALUES ('stored') RETURNING id",
+            )
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+            let bar = sqlx::query_scalar(
+                "INSERT INTO bars (foo_id) VALUES (?) RETURNING id",
+            )
+            .bind(foo)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+            (foo, bar)
+        });
+
+        let foo = Foo { id: foo_id, text: "saved".into() };
+        let entity = app.world_mut().spawn((foo, Persisted)).id();
+        app.world_mut().spawn(Bar { id: bar_id, foo_id });
+        assert_eq!(1, wait_for_save(&mut app));
+
+        app.world_mut().get_mut::<Foo>(entity).unwrap().text = "again".into();
+        let bar = Bar { id: bar_id, foo_id };
+        app.world_mut().spawn((bar, Persisted));
+        assert_eq!(2, wait_for_save(&mut app));
+
+        let text: String = block_on(async {
+            sqlx::query_scalar("SELECT text FROM foos WHERE id = ?")
+                .bind(foo_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap()
+        });
+        assert_eq!("again", text);
+    }
+
+    #[derive(States, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+    enum Level {
+        #[default]
+        Playing,
+        Menu,
+    }
+
+    #[test]
+    fn test_save_on_exit() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let mut app = App::new();
+        app.add_plugins(StatesPlugin);
+        app.init_state::<Level>();
+        app.add_plugins(SqlxPlugin::<Sqlite, Foo>::from_url(url));
+        app.add_plugins(
+            SqlxAutosavePlugin::<Sqlite>::every(Duration::from_secs(3600))
+                .with::<Foo>()
+                .with_save_on_exit(Level::Playing),
+        );
+        // Leaving the level despawns it, after its components are collected.
+        app.add_systems(
+            OnExit(Level::Playing),
+            (|mut commands: Commands, foos: Query<Entity, With<Foo>>| {
+                for entity in &foos {
+                    commands.entity(entity).despawn();
+                }
+            })
+            .after(SqlxAutosave::<Sqlite>::save_dirty),
+        );
+
+        let pool = app.world().resource::<SqlxDatabase<Sqlite>>().pool.clone();
+        let foo_id: u32 = block_on(
+            sqlx::query_scalar(
+                "INSERT INTO foos (text) VALUES ('level') RETURNING id",
+            )
+            .fetch_one(&pool),
+        )
+        .unwrap();
+        app.update();
+        let foo = Foo { id: foo_id, text: "left".into() };
+        app.world_mut().spawn((foo, Persisted));
+        app.update();
+
+        app.world_mut().resource_mut::<NextState<Level>>().set(Level::Menu);
+        assert_eq!(1, wait_for_save(&mut app));
+        let mut foos = app.world_mut().query::<&Foo>();
+        assert_eq!(0, foos.iter(app.world()).len());
+
+        let text: String = block_on(
+            sqlx::query_scalar("SELECT text FROM foos WHERE id = ?")
+                .bind(foo_id)
+                .fetch_one(&pool),
+        )
+        .unwrap();
+        assert_eq!("left", text);
+    }
+}
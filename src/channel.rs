@@ -0,0 +1,451 @@
+//! Messaging between apps through the database
+//!
+//! A [`SqlxChannelPlugin`] turns the messages published on a named channel,
+//! by any app using the same database, into Bevy events of type `T`.
+//! Messages are published with the [`SqlxChannelWriter`] system param, as
+//! JSON, so every app, including the publishing one, receives them.
+//!
+//! With the `postgres` feature, Postgres channels `LISTEN` for
+//! notifications sent with `pg_notify`. Other databases insert messages
+//! into the [`CHANNEL_TABLE`], which each app polls for rows newer than
+//! the last it saw. Those rows are kept, so the table should be pruned by
+//! the app every now and then.
+//!
+//! ### Example
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use serde::{Deserialize, Serialize};
+//! # use sqlx::Sqlite;
+//! # use bevy_sqlx::*;
+//! #[derive(Event, Serialize, Deserialize)]
+//! struct Chat(String);
+//!
+//! fn send(mut chat: SqlxChannelWriter<Sqlite, Chat>) {
+//!     chat.publish(&Chat("hello".into()));
+//! }
+//!
+//! fn receive(mut chat: EventReader<Chat>) {
+//!     for Chat(text) in chat.read() {
+//!         println!("{text}");
+//!     }
+//! }
+//!
+//! let url = "sqlite:db/sqlite.db";
+//! App::new()
+//!     .add_plugins(SqlxPlugin::<Sqlite, SqlxDummy>::from_url(url))
+//!     .add_plugins(SqlxChannelPlugin::<Sqlite, Chat>::new("chat"))
+//!     .add_systems(Update, (send, receive));
+//! ```
+use crate::*;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::tasks::futures_lite::future;
+use bevy::tasks::{block_on, AsyncComputeTaskPool, Task};
+use bevy::utils::synccell::SyncCell;
+use bevy::utils::{Duration, Instant};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sqlx::{ColumnIndex, Database, Decode, Encode, Error, Executor};
+use sqlx::{IntoArguments, Pool, Type};
+use std::marker::PhantomData;
+use std::sync::mpsc::Receiver;
+use std::time::SystemTime;
+
+/// The name of the table messages are stored in, on databases which aren't
+/// listened to
+pub const CHANNEL_TABLE: &str = "_bevy_sqlx_messages";
+
+/// A [`Plugin`] sending the messages published on a channel as `T` events
+///
+/// See the [`channel`](crate::channel) module for more information.
+pub struct SqlxChannelPlugin<DB, T> {
+    channel: &'static str,
+    interval: Duration,
+    _marker: PhantomData<fn() -> (DB, T)>,
+}
+
+impl<DB, T> SqlxChannelPlugin<DB, T> {
+    /// Receive the messages published on `channel`
+    ///
+    /// When polled, the [`CHANNEL_TABLE`] is checked every second.
+    pub fn new(channel: &'static str) -> Self {
+        SqlxChannelPlugin {
+            channel,
+            interval: Duration::from_secs(1),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Poll the [`CHANNEL_TABLE`] every `interval` instead
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+}
+
+impl<DB, T> Plugin for SqlxChannelPlugin<DB, T>
+where
+    DB: Database + Sync,
+    T: Event + DeserializeOwned,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+    for<'r> i64: Decode<'r, DB> + Type<DB>,
+    for<'r> String: Decode<'r, DB> + Type<DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    fn build(&self, app: &mut App) {
+        let pool = app.world().resource::<SqlxDatabase<DB>>().pool.clone();
+        let mut channel = SqlxChannel::<DB, T> {
+            channel: self.channel,
+            interval: self.interval,
+            polled: Instant::now(),
+            cursor: 0,
+            poll: None,
+            listener: None,
+            publishing: Vec::new(),
+            failed: Vec::new(),
+            _marker: PhantomData,
+        };
+        if listens::<DB>() {
+            #[cfg(feature = "postgres")]
+            if let Some(pool) = (&pool as &dyn std::any::Any)
+                .downcast_ref::<Pool<sqlx::Postgres>>()
+            {
+                let (sender, receiver) = std::sync::mpsc::channel();
+                let listen = listen(pool.clone(), self.channel, sender);
+                let task = AsyncComputeTaskPool::get().spawn(listen);
+                channel.listener = Some((task, SyncCell::new(receiver)));
+            }
+        } else {
+            channel.cursor = block_on(async {
+                create_channel_table(&pool).await?;
+                last_message(&pool, self.channel).await
+            })
+            .unwrap();
+        }
+        app.insert_resource(channel);
+        app.add_event::<T>();
+        app.add_event::<SqlxChannelError>();
+        app.add_systems(Update, SqlxChannel::<DB, T>::handle_channel);
+    }
+}
+
+/// Return true if the channels of `DB` are listened to, instead of polled
+fn listens<DB: Database>() -> bool {
+    cfg!(feature = "postgres") && DB::NAME == "PostgreSQL"
+}
+
+/// Create the [`CHANNEL_TABLE`] unless it already exists
+async fn create_channel_table<DB>(pool: &Pool<DB>) -> Result<(), Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    let id = match DB::NAME {
+        "PostgreSQL" => "BIGSERIAL PRIMARY KEY",
+        "MySQL" => "BIGINT AUTO_INCREMENT PRIMARY KEY",
+        _ => "INTEGER PRIMARY KEY",
+    };
+    let sql = format!(
+        "CREATE TABLE IF NOT EXISTS {CHANNEL_TABLE} (
+            id       {id},
+            channel  VARCHAR(64) NOT NULL,
+            payload  TEXT        NOT NULL,
+            sent_at  BIGINT      NOT NULL
+        )"
+    );
+    sqlx::query(&sql).execute(pool).await.map(|_| ())
+}
+
+/// The id of the last message published on `channel`, or 0
+async fn last_message<DB>(pool: &Pool<DB>, channel: &str) -> Result<i64, Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+    for<'r> i64: Decode<'r, DB> + Type<DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    let sql = format!(
+        "SELECT COALESCE(MAX(id), 0) FROM {CHANNEL_TABLE} WHERE channel = {}",
+        placeholder::<DB>(1),
+    );
+    sqlx::query_scalar(&sql)
+        .bind(SqlxValue::from(channel))
+        .fetch_one(pool)
+        .await
+}
+
+/// Forward the payloads of the notifications on `channel` to `sender`,
+/// until it's disconnected
+#[cfg(feature = "postgres")]
+async fn listen(
+    pool: Pool<sqlx::Postgres>,
+    channel: &'static str,
+    sender: std::sync::mpsc::Sender<String>,
+) -> Result<(), Error> {
+    let mut listener = sqlx::postgres::PgListener::connect_with(&pool).await?;
+    listener.listen(channel).await?;
+    loop {
+        let notification = listener.recv().await?;
+        if sender.send(notification.payload().into()).is_err() {
+            return Ok(());
+        }
+    }
+}
+
+/// An [`Event`] sent when a channel fails to publish, receive or decode a
+/// message
+#[derive(Event, Debug)]
+pub struct SqlxChannelError {
+    pub channel: &'static str,
+    pub error: Error,
+}
+
+/// The messages polled from the [`CHANNEL_TABLE`], with their ids
+type SqlxPoll = Task<Result<Vec<(i64, String)>, Error>>;
+
+/// The running `LISTEN` of a channel, and the payloads it received
+type SqlxListener = (Task<Result<(), Error>>, SyncCell<Receiver<String>>);
+
+/// A [`Resource`] holding the state of the [`SqlxChannelPlugin`] of `T`
+#[derive(Resource)]
+pub struct SqlxChannel<DB, T> {
+    channel: &'static str,
+    interval: Duration,
+    polled: Instant,
+    cursor: i64,
+    poll: Option<SqlxPoll>,
+    listener: Option<SqlxListener>,
+    publishing: Vec<Task<Result<(), Error>>>,
+    failed: Vec<Error>,
+    _marker: PhantomData<fn() -> (DB, T)>,
+}
+
+impl<DB, T> SqlxChannel<DB, T> {
+    /// The name of the channel
+    pub fn channel(&self) -> &'static str {
+        self.channel
+    }
+}
+
+impl<DB, T> SqlxChannel<DB, T>
+where
+    DB: Database + Sync,
+    T: Event + DeserializeOwned,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+    for<'r> i64: Decode<'r, DB> + Type<DB>,
+    for<'r> String: Decode<'r, DB> + Type<DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    /// A [`System`] sending the received messages as `T` events, and
+    /// polling for new ones once the interval elapsed
+    pub fn handle_channel(
+        database: Res<SqlxDatabase<DB>>,
+        mut channel: ResMut<Self>,
+        mut messages: EventWriter<T>,
+        mut errors: EventWriter<SqlxChannelError>,
+    ) {
+        let name = channel.channel;
+        let mut payloads = Vec::new();
+        let mut failed = std::mem::take(&mut channel.failed);
+        channel.publishing.retain_mut(|task| {
+            let Some(result) = block_on(future::poll_once(task)) else {
+                return true;
+            };
+            failed.extend(result.err());
+            false
+        });
+
+        if let Some((task, receiver)) = &mut channel.listener {
+            payloads.extend(receiver.get().try_iter());
+            if let Some(result) = block_on(future::poll_once(task)) {
+                failed.extend(result.err());
+                channel.listener = None;
+            }
+        } else if let Some(task) = &mut channel.poll {
+            if let Some(result) = block_on(future::poll_once(task)) {
+                channel.poll = None;
+                match result {
+                    Ok(rows) => {
+                        if let Some((id, _)) = rows.last() {
+                            channel.cursor = *id;
+                        }
+                        payloads.extend(rows.into_iter().map(|(_, p)| p));
+                    }
+                    Err(err) => failed.push(err),
+                }
+            }
+        } else if !listens::<DB>()
+            && channel.polled.elapsed() >= channel.interval
+        {
+            channel.polled = Instant::now();
+            let pool = database.pool.clone();
+            let task = AsyncComputeTaskPool::get().spawn(poll(
+                pool,
+                name,
+                channel.cursor,
+            ));
+            channel.poll = Some(task);
+        }
+
+        for payload in payloads {
+            match serde_json::from_str(&payload) {
+                Ok(message) => {
+                    messages.send(message);
+                }
+                Err(err) => failed.push(Error::Decode(err.into())),
+            }
+        }
+        for error in failed {
+            errors.send(SqlxChannelError { channel: name, error });
+        }
+    }
+}
+
+/// Select the messages published on `channel` after the message `cursor`
+async fn poll<DB>(
+    pool: Pool<DB>,
+    channel: &'static str,
+    cursor: i64,
+) -> Result<Vec<(i64, String)>, Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+    for<'r> i64: Decode<'r, DB> + Type<DB>,
+    for<'r> String: Decode<'r, DB> + Type<DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    let sql = format!(
+        "SELECT id, payload FROM {CHANNEL_TABLE}
+            WHERE channel = {} AND id > {}
+            ORDER BY id",
+        placeholder::<DB>(1),
+        placeholder::<DB>(2),
+    );
+    sqlx::query_as(&sql)
+        .bind(SqlxValue::from(channel))
+        .bind(SqlxValue::from(cursor))
+        .fetch_all(&pool)
+        .await
+}
+
+/// A [`SystemParam`] for publishing `T` messages on the channel of its
+/// [`SqlxChannelPlugin`]
+///
+/// See the [`channel`](crate::channel) module for more information.
+#[derive(SystemParam)]
+pub struct SqlxChannelWriter<'w, DB: Database, T: Event> {
+    database: Res<'w, SqlxDatabase<DB>>,
+    channel: ResMut<'w, SqlxChannel<DB, T>>,
+}
+
+impl<DB, T> SqlxChannelWriter<'_, DB, T>
+where
+    DB: Database,
+    T: Event + Serialize,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+{
+    /// Publish `message` to every app receiving the channel
+    ///
+    /// Failures are sent as [`SqlxChannelError`]s.
+    pub fn publish(&mut self, message: &T) {
+        let channel = self.channel.channel;
+        let payload = match serde_json::to_string(message) {
+            Ok(payload) => payload,
+            Err(err) => {
+                self.channel.failed.push(Error::Encode(err.into()));
+                return;
+            }
+        };
+        let (sql, sent_at) = if listens::<DB>() {
+            ("SELECT pg_notify($1, $2)".to_string(), None)
+        } else {
+            let sql = format!(
+                "INSERT INTO {CHANNEL_TABLE} (channel, payload, sent_at)
+                    VALUES ({}, {}, {})",
+                placeholder::<DB>(1),
+                placeholder::<DB>(2),
+                placeholder::<DB>(3),
+            );
+            let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH);
+            (sql, Some(now.map_or(0, |now| now.as_secs() as i64)))
+        };
+        let pool = self.database.pool.clone();
+        let task = AsyncComputeTaskPool::get().spawn(async move {
+            let mut query = sqlx::query(&sql)
+                .bind(SqlxValue::from(channel))
+                .bind(SqlxValue::from(payload));
+            if let Some(sent_at) = sent_at {
+                query = query.bind(SqlxValue::from(sent_at));
+            }
+            query.execute(&pool).await.map(|_| ())
+        });
+        self.channel.publishing.push(task);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::ecs::system::SystemState;
+    use bevy::prelude::*;
+    use bevy::tasks::{AsyncComputeTaskPool, TaskPool};
+    use bevy::utils::Duration;
+    use serde::{Deserialize, Serialize};
+    use sqlx::Sqlite;
+
+    #[derive(
+        Event, Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord,
+    )]
+    struct Ping(u32);
+
+    fn setup_app(url: &str) -> App {
+        let mut app = App::new();
+        app.add_plugins(SqlxPlugin::<Sqlite, SqlxDummy>::from_url(url));
+        app.add_plugins(
+            SqlxChannelPlugin::<Sqlite, Ping>::new("test_pings")
+                .with_poll_interval(Duration::ZERO),
+        );
+        app
+    }
+
+    #[test]
+    fn test_channel() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let mut publisher = setup_app(url);
+        let mut subscriber = setup_app(url);
+
+        let mut writer: SystemState<SqlxChannelWriter<Sqlite, Ping>> =
+            SystemState::new(publisher.world_mut());
+        writer.get_mut(publisher.world_mut()).publish(&Ping(1));
+        writer.get_mut(publisher.world_mut()).publish(&Ping(2));
+        let mut pings = Vec::new();
+        let mut tries = 0;
+        while pings.len() < 2 && tries < 1000 {
+            publisher.update();
+            subscriber.update();
+            let mut events =
+                subscriber.world_mut().resource_mut::<Events<Ping>>();
+            pings.extend(events.drain());
+            tries += 1;
+        }
+
+        // The publishes race each other, so they may be received in any order.
+        pings.sort();
+        assert_eq!(vec![Ping(1), Ping(2)], pings);
+        let errors = publisher.world().resource::<Events<SqlxChannelError>>();
+        assert!(errors.is_empty());
+    }
+}
@@ -0,0 +1,340 @@
+use crate::*;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use sqlx::{Database, Executor, IntoArguments};
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+/// A [`Plugin`] mirroring the `C` rows of every loaded `P` as child
+/// entities, for one-to-many data like inventories or guild members
+///
+/// When a `P` is spawned, the `C` rows which belong to it, see
+/// [`SqlxBelongsTo`], are selected and synced. They're selected again
+/// whenever a [`SqlxTableChanged`] is read for `C`'s table, so inserted rows
+/// are spawned, updated rows are synced, and the entities of deleted rows
+/// are despawned. Rows ignored by the plugin's [`SqlxApply`] count as
+/// deleted.
+///
+/// A [`SqlxBelongsToPlugin<DB, C, P>`] is added too, unless it already is,
+/// so each `C` entity is a child of its `P` entity.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::{FromRow, Sqlite};
+/// # use bevy_sqlx::*;
+/// # #[derive(Component, FromRow)]
+/// # struct Player { id: u32 }
+/// # impl PrimaryKey for Player {
+/// #     type Column = u32;
+/// #     fn primary_key(&self) -> Self::Column { self.id }
+/// # }
+/// # #[derive(Component, FromRow)]
+/// # struct Item { id: u32, player_id: u32 }
+/// # impl PrimaryKey for Item {
+/// #     type Column = u32;
+/// #     fn primary_key(&self) -> Self::Column { self.id }
+/// # }
+/// # impl ToRow for Item {
+/// #     fn table() -> &'static str { "items" }
+/// #     fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+/// #         vec![("player_id", self.player_id.into())]
+/// #     }
+/// # }
+/// # impl SqlxBelongsTo<Player> for Item {
+/// #     fn foreign_key_name() -> &'static str { "player_id" }
+/// #     fn foreign_key(&self) -> u32 { self.player_id }
+/// # }
+/// let url = "sqlite:db/sqlite.db";
+/// App::new()
+///     .add_plugins(SqlxPlugin::<Sqlite, Player>::from_url(url))
+///     .add_plugins(SqlxPlugin::<Sqlite, Item>::from_url(url))
+///     .add_plugins(SqlxCollectionPlugin::<Sqlite, Player, Item>::default());
+/// ```
+pub struct SqlxCollectionPlugin<DB, P, C> {
+    _db: PhantomData<DB>,
+    _p: PhantomData<P>,
+    _c: PhantomData<C>,
+}
+
+impl<DB, P, C> Default for SqlxCollectionPlugin<DB, P, C> {
+    fn default() -> Self {
+        SqlxCollectionPlugin {
+            _db: PhantomData,
+            _p: PhantomData,
+            _c: PhantomData,
+        }
+    }
+}
+
+impl<DB, P, C> Plugin for SqlxCollectionPlugin<DB, P, C>
+where
+    DB: Database + Sync,
+    P: SqlxComponent<DB::Row>,
+    P::Column: Into<SqlxValue> + Eq + Hash + 'static,
+    C: SqlxComponent<DB::Row> + SqlxBelongsTo<P> + ToRow,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<SqlxBelongsToPlugin<DB, C, P>>() {
+            app.add_plugins(SqlxBelongsToPlugin::<DB, C, P>::default());
+        }
+        if !SqlxSyncMode::of::<DB, C>(app.world()).reads() {
+            return;
+        }
+        app.insert_resource(SqlxCollectionLoads::<DB, P, C> {
+            loads: HashMap::new(),
+            stale: Vec::new(),
+            _db: PhantomData,
+        });
+        app.add_systems(
+            Update,
+            (
+                load_collections::<DB, P, C>
+                    .before(SqlxRegistry::<DB>::handle_events),
+                mirror_collections::<DB, P, C>
+                    .after(SqlxRegistry::<DB>::handle_tasks),
+            ),
+        );
+    }
+}
+
+/// The running selects of a [`SqlxCollectionPlugin`], with the key of their
+/// `P` and the keys of the `C`s synced so far
+#[derive(Resource)]
+struct SqlxCollectionLoads<DB, P: PrimaryKey, C: PrimaryKey> {
+    loads: HashMap<SqlxEventId, (P::Column, Vec<C::Column>)>,
+    /// The `P`s to select again once their running select ends
+    stale: Vec<P::Column>,
+    _db: PhantomData<fn() -> DB>,
+}
+
+impl<DB, P: PrimaryKey, C: PrimaryKey> SqlxCollectionLoads<DB, P, C> {
+    /// Return true while the `C`s of the `P` with `key` are being selected
+    fn is_loading(&self, key: &P::Column) -> bool {
+        self.loads.values().any(|(loading, _)| loading == key)
+    }
+}
+
+/// Select the `C`s of new `P`s, and of every `P` when `C`'s table changes
+///
+/// A `P` whose `C`s are still being selected is selected again once that
+/// select ends, so two selects never sync the same new row at once.
+fn load_collections<DB, P, C>(
+    parents: Query<Ref<P>>,
+    mut changes: EventReader<SqlxTableChanged>,
+    ids: Res<SqlxEventIds>,
+    mut loads: ResMut<SqlxCollectionLoads<DB, P, C>>,
+    mut events: EventWriter<SqlxEvent<DB, C>>,
+) where
+    DB: Database + Sync,
+    P: SqlxComponent<DB::Row>,
+    P::Column: Into<SqlxValue>,
+    C: SqlxComponent<DB::Row> + SqlxBelongsTo<P> + ToRow,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    let changed = changes.read().any(|change| &*change.table == C::table());
+    for parent in &parents {
+        let key = parent.primary_key();
+        let stale = loads.stale.contains(&key);
+        if !changed && !parent.is_added() && !stale {
+            continue;
+        }
+        if loads.is_loading(&key) {
+            if !stale {
+                loads.stale.push(key);
+            }
+            continue;
+        }
+        loads.stale.retain(|stale| *stale != key);
+        let stmt = SqlxStatement::select(C::table())
+            .filter(C::foreign_key_name(), key.clone());
+        let id = ids.next();
+        loads.loads.insert(id, (key, Vec::new()));
+        events.send(SqlxEvent::statement_sync(stmt).with_id(id));
+    }
+}
+
+/// Record the `C`s synced by each select, and despawn the `C`s of its `P`
+/// which weren't once it completes, unless it's selected again anyway
+fn mirror_collections<DB, P, C>(
+    mut commands: Commands,
+    children: Query<(Entity, &C)>,
+    mut loads: ResMut<SqlxCollectionLoads<DB, P, C>>,
+    mut statuses: EventReader<SqlxEventStatus<DB, C>>,
+) where
+    DB: Database + Sync,
+    P: SqlxComponent<DB::Row>,
+    C: SqlxComponent<DB::Row> + SqlxBelongsTo<P>,
+{
+    for status in statuses.read() {
+        match status {
+            SqlxEventStatus::Spawn(id, pk, _)
+            | SqlxEventStatus::Update(id, pk, _) => {
+                if let Some((_, synced)) = loads.loads.get_mut(id) {
+                    synced.push(pk.clone());
+                }
+            }
+            SqlxEventStatus::Complete(id, _) => {
+                let Some((key, synced)) = loads.loads.remove(id) else {
+                    continue;
+                };
+                if loads.stale.contains(&key) {
+                    continue;
+                }
+                for (entity, child) in &children {
+                    if child.foreign_key() == key
+                        && !synced.contains(&child.primary_key())
+                    {
+                        commands.entity(entity).despawn_recursive();
+                    }
+                }
+            }
+            SqlxEventStatus::Error(id, ..) => {
+                loads.loads.remove(id);
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{block_on, AsyncComputeTaskPool, TaskPool};
+    use sqlx::{FromRow, Sqlite};
+
+    #[derive(Component, FromRow, Debug)]
+    struct Guild {
+        id: u32,
+    }
+
+    impl PrimaryKey for Guild {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    #[derive(Component, FromRow, Debug)]
+    struct Member {
+        id: u32,
+        guild_id: u32,
+        name: String,
+    }
+
+    impl PrimaryKey for Member {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    impl SqlxBelongsTo<Guild> for Member {
+        fn foreign_key_name() -> &'static str {
+            "guild_id"
+        }
+
+        fn foreign_key(&self) -> u32 {
+            self.guild_id
+        }
+    }
+
+    impl ToRow for Member {
+        fn table() -> &'static str {
+            "collection_members"
+        }
+
+        fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+            vec![
+                ("id", self.id.into()),
+                ("guild_id", self.guild_id.into()),
+                ("name", self.name.clone().into()),
+            ]
+        }
+    }
+
+    fn members(app: &mut App, guild: Entity) -> Vec<(u32, String)> {
+        let mut query = app.world_mut().query::<(&Member, &Parent)>();
+        let mut members: Vec<_> = query
+            .iter(app.world())
+            .filter(|(_, parent)| parent.get() == guild)
+            .map(|(member, _)| (member.id, member.name.clone()))
+            .collect();
+        members.sort();
+        members
+    }
+
+    fn update_until(
+        app: &mut App,
+        guild: Entity,
+        expected: &[(u32, &str)],
+    ) -> Vec<(u32, String)> {
+        let expected: Vec<_> =
+            expected.iter().map(|(id, name)| (*id, name.to_string())).collect();
+        let mut tries = 0;
+        while members(app, guild) != expected && tries < 1000 {
+            app.update();
+            tries += 1;
+        }
+        members(app, guild)
+    }
+
+    #[test]
+    fn test_collection() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let mut app = App::new();
+        app.add_plugins(SqlxPlugin::<Sqlite, Guild>::from_url(url));
+        app.add_plugins(SqlxPlugin::<Sqlite, Member>::from_url(url));
+        app.add_plugins(
+            SqlxCollectionPlugin::<Sqlite, Guild, Member>::default(),
+        );
+        let pool = &app.world().resource::<SqlxDatabase<Sqlite>>().pool;
+        block_on(async {
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS collection_members \
+                 (id INTEGER PRIMARY KEY, guild_id INTEGER NOT NULL, \
+                 name TEXT NOT NULL)",
+            )
+            .execute(pool)
+            .await
+            .unwrap();
+            sqlx::query("DELETE FROM collection_members")
+                .execute(pool)
+                .await
+                .unwrap();
+            sqlx::query(
+                "INSERT INTO collection_members VALUES \
+                 (1, 5, 'ann'), (2, 5, 'bob'), (3, 6, 'cat')",
+            )
+            .execute(pool)
+            .await
+            .unwrap();
+        });
+
+        let guild = app.world_mut().spawn(Guild { id: 5 }).id();
+        let expected = [(1, "ann"), (2, "bob")];
+        assert_eq!(
+            vec![(1, "ann".into()), (2, "bob".into())],
+            update_until(&mut app, guild, &expected)
+        );
+
+        // Generated writes to the table reload the collection.
+        let dan = Member { id: 4, guild_id: 5, name: "dan".into() };
+        app.world_mut().send_event(SqlxEvent::<Sqlite, Member>::insert(&dan));
+        let delete =
+            SqlxStatement::delete("collection_members").filter("id", 1);
+        app.world_mut()
+            .send_event(SqlxEvent::<Sqlite, Member>::statement(delete));
+        let expected = [(2, "bob"), (4, "dan")];
+        assert_eq!(
+            vec![(2, "bob".into()), (4, "dan".into())],
+            update_until(&mut app, guild, &expected)
+        );
+        let mut query = app.world_mut().query::<&Member>();
+        assert_eq!(2, query.iter(app.world()).len());
+    }
+}
@@ -16,7 +16,19 @@
 //! ]
 //! ```
 //!
-//! TODO: Explain `ToRow` and `FromRow` here.
+//! Rows are decoded into components with [`FromRow`], and components can be
+//! written back with the statements generated from their [`ToRow`]
+//! implementation (see [`SqlxEvent::insert`](crate::SqlxEvent::insert)).
+//!
+//! A component's table metadata comes from these traits, and is used by
+//! every generated statement:
+//! - [`ToRow::table`], the table its rows are stored in
+//! - [`ToRow::to_row`], its columns, with typed [`SqlxValue`]s
+//! - [`PrimaryKey::primary_key_name`], the column of its primary key
+//!
+//! Lookups by example use a partial record implementing [`ToFilter`]
+//! instead, see [`SqlxEvent::find_where`](crate::SqlxEvent::find_where).
+use crate::SqlxValue;
 use bevy::prelude::*;
 use sqlx::{FromRow, Row};
 
@@ -38,14 +50,98 @@ where
 // TODO: Look into impl PartialEq<PrimaryKey<...>> for Foo
 pub trait PrimaryKey {
     type Column: Clone + PartialEq + Send + Sync;
-    // fn primary_key_name() -> &'static str;
+
+    /// The name of the primary key's column, `"id"` by default
+    ///
+    /// Generated statements filter on this column, e.g. in
+    /// [`SqlxEvent::select`](crate::SqlxEvent::select).
+    fn primary_key_name() -> &'static str {
+        "id"
+    }
+
     fn primary_key(&self) -> Self::Column;
 }
 
 /// A record that can be upserted into the database
-//
-// TODO: https://github.com/nixpulvis/bevy_sqlx/issues/7
-pub trait ToRow {}
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::FromRow;
+/// # use bevy_sqlx::{SqlxValue, ToRow};
+/// #[derive(Component, FromRow)]
+/// struct Foo {
+///     id: u32,
+///     text: String,
+/// }
+///
+/// impl ToRow for Foo {
+///     fn table() -> &'static str {
+///         "foos"
+///     }
+///
+///     fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+///         vec![("id", self.id.into()), ("text", self.text.clone().into())]
+///     }
+/// }
+/// ```
+pub trait ToRow {
+    /// The name of the table records are stored in
+    fn table() -> &'static str;
+
+    /// The columns of this record and their values
+    fn to_row(&self) -> Vec<(&'static str, SqlxValue)>;
+}
+
+/// A partial record, matching the rows whose columns equal its set values
+///
+/// Columns without a value are left unconstrained. It's typically a
+/// component-shaped struct with `Option` fields, but column and value pairs
+/// can be used directly too.
+///
+/// ```
+/// # use bevy_sqlx::{SqlxValue, ToFilter};
+/// #[derive(Default)]
+/// struct FooExample {
+///     text: Option<String>,
+///     flag: Option<bool>,
+/// }
+///
+/// impl ToFilter for FooExample {
+///     fn to_filter(&self) -> Vec<(&'static str, Option<SqlxValue>)> {
+///         vec![
+///             ("text", self.text.clone().map(Into::into)),
+///             ("flag", self.flag.map(Into::into)),
+///         ]
+///     }
+/// }
+/// ```
+pub trait ToFilter {
+    /// The columns of this record and their values, if they're set
+    fn to_filter(&self) -> Vec<(&'static str, Option<SqlxValue>)>;
+}
+
+impl ToFilter for Vec<(&'static str, SqlxValue)> {
+    fn to_filter(&self) -> Vec<(&'static str, Option<SqlxValue>)> {
+        self.iter().map(|(name, value)| (*name, Some(value.clone()))).collect()
+    }
+}
+
+impl<const N: usize> ToFilter for [(&'static str, SqlxValue); N] {
+    fn to_filter(&self) -> Vec<(&'static str, Option<SqlxValue>)> {
+        self.to_vec().to_filter()
+    }
+}
+
+/// A marker [`Component`] for entities which are never synced, even though
+/// their components are
+///
+/// Preview or ghost entities can share a component type with persisted
+/// ones this way. Their components aren't written back, e.g. by a
+/// [`SqlxAutosavePlugin`](crate::SqlxAutosavePlugin), and synced rows are
+/// never applied to them, so a row with the same primary key spawns a new
+/// entity instead.
+#[derive(Component, Clone, Copy, Default, Debug)]
+pub struct SqlxSkipSync;
 
 /// An empty [`Component`] for use without a backing table
 #[derive(Component, FromRow, Debug, Clone)]
@@ -0,0 +1,74 @@
+//! Run conditions for gating systems on the database
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use sqlx::Sqlite;
+//! # use bevy_sqlx::{sqlx_idle, sqlx_ready, SqlxDummy};
+//! # fn save() {}
+//! # fn report() {}
+//! # let mut app = App::new();
+//! app.add_systems(Update, save.run_if(sqlx_ready::<Sqlite>()));
+//! app.add_systems(Update, report.run_if(sqlx_idle::<Sqlite, SqlxDummy>()));
+//! ```
+use crate::*;
+use bevy::prelude::*;
+use sqlx::{Database, Executor, IntoArguments};
+
+/// A run condition which is true while the [`SqlxDatabase<DB>`] resource
+/// exists and its pool hasn't been closed
+pub fn sqlx_ready<DB: Database>(
+) -> impl FnMut(Option<Res<SqlxDatabase<DB>>>) -> bool + Clone {
+    |database| database.is_some_and(|database| !database.pool.is_closed())
+}
+
+/// A run condition which is true while no [`SqlxEvent<DB, C>`] is throttled
+/// or has a running task
+pub fn sqlx_idle<DB, C>(
+) -> impl FnMut(Option<Res<SqlxTasks<DB, C>>>) -> bool + Clone
+where
+    DB: Database + Sync,
+    C: SqlxComponent<DB::Row>,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    |tasks| tasks.is_none_or(|tasks| tasks.is_empty() && tasks.throttled() == 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{AsyncComputeTaskPool, TaskPool};
+    use sqlx::Sqlite;
+
+    #[derive(Resource, Default)]
+    struct Ran(usize);
+
+    #[test]
+    fn test_conditions() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let mut app = App::new();
+        app.init_resource::<Ran>();
+        app.add_systems(
+            Update,
+            (|mut ran: ResMut<Ran>| ran.0 += 1)
+                .run_if(sqlx_ready::<Sqlite>())
+                .run_if(sqlx_idle::<Sqlite, SqlxDummy>())
+                .after(SqlxRegistry::<Sqlite>::handle_events)
+                .before(SqlxRegistry::<Sqlite>::handle_tasks),
+        );
+        app.update();
+        assert_eq!(0, app.world().resource::<Ran>().0);
+
+        let url = "sqlite:db/sqlite.db";
+        app.add_plugins(SqlxPlugin::<Sqlite, SqlxDummy>::from_url(url));
+        app.update();
+        assert_eq!(1, app.world().resource::<Ran>().0);
+
+        let sql = "SELECT 1";
+        let event = SqlxEvent::<Sqlite, SqlxDummy>::aggregate(sql);
+        app.world_mut().send_event(event);
+        app.update();
+        assert_eq!(1, app.world().resource::<Ran>().0);
+    }
+}
@@ -0,0 +1,233 @@
+use crate::*;
+use bevy::prelude::*;
+use bevy::tasks::futures_lite::future;
+use bevy::tasks::{block_on, AsyncComputeTaskPool, Task};
+use serde::de::DeserializeOwned;
+use sqlx::{ColumnIndex, Database, Decode, Error, Executor, IntoArguments};
+use sqlx::{Pool, Type};
+use std::marker::PhantomData;
+
+/// A [`Plugin`] loading the resource `R` from a config table when the app
+/// starts, and again whenever a [`SqlxTableChanged`] is read for the table
+///
+/// The table has a `key` and a `value` column, both text. Its rows are
+/// deserialized as the fields of `R`, each value being parsed as JSON, or
+/// used as a string if it isn't valid JSON. Designers can tweak balance
+/// values in the database, and see them live in-game once the change is
+/// reported. The loaded resource is inserted over the existing one, which
+/// is kept when the table can't be loaded.
+///
+/// ### Example
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use serde::Deserialize;
+/// # use sqlx::Sqlite;
+/// # use bevy_sqlx::*;
+/// #[derive(Resource, Deserialize, Default)]
+/// struct Balance {
+///     gold_per_kill: u32,
+///     boss_health: f32,
+/// }
+///
+/// let url = "sqlite:db/sqlite.db";
+/// App::new()
+///     .add_plugins(SqlxPlugin::<Sqlite, SqlxDummy>::from_url(url))
+///     .init_resource::<Balance>()
+///     .add_plugins(SqlxConfigTablePlugin::<Sqlite, Balance>::new("balance"));
+/// ```
+pub struct SqlxConfigTablePlugin<DB, R> {
+    table: &'static str,
+    _db: PhantomData<fn() -> DB>,
+    _r: PhantomData<fn() -> R>,
+}
+
+impl<DB, R> SqlxConfigTablePlugin<DB, R> {
+    /// Load `R` from `table`
+    pub fn new(table: &'static str) -> Self {
+        SqlxConfigTablePlugin { table, _db: PhantomData, _r: PhantomData }
+    }
+}
+
+impl<DB, R> Plugin for SqlxConfigTablePlugin<DB, R>
+where
+    DB: Database + Sync,
+    R: Resource + DeserializeOwned,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'r> String: Decode<'r, DB> + Type<DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SqlxConfigTable::<DB, R> {
+            table: self.table,
+            stale: true,
+            loading: None,
+            _db: PhantomData,
+        });
+        app.add_systems(
+            Update,
+            SqlxConfigTable::<DB, R>::handle_config_table
+                .after(SqlxRegistry::<DB>::handle_tasks),
+        );
+    }
+}
+
+/// A [`Resource`] holding the state of the [`SqlxConfigTablePlugin`] of `R`
+#[derive(Resource)]
+pub struct SqlxConfigTable<DB, R> {
+    table: &'static str,
+    /// Whether the table changed since it was last loaded
+    stale: bool,
+    loading: Option<Task<Result<R, Error>>>,
+    _db: PhantomData<fn() -> DB>,
+}
+
+impl<DB, R> SqlxConfigTable<DB, R> {
+    /// The table `R` is loaded from
+    pub fn table(&self) -> &'static str {
+        self.table
+    }
+
+    /// Return true while the table is being loaded
+    pub fn is_loading(&self) -> bool {
+        self.stale || self.loading.is_some()
+    }
+
+    /// Load the table again on the next frame, even if no change was
+    /// reported
+    pub fn reload(&mut self) {
+        self.stale = true;
+    }
+}
+
+impl<DB, R> SqlxConfigTable<DB, R>
+where
+    DB: Database + Sync,
+    R: Resource + DeserializeOwned,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'r> String: Decode<'r, DB> + Type<DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    /// A [`System`] loading the table when it's stale, and inserting `R`
+    /// once it's loaded
+    pub fn handle_config_table(
+        mut commands: Commands,
+        database: Res<SqlxDatabase<DB>>,
+        mut config: ResMut<Self>,
+        mut changes: EventReader<SqlxTableChanged>,
+    ) {
+        if changes.read().any(|change| &*change.table == config.table) {
+            config.stale = true;
+        }
+
+        if let Some(loading) = &mut config.loading {
+            let Some(result) = block_on(future::poll_once(loading)) else {
+                return;
+            };
+            config.loading = None;
+            match result {
+                Ok(resource) => commands.insert_resource(resource),
+                Err(err) => warn!("failed to load {}: {err}", config.table),
+            }
+        }
+        if config.stale {
+            config.stale = false;
+            let loading = load_config(config.table, database.pool.clone());
+            config.loading = Some(AsyncComputeTaskPool::get().spawn(loading));
+        }
+    }
+}
+
+/// Deserialize the rows of the config `table` as `R`
+async fn load_config<DB, R>(table: &str, pool: Pool<DB>) -> Result<R, Error>
+where
+    DB: Database,
+    R: DeserializeOwned,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'r> String: Decode<'r, DB> + Type<DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    let sql = format!("SELECT key, value FROM {table}");
+    let rows: Vec<(String, String)> =
+        sqlx::query_as(&sql).fetch_all(&pool).await?;
+    let fields = rows
+        .into_iter()
+        .map(|(key, value)| {
+            let value = serde_json::from_str(&value)
+                .unwrap_or(serde_json::Value::String(value));
+            (key, value)
+        })
+        .collect();
+    serde_json::from_value(serde_json::Value::Object(fields))
+        .map_err(|err| Error::Decode(err.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{block_on, AsyncComputeTaskPool, TaskPool};
+    use serde::Deserialize;
+    use sqlx::Sqlite;
+
+    #[derive(Resource, Deserialize, Default, Debug, PartialEq)]
+    struct Balance {
+        gold_per_kill: u32,
+        boss_name: String,
+    }
+
+    fn update_until(app: &mut App, done: impl Fn(&App) -> bool) {
+        let mut tries = 0;
+        while !done(app) && tries < 1000 {
+            app.update();
+            tries += 1;
+        }
+    }
+
+    #[test]
+    fn test_config_table() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let mut app = App::new();
+        app.add_plugins(SqlxPlugin::<Sqlite, SqlxDummy>::from_url(url));
+        app.init_resource::<Balance>();
+        app.add_plugins(SqlxConfigTablePlugin::<Sqlite, Balance>::new(
+            "config_balances",
+        ));
+        let pool = app.world().resource::<SqlxDatabase<Sqlite>>().pool.clone();
+        block_on(async {
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS config_balances \
+                 (key TEXT PRIMARY KEY, value TEXT NOT NULL)",
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+            sqlx::query(
+                "INSERT OR REPLACE INTO config_balances VALUES \
+                 ('gold_per_kill', '5'), ('boss_name', 'Grendel')",
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+        });
+
+        update_until(&mut app, |app| {
+            app.world().resource::<Balance>().gold_per_kill == 5
+        });
+        let balance = Balance { gold_per_kill: 5, boss_name: "Grendel".into() };
+        assert_eq!(&balance, app.world().resource::<Balance>());
+
+        let sql = "UPDATE config_balances SET value = '8' \
+                   WHERE key = 'gold_per_kill'";
+        block_on(sqlx::query(sql).execute(&pool)).unwrap();
+        app.world_mut().send_event(SqlxTableChanged::new("config_balances"));
+        update_until(&mut app, |app| {
+            app.world().resource::<Balance>().gold_per_kill == 8
+        });
+        assert_eq!(8, app.world().resource::<Balance>().gold_per_kill);
+    }
+}
@@ -0,0 +1,343 @@
+use crate::*;
+use sqlx::{Column, ColumnIndex, Database, Decode, Encode, Error, Executor};
+use sqlx::{IntoArguments, Pool, Row, Type};
+use std::fmt;
+use std::sync::Arc;
+
+/// Merges the columns of a journaled write with those of the remote row it
+/// conflicts with, returning the columns to write, or `None` to keep the
+/// remote row
+pub type SqlxMergeFn = Arc<
+    dyn Fn(
+            &[(String, SqlxValue)],
+            &[(String, SqlxValue)],
+        ) -> Option<Vec<(String, SqlxValue)>>
+        + Send
+        + Sync,
+>;
+
+/// How a [`SqlxJournal`] resolves a journaled write whose rows changed
+/// remotely after it was queued
+///
+/// Set with [`SqlxPlugin::with_conflict_policy`], along with the timestamp
+/// column rows are changed at. That column has to be written with
+/// [`SqlxValue::Timestamp`]s, e.g. from a [`SystemTime`](std::time::SystemTime)
+/// field, so it compares with the time the write was queued.
+///
+/// ```
+/// use sqlx::Sqlite;
+/// use bevy_sqlx::{SqlxConflictPolicy, SqlxDummy, SqlxPlugin, SqlxValue};
+///
+/// // Keep the remote text, but write the local score.
+/// let policy = SqlxConflictPolicy::merge(|local, remote| {
+///     let mut merged = remote.to_vec();
+///     merged.retain(|(name, _)| name == "text");
+///     merged.extend(local.iter().filter(|(name, _)| name == "score").cloned());
+///     Some(merged)
+/// });
+/// SqlxPlugin::<Sqlite, SqlxDummy>::from_url("sqlite:db/sqlite.db")
+///     .with_journal("db/journal.jsonl")
+///     .with_conflict_policy("updated_at", policy);
+/// ```
+#[derive(Clone, Default)]
+pub enum SqlxConflictPolicy {
+    /// The later write wins, which is the remote row, so the journaled
+    /// write is dropped
+    #[default]
+    LastWriteWins,
+    /// Write the columns merged by the function instead
+    Merge(SqlxMergeFn),
+}
+
+impl SqlxConflictPolicy {
+    /// Resolve conflicts with `merge`, see [`SqlxMergeFn`]
+    pub fn merge(
+        merge: impl Fn(
+                &[(String, SqlxValue)],
+                &[(String, SqlxValue)],
+            ) -> Option<Vec<(String, SqlxValue)>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        SqlxConflictPolicy::Merge(Arc::new(merge))
+    }
+}
+
+impl fmt::Debug for SqlxConflictPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SqlxConflictPolicy::LastWriteWins => f.write_str("LastWriteWins"),
+            SqlxConflictPolicy::Merge(_) => {
+                f.debug_tuple("Merge").finish_non_exhaustive()
+            }
+        }
+    }
+}
+
+/// A journaled write whose rows changed remotely after it was queued, and
+/// how it was resolved
+#[derive(Clone, Debug, PartialEq)]
+pub struct SqlxConflict {
+    pub id: SqlxEventId,
+    pub table: String,
+    /// When the write was journaled, in microseconds since the Unix epoch
+    pub queued_at: i64,
+    /// The columns of the journaled write
+    pub local: Vec<(String, SqlxValue)>,
+    /// The columns of the first changed remote row, leaving out those which
+    /// can't be decoded into a [`SqlxValue`]
+    pub remote: Vec<(String, SqlxValue)>,
+    pub resolution: SqlxConflictResolution,
+}
+
+/// What a [`SqlxConflictPolicy`] did with a [`SqlxConflict`]
+#[derive(Clone, Debug, PartialEq)]
+pub enum SqlxConflictResolution {
+    /// The journaled write was dropped
+    KeptRemote,
+    /// These merged columns were written instead
+    Merged(Vec<(String, SqlxValue)>),
+}
+
+/// Resolve `entry` with `policy` if its rows changed after it was queued,
+/// according to `column`, returning the conflict
+pub(crate) async fn resolve_conflict<DB>(
+    entry: &SqlxJournalEntry,
+    (column, policy): (&str, &SqlxConflictPolicy),
+    pool: &Pool<DB>,
+) -> Result<Option<SqlxConflict>, Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+    for<'r> SqlxValue: Decode<'r, DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    let Some(filters) = &entry.filters else {
+        return Ok(None);
+    };
+
+    let queued_at = SqlxValue::Timestamp(entry.queued_at);
+    let mut binds: Vec<_> =
+        filters.iter().map(|(_, value)| value.clone()).collect();
+    binds.push(queued_at);
+    let mut conditions: Vec<_> = filters
+        .iter()
+        .enumerate()
+        .map(|(i, (name, value))| {
+            format!("{name} = {}", typed_placeholder::<DB>(i + 1, value))
+        })
+        .collect();
+    let n = binds.len();
+    conditions.push(format!(
+        "{column} > {}",
+        typed_placeholder::<DB>(n, &binds[n - 1])
+    ));
+    let sql = format!(
+        "SELECT * FROM {} WHERE {}",
+        entry.table,
+        conditions.join(" AND ")
+    );
+    let mut query = sqlx::query(&sql);
+    for value in binds {
+        query = query.bind(value);
+    }
+    let Some(row) = query.fetch_optional(pool).await? else {
+        return Ok(None);
+    };
+    let remote: Vec<_> = row
+        .columns()
+        .iter()
+        .filter_map(|col| {
+            let value = row.try_get::<SqlxValue, _>(col.ordinal()).ok()?;
+            Some((col.name().to_string(), value))
+        })
+        .collect();
+
+    let merged = match policy {
+        SqlxConflictPolicy::LastWriteWins => None,
+        SqlxConflictPolicy::Merge(merge) => merge(&entry.columns, &remote),
+    };
+    if let Some(columns) = &merged {
+        update(&entry.table, columns, filters, pool).await?;
+    }
+    Ok(Some(SqlxConflict {
+        id: entry.id,
+        table: entry.table.clone(),
+        queued_at: entry.queued_at,
+        local: entry.columns.clone(),
+        remote,
+        resolution: merged.map_or(
+            SqlxConflictResolution::KeptRemote,
+            SqlxConflictResolution::Merged,
+        ),
+    }))
+}
+
+/// Update the rows of `table` matching `filters` with `columns`
+async fn update<DB>(
+    table: &str,
+    columns: &[(String, SqlxValue)],
+    filters: &[(String, SqlxValue)],
+    pool: &Pool<DB>,
+) -> Result<(), Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+{
+    if columns.is_empty() {
+        return Ok(());
+    }
+    let mut binds = 0;
+    let mut next = |(name, value): &(String, SqlxValue)| {
+        binds += 1;
+        format!("{name} = {}", typed_placeholder::<DB>(binds, value))
+    };
+    let sets: Vec<_> = columns.iter().map(&mut next).collect();
+    let conditions: Vec<_> = filters.iter().map(&mut next).collect();
+    let mut sql = format!("UPDATE {table} SET {}", sets.join(", "));
+    if !conditions.is_empty() {
+        sql.push_str(&format!(" WHERE {}", conditions.join(" AND ")));
+    }
+    let mut query = sqlx::query(&sql);
+    for (_, value) in columns.iter().chain(filters) {
+        query = query.bind(value.clone());
+    }
+    query.execute(pool).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{block_on, AsyncComputeTaskPool, TaskPool};
+    use bevy::utils::Duration;
+    use sqlx::{FromRow, Pool, Sqlite};
+    use std::time::SystemTime;
+
+    #[derive(Component, FromRow, Debug)]
+    struct Baz {
+        id: u32,
+        text: String,
+        score: u32,
+    }
+
+    impl PrimaryKey for Baz {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    impl ToRow for Baz {
+        fn table() -> &'static str {
+            "conflicts"
+        }
+
+        fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+            vec![
+                ("id", self.id.into()),
+                ("text", self.text.clone().into()),
+                ("score", self.score.into()),
+                ("updated_at", SystemTime::now().into()),
+            ]
+        }
+    }
+
+    #[test]
+    fn test_conflict_merge() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let path = std::env::temp_dir().join("bevy_sqlx_test_conflict.jsonl");
+        let _ = std::fs::remove_file(&path);
+        let pool: Pool<Sqlite> = block_on(Pool::connect(url)).unwrap();
+        let day = Duration::from_secs(86_400);
+        let changed = [SystemTime::now() + day, SystemTime::UNIX_EPOCH + day];
+        block_on(async {
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS conflicts (id INTEGER PRIMARY KEY, \
+                 text TEXT NOT NULL, score INTEGER NOT NULL, \
+                 updated_at TEXT NOT NULL)",
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+            sqlx::query("DELETE FROM conflicts").execute(&pool).await.unwrap();
+            for (id, updated_at) in [1, 2].into_iter().zip(changed) {
+                sqlx::query("INSERT INTO conflicts VALUES (?, 'remote', 1, ?)")
+                    .bind(id)
+                    .bind(SqlxValue::from(updated_at))
+                    .fetch_all(&pool)
+                    .await
+                    .unwrap();
+            }
+        });
+
+        // Remote rows 1 and 2 were changed after and before the writes.
+        let config = SqlxConfig::<Sqlite, Baz>::default();
+        let mut journal = SqlxJournal::<Sqlite, Baz>::new(path.clone());
+        let event_ids = SqlxEventIds::default();
+        let mut ids = Vec::new();
+        for id in [1, 2] {
+            let baz = Baz { id, text: "local".into(), score: 2 };
+            let event = SqlxEvent::<Sqlite, Baz>::upsert(&baz)
+                .with_id(event_ids.next());
+            let entry = event.journal_entry(&config, None).unwrap().unwrap();
+            journal.append(&entry).unwrap();
+            ids.push(entry.id);
+        }
+
+        let policy = SqlxConflictPolicy::merge(|local, remote| {
+            let text = remote.iter().find(|(name, _)| name == "text");
+            let score = local.iter().find(|(name, _)| name == "score");
+            Some(vec![text?.clone(), score?.clone()])
+        });
+        let mut app = App::new();
+        app.add_plugins(
+            SqlxPlugin::<Sqlite, Baz>::from_url(url)
+                .with_journal(&path)
+                .with_conflict_policy("updated_at", policy),
+        );
+        let mut conflicts = Vec::new();
+        let mut tries = 0;
+        while !app.world().resource::<SqlxJournal<Sqlite, Baz>>().is_empty()
+            && tries < 1000
+        {
+            app.update();
+            let events = app
+                .world()
+                .resource::<Events<SqlxJournalStatus<Sqlite, Baz>>>();
+            for status in events.iter_current_update_events() {
+                if let SqlxJournalStatus::Conflict(conflict) = status {
+                    conflicts.push(conflict.clone());
+                }
+            }
+            tries += 1;
+        }
+
+        assert_eq!(1, conflicts.len());
+        assert_eq!(
+            (ids[0], "conflicts"),
+            (conflicts[0].id, &*conflicts[0].table)
+        );
+        let merged = vec![
+            ("text".to_string(), SqlxValue::from("remote")),
+            ("score".to_string(), SqlxValue::Int(2)),
+        ];
+        assert_eq!(
+            SqlxConflictResolution::Merged(merged),
+            conflicts[0].resolution
+        );
+        let rows: Vec<(u32, String, u32)> = block_on(
+            sqlx::query_as("SELECT id, text, score FROM conflicts ORDER BY id")
+                .fetch_all(&pool),
+        )
+        .unwrap();
+        assert_eq!(vec![(1, "remote".into(), 2), (2, "local".into(), 2)], rows);
+    }
+}
@@ -0,0 +1,288 @@
+use crate::{SqlxCredentialsProvider, SqlxDatabase};
+use bevy::prelude::*;
+use bevy::tasks::futures_lite::future;
+use bevy::tasks::{block_on, AsyncComputeTaskPool, Task};
+use bevy::utils::{Duration, Instant};
+use sqlx::{Database, Error, Pool};
+use std::marker::PhantomData;
+
+/// An [`Event`] sent when the connection to the database is lost or restored
+///
+/// The connection is considered lost when an event's task fails with an I/O,
+/// TLS or pool timeout error, or with a database error in the SQL standard's
+/// connection exception class (`08`) or PostgreSQL's operator intervention
+/// class (`57P`), e.g. when the server is restarted.
+///
+/// While the connection is lost, failed and newly sent
+/// [`SqlxEvent`](crate::SqlxEvent)s are held, and
+/// [`SqlxTasks::handle_reconnect`](crate::SqlxTasks::handle_reconnect)
+/// tries to reconnect. Once restored, the held events are dispatched again.
+/// Generated writes can be kept in a durable [`SqlxJournal`](crate::SqlxJournal)
+/// instead.
+///
+/// ### Example
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::Sqlite;
+/// # use bevy_sqlx::SqlxConnectionStatus;
+/// fn connection(mut statuses: EventReader<SqlxConnectionStatus<Sqlite>>) {
+///     for status in statuses.read() {
+///         match status {
+///             SqlxConnectionStatus::Lost(_) => {},
+///             SqlxConnectionStatus::Restored(_) => {},
+///         }
+///     }
+/// }
+/// ```
+#[derive(Event, Debug)]
+pub enum SqlxConnectionStatus<DB: Database> {
+    Lost(PhantomData<DB>),
+    Restored(PhantomData<DB>),
+}
+
+/// Return true if `err` means the connection to the database was lost
+pub(crate) fn is_connection_error(err: &Error) -> bool {
+    match err {
+        Error::Io(_) | Error::Tls(_) | Error::PoolTimedOut => true,
+        Error::Database(err) => err.code().is_some_and(|code| {
+            code.starts_with("08") || code.starts_with("57P")
+        }),
+        _ => false,
+    }
+}
+
+/// Reconnection attempts, with exponential backoff
+#[derive(Debug)]
+pub(crate) struct SqlxReconnect {
+    delay: Duration,
+    next_attempt: Instant,
+    attempt: Option<Task<Result<(), Error>>>,
+}
+
+impl SqlxReconnect {
+    const INITIAL_DELAY: Duration = Duration::from_millis(100);
+    const MAX_DELAY: Duration = Duration::from_secs(30);
+
+    pub(crate) fn new() -> Self {
+        SqlxReconnect {
+            delay: Self::INITIAL_DELAY,
+            next_attempt: Instant::now(),
+            attempt: None,
+        }
+    }
+
+    /// Make progress reconnecting to `pool`, returning true once a connection
+    /// has been acquired
+    ///
+    /// Each attempt first reads the URL from `credentials` again, if any.
+    pub(crate) fn poll<DB: Database>(
+        &mut self,
+        pool: &Pool<DB>,
+        credentials: Option<&SqlxCredentialsProvider>,
+    ) -> bool {
+        if let Some(attempt) = &mut self.attempt {
+            match block_on(future::poll_once(attempt)) {
+                None => return false,
+                Some(Ok(())) => return true,
+                Some(Err(_)) => {
+                    self.attempt = None;
+                    self.next_attempt = Instant::now() + self.delay;
+                    self.delay = (self.delay * 2).min(Self::MAX_DELAY);
+                }
+            }
+        }
+
+        if self.attempt.is_none() && Instant::now() >= self.next_attempt {
+            let (pool, credentials) = (pool.clone(), credentials.cloned());
+            let attempt = async move {
+                if let Some(credentials) = credentials {
+                    credentials.apply(&pool)?;
+                }
+                pool.acquire().await.map(|_| ())
+            };
+            self.attempt = Some(AsyncComputeTaskPool::get().spawn(attempt));
+        }
+        false
+    }
+}
+
+/// An [`Event`] sent when the pool of `DB` is first established, and each
+/// time the connection is restored
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::Sqlite;
+/// # use bevy_sqlx::{SqlxConnected, SqlxDisconnected};
+/// fn hud(
+///     mut connected: EventReader<SqlxConnected<Sqlite>>,
+///     mut disconnected: EventReader<SqlxDisconnected<Sqlite>>,
+/// ) {
+///     for _ in connected.read() {
+///         info!("database online");
+///     }
+///     for _ in disconnected.read() {
+///         warn!("database offline");
+///     }
+/// }
+/// ```
+#[derive(Event, Debug)]
+pub struct SqlxConnected<DB: Database>(PhantomData<DB>);
+
+/// An [`Event`] sent when the connection to `DB` is lost, or couldn't be
+/// established in the first place
+#[derive(Event, Debug)]
+pub struct SqlxDisconnected<DB: Database>(PhantomData<DB>);
+
+/// A [`Resource`] tracking whether `DB` is available, shared by every plugin
+/// using `DB`
+///
+/// The pool is checked once when the app starts, and then follows the
+/// [`SqlxConnectionStatus`] of every plugin.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::Sqlite;
+/// # use bevy_sqlx::SqlxConnectionState;
+/// fn save_button(state: Res<SqlxConnectionState<Sqlite>>) {
+///     let enabled = state.is_connected();
+/// }
+/// ```
+#[derive(Resource, Debug)]
+pub struct SqlxConnectionState<DB: Database> {
+    connected: Option<bool>,
+    since: Option<Instant>,
+    checking: Option<Task<Result<(), Error>>>,
+    _db: PhantomData<fn() -> DB>,
+}
+
+impl<DB: Database> Default for SqlxConnectionState<DB> {
+    fn default() -> Self {
+        SqlxConnectionState {
+            connected: None,
+            since: None,
+            checking: None,
+            _db: PhantomData,
+        }
+    }
+}
+
+impl<DB: Database> SqlxConnectionState<DB> {
+    /// Return true while the database is available
+    pub fn is_connected(&self) -> bool {
+        self.connected == Some(true)
+    }
+
+    /// Return true until the first connection check has finished
+    pub fn is_connecting(&self) -> bool {
+        self.connected.is_none()
+    }
+
+    /// When the database became available or unavailable
+    pub fn since(&self) -> Option<Instant> {
+        self.since
+    }
+
+    /// Record whether the database is available, returning true if that
+    /// changed
+    fn set(&mut self, connected: bool) -> bool {
+        if self.connected == Some(connected) {
+            return false;
+        }
+        self.connected = Some(connected);
+        self.since = Some(Instant::now());
+        true
+    }
+}
+
+impl<DB: Database + Sync> SqlxConnectionState<DB> {
+    /// A [`System`] checking the pool once, then following the
+    /// [`SqlxConnectionStatus`] events, and sending [`SqlxConnected`] and
+    /// [`SqlxDisconnected`] events when the state changes
+    pub fn handle_connection(
+        database: Res<SqlxDatabase<DB>>,
+        mut state: ResMut<Self>,
+        mut statuses: EventReader<SqlxConnectionStatus<DB>>,
+        mut connected: EventWriter<SqlxConnected<DB>>,
+        mut disconnected: EventWriter<SqlxDisconnected<DB>>,
+    ) {
+        let mut changes = Vec::new();
+        if state.is_connecting() {
+            let checking = state.checking.get_or_insert_with(|| {
+                let pool = database.pool.clone();
+                let check = async move { pool.acquire().await.map(|_| ()) };
+                AsyncComputeTaskPool::get().spawn(check)
+            });
+            if let Some(result) = block_on(future::poll_once(checking)) {
+                state.checking = None;
+                changes.push(result.is_ok());
+            }
+        }
+        changes.extend(
+            statuses.read().map(|status| {
+                matches!(status, SqlxConnectionStatus::Restored(_))
+            }),
+        );
+
+        for change in changes {
+            if !state.set(change) {
+                continue;
+            }
+            if change {
+                connected.send(SqlxConnected(PhantomData));
+            } else {
+                disconnected.send(SqlxDisconnected(PhantomData));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{AsyncComputeTaskPool, TaskPool};
+    use sqlx::Sqlite;
+    use std::marker::PhantomData;
+
+    fn update_until(app: &mut App, done: impl Fn(&App) -> bool) -> Vec<bool> {
+        let mut changes = Vec::new();
+        let mut tries = 0;
+        while tries == 0 || !done(app) && tries < 1000 {
+            app.update();
+            let world = app.world();
+            let connected = world.resource::<Events<SqlxConnected<Sqlite>>>();
+            let disconnected =
+                world.resource::<Events<SqlxDisconnected<Sqlite>>>();
+            changes
+                .extend(connected.iter_current_update_events().map(|_| true));
+            changes.extend(
+                disconnected.iter_current_update_events().map(|_| false),
+            );
+            tries += 1;
+        }
+        changes
+    }
+
+    #[test]
+    fn test_connection_state() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let mut app = App::new();
+        app.add_plugins(SqlxPlugin::<Sqlite, SqlxDummy>::from_url(url));
+        let connected = |app: &App| {
+            app.world().resource::<SqlxConnectionState<Sqlite>>().is_connected()
+        };
+
+        assert_eq!(vec![true], update_until(&mut app, connected));
+
+        let lost = SqlxConnectionStatus::<Sqlite>::Lost(PhantomData);
+        app.world_mut().send_event(lost);
+        let changes = update_until(&mut app, |app| !connected(app));
+        assert_eq!(vec![false], changes);
+
+        let restored = SqlxConnectionStatus::<Sqlite>::Restored(PhantomData);
+        app.world_mut().send_event(restored);
+        assert_eq!(vec![true], update_until(&mut app, connected));
+    }
+}
@@ -0,0 +1,327 @@
+use crate::*;
+use sqlx::{Database, Encode, Error, Executor, IntoArguments, Pool, Type};
+use std::sync::Arc;
+
+/// The number of rows in each `INSERT` of a [`SqlxEvent::copy_in`], or
+/// each chunk of `COPY` data sent to Postgres
+pub const COPY_BATCH_SIZE: usize = 100;
+
+/// The rows of a [`SqlxEvent::copy_in`] or [`SqlxEvent::upsert_many`], from
+/// [`ToRow::to_row`]
+pub(crate) type SqlxCopyRows = Arc<[Vec<(&'static str, SqlxValue)>]>;
+
+/// Insert `rows` into `table` in a single transaction, returning the number
+/// of rows inserted
+///
+/// With the `postgres` feature, Postgres pools stream the rows with
+/// `COPY ... FROM STDIN`. Other databases insert [`COPY_BATCH_SIZE`] rows
+/// per `INSERT` statement. The columns are those of the first row.
+pub(crate) async fn copy_in<DB>(
+    table: &str,
+    rows: SqlxCopyRows,
+    progress: Arc<SqlxProgress>,
+    pool: Pool<DB>,
+) -> Result<u64, Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+{
+    let Some(first) = rows.first() else {
+        return Ok(0);
+    };
+    let names: Vec<_> = first.iter().map(|(name, _)| *name).collect();
+
+    #[cfg(feature = "postgres")]
+    if let Some(pool) =
+        (&pool as &dyn std::any::Any).downcast_ref::<Pool<sqlx::Postgres>>()
+    {
+        return copy_in_postgres(table, &names, &rows, progress, pool).await;
+    }
+
+    let mut tx = pool.begin().await?;
+    for batch in rows.chunks(COPY_BATCH_SIZE) {
+        let sql = insert_sql::<DB>(table, &names, batch);
+        let mut query = sqlx::query(&sql);
+        for (_, value) in batch.iter().flatten() {
+            query = query.bind(value.clone());
+        }
+        query.execute(&mut *tx).await?;
+        progress.add(batch.len() as u64);
+    }
+    tx.commit().await?;
+    Ok(rows.len() as u64)
+}
+
+/// Upsert `rows` into `table` on its `key` column in a single transaction,
+/// returning the number of rows upserted
+///
+/// Each `INSERT` has as many rows as the database can bind at once. The
+/// columns are those of the first row.
+pub(crate) async fn upsert_many<DB>(
+    table: &str,
+    key: &'static str,
+    rows: SqlxCopyRows,
+    progress: Arc<SqlxProgress>,
+    pool: Pool<DB>,
+) -> Result<u64, Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+{
+    let Some(first) = rows.first() else {
+        return Ok(0);
+    };
+    let names: Vec<_> = first.iter().map(|(name, _)| *name).collect();
+    let chunk = (max_binds::<DB>() / names.len().max(1)).max(1);
+
+    let mut tx = pool.begin().await?;
+    for batch in rows.chunks(chunk) {
+        let mut sql = insert_sql::<DB>(table, &names, batch);
+        sql.push_str(&on_conflict::<DB>(key, &names));
+        let mut query = sqlx::query(&sql);
+        for (_, value) in batch.iter().flatten() {
+            query = query.bind(value.clone());
+        }
+        query.execute(&mut *tx).await?;
+        progress.add(batch.len() as u64);
+    }
+    tx.commit().await?;
+    Ok(rows.len() as u64)
+}
+
+/// An `INSERT` of every row in `batch` into the `names` columns of `table`
+fn insert_sql<DB: Database>(
+    table: &str,
+    names: &[&str],
+    batch: &[Vec<(&'static str, SqlxValue)>],
+) -> String {
+    let mut n = 0;
+    let values: Vec<_> = batch
+        .iter()
+        .map(|row| {
+            let placeholders: Vec<_> = row
+                .iter()
+                .map(|(_, value)| {
+                    n += 1;
+                    typed_placeholder::<DB>(n, value)
+                })
+                .collect();
+            format!("({})", placeholders.join(", "))
+        })
+        .collect();
+    format!(
+        "INSERT INTO {table} ({}) VALUES {}",
+        names.join(", "),
+        values.join(", ")
+    )
+}
+
+/// Stream `rows` to Postgres as CSV `COPY` data
+#[cfg(feature = "postgres")]
+async fn copy_in_postgres(
+    table: &str,
+    names: &[&str],
+    rows: &[Vec<(&'static str, SqlxValue)>],
+    progress: Arc<SqlxProgress>,
+    pool: &Pool<sqlx::Postgres>,
+) -> Result<u64, Error> {
+    use sqlx::postgres::PgPoolCopyExt;
+
+    let sql = format!(
+        "COPY {table} ({}) FROM STDIN WITH (FORMAT csv)",
+        names.join(", ")
+    );
+    let mut copy = pool.copy_in_raw(&sql).await?;
+    for batch in rows.chunks(COPY_BATCH_SIZE) {
+        let mut data = String::new();
+        for row in batch {
+            let values: Vec<_> =
+                row.iter().map(|(_, value)| copy_value(value)).collect();
+            data.push_str(&values.join(","));
+            data.push('\n');
+        }
+        if let Err(err) = copy.send(data.into_bytes()).await {
+            copy.abort(err.to_string()).await?;
+            return Err(err);
+        }
+        progress.add(batch.len() as u64);
+    }
+    copy.finish().await
+}
+
+/// A CSV `COPY` field, where only unquoted empty fields are `NULL`
+#[cfg(feature = "postgres")]
+fn copy_value(value: &SqlxValue) -> String {
+    let quoted = |field: &str| format!("\"{}\"", field.replace('"', "\"\""));
+    match value {
+        SqlxValue::Text(text) => quoted(text),
+        SqlxValue::Array(array) => quoted(&array.literal()),
+        SqlxValue::Bytes(bytes) => {
+            let hex: String =
+                bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+            format!("\\x{hex}")
+        }
+        value => csv_value(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{block_on, AsyncComputeTaskPool, TaskPool};
+    use sqlx::{FromRow, Pool, Sqlite};
+
+    #[derive(Component, FromRow, Debug)]
+    struct Baz {
+        id: u32,
+        text: String,
+    }
+
+    impl PrimaryKey for Baz {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    impl ToRow for Baz {
+        fn table() -> &'static str {
+            "copies"
+        }
+
+        fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+            vec![("id", self.id.into()), ("text", self.text.clone().into())]
+        }
+    }
+
+    #[test]
+    fn test_copy_in() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let pool: Pool<Sqlite> = block_on(Pool::connect(url)).unwrap();
+        block_on(async {
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS copies (id INTEGER PRIMARY KEY, \
+                 text TEXT NOT NULL)",
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+            sqlx::query("DELETE FROM copies").execute(&pool).await.unwrap();
+        });
+
+        let mut app = App::new();
+        app.add_plugins(SqlxPlugin::<Sqlite, Baz>::from_url(url));
+        let count = COPY_BATCH_SIZE as u32 * 2 + 50;
+        let bazs = (1..=count)
+            .map(|id| Baz { id, text: format!("baz {id}") })
+            .collect();
+        let event = SqlxEvent::<Sqlite, Baz>::copy_in(bazs);
+        let id = app.world().resource::<SqlxEventIds>().next();
+        let event = event.with_id(id);
+        app.world_mut().send_event(event);
+        let mut done = None;
+        let mut tries = 0;
+        while done.is_none() && tries < 1000 {
+            app.update();
+            let events =
+                app.world().resource::<Events<SqlxEventStatus<Sqlite, Baz>>>();
+            for status in events.iter_current_update_events() {
+                if let SqlxEventStatus::Done(done_id, rows) = status {
+                    done = (*done_id == id).then_some(*rows);
+                }
+            }
+            tries += 1;
+        }
+
+        assert_eq!(Some(count as u64), done);
+        let (rows, last): (u32, String) = block_on(
+            sqlx::query_as("SELECT COUNT(*), MAX(text) FROM copies")
+                .fetch_one(&pool),
+        )
+        .unwrap();
+        assert_eq!((count, "baz 99".into()), (rows, last));
+    }
+
+    #[derive(Component, FromRow, Debug)]
+    struct Qux {
+        id: u32,
+        text: String,
+    }
+
+    impl PrimaryKey for Qux {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    impl ToRow for Qux {
+        fn table() -> &'static str {
+            "upserts"
+        }
+
+        fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+            vec![("id", self.id.into()), ("text", self.text.clone().into())]
+        }
+    }
+
+    #[test]
+    fn test_upsert_many() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let pool: Pool<Sqlite> = block_on(Pool::connect(url)).unwrap();
+        block_on(async {
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS upserts (id INTEGER PRIMARY KEY, \
+                 text TEXT NOT NULL)",
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+            sqlx::query("DELETE FROM upserts").execute(&pool).await.unwrap();
+            sqlx::query("INSERT INTO upserts VALUES (1, 'old'), (2, 'old')")
+                .execute(&pool)
+                .await
+                .unwrap();
+        });
+
+        let mut app = App::new();
+        app.add_plugins(SqlxPlugin::<Sqlite, Qux>::from_url(url));
+        // More rows than SQLite binds in one statement, two columns each.
+        let count = 20_000;
+        let quxs = (1..=count)
+            .map(|id| Qux { id, text: format!("qux {id}") })
+            .collect();
+        let event = SqlxEvent::<Sqlite, Qux>::upsert_many(quxs);
+        let id = app.world().resource::<SqlxEventIds>().next();
+        let event = event.with_id(id);
+        app.world_mut().send_event(event);
+        let mut done = None;
+        let mut tries = 0;
+        while done.is_none() && tries < 1000 {
+            app.update();
+            let events =
+                app.world().resource::<Events<SqlxEventStatus<Sqlite, Qux>>>();
+            for status in events.iter_current_update_events() {
+                if let SqlxEventStatus::Done(done_id, rows) = status {
+                    done = (*done_id == id).then_some(*rows);
+                }
+            }
+            tries += 1;
+        }
+
+        assert_eq!(Some(count as u64), done);
+        let (rows, first): (u32, String) = block_on(
+            sqlx::query_as("SELECT COUNT(*), MIN(text) FROM upserts")
+                .fetch_one(&pool),
+        )
+        .unwrap();
+        assert_eq!((count, "qux 1".into()), (rows, first));
+    }
+}
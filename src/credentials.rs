@@ -0,0 +1,210 @@
+use crate::SqlxDatabase;
+use bevy::prelude::*;
+use bevy::tasks::futures_lite::future;
+use bevy::tasks::{block_on, AsyncComputeTaskPool, Task};
+use bevy::utils::{Duration, Instant};
+use sqlx::{Connection, Database, Error, Pool};
+use std::fmt;
+use std::marker::PhantomData;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// The connect options of `DB`
+type SqlxConnectOptions<DB> =
+    <<DB as Database>::Connection as Connection>::Options;
+
+/// Where the URL of the database is read from, each time it's needed
+///
+/// A plugin built [`SqlxPlugin::from_credentials`](crate::SqlxPlugin::from_credentials)
+/// reads the URL when it's built, before every reconnection attempt, see
+/// [`SqlxConnectionStatus`](crate::SqlxConnectionStatus), and every
+/// [`SqlxPlugin::with_credentials_refresh`](crate::SqlxPlugin::with_credentials_refresh)
+/// interval. New connections of the pool use the latest URL, so short lived
+/// tokens, like cloud IAM authentication, can be rotated without restarting
+/// the app.
+///
+/// ```
+/// use sqlx::Sqlite;
+/// use bevy_sqlx::{SqlxPlugin, SqlxDummy, SqlxCredentialsProvider};
+///
+/// # std::env::set_var("DATABASE_URL", "sqlite:db/sqlite.db");
+/// let credentials = SqlxCredentialsProvider::env("DATABASE_URL");
+/// SqlxPlugin::<Sqlite, SqlxDummy>::from_credentials(credentials);
+/// ```
+#[derive(Clone)]
+pub enum SqlxCredentialsProvider {
+    /// The URL in an environment variable
+    Env(String),
+    /// The URL in a file, like a mounted secret, ignoring surrounding
+    /// whitespace
+    File(PathBuf),
+    /// The URL returned by a function, like one generating an access token
+    Callback(Arc<dyn Fn() -> Result<String, Error> + Send + Sync>),
+}
+
+impl fmt::Debug for SqlxCredentialsProvider {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SqlxCredentialsProvider::Env(var) => {
+                f.debug_tuple("Env").field(var).finish()
+            }
+            SqlxCredentialsProvider::File(path) => {
+                f.debug_tuple("File").field(path).finish()
+            }
+            SqlxCredentialsProvider::Callback(_) => {
+                f.debug_tuple("Callback").finish_non_exhaustive()
+            }
+        }
+    }
+}
+
+impl SqlxCredentialsProvider {
+    /// Read the URL from the environment variable `var`
+    pub fn env(var: impl Into<String>) -> Self {
+        SqlxCredentialsProvider::Env(var.into())
+    }
+
+    /// Read the URL from the file at `path`
+    pub fn file(path: impl Into<PathBuf>) -> Self {
+        SqlxCredentialsProvider::File(path.into())
+    }
+
+    /// Get the URL from `callback`
+    pub fn callback(
+        callback: impl Fn() -> Result<String, Error> + Send + Sync + 'static,
+    ) -> Self {
+        SqlxCredentialsProvider::Callback(Arc::new(callback))
+    }
+
+    /// Read the current URL
+    pub fn url(&self) -> Result<String, Error> {
+        match self {
+            SqlxCredentialsProvider::Env(var) => {
+                std::env::var(var).map_err(|err| {
+                    Error::Configuration(format!("{var}: {err}").into())
+                })
+            }
+            SqlxCredentialsProvider::File(path) => {
+                let url = std::fs::read_to_string(path)?;
+                Ok(url.trim().into())
+            }
+            SqlxCredentialsProvider::Callback(callback) => callback(),
+        }
+    }
+
+    /// Read the current URL, as the connect options of `DB`
+    pub fn options<DB: Database>(
+        &self,
+    ) -> Result<SqlxConnectOptions<DB>, Error> {
+        self.url()?.parse()
+    }
+
+    /// Read the current URL, and use it for the new connections of `pool`
+    pub(crate) fn apply<DB: Database>(
+        &self,
+        pool: &Pool<DB>,
+    ) -> Result<(), Error> {
+        pool.set_connect_options(self.options::<DB>()?);
+        Ok(())
+    }
+}
+
+/// A [`Resource`] holding the [`SqlxCredentialsProvider`] of `DB`'s pool
+///
+/// Added by a plugin built with
+/// [`SqlxPlugin::from_credentials`](crate::SqlxPlugin::from_credentials).
+#[derive(Resource, Debug)]
+pub struct SqlxCredentials<DB: Database> {
+    pub provider: SqlxCredentialsProvider,
+    /// How often the URL is read again
+    pub refresh: Option<Duration>,
+    next_refresh: Instant,
+    refreshing: Option<Task<Result<(), Error>>>,
+    _db: PhantomData<fn() -> DB>,
+}
+
+impl<DB: Database> SqlxCredentials<DB> {
+    pub fn new(
+        provider: SqlxCredentialsProvider,
+        refresh: Option<Duration>,
+    ) -> Self {
+        SqlxCredentials {
+            provider,
+            refresh,
+            next_refresh: Instant::now() + refresh.unwrap_or_default(),
+            refreshing: None,
+            _db: PhantomData,
+        }
+    }
+
+    /// A [`System`] reading the URL again every [`Self::refresh`] interval,
+    /// for the new connections of the [`SqlxDatabase`]
+    ///
+    /// A URL which can't be read is logged, and the previous one is kept.
+    pub fn handle_refresh(
+        database: Res<SqlxDatabase<DB>>,
+        mut credentials: ResMut<Self>,
+    ) {
+        if let Some(refreshing) = &mut credentials.refreshing {
+            match block_on(future::poll_once(refreshing)) {
+                None => return,
+                Some(Ok(())) => {}
+                Some(Err(err)) => {
+                    warn!("failed to refresh {} credentials: {err}", DB::NAME);
+                }
+            }
+            credentials.refreshing = None;
+        }
+
+        let Some(refresh) = credentials.refresh else {
+            return;
+        };
+        if Instant::now() < credentials.next_refresh {
+            return;
+        }
+        credentials.next_refresh = Instant::now() + refresh;
+        let (provider, pool) =
+            (credentials.provider.clone(), database.pool.clone());
+        let refreshing = async move { provider.apply(&pool) };
+        credentials.refreshing =
+            Some(AsyncComputeTaskPool::get().spawn(refreshing));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{AsyncComputeTaskPool, TaskPool};
+    use bevy::utils::Duration;
+    use sqlx::Sqlite;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_credentials() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = Arc::new(Mutex::new(String::from("sqlite:db/sqlite.db")));
+        let current = url.clone();
+        let provider = SqlxCredentialsProvider::callback(move || {
+            Ok(current.lock().unwrap().clone())
+        });
+        let mut app = App::new();
+        app.add_plugins(
+            SqlxPlugin::<Sqlite, SqlxDummy>::from_credentials(provider)
+                .with_credentials_refresh(Duration::ZERO),
+        );
+
+        let path = std::env::temp_dir().join("bevy_sqlx_test_credentials.db");
+        *url.lock().unwrap() = format!("sqlite:{}", path.display());
+        let filename = |app: &App| {
+            let database = app.world().resource::<SqlxDatabase<Sqlite>>();
+            database.pool.connect_options().get_filename().to_path_buf()
+        };
+        let mut tries = 0;
+        while filename(&app) != path && tries < 1000 {
+            app.update();
+            tries += 1;
+        }
+        assert_eq!(path, filename(&app));
+    }
+}
@@ -1,3 +1,5 @@
+use crate::*;
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use sqlx::{Database, Pool};
 
@@ -33,3 +35,121 @@ use sqlx::{Database, Pool};
 pub struct SqlxDatabase<DB: Database> {
     pub pool: Pool<DB>,
 }
+
+/// A [`SystemParam`] for using the [`Pool`] of `DB` directly
+///
+/// The pool is cheap to clone, and can be moved into futures. To run them
+/// without blocking the frame, send them as events with [`Self::send`],
+/// which returns the id of their statuses. [`SqlxEvent::call`] futures
+/// return components, and [`SqlxEvent::call_scalar`] futures a single
+/// value.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::{Row, Sqlite};
+/// # use bevy_sqlx::*;
+/// #[derive(Resource)]
+/// struct Counting(SqlxEventId);
+///
+/// fn count(mut commands: Commands, mut db: SqlxPool<Sqlite>) {
+///     let sql = "SELECT COUNT(*) FROM foos";
+///     let event = SqlxEvent::<Sqlite, SqlxDummy>::call_scalar(move |pool| {
+///         async move {
+///             let row = sqlx::query(sql).fetch_one(&pool).await?;
+///             Ok(row.try_get::<i64, _>(0)?.into())
+///         }
+///     });
+///     commands.insert_resource(Counting(db.send(event)));
+/// }
+///
+/// fn counted(
+///     counting: Res<Counting>,
+///     mut statuses: EventReader<SqlxEventStatus<Sqlite, SqlxDummy>>,
+/// ) {
+///     for status in statuses.read() {
+///         if let SqlxEventStatus::Scalar(id, count) = status {
+///             if *id == counting.0 {
+///                 info!("{count:?} foos");
+///             }
+///         }
+///     }
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct SqlxPool<'w, 's, DB: Database> {
+    database: Res<'w, SqlxDatabase<DB>>,
+    ids: Res<'w, SqlxEventIds>,
+    commands: Commands<'w, 's>,
+}
+
+impl<DB: Database + Sync> SqlxPool<'_, '_, DB> {
+    /// A clone of the pool
+    pub fn pool(&self) -> Pool<DB> {
+        self.database.pool.clone()
+    }
+
+    /// Send `event` once commands are applied, returning its id
+    pub fn send<C>(&mut self, event: SqlxEvent<DB, C>) -> SqlxEventId
+    where
+        C: SqlxComponent<DB::Row>,
+    {
+        let id = self.ids.next();
+        self.commands.add(event.with_id(id));
+        id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{block_on, AsyncComputeTaskPool, TaskPool};
+    use sqlx::{Row, Sqlite};
+
+    #[derive(Resource)]
+    struct Sent(SqlxEventId);
+
+    fn send_scalar(mut commands: Commands, mut db: SqlxPool<Sqlite>) {
+        let sql = "SELECT 41";
+        let answer: i64 =
+            block_on(sqlx::query_scalar(sql).fetch_one(&db.pool())).unwrap();
+        let event = SqlxEvent::<Sqlite, SqlxDummy>::call_scalar(
+            move |pool| async move {
+                let row = sqlx::query("SELECT ? + 1")
+                    .bind(answer)
+                    .fetch_one(&pool)
+                    .await?;
+                Ok(row.try_get::<i64, _>(0)?.into())
+            },
+        );
+        commands.insert_resource(Sent(db.send(event)));
+    }
+
+    #[test]
+    fn test_pool() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let mut app = App::new();
+        app.add_plugins(SqlxPlugin::<Sqlite, SqlxDummy>::from_url(url));
+        app.add_systems(Startup, send_scalar);
+
+        let mut scalar = None;
+        let mut tries = 0;
+        while scalar.is_none() && tries < 1000 {
+            app.update();
+            let sent = app.world().get_resource::<Sent>().map(|sent| sent.0);
+            let events = app
+                .world()
+                .resource::<Events<SqlxEventStatus<Sqlite, SqlxDummy>>>();
+            for status in events.iter_current_update_events() {
+                if let SqlxEventStatus::Scalar(id, value) = status {
+                    if Some(*id) == sent {
+                        scalar = Some(value.clone());
+                    }
+                }
+            }
+            tries += 1;
+        }
+        assert_eq!(Some(SqlxValue::from(42i64)), scalar);
+    }
+}
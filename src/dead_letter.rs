@@ -0,0 +1,205 @@
+use crate::*;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use sqlx::{Database, Error};
+use std::time::SystemTime;
+
+/// A failed [`SqlxEvent`] kept in the [`SqlxDeadLetters`]
+pub struct SqlxDeadLetter<DB: Database, C: SqlxComponent<DB::Row>> {
+    pub event: SqlxEvent<DB, C>,
+    /// The error of the last attempt
+    pub error: String,
+    /// How many times the event failed
+    pub attempts: u32,
+    pub first_failed: SystemTime,
+    pub last_failed: SystemTime,
+}
+
+/// A [`Resource`] retaining the events which failed, so they can be retried
+/// once the problem is fixed, or dropped
+///
+/// Added by a [`SqlxPlugin`] built with [`SqlxPlugin::with_dead_letters`].
+/// Events held while the connection is lost aren't dead, see
+/// [`SqlxConnectionStatus`].
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::Sqlite;
+/// # use bevy_sqlx::{SqlxDeadLetters, SqlxDummy};
+/// fn retry_all(mut letters: ResMut<SqlxDeadLetters<Sqlite, SqlxDummy>>) {
+///     for id in letters.ids() {
+///         letters.retry(id);
+///     }
+/// }
+/// ```
+#[derive(Resource)]
+pub struct SqlxDeadLetters<DB: Database, C: SqlxComponent<DB::Row>> {
+    letters: Vec<SqlxDeadLetter<DB, C>>,
+    retrying: HashMap<SqlxEventId, (u32, SystemTime)>,
+    retries: Vec<SqlxEvent<DB, C>>,
+}
+
+impl<DB: Database, C: SqlxComponent<DB::Row>> Default
+    for SqlxDeadLetters<DB, C>
+{
+    fn default() -> Self {
+        SqlxDeadLetters {
+            letters: Vec::new(),
+            retrying: HashMap::new(),
+            retries: Vec::new(),
+        }
+    }
+}
+
+impl<DB: Database + Sync, C: SqlxComponent<DB::Row>> SqlxDeadLetters<DB, C> {
+    /// The number of failed events
+    pub fn len(&self) -> usize {
+        self.letters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.letters.is_empty()
+    }
+
+    /// The ids of the failed events, oldest first
+    pub fn ids(&self) -> Vec<SqlxEventId> {
+        self.letters.iter().map(|letter| letter.event.assigned_id()).collect()
+    }
+
+    pub fn get(&self, id: SqlxEventId) -> Option<&SqlxDeadLetter<DB, C>> {
+        self.letters.iter().find(|letter| letter.event.id() == Some(id))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &SqlxDeadLetter<DB, C>> {
+        self.letters.iter()
+    }
+
+    /// Send the failed event `id` again, returning false if there's no such
+    /// event
+    ///
+    /// If it fails again, it's kept with its attempts counted.
+    pub fn retry(&mut self, id: SqlxEventId) -> bool {
+        let Some(letter) = self.remove(id) else {
+            return false;
+        };
+        self.retrying.insert(id, (letter.attempts, letter.first_failed));
+        self.retries.push(letter.event);
+        true
+    }
+
+    /// Forget the failed event `id`, returning it
+    pub fn remove(&mut self, id: SqlxEventId) -> Option<SqlxDeadLetter<DB, C>> {
+        let index = self
+            .letters
+            .iter()
+            .position(|letter| letter.event.id() == Some(id))?;
+        Some(self.letters.remove(index))
+    }
+
+    /// Forget every failed event
+    pub fn clear(&mut self) {
+        self.letters.clear();
+    }
+
+    /// Keep `event`, which failed with `err`
+    pub(crate) fn fail(&mut self, event: SqlxEvent<DB, C>, err: &Error) {
+        let now = SystemTime::now();
+        let (attempts, first_failed) =
+            self.retrying.remove(&event.assigned_id()).unwrap_or((0, now));
+        self.letters.push(SqlxDeadLetter {
+            event,
+            error: err.to_string(),
+            attempts: attempts + 1,
+            first_failed,
+            last_failed: now,
+        });
+    }
+
+    /// Forget the attempts of a retried event which succeeded
+    pub(crate) fn succeed(&mut self, id: SqlxEventId) {
+        self.retrying.remove(&id);
+    }
+
+    /// A [`System`] sending the events being retried
+    pub fn handle_retries(
+        mut letters: ResMut<Self>,
+        mut events: EventWriter<SqlxEvent<DB, C>>,
+    ) {
+        if !letters.retries.is_empty() {
+            events.send_batch(letters.retries.drain(..));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{AsyncComputeTaskPool, TaskPool};
+    use sqlx::{FromRow, Sqlite};
+
+    #[derive(Component, FromRow, Debug)]
+    struct Foo {
+        id: u32,
+    }
+
+    impl PrimaryKey for Foo {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    fn wait_for_error(app: &mut App, id: SqlxEventId) {
+        let mut tries = 0;
+        let mut failed = false;
+        while !failed && tries < 1000 {
+            app.update();
+            let events =
+                app.world().resource::<Events<SqlxEventStatus<Sqlite, Foo>>>();
+            failed = events.iter_current_update_events().any(|status| {
+                matches!(status, SqlxEventStatus::Error(e, ..) if *e == id)
+            });
+            tries += 1;
+        }
+        assert!(failed);
+    }
+
+    #[test]
+    fn test_dead_letters() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let mut app = App::new();
+        app.add_plugins(
+            SqlxPlugin::<Sqlite, Foo>::from_url(url).with_dead_letters(),
+        );
+
+        let event = SqlxEvent::<Sqlite, Foo>::query("SELECT * FROM missing");
+        let id = app.world().resource::<SqlxEventIds>().next();
+        let event = event.with_id(id);
+        app.world_mut().send_event(event);
+        wait_for_error(&mut app, id);
+
+        let letters = app.world().resource::<SqlxDeadLetters<Sqlite, Foo>>();
+        assert_eq!(vec![id], letters.ids());
+        let letter = letters.get(id).unwrap();
+        assert_eq!(1, letter.attempts);
+        assert!(letter.error.contains("missing"));
+        let first_failed = letter.first_failed;
+
+        let mut letters =
+            app.world_mut().resource_mut::<SqlxDeadLetters<Sqlite, Foo>>();
+        assert!(letters.retry(id));
+        assert!(letters.is_empty());
+        wait_for_error(&mut app, id);
+
+        let mut letters =
+            app.world_mut().resource_mut::<SqlxDeadLetters<Sqlite, Foo>>();
+        let letter = letters.remove(id).unwrap();
+        assert_eq!(2, letter.attempts);
+        assert_eq!(first_failed, letter.first_failed);
+        assert!(letter.last_failed >= first_failed);
+        assert!(letters.is_empty());
+        assert!(!letters.retry(id));
+    }
+}
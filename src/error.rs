@@ -0,0 +1,288 @@
+use crate::*;
+use bevy::prelude::*;
+use sqlx::error::ErrorKind;
+use sqlx::{Database, Error};
+use std::fmt;
+use std::sync::Arc;
+
+/// What went wrong with a failed [`SqlxEvent`], regardless of the database
+///
+/// Gameplay code can branch on it, e.g. telling a player a name is already
+/// taken on a [`Self::UniqueViolation`], without matching the messages of
+/// each backend. See [`SqlxEventStatus::error_kind`].
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::Sqlite;
+/// # use bevy_sqlx::{SqlxEventStatus, SqlxDummy, SqlxErrorKind};
+/// fn check_name(
+///     mut statuses: EventReader<SqlxEventStatus<Sqlite, SqlxDummy>>,
+/// ) {
+///     for status in statuses.read() {
+///         if status.error_kind() == Some(SqlxErrorKind::UniqueViolation) {
+///             info!("name already taken");
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SqlxErrorKind {
+    /// The connection to the database failed or was lost
+    Connection,
+    /// No connection was available in time, or the database gave up on a
+    /// statement or lock
+    Timeout,
+    /// A row or column couldn't be decoded
+    Decode,
+    UniqueViolation,
+    ForeignKeyViolation,
+    Other,
+}
+
+impl SqlxErrorKind {
+    /// Classify `err`
+    pub fn of(err: &Error) -> Self {
+        match err {
+            Error::PoolTimedOut => SqlxErrorKind::Timeout,
+            Error::Decode(_) | Error::ColumnDecode { .. } => {
+                SqlxErrorKind::Decode
+            }
+            Error::Database(db_err) => match db_err.kind() {
+                ErrorKind::UniqueViolation => SqlxErrorKind::UniqueViolation,
+                ErrorKind::ForeignKeyViolation => {
+                    SqlxErrorKind::ForeignKeyViolation
+                }
+                // Postgres' query canceled, SQLite's busy, and MySQL's lock
+                // wait timeout.
+                _ if matches!(
+                    db_err.code().as_deref(),
+                    Some("57014" | "5" | "1205")
+                ) =>
+                {
+                    SqlxErrorKind::Timeout
+                }
+                _ if is_connection_error(err) => SqlxErrorKind::Connection,
+                _ => SqlxErrorKind::Other,
+            },
+            _ if is_connection_error(err) => SqlxErrorKind::Connection,
+            _ => SqlxErrorKind::Other,
+        }
+    }
+}
+
+/// A write rejected by a unique or foreign key constraint, sent with
+/// [`SqlxEventStatus::Constraint`]
+///
+/// Databases report constraints differently, so either may be missing:
+/// Postgres names the constraint and its columns, while SQLite only names
+/// the columns of unique constraints, as `table.column`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SqlxConstraintViolation {
+    /// Either [`SqlxErrorKind::UniqueViolation`] or
+    /// [`SqlxErrorKind::ForeignKeyViolation`]
+    pub kind: SqlxErrorKind,
+    /// The name of the violated constraint
+    pub constraint: Option<String>,
+    /// The columns of the violated constraint
+    pub columns: Vec<String>,
+}
+
+impl SqlxConstraintViolation {
+    /// The constraint violation `err` is, if any
+    pub fn of(err: &Error) -> Option<Self> {
+        let kind = SqlxErrorKind::of(err);
+        if !matches!(
+            kind,
+            SqlxErrorKind::UniqueViolation | SqlxErrorKind::ForeignKeyViolation
+        ) {
+            return None;
+        }
+        let db_err = err.as_database_error()?;
+        let mut columns = Vec::new();
+        // SQLite: "UNIQUE constraint failed: foos.id, foos.text"
+        if let Some((_, names)) = db_err.message().split_once("failed: ") {
+            columns.extend(names.split(", ").map(String::from));
+        }
+        // Postgres: "Key (id, text)=(1, x) already exists."
+        #[cfg(feature = "postgres")]
+        if let Some(names) = db_err
+            .try_downcast_ref::<sqlx::postgres::PgDatabaseError>()
+            .and_then(|err| {
+                err.detail()?.strip_prefix("Key (")?.split_once(")=")
+            })
+        {
+            columns.extend(names.0.split(", ").map(String::from));
+        }
+        Some(SqlxConstraintViolation {
+            kind,
+            constraint: db_err.constraint().map(String::from),
+            columns,
+        })
+    }
+}
+
+/// A failed [`SqlxEvent`], as seen by the [`SqlxErrorHandler`]
+#[derive(Debug)]
+pub struct SqlxErrorContext<'a> {
+    pub id: SqlxEventId,
+    pub kind: SqlxErrorKind,
+    /// The [`Database::NAME`] of the event's database
+    pub database: &'static str,
+    /// The type name of the event's component
+    pub component: &'static str,
+    pub error: &'a Error,
+}
+
+/// A [`Resource`] holding a handler called with every
+/// [`SqlxEventStatus::Error`], of every [`SqlxPlugin`]
+///
+/// This is one place to log or report failures, instead of a system reading
+/// the statuses of each component type.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_sqlx::{SqlxErrorContext, SqlxErrorHandler};
+/// let mut app = App::new();
+/// app.insert_resource(SqlxErrorHandler::new(|err: &SqlxErrorContext| {
+///     error!("{} event {} failed: {}", err.component, err.id, err.error);
+/// }));
+/// ```
+#[derive(Resource, Clone)]
+pub struct SqlxErrorHandler(
+    Arc<dyn Fn(&SqlxErrorContext) + Send + Sync + 'static>,
+);
+
+impl SqlxErrorHandler {
+    pub fn new(
+        handler: impl Fn(&SqlxErrorContext) + Send + Sync + 'static,
+    ) -> Self {
+        SqlxErrorHandler(Arc::new(handler))
+    }
+
+    /// A [`System`] calling the handler with the errors of `C`'s events
+    pub fn handle_errors<DB, C>(
+        handler: Option<Res<Self>>,
+        mut statuses: EventReader<SqlxEventStatus<DB, C>>,
+    ) where
+        DB: Database + Sync,
+        C: SqlxComponent<DB::Row>,
+    {
+        let Some(handler) = handler else {
+            statuses.clear();
+            return;
+        };
+        for status in statuses.read() {
+            if let SqlxEventStatus::Error(id, error, _) = status {
+                (handler.0)(&SqlxErrorContext {
+                    id: *id,
+                    kind: SqlxErrorKind::of(error),
+                    database: DB::NAME,
+                    component: std::any::type_name::<C>(),
+                    error,
+                });
+            }
+        }
+    }
+}
+
+impl fmt::Debug for SqlxErrorHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SqlxErrorHandler").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{block_on, AsyncComputeTaskPool, TaskPool};
+    use sqlx::{FromRow, Pool, Sqlite};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Component, FromRow, Debug)]
+    struct Foo {
+        id: u32,
+    }
+
+    impl PrimaryKey for Foo {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    #[test]
+    fn test_error_handler() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let handled = errors.clone();
+        let mut app = App::new();
+        app.add_plugins(
+            SqlxPlugin::<Sqlite, Foo>::from_url(url).with_error_handler(
+                move |err: &SqlxErrorContext| {
+                    let mut handled = handled.lock().unwrap();
+                    handled.push((err.id, err.database, err.component));
+                },
+            ),
+        );
+        app.add_plugins(SqlxPlugin::<Sqlite, SqlxDummy>::from_url(url));
+
+        let foo = SqlxEvent::<Sqlite, Foo>::query("SELECT * FROM missing");
+        let dummy = SqlxEvent::<Sqlite, SqlxDummy>::aggregate("SELECT nothing");
+        let ids = app.world().resource::<SqlxEventIds>();
+        let (foo_id, dummy_id) = (ids.next(), ids.next());
+        let (foo, dummy) = (foo.with_id(foo_id), dummy.with_id(dummy_id));
+        app.world_mut().send_event(foo);
+        app.world_mut().send_event(dummy);
+
+        let mut tries = 0;
+        while errors.lock().unwrap().len() < 2 && tries < 1000 {
+            app.update();
+            tries += 1;
+        }
+        let mut errors = errors.lock().unwrap().clone();
+        errors.sort();
+        assert_eq!(2, errors.len());
+        assert_eq!((foo_id, "SQLite"), (errors[0].0, errors[0].1));
+        assert!(errors[0].2.ends_with("Foo"));
+        assert_eq!(dummy_id, errors[1].0);
+        assert!(errors[1].2.ends_with("SqlxDummy"));
+    }
+
+    #[test]
+    fn test_error_kind() {
+        let url = "sqlite:db/sqlite.db";
+        let pool: Pool<Sqlite> = block_on(Pool::connect(url)).unwrap();
+        let kind = |sql: &str| {
+            let err = block_on(sqlx::query(sql).execute(&pool)).unwrap_err();
+            SqlxErrorKind::of(&err)
+        };
+        block_on(async {
+            for sql in [
+                "CREATE TABLE IF NOT EXISTS kinds (id INTEGER PRIMARY KEY)",
+                "CREATE TABLE IF NOT EXISTS kind_refs \
+                 (id INTEGER PRIMARY KEY, kind_id REFERENCES kinds(id))",
+                "INSERT OR IGNORE INTO kinds VALUES (1)",
+            ] {
+                sqlx::query(sql).execute(&pool).await.unwrap();
+            }
+        });
+
+        let unique = kind("INSERT INTO kinds VALUES (1)");
+        assert_eq!(SqlxErrorKind::UniqueViolation, unique);
+        let foreign = kind("INSERT INTO kind_refs (kind_id) VALUES (-1)");
+        assert_eq!(SqlxErrorKind::ForeignKeyViolation, foreign);
+        assert_eq!(SqlxErrorKind::Other, kind("SELECT * FROM missing"));
+
+        let decode: Result<(i64,), _> =
+            block_on(sqlx::query_as("SELECT 'text'").fetch_one(&pool));
+        let decode = SqlxErrorKind::of(&decode.unwrap_err());
+        assert_eq!(SqlxErrorKind::Decode, decode);
+        let timeout = SqlxErrorKind::of(&sqlx::Error::PoolTimedOut);
+        assert_eq!(SqlxErrorKind::Timeout, timeout);
+        let io = std::io::Error::from(std::io::ErrorKind::ConnectionReset);
+        let connection = SqlxErrorKind::of(&sqlx::Error::Io(io));
+        assert_eq!(SqlxErrorKind::Connection, connection);
+    }
+}
@@ -3,35 +3,148 @@
 //! Sending a single [`SqlxEvent`] will start by sending it's own:
 //! - [`SqlxEventStatus::Start`]
 //!
+//! Unless it's over the plugin's [`SqlxRateLimit`], in which case
+//! [`SqlxEventStatus::Throttled`] is sent first.
+//!
+//! If the plugin was built [`SqlxPlugin::with_detailed_statuses`], it's
+//! followed by:
+//! - [`SqlxEventStatus::Queued`]
+//! - [`SqlxEventStatus::Executing`]
+//! - [`SqlxEventStatus::Decoded`], for events returning components
+//!
 //! Then, depending on how the event's task in [`SqlxTasks`] is
 //! processed, one of:
 //! - [`SqlxEventStatus::Spawn`]
 //! - [`SqlxEventStatus::Update`]
+//! - [`SqlxEventStatus::Return`]
+//! - [`SqlxEventStatus::Scalar`]
+//! - [`SqlxEventStatus::Done`]
+//! - [`SqlxEventStatus::SchemaChanged`]
+//! - [`SqlxEventStatus::Error`]
 use crate::*;
+use bevy::ecs::system::{EntityCommand, SystemParam};
+use bevy::ecs::world::Command;
 use bevy::prelude::*;
-use bevy::tasks::AsyncComputeTaskPool;
-use sqlx::{Database, Error, Executor, IntoArguments, Pool};
+use bevy::utils::{Duration, Instant};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sqlx::{ColumnIndex, Type};
+use sqlx::{Database, Decode, Encode, Error, Executor, IntoArguments, Pool};
+use std::borrow::Cow;
+use std::fmt;
 use std::future::Future;
 use std::marker::PhantomData;
+use std::path::PathBuf;
 use std::pin::Pin;
-use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::SystemTime;
+
+/// The id of an [`SqlxEvent`], unique within its [`World`]
+///
+/// Ids are opaque, they're only compared, hashed and displayed. They're
+/// generated by the world's [`SqlxEventIds`].
+#[derive(
+    Reflect,
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+)]
+pub struct SqlxEventId(u64);
+
+impl fmt::Display for SqlxEventId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<SqlxEventId> for SqlxValue {
+    fn from(id: SqlxEventId) -> Self {
+        SqlxValue::Int(id.0 as i64)
+    }
+}
+
+/// A [`Resource`] generating the [`SqlxEventId`]s of a [`World`]
+///
+/// Each world counts on its own, so several apps in one process don't share
+/// ids. Events are assigned the next id when they're sent with a
+/// [`SqlxEventSender`] or [`Commands`], or otherwise when they're handled.
+/// To know an event's id before sending it, build it [`SqlxEvent::with_id`]:
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::Sqlite;
+/// # use bevy_sqlx::{SqlxEvent, SqlxDummy, SqlxEventIds};
+/// fn insert(
+///     ids: Res<SqlxEventIds>,
+///     mut events: EventWriter<SqlxEvent<Sqlite, SqlxDummy>>,
+/// ) {
+///     let sql = "INSERT INTO foos (text) VALUES ('x')";
+///     let id = ids.next();
+///     info!("inserting with {id}");
+///     events.send(SqlxEvent::query(sql).with_id(id));
+/// }
+/// ```
+#[derive(Resource, Debug)]
+pub struct SqlxEventIds {
+    next: AtomicU64,
+}
+
+impl Default for SqlxEventIds {
+    fn default() -> Self {
+        SqlxEventIds { next: AtomicU64::new(1) }
+    }
+}
 
-/// The type of [`SqlxEvent`] IDs
-pub type SqlxEventId = u32;
+impl SqlxEventIds {
+    /// Generate the next id
+    pub fn next(&self) -> SqlxEventId {
+        SqlxEventId(self.next.fetch_add(1, Ordering::Relaxed))
+    }
+}
 
-pub(crate) static EVENT_ID_GENERATOR: AtomicU32 = AtomicU32::new(1);
+/// A [`SystemParam`] for sending [`SqlxEvent`]s, returning their ids
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::Sqlite;
+/// # use bevy_sqlx::{SqlxEvent, SqlxDummy, SqlxEventSender};
+/// fn insert(mut events: SqlxEventSender<Sqlite, SqlxDummy>) {
+///     let sql = "INSERT INTO foos (text) VALUES ('x')";
+///     let id = events.send(SqlxEvent::query(sql));
+///     info!("inserting with {id}");
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct SqlxEventSender<'w, DB: Database + Sync, C: SqlxComponent<DB::Row>> {
+    ids: Res<'w, SqlxEventIds>,
+    events: EventWriter<'w, SqlxEvent<DB, C>>,
+}
 
-// TODO: Take note of when this should be expected to overflow and if it's
-// worth the cost in generating unique IDs
-pub fn next_event_id() -> SqlxEventId {
-    EVENT_ID_GENERATOR.fetch_add(1, Ordering::Relaxed)
+impl<DB: Database + Sync, C: SqlxComponent<DB::Row>>
+    SqlxEventSender<'_, DB, C>
+{
+    /// Send `event`, returning its id
+    ///
+    /// Events built [`SqlxEvent::with_id`] keep their id.
+    pub fn send(&mut self, mut event: SqlxEvent<DB, C>) -> SqlxEventId {
+        let id = event.assign_id(&self.ids);
+        self.events.send(event);
+        id
+    }
 }
 
 /// An [`Event`] for fetching data from the [`SqlxDatabase`]
 ///
 /// When a [`SqlxPlugin`] is added to an app, [`SqlxEvent::handle_events`] is
-/// added too.
+/// registered in the [`SqlxRegistry`] of its database too.
 ///
 /// ### Example
 ///
@@ -50,23 +163,421 @@ pub fn next_event_id() -> SqlxEventId {
 ///     events.send(SqlxEvent::<Sqlite, Foo>::query_sync(sql));
 /// }
 /// ```
-#[derive(Event, Clone)]
+#[derive(Event)]
 pub struct SqlxEvent<DB: Database, C: SqlxComponent<DB::Row>> {
-    pub(crate) func: SqlxEventFunc<DB, C>,
-    id: SqlxEventId,
+    pub(crate) op: SqlxEventOp<DB, C>,
+    id: Option<SqlxEventId>,
+    label: Option<Arc<str>>,
     will_sync: bool,
+    target: Option<Entity>,
+    task_pool: Option<SqlxTaskPool>,
+    statement_timeout: Option<Duration>,
+    /// Why this event was misused while it was built, reported when it's
+    /// dispatched
+    misuse: Option<&'static str>,
     _db: PhantomData<DB>,
     _c: PhantomData<C>,
 }
 
-type SqlxEventFunc<DB, C> = Arc<
-    dyn Fn(
-            Pool<DB>,
-        )
-            -> Pin<Box<dyn Future<Output = Result<Vec<C>, Error>> + Send>>
-        + Send
-        + Sync,
->;
+impl<DB: Database, C: SqlxComponent<DB::Row>> Clone for SqlxEvent<DB, C> {
+    fn clone(&self) -> Self {
+        SqlxEvent {
+            op: self.op.clone(),
+            id: self.id,
+            label: self.label.clone(),
+            will_sync: self.will_sync,
+            target: self.target,
+            task_pool: self.task_pool.clone(),
+            statement_timeout: self.statement_timeout,
+            misuse: self.misuse,
+            _db: PhantomData,
+            _c: PhantomData,
+        }
+    }
+}
+
+impl<DB: Database, C: SqlxComponent<DB::Row>> fmt::Debug for SqlxEvent<DB, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (kind, sql) = self.describe();
+        f.debug_struct("SqlxEvent")
+            .field("id", &self.id)
+            .field("label", &self.label)
+            .field("kind", &kind)
+            .field("sql", &sql.as_deref().map(abbreviated))
+            .field("will_sync", &self.will_sync)
+            .field("target", &self.target)
+            .field("task_pool", &self.task_pool)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Show the event's id, label, kind and SQL on one line, e.g.
+/// `#3 "load level" query (sync): SELECT * FROM levels`
+impl<DB: Database, C: SqlxComponent<DB::Row>> fmt::Display
+    for SqlxEvent<DB, C>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (kind, sql) = self.describe();
+        match self.id {
+            Some(id) => write!(f, "#{id} ")?,
+            None => write!(f, "#? ")?,
+        }
+        if let Some(label) = &self.label {
+            write!(f, "{label:?} ")?;
+        }
+        write!(f, "{kind}")?;
+        if self.will_sync {
+            write!(f, " (sync)")?;
+        }
+        if let Some(sql) = &sql {
+            write!(f, ": {}", abbreviated(sql))?;
+        }
+        Ok(())
+    }
+}
+
+/// The SQL of `sql` on one line, cut after 80 characters
+fn abbreviated(sql: &str) -> String {
+    const MAX: usize = 80;
+    let sql = sql.split_whitespace().collect::<Vec<_>>().join(" ");
+    match sql.char_indices().nth(MAX) {
+        Some((end, _)) => format!("{}...", &sql[..end]),
+        None => sql,
+    }
+}
+
+/// Send the event from [`Commands`], where there's no [`EventWriter`], like
+/// observers, hooks or exclusive systems
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::Sqlite;
+/// # use bevy_sqlx::{SqlxEvent, SqlxDummy};
+/// fn reset(mut commands: Commands) {
+///     commands.add(SqlxEvent::<Sqlite, SqlxDummy>::query("DELETE FROM foos"));
+/// }
+/// ```
+impl<DB: Database + Sync, C: SqlxComponent<DB::Row>> Command
+    for SqlxEvent<DB, C>
+{
+    fn apply(mut self, world: &mut World) {
+        self.assign_id(world.resource::<SqlxEventIds>());
+        world.send_event(self);
+    }
+}
+
+/// Send the event from [`EntityCommands`], inserting its first component
+/// onto the entity, like [`SqlxEvent::load_into`]
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::Sqlite;
+/// # use bevy_sqlx::{SqlxEvent, SqlxDummy};
+/// fn hydrate(mut commands: Commands) {
+///     let sql = "SELECT * FROM foos WHERE id = 1";
+///     commands
+///         .spawn_empty()
+///         .add(SqlxEvent::<Sqlite, SqlxDummy>::query(sql));
+/// }
+/// ```
+impl<DB: Database + Sync, C: SqlxComponent<DB::Row>> EntityCommand
+    for SqlxEvent<DB, C>
+{
+    fn apply(mut self, entity: Entity, world: &mut World) {
+        self.target = Some(entity);
+        self.assign_id(world.resource::<SqlxEventIds>());
+        world.send_event(self);
+    }
+}
+
+impl<DB: Database, C: SqlxComponent<DB::Row>> SqlxEvent<DB, C> {
+    /// Attach a human readable label to this event
+    ///
+    /// ```
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxEvent, SqlxDummy};
+    ///
+    /// let event = SqlxEvent::<Sqlite, SqlxDummy>::query("DELETE FROM foos")
+    ///     .with_label("reset foos");
+    /// assert_eq!(Some("reset foos"), event.label());
+    /// ```
+    pub fn with_label(mut self, label: impl Into<Arc<str>>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Decide whether this event syncs its components to the ECS, whichever
+    /// constructor built it
+    ///
+    /// `SqlxEvent::query(sql).sync(true)` is the same as
+    /// `SqlxEvent::query_sync(sql)`, and likewise for [`Self::call`] and
+    /// [`Self::call_sync`]. Only events returning components are synced.
+    ///
+    /// ```
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxEvent, SqlxDummy};
+    ///
+    /// # let loading_level = true;
+    /// let event = SqlxEvent::<Sqlite, SqlxDummy>::query("SELECT * FROM foos")
+    ///     .sync(loading_level);
+    /// assert!(event.will_sync());
+    /// ```
+    pub fn sync(mut self, sync: bool) -> Self {
+        self.will_sync = sync;
+        self
+    }
+
+    /// Sync this event's components to the ECS, see [`Self::sync`]
+    pub fn with_sync(self) -> Self {
+        self.sync(true)
+    }
+
+    /// Run this event on `task_pool`, instead of the plugin's
+    /// [`SqlxTaskPool`]
+    ///
+    /// ```
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxEvent, SqlxDummy, SqlxTaskPool};
+    ///
+    /// SqlxEvent::<Sqlite, SqlxDummy>::query("SELECT * FROM foos")
+    ///     .with_task_pool(SqlxTaskPool::Io);
+    /// ```
+    pub fn with_task_pool(mut self, task_pool: SqlxTaskPool) -> Self {
+        self.task_pool = Some(task_pool);
+        self
+    }
+
+    /// Let the database cancel this event's statements after `timeout`,
+    /// instead of the plugin's [`SqlxPlugin::with_statement_timeout`]
+    ///
+    /// ```
+    /// use bevy::utils::Duration;
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxEvent, SqlxDummy};
+    ///
+    /// SqlxEvent::<Sqlite, SqlxDummy>::query("SELECT * FROM foos")
+    ///     .with_statement_timeout(Duration::from_millis(500));
+    /// ```
+    pub fn with_statement_timeout(mut self, timeout: Duration) -> Self {
+        self.statement_timeout = Some(timeout);
+        self
+    }
+
+    /// Give this event the id `id`, generated by the [`SqlxEventIds`] of
+    /// the world it's sent to
+    pub fn with_id(mut self, id: SqlxEventId) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Return the id of this event, if it has one
+    ///
+    /// Events only have an id once they're sent with a [`SqlxEventSender`]
+    /// or [`Commands`], or handled, unless they were built [`Self::with_id`].
+    pub fn id(&self) -> Option<SqlxEventId> {
+        self.id
+    }
+
+    /// Return the id of this event, which was handled
+    ///
+    /// # Panics
+    ///
+    /// Panics if the event wasn't assigned an id, which [`Self::handle_events`]
+    /// does for every event before it's tracked.
+    pub(crate) fn assigned_id(&self) -> SqlxEventId {
+        self.id.expect("handled events are assigned an id")
+    }
+
+    /// Give this event the next id of `ids`, unless it already has one
+    pub(crate) fn assign_id(&mut self, ids: &SqlxEventIds) -> SqlxEventId {
+        *self.id.get_or_insert_with(|| ids.next())
+    }
+
+    /// Return the label of this event, if it has one
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// Return true if this event will sync its component to the ECS
+    pub fn will_sync(&self) -> bool {
+        self.will_sync
+    }
+
+    /// Return the entity this event's component will be inserted onto, if
+    /// it was constructed with [`Self::load_into`]
+    pub fn target(&self) -> Option<Entity> {
+        self.target
+    }
+
+    /// Return the task pool this event runs on, if it was built
+    /// [`Self::with_task_pool`]
+    pub fn task_pool(&self) -> Option<&SqlxTaskPool> {
+        self.task_pool.as_ref()
+    }
+
+    /// The number of rows processed so far, if it changed since last asked
+    pub(crate) fn progress(&self) -> Option<u64> {
+        match &self.op {
+            SqlxEventOp::Export(.., progress)
+            | SqlxEventOp::Import(.., progress) => progress.report(),
+            _ => None,
+        }
+    }
+
+    /// Return true if this event only selects, or calls a function
+    fn is_select(&self) -> bool {
+        let select = Some(SqlxStatementKind::Select);
+        match &self.op {
+            SqlxEventOp::Query(sql, ..) => SqlxStatementKind::of(sql) == select,
+            SqlxEventOp::Statement(stmt, _) => {
+                stmt.kind() == SqlxStatementKind::Select
+            }
+            SqlxEventOp::Aggregate(sql) | SqlxEventOp::Export(sql, ..) => {
+                SqlxStatementKind::of(sql) == select
+            }
+            SqlxEventOp::Call(_) | SqlxEventOp::Backup(_) => true,
+            SqlxEventOp::Import(..) | SqlxEventOp::Ddl(_) => false,
+        }
+    }
+
+    /// This event's generated write as a [`SqlxJournalEntry`], scoped to
+    /// the tenant
+    pub(crate) fn journal_entry(
+        &self,
+        config: &SqlxConfig<DB, C>,
+        tenant: Option<&TenantId>,
+    ) -> Option<Result<SqlxJournalEntry, Error>> {
+        let SqlxEventOp::Statement(stmt, _) = &self.op else {
+            return None;
+        };
+        if stmt.kind() == SqlxStatementKind::Select {
+            return None;
+        }
+        let named = |values: &[(&str, SqlxValue)]| {
+            values
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.clone()))
+                .collect()
+        };
+        let queued_at = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |since| since.as_micros() as i64);
+        Some(scoped(stmt.as_ref().clone(), config, tenant).map(|stmt| {
+            SqlxJournalEntry {
+                id: self.assigned_id(),
+                sql: stmt.sql::<DB>(),
+                binds: stmt.binds(),
+                table: stmt.table().into(),
+                queued_at,
+                columns: named(stmt.columns()),
+                filters: stmt.selection().map(|select| named(select.filters())),
+            }
+        }))
+    }
+
+    /// The table this event writes to, if it's a generated write
+    pub(crate) fn written_table(&self) -> Option<&'static str> {
+        match &self.op {
+            SqlxEventOp::Statement(stmt, _)
+                if stmt.kind() != SqlxStatementKind::Select =>
+            {
+                Some(stmt.table())
+            }
+            SqlxEventOp::Import(table, ..) => Some(table),
+            _ => None,
+        }
+    }
+}
+
+type SqlxEventFunc<DB, C> =
+    Arc<dyn Fn(Pool<DB>) -> SqlxEventFuture<C> + Send + Sync>;
+
+/// An import into its table, with the prefix it's given
+type SqlxImportFunc<DB, C> =
+    Arc<dyn Fn(Pool<DB>, String) -> SqlxEventFuture<C> + Send + Sync>;
+
+pub(crate) type SqlxEventFuture<C> =
+    Pin<Box<dyn Future<Output = Result<SqlxTaskOutput<C>, Error>> + Send>>;
+
+/// What an [`SqlxEvent`] does with the database once it's handled
+///
+/// Plain SQL queries carry which rows they fetch, see [`SqlxEvent::query`].
+/// Statements generated for a [`ToRow`] component carry its
+/// [`ToRow::to_row`], so the rows they change can be inspected.
+pub(crate) enum SqlxEventOp<DB: Database, C: SqlxComponent<DB::Row>> {
+    Query(Cow<'static, str>, Vec<SqlxValue>, SqlxFetch),
+    Call(SqlxEventFunc<DB, C>),
+    Statement(Box<SqlxStatement>, Option<SqlxRowFn<C>>),
+    Aggregate(Arc<str>),
+    Export(Arc<str>, PathBuf, SqlxFileFormat, Arc<SqlxProgress>),
+    Import(&'static str, SqlxImportFunc<DB, C>, Arc<SqlxProgress>),
+    Ddl(Arc<str>),
+    Backup(PathBuf),
+}
+
+/// The rows a plain SQL query fetches
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum SqlxFetch {
+    /// No rows, the query is executed
+    None,
+    /// Every row, see [`SqlxEvent::query`]
+    All,
+    /// Exactly one row, see [`SqlxEvent::query_one`]
+    One,
+    /// At most one row, see [`SqlxEvent::query_optional`]
+    Optional,
+}
+
+impl SqlxFetch {
+    /// The output of a task which fetched `components`
+    fn output<C>(self, components: Vec<C>) -> Result<SqlxTaskOutput<C>, Error> {
+        let mut rows = components.into_iter();
+        match (self, rows.len()) {
+            (SqlxFetch::None | SqlxFetch::All, _) => {
+                Ok(SqlxTaskOutput::Components(rows.collect()))
+            }
+            (SqlxFetch::One, 0) => Err(Error::RowNotFound),
+            (SqlxFetch::One, 1) => {
+                Ok(SqlxTaskOutput::Components(rows.collect()))
+            }
+            (SqlxFetch::Optional, 0 | 1) => {
+                Ok(SqlxTaskOutput::Optional(rows.next()))
+            }
+            (_, n) => {
+                let err = format!("expected at most one row, found {n}");
+                Err(Error::Decode(err.into()))
+            }
+        }
+    }
+}
+
+impl<DB: Database, C: SqlxComponent<DB::Row>> Clone for SqlxEventOp<DB, C> {
+    fn clone(&self) -> Self {
+        match self {
+            SqlxEventOp::Query(sql, binds, fetch) => {
+                SqlxEventOp::Query(sql.clone(), binds.clone(), *fetch)
+            }
+            SqlxEventOp::Call(func) => SqlxEventOp::Call(func.clone()),
+            SqlxEventOp::Statement(stmt, to_row) => {
+                SqlxEventOp::Statement(stmt.clone(), *to_row)
+            }
+            SqlxEventOp::Aggregate(sql) => SqlxEventOp::Aggregate(sql.clone()),
+            SqlxEventOp::Export(sql, path, format, progress) => {
+                SqlxEventOp::Export(
+                    sql.clone(),
+                    path.clone(),
+                    *format,
+                    progress.clone(),
+                )
+            }
+            SqlxEventOp::Import(table, func, progress) => {
+                SqlxEventOp::Import(table, func.clone(), progress.clone())
+            }
+            SqlxEventOp::Backup(path) => SqlxEventOp::Backup(path.clone()),
+            SqlxEventOp::Ddl(sql) => SqlxEventOp::Ddl(sql.clone()),
+        }
+    }
+}
 
 impl<DB: Database + Sync, C: SqlxComponent<DB::Row>> SqlxEvent<DB, C>
 where
@@ -75,6 +586,12 @@ where
 {
     /// Construct a new [`SqlxEvent`] from the given SQL string
     ///
+    /// The SQL is stored as is, and only turned into a future when the event
+    /// is dispatched, so static strings don't allocate. `INSERT`s, `UPDATE`s
+    /// and `DELETE`s without a `RETURNING` clause are executed, and send a
+    /// [`SqlxEventStatus::Done`] with the number of rows affected. On SQLite
+    /// and MySQL, `INSERT`s which inserted rows first send an
+    /// [`SqlxEventStatus::Inserted`] with the key generated for the last row.
     /// See [`Self::call`] for information.
     /// ```
     /// use sqlx::Sqlite;
@@ -82,23 +599,151 @@ where
     ///
     /// SqlxEvent::<Sqlite, SqlxDummy>::query("SELECT * FROM foos");
     /// ```
-    pub fn query(sql: &str) -> Self {
-        Self::query_private(false, sql)
+    pub fn query(sql: impl Into<Cow<'static, str>>) -> Self {
+        Self::query_private(false, sql.into())
     }
 
     /// Construct a new synchronizing [`SqlxEvent`] from the given SQL string
     ///
     /// See [`Self::call_sync`] for more information.
-    pub fn query_sync(sql: &str) -> Self {
-        Self::query_private(true, sql)
+    pub fn query_sync(sql: impl Into<Cow<'static, str>>) -> Self {
+        Self::query_private(true, sql.into())
+    }
+
+    /// Construct a new [`SqlxEvent`] from the given SQL string, which
+    /// returns exactly one row
+    ///
+    /// Upon a successful DB interaction, a [`SqlxEventStatus::Return`] event
+    /// will be sent with the one component. With no row, a
+    /// [`SqlxEventStatus::Error`] is sent with [`Error::RowNotFound`], and
+    /// with more, with an [`Error::Decode`].
+    ///
+    /// ```
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxEvent, SqlxDummy};
+    ///
+    /// let sql = "SELECT * FROM foos WHERE id = ?";
+    /// SqlxEvent::<Sqlite, SqlxDummy>::query_one(sql).bind(1);
+    /// ```
+    pub fn query_one(sql: impl Into<Cow<'static, str>>) -> Self {
+        let op = SqlxEventOp::Query(sql.into(), Vec::new(), SqlxFetch::One);
+        Self::new(false, op)
+    }
+
+    /// Construct a new [`SqlxEvent`] from the given SQL string, which
+    /// returns at most one row
+    ///
+    /// Upon a successful DB interaction, a [`SqlxEventStatus::Optional`]
+    /// event will be sent with the component, if there was a row. With more
+    /// rows, a [`SqlxEventStatus::Error`] is sent with an [`Error::Decode`].
+    pub fn query_optional(sql: impl Into<Cow<'static, str>>) -> Self {
+        let op =
+            SqlxEventOp::Query(sql.into(), Vec::new(), SqlxFetch::Optional);
+        Self::new(false, op)
+    }
+
+    fn query_private(sync: bool, sql: Cow<'static, str>) -> Self {
+        let fetch =
+            if returns_rows(&sql) { SqlxFetch::All } else { SqlxFetch::None };
+        Self::new(sync, SqlxEventOp::Query(sql, Vec::new(), fetch))
+    }
+
+    /// Bind `value` to the next parameter of this event's query
+    ///
+    /// ```
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxEvent, SqlxDummy};
+    ///
+    /// let sql = "INSERT INTO foos (text) VALUES (?) RETURNING *";
+    /// SqlxEvent::<Sqlite, SqlxDummy>::query(sql).bind("hello");
+    /// ```
+    ///
+    /// If this event wasn't constructed with [`Self::query`], or one of its
+    /// variants, like [`Self::query_one`], a [`SqlxEventStatus::Error`] is
+    /// sent with an [`Error::Configuration`] when it's handled.
+    pub fn bind(mut self, value: impl Into<SqlxValue>) -> Self {
+        match &mut self.op {
+            SqlxEventOp::Query(_, binds, _) => binds.push(value.into()),
+            _ => {
+                self.misuse.get_or_insert("only query events have binds");
+            }
+        }
+        self
+    }
+
+    /// Order the rows this event's statement selects by `column`, ascending
+    ///
+    /// See [`SqlxStatement::order_by`] for more information.
+    ///
+    /// If this event wasn't constructed from a [`SqlxStatement`], e.g. with
+    /// [`Self::statement`] or [`Self::select_all`], a
+    /// [`SqlxEventStatus::Error`] is sent with an [`Error::Configuration`]
+    /// when it's handled.
+    pub fn order_by(self, column: &'static str) -> Self {
+        self.map_statement(|stmt| stmt.order_by(column))
+    }
+
+    /// Order the rows this event's statement selects by `column`, descending
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use sqlx::{FromRow, Sqlite};
+    /// # use bevy_sqlx::{SqlxEvent, PrimaryKey, SqlxValue, ToRow};
+    /// # #[derive(Component, FromRow)]
+    /// # struct Foo(u32);
+    /// # impl PrimaryKey for Foo {
+    /// #     type Column = u32;
+    /// #     fn primary_key(&self) -> Self::Column { self.0 }
+    /// # }
+    /// # impl ToRow for Foo {
+    /// #     fn table() -> &'static str { "foos" }
+    /// #     fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+    /// #         vec![("id", self.0.into())]
+    /// #     }
+    /// # }
+    /// SqlxEvent::<Sqlite, Foo>::select_all().order_by_desc("score").limit(100);
+    /// ```
+    ///
+    /// If this event wasn't constructed from a [`SqlxStatement`], it's handled
+    /// with a [`SqlxEventStatus::Error`].
+    pub fn order_by_desc(self, column: &'static str) -> Self {
+        self.map_statement(|stmt| stmt.order_by_desc(column))
+    }
+
+    /// Select at most `limit` rows with this event's statement
+    ///
+    /// See [`SqlxStatement::limit`] for more information.
+    ///
+    /// If this event wasn't constructed from a [`SqlxStatement`], it's handled
+    /// with a [`SqlxEventStatus::Error`].
+    pub fn limit(self, limit: u64) -> Self {
+        self.map_statement(|stmt| stmt.limit(limit))
+    }
+
+    /// Leave out the `RETURNING *` clause of this event's statement
+    ///
+    /// See [`SqlxStatement::without_returning`] for more information.
+    ///
+    /// If this event wasn't constructed from a [`SqlxStatement`], it's handled
+    /// with a [`SqlxEventStatus::Error`].
+    pub fn without_returning(self) -> Self {
+        self.map_statement(SqlxStatement::without_returning)
     }
 
-    fn query_private(sync: bool, sql: &str) -> Self {
-        let arc: Arc<str> = sql.into();
-        Self::call_private(sync, move |db| {
-            let s = arc.clone();
-            async move { sqlx::query_as(&s).fetch_all(&db).await }
-        })
+    fn map_statement(
+        mut self,
+        f: impl FnOnce(SqlxStatement) -> SqlxStatement,
+    ) -> Self {
+        match &mut self.op {
+            SqlxEventOp::Statement(stmt, _) => {
+                **stmt = f(stmt.as_ref().clone());
+            }
+            _ => {
+                self.misuse
+                    .get_or_insert("only statement events have a statement");
+            }
+        }
+        self
     }
 
     /// Construct a new [`SqlxEvent`] from the given function with access
@@ -143,68 +788,755 @@ where
         Self::call_private(true, func)
     }
 
+    /// Construct a new [`SqlxEvent`] from the given function with access
+    /// to a [`Pool<DB>`], returning a single value
+    ///
+    /// Upon a successful DB interaction, a [`SqlxEventStatus::Scalar`] event
+    /// will be sent with the value, like [`Self::aggregate`].
+    ///
+    /// ```
+    /// use sqlx::{Row, Sqlite};
+    /// use bevy_sqlx::{SqlxEvent, SqlxDummy};
+    ///
+    /// SqlxEvent::<Sqlite, SqlxDummy>::call_scalar(move |db| { async move {
+    ///     let row = sqlx::query("SELECT COUNT(*) FROM foos")
+    ///         .fetch_one(&db).await?;
+    ///     Ok(row.try_get::<i64, _>(0)?.into())
+    /// }});
+    /// ```
+    pub fn call_scalar<F, T>(func: F) -> Self
+    where
+        F: Fn(Pool<DB>) -> T + Send + Sync + 'static,
+        T: Future<Output = Result<SqlxValue, Error>> + Send + 'static,
+    {
+        let func = Arc::new(move |db: Pool<DB>| {
+            let future = func(db);
+            Box::pin(async move { future.await.map(SqlxTaskOutput::Scalar) })
+                as SqlxEventFuture<C>
+        });
+        Self::new(false, SqlxEventOp::Call(func))
+    }
+
     fn call_private<F, T>(sync: bool, func: F) -> Self
     where
         F: Fn(Pool<DB>) -> T + Send + Sync + 'static,
         T: Future<Output = Result<Vec<C>, Error>> + Send + 'static,
     {
-        SqlxEvent {
-            func: Arc::new(move |db: Pool<DB>| Box::pin(func(db))),
-            id: next_event_id(),
-            will_sync: sync,
-            _db: PhantomData::<DB>,
-            _c: PhantomData::<C>,
-        }
+        let func = Arc::new(move |db: Pool<DB>| {
+            let future = func(db);
+            Box::pin(
+                async move { future.await.map(SqlxTaskOutput::Components) },
+            ) as SqlxEventFuture<C>
+        });
+        Self::new(sync, SqlxEventOp::Call(func))
     }
 
-    /// Return the id of this event
-    pub fn id(&self) -> SqlxEventId {
-        self.id
+    /// Construct a new [`SqlxEvent`] from a generated [`SqlxStatement`]
+    ///
+    /// Upon a successful DB interaction, a [`SqlxEventStatus::Return`] event
+    /// will be sent.
+    ///
+    /// ```
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxEvent, SqlxDummy, SqlxStatement};
+    ///
+    /// let stmt = SqlxStatement::delete("foos").filter("flag", true);
+    /// SqlxEvent::<Sqlite, SqlxDummy>::statement(stmt);
+    /// ```
+    pub fn statement(stmt: SqlxStatement) -> Self {
+        Self::new(false, SqlxEventOp::Statement(Box::new(stmt), None))
     }
 
-    /// Return true if this event will sync its component to the ECS
-    pub fn will_sync(&self) -> bool {
-        self.will_sync
+    /// Construct a new synchronizing [`SqlxEvent`] from a generated
+    /// [`SqlxStatement`]
+    ///
+    /// See [`Self::call_sync`] for more information.
+    pub fn statement_sync(stmt: SqlxStatement) -> Self {
+        Self::new(true, SqlxEventOp::Statement(Box::new(stmt), None))
     }
-}
 
-/// An [`Event`] sent while processing an [`SqlxEvent`]
-///
-/// ### Example
-///
-/// ```
-/// # use bevy::prelude::*;
-/// # use sqlx::Sqlite;
-/// # use bevy_sqlx::{SqlxEventStatus, SqlxDummy};
-/// fn status(mut statuses: EventReader<SqlxEventStatus<Sqlite, SqlxDummy>>) {
-///     for status in statuses.read() {
-///         match status {
-///             SqlxEventStatus::Start(id) => {},
-///             SqlxEventStatus::Return(id, comp) => {},
-///             SqlxEventStatus::Spawn(id, pk, _) => {},
-///             SqlxEventStatus::Update(id, pk, _) => {},
-///             SqlxEventStatus::Error(id, err) => {},
-///         }
-///     }
-/// }
-/// ```
-#[derive(Event, Debug)]
+    /// Construct a new [`SqlxEvent`] from an aggregate SQL string, like
+    /// `COUNT`, `SUM` or `MAX`
+    ///
+    /// The first column of the first row is decoded as a [`SqlxValue`], and
+    /// a [`SqlxEventStatus::Scalar`] event is sent with it, so no component
+    /// is needed to hold the result.
+    ///
+    /// ```
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxEvent, SqlxDummy};
+    ///
+    /// SqlxEvent::<Sqlite, SqlxDummy>::aggregate("SELECT COUNT(*) FROM foos");
+    /// ```
+    pub fn aggregate(sql: &str) -> Self {
+        Self::new(false, SqlxEventOp::Aggregate(sql.into()))
+    }
+
+    /// Construct a new [`SqlxEvent`] writing the rows of the given SQL
+    /// string to a file
+    ///
+    /// Rows are streamed to the file at `path` from a background task, so
+    /// whole tables can be exported. While running, a
+    /// [`SqlxEventStatus::Progress`] event is sent every frame more rows have
+    /// been written, followed by a [`SqlxEventStatus::Done`] with the total.
+    ///
+    /// ```
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxEvent, SqlxDummy, SqlxFileFormat};
+    ///
+    /// SqlxEvent::<Sqlite, SqlxDummy>::export(
+    ///     "SELECT * FROM foos",
+    ///     "foos.csv",
+    ///     SqlxFileFormat::Csv,
+    /// );
+    /// ```
+    pub fn export(
+        sql: &str,
+        path: impl Into<PathBuf>,
+        format: SqlxFileFormat,
+    ) -> Self {
+        let progress = Arc::new(SqlxProgress::default());
+        let op = SqlxEventOp::Export(sql.into(), path.into(), format, progress);
+        Self::new(false, op)
+    }
+
+    /// Construct a new [`SqlxEvent`] snapshotting the live SQLite database
+    /// to the file at `path`
+    ///
+    /// The snapshot is taken with `VACUUM INTO` from a background task,
+    /// without pausing the game or blocking other connections, and is a
+    /// consistent copy even while rows are written. It's written next to
+    /// `path` first, and renamed over it once complete, so a save file is
+    /// never left half written. SQLite can't report how far along it is, so
+    /// rather than [`SqlxEventStatus::Progress`] events, a single
+    /// [`SqlxEventStatus::Done`] is sent with the size of the file in bytes.
+    /// Other databases send a [`SqlxEventStatus::Error`].
+    ///
+    /// ```
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxEvent, SqlxDummy};
+    ///
+    /// SqlxEvent::<Sqlite, SqlxDummy>::backup("saves/slot1.db");
+    /// ```
+    pub fn backup(path: impl Into<PathBuf>) -> Self {
+        Self::new(false, SqlxEventOp::Backup(path.into()))
+    }
+
+    /// Construct a new [`SqlxEvent`] changing the schema with the given
+    /// SQL, like a `CREATE TABLE` or `CREATE INDEX`
+    ///
+    /// The SQL is executed as is, and may hold several statements. Nothing
+    /// is decoded or synced, and a [`SqlxEventStatus::SchemaChanged`] is sent
+    /// once it's done. Plugins built [`SqlxPlugin::with_read_only`] reject
+    /// it.
+    ///
+    /// ```
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxEvent, SqlxDummy};
+    ///
+    /// SqlxEvent::<Sqlite, SqlxDummy>::ddl(
+    ///     "CREATE TABLE IF NOT EXISTS save_3 (id INTEGER PRIMARY KEY)",
+    /// );
+    /// ```
+    pub fn ddl(sql: &str) -> Self {
+        Self::new(false, SqlxEventOp::Ddl(sql.into()))
+    }
+
+    fn new(sync: bool, op: SqlxEventOp<DB, C>) -> Self {
+        SqlxEvent {
+            op,
+            id: None,
+            label: None,
+            will_sync: sync,
+            target: None,
+            task_pool: None,
+            statement_timeout: None,
+            misuse: None,
+            _db: PhantomData::<DB>,
+            _c: PhantomData::<C>,
+        }
+    }
+}
+
+impl<DB: Database + Sync, C: SqlxComponent<DB::Row> + ToRow> SqlxEvent<DB, C>
+where
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'a> <DB as sqlx::Database>::Arguments<'a>: IntoArguments<'a, DB>,
+{
+    /// Construct a new [`SqlxEvent`] selecting every row of `C`'s table
+    ///
+    /// See [`Self::statement`] for more information.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use sqlx::{FromRow, Sqlite};
+    /// # use bevy_sqlx::{SqlxEvent, PrimaryKey, SqlxValue, ToRow};
+    /// # #[derive(Component, FromRow)]
+    /// # struct Foo(u32);
+    /// # impl PrimaryKey for Foo {
+    /// #     type Column = u32;
+    /// #     fn primary_key(&self) -> Self::Column { self.0 }
+    /// # }
+    /// # impl ToRow for Foo {
+    /// #     fn table() -> &'static str { "foos" }
+    /// #     fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+    /// #         vec![("id", self.0.into())]
+    /// #     }
+    /// # }
+    /// SqlxEvent::<Sqlite, Foo>::select_all();
+    /// ```
+    pub fn select_all() -> Self {
+        Self::generated(SqlxStatement::select(C::table()))
+    }
+
+    /// Construct a new [`SqlxEvent`] selecting the row with primary key `pk`
+    ///
+    /// See [`Self::statement`] for more information.
+    pub fn select(pk: C::Column) -> Self
+    where
+        C::Column: Into<SqlxValue>,
+    {
+        let stmt =
+            SqlxStatement::select(C::table()).filter(C::primary_key_name(), pk);
+        Self::generated(stmt)
+    }
+
+    /// Construct a new [`SqlxEvent`] selecting the rows with any of the
+    /// primary keys in `keys`
+    ///
+    /// PostgreSQL binds the keys as a single array, with
+    /// `WHERE id = ANY($1)`, other databases bind each key, with
+    /// `WHERE id IN (?, ?, ...)`. Rows are returned in no particular order,
+    /// and keys without a row are skipped. See [`Self::statement`] for more
+    /// information.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use sqlx::{FromRow, Sqlite};
+    /// # use bevy_sqlx::{SqlxEvent, PrimaryKey, SqlxValue, ToRow};
+    /// # #[derive(Component, FromRow)]
+    /// # struct Foo(u32);
+    /// # impl PrimaryKey for Foo {
+    /// #     type Column = u32;
+    /// #     fn primary_key(&self) -> Self::Column { self.0 }
+    /// # }
+    /// # impl ToRow for Foo {
+    /// #     fn table() -> &'static str { "foos" }
+    /// #     fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+    /// #         vec![("id", self.0.into())]
+    /// #     }
+    /// # }
+    /// SqlxEvent::<Sqlite, Foo>::load_many(vec![1, 2, 3]);
+    /// ```
+    pub fn load_many(keys: Vec<C::Column>) -> Self
+    where
+        C::Column: Into<SqlxValue>,
+    {
+        let key = C::primary_key_name();
+        let values: Vec<SqlxValue> = keys.into_iter().map(Into::into).collect();
+        let stmt = SqlxStatement::select(C::table());
+        let stmt = match SqlxArray::from_values(&values) {
+            Some(array) if DB::NAME == "PostgreSQL" => {
+                stmt.filter_any(key, array)
+            }
+            _ => stmt.filter_in(key, values),
+        };
+        Self::generated(stmt)
+    }
+
+    /// Construct a new [`SqlxEvent`] selecting the rows matching `partial`
+    ///
+    /// Every set column of `partial` is bound as a filter, joined with
+    /// `AND`, so common lookups don't need SQL written by hand. A `partial`
+    /// without set columns selects every row, like [`Self::select_all`].
+    /// See [`Self::statement`] for more information.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use sqlx::{FromRow, Sqlite};
+    /// # use bevy_sqlx::{SqlxEvent, PrimaryKey, SqlxValue, ToRow};
+    /// # #[derive(Component, FromRow)]
+    /// # struct Foo(u32);
+    /// # impl PrimaryKey for Foo {
+    /// #     type Column = u32;
+    /// #     fn primary_key(&self) -> Self::Column { self.0 }
+    /// # }
+    /// # impl ToRow for Foo {
+    /// #     fn table() -> &'static str { "foos" }
+    /// #     fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+    /// #         vec![("id", self.0.into())]
+    /// #     }
+    /// # }
+    /// SqlxEvent::<Sqlite, Foo>::find_where([
+    ///     ("text", "hello".into()),
+    ///     ("flag", true.into()),
+    /// ]);
+    /// ```
+    pub fn find_where(partial: impl ToFilter) -> Self {
+        let stmt = partial.to_filter().into_iter().fold(
+            SqlxStatement::select(C::table()),
+            |stmt, (name, value)| match value {
+                Some(value) => stmt.filter(name, value),
+                None => stmt,
+            },
+        );
+        Self::generated(stmt)
+    }
+
+    /// Construct a new [`SqlxEvent`] inserting `component` as a new row
+    ///
+    /// The inserted row is returned with `RETURNING *`, so its component has
+    /// the generated primary key. When built [`Self::without_returning`],
+    /// SQLite and MySQL select the row back by the key they generated for
+    /// it, and an [`SqlxEventStatus::Inserted`] is sent with the key first.
+    /// See [`Self::statement`] for more information.
+    pub fn insert(component: &C) -> Self {
+        Self::generated(SqlxStatement::insert(C::table(), component.to_row()))
+    }
+
+    /// Construct a new [`SqlxEvent`] updating the row of `component`
+    ///
+    /// See [`Self::statement`] for more information.
+    pub fn update(component: &C) -> Self
+    where
+        C::Column: Into<SqlxValue>,
+    {
+        let key = C::primary_key_name();
+        let mut columns = component.to_row();
+        columns.retain(|(name, _)| *name != key);
+        let stmt = SqlxStatement::update(C::table(), columns)
+            .filter(key, component.primary_key());
+        Self::generated(stmt)
+    }
+
+    /// Construct a new [`SqlxEvent`] inserting `component`, or updating its
+    /// row if it already exists
+    ///
+    /// MySQL can't restrict the update to a tenant's rows, so upserts of
+    /// plugins with a tenant column fail there with an
+    /// [`Error::Configuration`]. See [`Self::statement`] for more
+    /// information.
+    pub fn upsert(component: &C) -> Self {
+        let stmt = SqlxStatement::upsert(
+            C::table(),
+            C::primary_key_name(),
+            component.to_row(),
+        );
+        Self::generated(stmt)
+    }
+
+    /// Construct a new [`SqlxEvent`] deleting the row of `component`
+    ///
+    /// See [`Self::statement`] for more information.
+    pub fn delete(component: &C) -> Self
+    where
+        C::Column: Into<SqlxValue>,
+    {
+        let stmt = SqlxStatement::delete(C::table())
+            .filter(C::primary_key_name(), component.primary_key());
+        Self::generated(stmt)
+    }
+
+    /// Construct a new [`SqlxEvent`] inserting the components in a file
+    ///
+    /// The file at `path` is read from a background task, and each CSV
+    /// record or JSON object is deserialized into a `C` and inserted in
+    /// transactions of [`IMPORT_BATCH_SIZE`] rows. CSV headers name the
+    /// fields of `C`. Like [`Self::export`], [`SqlxEventStatus::Progress`]
+    /// events are sent as batches are committed, followed by a
+    /// [`SqlxEventStatus::Done`] with the total.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use serde::Deserialize;
+    /// # use sqlx::{FromRow, Sqlite};
+    /// # use bevy_sqlx::{SqlxEvent, PrimaryKey, SqlxFileFormat, SqlxValue, ToRow};
+    /// # #[derive(Component, FromRow, Deserialize)]
+    /// # struct Foo { id: u32, text: String }
+    /// # impl PrimaryKey for Foo {
+    /// #     type Column = u32;
+    /// #     fn primary_key(&self) -> Self::Column { self.id }
+    /// # }
+    /// # impl ToRow for Foo {
+    /// #     fn table() -> &'static str { "foos" }
+    /// #     fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+    /// #         vec![("text", self.text.clone().into())]
+    /// #     }
+    /// # }
+    /// SqlxEvent::<Sqlite, Foo>::import("foos.csv", SqlxFileFormat::Csv);
+    /// ```
+    pub fn import(path: impl Into<PathBuf>, format: SqlxFileFormat) -> Self
+    where
+        C: DeserializeOwned,
+        for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+    {
+        let path = path.into();
+        let progress = Arc::new(SqlxProgress::default());
+        let task_progress = progress.clone();
+        let func = Arc::new(move |db: Pool<DB>, prefix: String| {
+            let (path, progress) = (path.clone(), task_progress.clone());
+            Box::pin(async move {
+                import::<DB, C>(&prefix, path, format, progress, db)
+                    .await
+                    .map(SqlxTaskOutput::Done)
+            }) as SqlxEventFuture<C>
+        });
+        Self::new(false, SqlxEventOp::Import(C::table(), func, progress))
+    }
+
+    /// Construct a new [`SqlxEvent`] inserting many components at once
+    ///
+    /// Meant for thousands of rows, like generated worlds or telemetry. With
+    /// the `postgres` feature, Postgres streams them with a single `COPY`,
+    /// which is much faster than inserting row by row. Other databases
+    /// insert [`COPY_BATCH_SIZE`] rows per `INSERT`, in one transaction. Like
+    /// [`Self::import`], [`SqlxEventStatus::Progress`] events are sent as
+    /// rows are sent, followed by a [`SqlxEventStatus::Done`] with the total.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use sqlx::{FromRow, Sqlite};
+    /// # use bevy_sqlx::{SqlxEvent, PrimaryKey, SqlxValue, ToRow};
+    /// # #[derive(Component, FromRow)]
+    /// # struct Foo { id: u32, text: String }
+    /// # impl PrimaryKey for Foo {
+    /// #     type Column = u32;
+    /// #     fn primary_key(&self) -> Self::Column { self.id }
+    /// # }
+    /// # impl ToRow for Foo {
+    /// #     fn table() -> &'static str { "foos" }
+    /// #     fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+    /// #         vec![("text", self.text.clone().into())]
+    /// #     }
+    /// # }
+    /// let foos = (0..10_000)
+    ///     .map(|id| Foo { id, text: format!("foo {id}") })
+    ///     .collect();
+    /// SqlxEvent::<Sqlite, Foo>::copy_in(foos);
+    /// ```
+    pub fn copy_in(components: Vec<C>) -> Self
+    where
+        for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+    {
+        let rows: SqlxCopyRows = components.iter().map(C::to_row).collect();
+        let progress = Arc::new(SqlxProgress::default());
+        let task_progress = progress.clone();
+        let func = Arc::new(move |db: Pool<DB>, prefix: String| {
+            let (rows, progress) = (rows.clone(), task_progress.clone());
+            Box::pin(async move {
+                let table = format!("{prefix}{}", C::table());
+                copy_in::<DB>(&table, rows, progress, db)
+                    .await
+                    .map(SqlxTaskOutput::Done)
+            }) as SqlxEventFuture<C>
+        });
+        Self::new(false, SqlxEventOp::Import(C::table(), func, progress))
+    }
+
+    /// Construct a new [`SqlxEvent`] upserting many components at once, like
+    /// a whole inventory
+    ///
+    /// Rows are inserted with as many per `INSERT` as the database can bind,
+    /// updating the rows whose primary key already exists, all in one
+    /// transaction. Like [`Self::copy_in`], [`SqlxEventStatus::Progress`]
+    /// events are sent as rows are written, followed by a
+    /// [`SqlxEventStatus::Done`] with the total. Components should have
+    /// distinct primary keys, as one statement can't update a row twice on
+    /// every database.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use sqlx::{FromRow, Sqlite};
+    /// # use bevy_sqlx::{SqlxEvent, PrimaryKey, SqlxValue, ToRow};
+    /// # #[derive(Component, FromRow)]
+    /// # struct Foo { id: u32, text: String }
+    /// # impl PrimaryKey for Foo {
+    /// #     type Column = u32;
+    /// #     fn primary_key(&self) -> Self::Column { self.id }
+    /// # }
+    /// # impl ToRow for Foo {
+    /// #     fn table() -> &'static str { "foos" }
+    /// #     fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+    /// #         vec![("id", self.id.into()), ("text", self.text.clone().into())]
+    /// #     }
+    /// # }
+    /// let inventory = vec![
+    ///     Foo { id: 1, text: "sword".into() },
+    ///     Foo { id: 2, text: "shield".into() },
+    /// ];
+    /// SqlxEvent::<Sqlite, Foo>::upsert_many(inventory);
+    /// ```
+    pub fn upsert_many(components: Vec<C>) -> Self
+    where
+        for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+    {
+        Self::upsert_rows(components.iter().map(C::to_row).collect())
+    }
+
+    /// Construct a new [`SqlxEvent`] upserting the rows of many components,
+    /// see [`Self::upsert_many`]
+    pub(crate) fn upsert_rows(rows: SqlxCopyRows) -> Self
+    where
+        for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+    {
+        let progress = Arc::new(SqlxProgress::default());
+        let task_progress = progress.clone();
+        let func = Arc::new(move |db: Pool<DB>, prefix: String| {
+            let (rows, progress) = (rows.clone(), task_progress.clone());
+            Box::pin(async move {
+                let table = format!("{prefix}{}", C::table());
+                let key = C::primary_key_name();
+                upsert_many::<DB>(&table, key, rows, progress, db)
+                    .await
+                    .map(SqlxTaskOutput::Done)
+            }) as SqlxEventFuture<C>
+        });
+        Self::new(false, SqlxEventOp::Import(C::table(), func, progress))
+    }
+
+    /// Construct a new [`SqlxEvent`] selecting the row with primary key `pk`,
+    /// and inserting its component onto `entity`
+    ///
+    /// Unlike synchronizing events, the component isn't matched to an
+    /// entity by primary key or spawned, so game code can own the entity's
+    /// creation. Upon a successful DB interaction, a
+    /// [`SqlxEventStatus::Update`] event will be sent. If there's no such
+    /// row, a [`SqlxEventStatus::Error`] is sent instead. If `entity` was
+    /// despawned in the meantime, the plugin's [`SqlxDespawnedTarget`] says
+    /// what's done with the component.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use sqlx::{FromRow, Sqlite};
+    /// # use bevy_sqlx::{SqlxEvent, PrimaryKey, SqlxValue, ToRow};
+    /// # #[derive(Component, FromRow)]
+    /// # struct Foo(u32);
+    /// # impl PrimaryKey for Foo {
+    /// #     type Column = u32;
+    /// #     fn primary_key(&self) -> Self::Column { self.0 }
+    /// # }
+    /// # impl ToRow for Foo {
+    /// #     fn table() -> &'static str { "foos" }
+    /// #     fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+    /// #         vec![("id", self.0.into())]
+    /// #     }
+    /// # }
+    /// fn hydrate(
+    ///     mut commands: Commands,
+    ///     mut events: EventWriter<SqlxEvent<Sqlite, Foo>>,
+    /// ) {
+    ///     let entity = commands.spawn_empty().id();
+    ///     events.send(SqlxEvent::<Sqlite, Foo>::load_into(entity, 1));
+    /// }
+    /// ```
+    pub fn load_into(entity: Entity, pk: C::Column) -> Self
+    where
+        C::Column: Into<SqlxValue>,
+    {
+        let mut event = Self::select(pk);
+        event.target = Some(entity);
+        event
+    }
+
+    fn generated(stmt: SqlxStatement) -> Self {
+        Self::new(
+            false,
+            SqlxEventOp::Statement(Box::new(stmt), Some(C::to_row)),
+        )
+    }
+}
+
+/// An [`Event`] sent while processing an [`SqlxEvent`]
+///
+/// ### Example
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::Sqlite;
+/// # use bevy_sqlx::{SqlxEventStatus, SqlxDummy};
+/// fn status(mut statuses: EventReader<SqlxEventStatus<Sqlite, SqlxDummy>>) {
+///     for status in statuses.read() {
+///         match status {
+///             SqlxEventStatus::Throttled(id) => {},
+///             SqlxEventStatus::Start(id) => {},
+///             SqlxEventStatus::Queued(id) => {},
+///             SqlxEventStatus::Executing(id) => {},
+///             SqlxEventStatus::Decoded(id, rows) => {},
+///             SqlxEventStatus::Return(id, comp) => {},
+///             SqlxEventStatus::Optional(id, comp) => {},
+///             SqlxEventStatus::Scalar(id, value) => {},
+///             SqlxEventStatus::Progress(id, rows) => {},
+///             SqlxEventStatus::Done(id, rows) => {},
+///             SqlxEventStatus::Inserted(id, key) => {},
+///             SqlxEventStatus::Spawn(id, pk, _) => {},
+///             SqlxEventStatus::Update(id, pk, _) => {},
+///             SqlxEventStatus::Constraint(id, violation) => {},
+///             SqlxEventStatus::Error(id, err, event) => {},
+///             SqlxEventStatus::Complete(id, summary) => {},
+///             SqlxEventStatus::SchemaChanged(id) => {},
+///         }
+///     }
+/// }
+/// ```
+#[derive(Event)]
 pub enum SqlxEventStatus<DB: Database, C: SqlxComponent<DB::Row>> {
+    Throttled(SqlxEventId),
     Start(SqlxEventId),
+    /// The event's task was spawned, and waits for a thread of its
+    /// [`SqlxTaskPool`]
+    Queued(SqlxEventId),
+    /// The event's task started running
+    Executing(SqlxEventId),
+    /// The event's rows were decoded into this many components, which are
+    /// synced or returned next
+    Decoded(SqlxEventId, u64),
     Return(SqlxEventId, Vec<C>),
+    /// The component of a [`SqlxEvent::query_optional`], if it had a row
+    Optional(SqlxEventId, Option<C>),
+    Scalar(SqlxEventId, SqlxValue),
+    Progress(SqlxEventId, u64),
+    Done(SqlxEventId, u64),
+    /// The key generated for an inserted row, when it isn't returned
+    Inserted(SqlxEventId, i64),
     Spawn(SqlxEventId, C::Column, PhantomData<DB>),
     Update(SqlxEventId, C::Column, PhantomData<DB>),
-    Error(SqlxEventId, Error),
+    /// The constraint a write violated, sent just before its
+    /// [`SqlxEventStatus::Error`]
+    Constraint(SqlxEventId, SqlxConstraintViolation),
+    Error(SqlxEventId, Error, SqlxEvent<DB, C>),
+    /// The synchronizing event is finished, and every component was synced
+    Complete(SqlxEventId, SqlxSyncSummary),
+    /// The SQL of a [`SqlxEvent::ddl`] was executed
+    SchemaChanged(SqlxEventId),
+}
+
+/// Name the data of each status, and show the context of a failed event,
+/// e.g. `Error { id: 3, error: ..., event: #3 "save" query: UPDATE ... }`
+impl<DB: Database, C: SqlxComponent<DB::Row>> fmt::Debug
+    for SqlxEventStatus<DB, C>
+where
+    C: fmt::Debug,
+    C::Column: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (name, id) = match self {
+            SqlxEventStatus::Throttled(id) => ("Throttled", id),
+            SqlxEventStatus::Start(id) => ("Start", id),
+            SqlxEventStatus::Queued(id) => ("Queued", id),
+            SqlxEventStatus::Executing(id) => ("Executing", id),
+            SqlxEventStatus::Decoded(id, _) => ("Decoded", id),
+            SqlxEventStatus::Return(id, _) => ("Return", id),
+            SqlxEventStatus::Optional(id, _) => ("Optional", id),
+            SqlxEventStatus::Scalar(id, _) => ("Scalar", id),
+            SqlxEventStatus::Progress(id, _) => ("Progress", id),
+            SqlxEventStatus::Done(id, _) => ("Done", id),
+            SqlxEventStatus::Inserted(id, _) => ("Inserted", id),
+            SqlxEventStatus::Spawn(id, _, _) => ("Spawn", id),
+            SqlxEventStatus::Update(id, _, _) => ("Update", id),
+            SqlxEventStatus::Constraint(id, _) => ("Constraint", id),
+            SqlxEventStatus::Error(id, _, _) => ("Error", id),
+            SqlxEventStatus::Complete(id, _) => ("Complete", id),
+            SqlxEventStatus::SchemaChanged(id) => ("SchemaChanged", id),
+        };
+        let mut f = f.debug_struct(name);
+        f.field("id", &format_args!("{id}"));
+        match self {
+            SqlxEventStatus::Decoded(_, rows) => f.field("rows", rows),
+            SqlxEventStatus::Return(_, components) => {
+                f.field("components", components)
+            }
+            SqlxEventStatus::Optional(_, component) => {
+                f.field("component", component)
+            }
+            SqlxEventStatus::Scalar(_, value) => f.field("value", value),
+            SqlxEventStatus::Progress(_, rows) => f.field("rows", rows),
+            SqlxEventStatus::Done(_, rows) => f.field("rows", rows),
+            SqlxEventStatus::Inserted(_, key) => f.field("key", key),
+            SqlxEventStatus::Spawn(_, pk, _) => f.field("pk", pk),
+            SqlxEventStatus::Update(_, pk, _) => f.field("pk", pk),
+            SqlxEventStatus::Constraint(_, violation) => {
+                f.field("violation", violation)
+            }
+            SqlxEventStatus::Error(_, err, event) => f
+                .field("error", &format_args!("{err}"))
+                .field("event", &format_args!("{event}")),
+            SqlxEventStatus::Complete(_, summary) => {
+                f.field("summary", summary)
+            }
+            _ => &mut f,
+        };
+        f.finish()
+    }
 }
 
 impl<DB: Database, C: SqlxComponent<DB::Row>> SqlxEventStatus<DB, C> {
     pub fn id(&self) -> SqlxEventId {
         match *self {
-            SqlxEventStatus::Start(id)
+            SqlxEventStatus::Throttled(id)
+            | SqlxEventStatus::Start(id)
+            | SqlxEventStatus::Queued(id)
+            | SqlxEventStatus::Executing(id)
+            | SqlxEventStatus::Decoded(id, _)
             | SqlxEventStatus::Return(id, _)
+            | SqlxEventStatus::Optional(id, _)
+            | SqlxEventStatus::Scalar(id, _)
+            | SqlxEventStatus::Progress(id, _)
+            | SqlxEventStatus::Done(id, _)
+            | SqlxEventStatus::Inserted(id, _)
             | SqlxEventStatus::Spawn(id, _, _)
             | SqlxEventStatus::Update(id, _, _)
-            | SqlxEventStatus::Error(id, _) => id,
+            | SqlxEventStatus::Constraint(id, _)
+            | SqlxEventStatus::Error(id, ..)
+            | SqlxEventStatus::Complete(id, _)
+            | SqlxEventStatus::SchemaChanged(id) => id,
+        }
+    }
+
+    /// Return the [`SqlxErrorKind`] of the error, if this is an
+    /// [`SqlxEventStatus::Error`]
+    pub fn error_kind(&self) -> Option<SqlxErrorKind> {
+        match self {
+            SqlxEventStatus::Error(_, err, _) => Some(SqlxErrorKind::of(err)),
+            _ => None,
+        }
+    }
+
+    /// Return the event which failed, if this is an
+    /// [`SqlxEventStatus::Error`]
+    ///
+    /// Events are [`Clone`], so it can simply be sent again, for example
+    /// once the connection is restored.
+    pub fn failed_event(&self) -> Option<&SqlxEvent<DB, C>> {
+        match self {
+            SqlxEventStatus::Error(_, _, event) => Some(event),
+            _ => None,
+        }
+    }
+}
+
+/// What a synchronizing [`SqlxEvent`] did, sent with
+/// [`SqlxEventStatus::Complete`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SqlxSyncSummary {
+    pub spawned: u64,
+    pub updated: u64,
+    /// Components the plugin's [`SqlxApply`] ignored
+    pub ignored: u64,
+    /// Errors sent for the event, when its task failed
+    pub errors: u64,
+}
+
+impl SqlxSyncSummary {
+    /// The number of components which were synced
+    pub fn synced(&self) -> u64 {
+        self.spawned + self.updated + self.ignored
+    }
+
+    pub(crate) fn add(&mut self, applied: SqlxApplied) {
+        match applied {
+            SqlxApplied::Spawned => self.spawned += 1,
+            SqlxApplied::Updated => self.updated += 1,
+            SqlxApplied::Ignored => self.ignored += 1,
         }
     }
 }
@@ -213,40 +1545,339 @@ impl<DB: Database + Sync, C: SqlxComponent<DB::Row>> SqlxEvent<DB, C>
 where
     for<'c> &'c mut <DB as Database>::Connection: Executor<'c, Database = DB>,
     for<'q> <DB as Database>::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+    for<'r> SqlxValue: Decode<'r, DB>,
+    usize: ColumnIndex<DB::Row>,
 {
     /// A [`System`] which listens for [`SqlxEvent`]s and processes them
     ///
     /// This system performs the following actions:
-    /// - A [`SqlxEventStatus::Start`] event is sent
+    /// - Events without an id are assigned the next one of the
+    ///   [`SqlxEventIds`]
+    /// - Events sent while the connection to the database is lost are held
+    ///   until it's restored, see [`SqlxTasks::handle_reconnect`]
+    /// - Events sent while the [`SqlxDatabase`] is being swapped are held
+    ///   until it's swapped, see [`SqlxSwapDatabase`]
+    /// - Events over the plugin's [`SqlxRateLimit`] are queued for a later
+    ///   frame, and a [`SqlxEventStatus::Throttled`] event is sent
+    /// - Events are added to the [`SqlxEventRegistry`]
+    /// - A [`SqlxEventStatus::Start`] event is sent, followed by a
+    ///   [`SqlxEventStatus::Queued`] if the plugin was built
+    ///   [`SqlxPlugin::with_detailed_statuses`]
+    /// - Generated statements are scoped to the [`TenantId`], if the plugin
+    ///   was built [`SqlxPlugin::with_tenant`]
+    /// - Generated writes are logged to the [`AUDIT_TABLE`], if the plugin
+    ///   was built [`SqlxPlugin::with_audit`]
+    /// - Statements run with the event's or plugin's statement timeout, see
+    ///   [`SqlxPlugin::with_statement_timeout`]
     /// - A new [`Task`](bevy::tasks::Task) for [`SqlxTasks::handle_tasks`]
-    /// is spawned
+    ///   is spawned
+    #[allow(clippy::too_many_arguments)]
     pub fn handle_events(
         database: Res<SqlxDatabase<DB>>,
+        config: Res<SqlxConfig<DB, C>>,
+        tenant: Option<Res<TenantId>>,
+        swap: Res<SqlxSwap<DB>>,
+        ids: Res<SqlxEventIds>,
         mut tasks: ResMut<SqlxTasks<DB, C>>,
+        mut registry: ResMut<SqlxEventRegistry<DB, C>>,
         mut events: EventReader<SqlxEvent<DB, C>>,
         mut status: EventWriter<SqlxEventStatus<DB, C>>,
     ) {
-        let task_pool = AsyncComputeTaskPool::get();
-        for event in events.read() {
-            status.send(SqlxEventStatus::Start(event.id()));
+        let mut events: Vec<_> = events.read().cloned().collect();
+        for event in &mut events {
+            event.assign_id(&ids);
+        }
+        registry.insert(&events);
+        if tasks.is_connection_lost() {
+            tasks.lost.extend(events);
+            return;
+        }
+        if swap.is_swapping() {
+            // Held as throttled, to be dispatched first once swapped.
+            tasks.throttled.extend(events);
+            return;
+        }
+
+        let throttled = std::mem::take(&mut tasks.throttled);
+        let pending = throttled
+            .into_iter()
+            .map(|event| (event, true))
+            .chain(events.into_iter().map(|event| (event, false)));
+
+        for (event, was_throttled) in pending {
+            if let Some(limit) = config.rate_limit {
+                // Keep events in order once the first one is throttled.
+                let throttle = !tasks.throttled.is_empty()
+                    || !tasks
+                        .bucket
+                        .get_or_insert_with(|| SqlxTokenBucket::new(&limit))
+                        .try_acquire(&limit);
+                if throttle {
+                    if !was_throttled {
+                        status.send(SqlxEventStatus::Throttled(
+                            event.assigned_id(),
+                        ));
+                    }
+                    tasks.throttled.push_back(event);
+                    continue;
+                }
+            }
+
             let db = database.pool.clone();
-            let future = (event.func)(db);
-            let task = task_pool.spawn(async move { future.await });
-            tasks.components.push((event.id(), event.will_sync(), task));
+            let tenant = tenant.as_deref();
+            event.dispatch(db, &config, tenant, &mut tasks, &mut status);
+        }
+    }
+
+    /// Send a [`SqlxEventStatus::Start`] and spawn this event's task,
+    /// regardless of the plugin's [`SqlxRateLimit`]
+    pub(crate) fn dispatch(
+        &self,
+        db: Pool<DB>,
+        config: &SqlxConfig<DB, C>,
+        tenant: Option<&TenantId>,
+        tasks: &mut SqlxTasks<DB, C>,
+        status: &mut EventWriter<SqlxEventStatus<DB, C>>,
+    ) {
+        status.send(SqlxEventStatus::Start(self.assigned_id()));
+        match self.future(db, config, tenant) {
+            Ok(future) => {
+                let pool = self.task_pool.as_ref().unwrap_or(&config.task_pool);
+                let (id, sender) = (self.assigned_id(), tasks.sender.clone());
+                let started = config.detailed_statuses.then(|| {
+                    let started = Arc::new(AtomicBool::new(false));
+                    tasks.queued.push((id, started.clone()));
+                    status.send(SqlxEventStatus::Queued(id));
+                    started
+                });
+                let task = pool.spawn(async move {
+                    if let Some(started) = started {
+                        started.store(true, Ordering::Relaxed);
+                    }
+                    // The receiver is only gone with the plugin's resource.
+                    let _ = sender.send((id, future.await));
+                });
+                tasks.components.push((self.clone(), Instant::now(), task));
+            }
+            Err(err) => {
+                status.send(SqlxEventStatus::Error(
+                    self.assigned_id(),
+                    err,
+                    self.clone(),
+                ));
+            }
+        }
+    }
+
+    /// Build the future performing this event's database interaction
+    fn future(
+        &self,
+        db: Pool<DB>,
+        config: &SqlxConfig<DB, C>,
+        tenant: Option<&TenantId>,
+    ) -> Result<SqlxEventFuture<C>, Error> {
+        if let Some(misuse) = self.misuse {
+            return Err(Error::Configuration(misuse.into()));
+        }
+        if config.read_only && !self.is_select() {
+            let err = "plugin is read only, and the event isn't a SELECT";
+            return Err(Error::Configuration(err.into()));
+        }
+
+        let timeout = self.statement_timeout.or(config.statement_timeout);
+        let (stmt, to_row) = match &self.op {
+            SqlxEventOp::Query(sql, binds, fetch)
+                if *fetch != SqlxFetch::None =>
+            {
+                let (sql, binds, fetch) = (sql.clone(), binds.clone(), *fetch);
+                if let Some(migrations) = config.migrations.clone() {
+                    return Ok(timed(db, timeout, move |conn| {
+                        Box::pin(async move {
+                            let components =
+                                fetch_migrated(&sql, binds, &migrations, conn)
+                                    .await?;
+                            fetch.output(components)
+                        })
+                    }));
+                }
+                return Ok(timed(db, timeout, move |conn| {
+                    Box::pin(async move {
+                        let mut query = sqlx::query_as(&sql);
+                        for value in binds {
+                            query = query.bind(value);
+                        }
+                        fetch.output(query.fetch_all(conn).await?)
+                    })
+                }));
+            }
+            SqlxEventOp::Query(sql, binds, _) => {
+                let (sql, binds) = (sql.clone(), binds.clone());
+                let insert = SqlxStatementKind::of(&sql)
+                    == Some(SqlxStatementKind::Insert);
+                return Ok(timed(db, timeout, move |conn| {
+                    Box::pin(async move {
+                        if insert {
+                            let inserted =
+                                execute_insert(&sql, binds.clone(), &mut *conn)
+                                    .await?;
+                            match inserted {
+                                Some((rows, key)) if rows > 0 => {
+                                    let done = SqlxTaskOutput::Done(rows);
+                                    let done = Box::new(done);
+                                    return Ok(SqlxTaskOutput::Inserted(
+                                        key, done,
+                                    ));
+                                }
+                                Some(_) => return Ok(SqlxTaskOutput::Done(0)),
+                                None => {}
+                            }
+                        }
+                        execute_counted(&sql, binds, conn)
+                            .await
+                            .map(SqlxTaskOutput::Done)
+                    })
+                }));
+            }
+            SqlxEventOp::Call(func) => return Ok(func(db)),
+            SqlxEventOp::Import(_, func, _) => {
+                let prefix = config.table_name("");
+                return Ok(func(db, prefix));
+            }
+            SqlxEventOp::Statement(stmt, to_row) => (stmt.clone(), *to_row),
+            SqlxEventOp::Aggregate(sql) => {
+                let sql = sql.clone();
+                return Ok(timed(db, timeout, move |conn| {
+                    Box::pin(async move {
+                        sqlx::query_scalar(&sql)
+                            .fetch_one(conn)
+                            .await
+                            .map(SqlxTaskOutput::Scalar)
+                    })
+                }));
+            }
+            SqlxEventOp::Export(sql, path, format, progress) => {
+                let (sql, path) = (sql.clone(), path.clone());
+                let (format, progress) = (*format, progress.clone());
+                return Ok(Box::pin(async move {
+                    export(sql, path, format, progress, db)
+                        .await
+                        .map(SqlxTaskOutput::Done)
+                }));
+            }
+            SqlxEventOp::Backup(path) => {
+                let path = path.clone();
+                return Ok(Box::pin(async move {
+                    backup(path, db).await.map(SqlxTaskOutput::Done)
+                }));
+            }
+            SqlxEventOp::Ddl(sql) => {
+                let sql = sql.clone();
+                return Ok(timed(db, timeout, move |conn| {
+                    Box::pin(async move {
+                        conn.execute(&*sql).await?;
+                        Ok(SqlxTaskOutput::SchemaChanged)
+                    })
+                }));
+            }
+        };
+
+        let stmt = scoped(*stmt, config, tenant)?;
+
+        if stmt.kind() == SqlxStatementKind::Insert && !stmt.is_returning() {
+            let key = C::primary_key_name();
+            let (sql, binds) = (stmt.sql::<DB>(), stmt.binds());
+            Ok(timed(db, timeout, move |conn| {
+                Box::pin(async move {
+                    match execute_insert(&sql, binds, &mut *conn).await? {
+                        Some((rows, id)) if rows > 0 => {
+                            let mut select =
+                                SqlxStatement::select(stmt.table())
+                                    .prefixed(stmt.prefix())
+                                    .filter(key, id);
+                            if let Some(schema) = stmt.schema() {
+                                select = select.in_schema(schema);
+                            }
+                            let components = select.fetch_all(conn).await?;
+                            let output = SqlxTaskOutput::Components(components);
+                            Ok(SqlxTaskOutput::Inserted(id, Box::new(output)))
+                        }
+                        _ => Ok(SqlxTaskOutput::Components(Vec::new())),
+                    }
+                })
+            }))
+        } else if config.audit && stmt.kind() != SqlxStatementKind::Select {
+            let (id, label) = (self.assigned_id(), self.label.clone());
+            Ok(timed(db, timeout, move |conn| {
+                Box::pin(async move {
+                    audited(stmt, to_row, id, label, conn)
+                        .await
+                        .map(SqlxTaskOutput::Components)
+                })
+            }))
+        } else if let Some(migrations) = config.migrations.clone() {
+            let (sql, binds) = (stmt.sql::<DB>(), stmt.binds());
+            Ok(timed(db, timeout, move |conn| {
+                Box::pin(async move {
+                    fetch_migrated(&sql, binds, &migrations, conn)
+                        .await
+                        .map(SqlxTaskOutput::Components)
+                })
+            }))
+        } else {
+            Ok(timed(db, timeout, move |conn| {
+                Box::pin(async move {
+                    stmt.fetch_all(conn).await.map(SqlxTaskOutput::Components)
+                })
+            }))
+        }
+    }
+}
+
+/// Scope `stmt` to the tenant, if the plugin was built
+/// [`SqlxPlugin::with_tenant`], and prefix and qualify its table
+fn scoped<DB: Database, C: SqlxComponent<DB::Row>>(
+    mut stmt: SqlxStatement,
+    config: &SqlxConfig<DB, C>,
+    tenant: Option<&TenantId>,
+) -> Result<SqlxStatement, Error> {
+    if let Some(prefix) = &config.table_prefix {
+        stmt = stmt.prefixed(prefix);
+    }
+    if let Some(schema) = &config.schema {
+        stmt = stmt.in_schema(schema);
+    }
+    stmt = match (config.tenant_column, tenant) {
+        (Some(column), Some(tenant)) => stmt.tenant(column, tenant.0.clone()),
+        (Some(_), None) => {
+            let err = "missing TenantId resource".into();
+            return Err(Error::Configuration(err));
         }
+        (None, _) => stmt,
+    };
+    // `ON DUPLICATE KEY UPDATE` has no `WHERE`.
+    let upsert = stmt.kind() == SqlxStatementKind::Upsert;
+    if DB::NAME == "MySQL" && upsert && !stmt.filters().is_empty() {
+        let err = "MySQL upserts can't be filtered".into();
+        return Err(Error::Configuration(err));
     }
+    Ok(stmt)
 }
 
 #[cfg(test)]
 mod tests {
     use crate::*;
+    use assert_matches::assert_matches;
     use bevy::ecs::system::SystemState;
     use bevy::prelude::*;
-    use bevy::tasks::{AsyncComputeTaskPool, TaskPool};
+    use bevy::tasks::{
+        AsyncComputeTaskPool, IoTaskPool, TaskPool, TaskPoolBuilder,
+    };
+    use serde::Deserialize;
     use sqlx::{FromRow, Sqlite};
-    use assert_matches::assert_matches;
+    use std::sync::Arc;
 
-    #[derive(Component, FromRow, Debug)]
+    #[derive(Component, FromRow, Deserialize, Debug)]
     struct Foo {
         id: u32,
         text: String,
@@ -259,11 +1890,27 @@ mod tests {
         }
     }
 
+    impl ToRow for Foo {
+        fn table() -> &'static str {
+            "foos"
+        }
+
+        fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+            vec![("text", self.text.clone().into())]
+        }
+    }
+
     fn setup_app() -> App {
-        AsyncComputeTaskPool::get_or_init(|| TaskPool::new());
+        setup_app_with(|plugin| plugin)
+    }
+
+    fn setup_app_with(
+        configure: impl FnOnce(SqlxPlugin<Sqlite, Foo>) -> SqlxPlugin<Sqlite, Foo>,
+    ) -> App {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
         let url = "sqlite:db/sqlite.db";
         let mut app = App::new();
-        app.add_plugins(SqlxPlugin::<Sqlite, Foo>::from_url(url));
+        app.add_plugins(configure(SqlxPlugin::<Sqlite, Foo>::from_url(url)));
         app
     }
 
@@ -279,23 +1926,23 @@ mod tests {
     }
 
     fn wait_for_event(
-        mut app: &mut App,
-        mut system_state: &mut SystemState<
+        app: &mut App,
+        system_state: &mut SystemState<
             EventReader<SqlxEventStatus<Sqlite, Foo>>,
         >,
     ) {
-        while no_events(&mut app, &mut system_state) {
+        while no_events(app, system_state) {
             app.update();
         }
     }
 
     fn skip_started_event(
-        mut app: &mut App,
-        mut system_state: &mut SystemState<
+        app: &mut App,
+        system_state: &mut SystemState<
             EventReader<SqlxEventStatus<Sqlite, Foo>>,
         >,
     ) {
-        while no_events(&mut app, &mut system_state) {
+        while no_events(app, system_state) {
             app.update();
         }
         let mut reader = system_state.get(app.world());
@@ -303,6 +1950,86 @@ mod tests {
         assert_matches!(events.next().unwrap(), SqlxEventStatus::Start(_));
     }
 
+    #[test]
+    fn test_event_ids_per_world() {
+        let (mut first, mut second) = (setup_app(), setup_app());
+        let sql = "SELECT * FROM foos";
+        first.world_mut().commands().add(SqlxEvent::<Sqlite, Foo>::query(sql));
+        second.world_mut().commands().add(SqlxEvent::<Sqlite, Foo>::query(sql));
+        first.world_mut().flush();
+        second.world_mut().flush();
+
+        let started = |app: &mut App| {
+            app.update();
+            let events =
+                app.world().resource::<Events<SqlxEventStatus<Sqlite, Foo>>>();
+            events
+                .iter_current_update_events()
+                .find_map(|status| match status {
+                    SqlxEventStatus::Start(id) => Some(*id),
+                    _ => None,
+                })
+                .unwrap()
+        };
+        assert_eq!(started(&mut first), started(&mut second));
+    }
+
+    #[test]
+    fn test_event_sender_id() {
+        let mut app = setup_app();
+        let mut system_state: SystemState<SqlxEventSender<Sqlite, Foo>> =
+            SystemState::new(app.world_mut());
+        let mut sender = system_state.get_mut(app.world_mut());
+        let sql = "SELECT * FROM foos";
+        let id = sender.send(SqlxEvent::query(sql));
+        system_state.apply(app.world_mut());
+
+        app.update();
+        let events =
+            app.world().resource::<Events<SqlxEventStatus<Sqlite, Foo>>>();
+        assert!(events.iter_current_update_events().any(
+            |status| matches!(status, SqlxEventStatus::Start(i) if *i == id)
+        ));
+    }
+
+    #[test]
+    fn test_debug() {
+        let sql = "SELECT *
+                   FROM foos WHERE text = 'a long enough string to be cut off'";
+        let event = SqlxEvent::<Sqlite, Foo>::query_sync(sql);
+        assert_eq!(
+            "#? query (sync): SELECT * FROM foos WHERE text = \
+             'a long enough string to be cut off'",
+            event.to_string()
+        );
+        let id = SqlxEventIds::default().next();
+        let event = event.with_id(id).with_label("load");
+        let debug = format!("{event:?}");
+        assert!(debug.contains("label: Some(\"load\")"));
+        assert!(debug.contains("kind: \"query\""));
+        assert!(debug.contains("sql: Some(\"SELECT * FROM foos WHERE"));
+
+        let long = format!("SELECT {}1", "1 + ".repeat(30));
+        let event = SqlxEvent::<Sqlite, Foo>::aggregate(&long).with_id(id);
+        let display = event.to_string();
+        assert!(display.ends_with("1 + 1..."));
+        assert!(display.len() < long.len());
+
+        let status = SqlxEventStatus::<Sqlite, Foo>::Done(id, 2);
+        assert_eq!(
+            format!("Done {{ id: {id}, rows: 2 }}"),
+            format!("{status:?}")
+        );
+        let status = SqlxEventStatus::<Sqlite, Foo>::Error(
+            id,
+            sqlx::Error::RowNotFound,
+            SqlxEvent::query(sql).with_id(id).with_label("load"),
+        );
+        let debug = format!("{status:?}");
+        assert!(debug.starts_with(&format!("Error {{ id: {id}, error: ")));
+        assert!(debug.contains(&format!("event: #{id} \"load\" query: SELECT")));
+    }
+
     #[test]
     fn test_query() {
         let mut app = setup_app();
@@ -325,37 +2052,337 @@ mod tests {
     }
 
     #[test]
-    fn test_query_sync() {
+    fn test_query_bind() {
         let mut app = setup_app();
-        let mut system_state: SystemState<Query<&Foo>> =
-            SystemState::new(app.world_mut());
+        let mut system_state: SystemState<
+            EventReader<SqlxEventStatus<Sqlite, Foo>>,
+        > = SystemState::new(app.world_mut());
 
-        let sql = "INSERT INTO foos (text) VALUES ('query_sync') RETURNING *";
-        let insert = SqlxEvent::<Sqlite, Foo>::query_sync(sql);
+        let sql = "INSERT INTO foos (text) VALUES (?) RETURNING *";
+        let insert = SqlxEvent::<Sqlite, Foo>::query(sql).bind("query_bind");
         app.world_mut().send_event(insert);
 
-        let mut tries = 0;
-        let mut len = system_state.get(app.world()).iter().len();
-        while !(len > 0) && tries < 1000 {
-            app.update();
-            len = system_state.get(app.world()).iter().len();
-            tries += 1;
-        }
+        skip_started_event(&mut app, &mut system_state);
+        wait_for_event(&mut app, &mut system_state);
 
-        let query = system_state.get(app.world());
-        assert_eq!("query_sync", query.single().text);
+        let mut reader = system_state.get(app.world());
+        let mut events = reader.read();
+        assert_matches!(events.next().unwrap(),
+            SqlxEventStatus::Return(_, components)
+                if components[0].text == "query_bind");
     }
 
     #[test]
-    fn test_call_sync() {
+    fn test_bind_misuse() {
         let mut app = setup_app();
-        let mut system_state: SystemState<Query<&Foo>> =
-            SystemState::new(app.world_mut());
+        let mut system_state: SystemState<
+            EventReader<SqlxEventStatus<Sqlite, Foo>>,
+        > = SystemState::new(app.world_mut());
 
-        let text = "call_sync";
-        let insert =
-            SqlxEvent::<Sqlite, Foo>::call_sync(move |db| async move {
-                sqlx::query_as("INSERT INTO foos (text) VALUES (?) RETURNING *")
+        let select = SqlxEvent::<Sqlite, Foo>::select_all().bind("misuse");
+        app.world_mut().send_event(select);
+        skip_started_event(&mut app, &mut system_state);
+        wait_for_event(&mut app, &mut system_state);
+        let mut reader = system_state.get(app.world());
+        assert_matches!(
+            reader.read().next().unwrap(),
+            SqlxEventStatus::Error(_, sqlx::Error::Configuration(_), _)
+        );
+    }
+
+    #[test]
+    fn test_statement_misuse() {
+        let mut app = setup_app();
+        let mut system_state: SystemState<
+            EventReader<SqlxEventStatus<Sqlite, Foo>>,
+        > = SystemState::new(app.world_mut());
+
+        let sql = "SELECT * FROM foos";
+        let query = SqlxEvent::<Sqlite, Foo>::query(sql).limit(1);
+        app.world_mut().send_event(query);
+        skip_started_event(&mut app, &mut system_state);
+        wait_for_event(&mut app, &mut system_state);
+        let mut reader = system_state.get(app.world());
+        assert_matches!(
+            reader.read().next().unwrap(),
+            SqlxEventStatus::Error(_, sqlx::Error::Configuration(_), _)
+        );
+    }
+
+    #[test]
+    fn test_query_done() {
+        let mut app = setup_app();
+        let mut system_state: SystemState<
+            EventReader<SqlxEventStatus<Sqlite, Foo>>,
+        > = SystemState::new(app.world_mut());
+
+        let sql = "INSERT INTO foos (text) VALUES ('done'), ('done');";
+        app.world_mut().send_event(SqlxEvent::<Sqlite, Foo>::query(sql));
+        skip_started_event(&mut app, &mut system_state);
+        wait_for_event(&mut app, &mut system_state);
+        let mut reader = system_state.get(app.world());
+        let mut events = reader.read();
+        assert_matches!(
+            events.next().unwrap(),
+            SqlxEventStatus::Inserted(_, key) if *key > 0
+        );
+        assert_matches!(events.next().unwrap(), SqlxEventStatus::Done(_, 2));
+    }
+
+    #[test]
+    fn test_query_insert_nothing() {
+        let mut app = setup_app();
+        let mut system_state: SystemState<
+            EventReader<SqlxEventStatus<Sqlite, Foo>>,
+        > = SystemState::new(app.world_mut());
+
+        let sql = "INSERT INTO foos (text) SELECT 'nothing' WHERE 0";
+        app.world_mut().send_event(SqlxEvent::<Sqlite, Foo>::query(sql));
+        skip_started_event(&mut app, &mut system_state);
+        wait_for_event(&mut app, &mut system_state);
+        let mut reader = system_state.get(app.world());
+        let mut events = reader.read();
+        assert_matches!(events.next().unwrap(), SqlxEventStatus::Done(_, 0));
+    }
+
+    #[test]
+    fn test_query_done_comment() {
+        let mut app = setup_app();
+        let mut system_state: SystemState<
+            EventReader<SqlxEventStatus<Sqlite, Foo>>,
+        > = SystemState::new(app.world_mut());
+
+        let sql = "INSERT INTO foos (text) VALUES ('done_comment')";
+        app.world_mut().send_event(SqlxEvent::<Sqlite, Foo>::query(sql));
+        skip_started_event(&mut app, &mut system_state);
+        wait_for_event(&mut app, &mut system_state);
+        system_state.get(app.world()).read().for_each(drop);
+
+        let sql = "DELETE FROM foos WHERE text = 'done_comment'; -- cleanup";
+        app.world_mut().send_event(SqlxEvent::<Sqlite, Foo>::query(sql));
+        skip_started_event(&mut app, &mut system_state);
+        wait_for_event(&mut app, &mut system_state);
+        let mut reader = system_state.get(app.world());
+        let mut events = reader.read();
+        assert_matches!(events.next().unwrap(), SqlxEventStatus::Done(_, 1));
+    }
+
+    #[test]
+    fn test_query_one() {
+        let mut app = setup_app();
+        let mut system_state: SystemState<
+            EventReader<SqlxEventStatus<Sqlite, Foo>>,
+        > = SystemState::new(app.world_mut());
+
+        let pool = app.world().resource::<SqlxDatabase<Sqlite>>().pool.clone();
+        let foo: Foo = bevy::tasks::block_on(async {
+            sqlx::query_as(
+                "INSERT INTO foos (text) VALUES ('query one') RETURNING *",
+            )
+            .fetch_one(&pool)
+            .await
+            .unwrap()
+        });
+
+        let sql = "SELECT * FROM foos WHERE id = ?";
+        let one = SqlxEvent::<Sqlite, Foo>::query_one(sql).bind(foo.id);
+        app.world_mut().send_event(one);
+        skip_started_event(&mut app, &mut system_state);
+        wait_for_event(&mut app, &mut system_state);
+        let mut reader = system_state.get(app.world());
+        assert_matches!(
+            reader.read().next().unwrap(),
+            SqlxEventStatus::Return(_, foos) if
+                foos.len() == 1 && foos[0].text == "query one"
+        );
+
+        let one = SqlxEvent::<Sqlite, Foo>::query_one(sql).bind(u32::MAX);
+        app.world_mut().send_event(one);
+        skip_started_event(&mut app, &mut system_state);
+        wait_for_event(&mut app, &mut system_state);
+        let mut reader = system_state.get(app.world());
+        assert_matches!(
+            reader.read().next().unwrap(),
+            SqlxEventStatus::Error(_, sqlx::Error::RowNotFound, _)
+        );
+
+        let sql = "SELECT * FROM foos LIMIT 2";
+        app.world_mut().send_event(SqlxEvent::<Sqlite, Foo>::query_one(sql));
+        skip_started_event(&mut app, &mut system_state);
+        wait_for_event(&mut app, &mut system_state);
+        let mut reader = system_state.get(app.world());
+        assert_matches!(
+            reader.read().next().unwrap(),
+            SqlxEventStatus::Error(_, sqlx::Error::Decode(_), _)
+        );
+    }
+
+    #[test]
+    fn test_constraint() {
+        let mut app = setup_app();
+        let mut system_state: SystemState<
+            EventReader<SqlxEventStatus<Sqlite, Foo>>,
+        > = SystemState::new(app.world_mut());
+
+        let pool = app.world().resource::<SqlxDatabase<Sqlite>>().pool.clone();
+        let foo: Foo = bevy::tasks::block_on(async {
+            sqlx::query_as(
+                "INSERT INTO foos (text) VALUES ('constraint') RETURNING *",
+            )
+            .fetch_one(&pool)
+            .await
+            .unwrap()
+        });
+
+        let sql = "INSERT INTO foos (id, text) VALUES (?, 'duplicate')";
+        let insert = SqlxEvent::<Sqlite, Foo>::query(sql).bind(foo.id);
+        app.world_mut().send_event(insert);
+        skip_started_event(&mut app, &mut system_state);
+        wait_for_event(&mut app, &mut system_state);
+        let mut reader = system_state.get(app.world());
+        let mut events = reader.read();
+        assert_matches!(
+            events.next().unwrap(),
+            SqlxEventStatus::Constraint(_, violation) if *violation ==
+                SqlxConstraintViolation {
+                    kind: SqlxErrorKind::UniqueViolation,
+                    constraint: None,
+                    columns: vec!["foos.id".into()],
+                }
+        );
+        assert_matches!(
+            events.next().unwrap(),
+            SqlxEventStatus::Error(_, sqlx::Error::Database(_), _)
+        );
+    }
+
+    #[test]
+    fn test_query_optional() {
+        let mut app = setup_app();
+        let mut system_state: SystemState<
+            EventReader<SqlxEventStatus<Sqlite, Foo>>,
+        > = SystemState::new(app.world_mut());
+
+        let sql = "SELECT * FROM foos WHERE id = ?";
+        let optional =
+            SqlxEvent::<Sqlite, Foo>::query_optional(sql).bind(u32::MAX);
+        app.world_mut().send_event(optional);
+        skip_started_event(&mut app, &mut system_state);
+        wait_for_event(&mut app, &mut system_state);
+        let mut reader = system_state.get(app.world());
+        assert_matches!(
+            reader.read().next().unwrap(),
+            SqlxEventStatus::Optional(_, None)
+        );
+
+        let sql = "INSERT INTO foos (text) VALUES ('optional') RETURNING *";
+        let optional = SqlxEvent::<Sqlite, Foo>::query_optional(sql);
+        app.world_mut().send_event(optional);
+        skip_started_event(&mut app, &mut system_state);
+        wait_for_event(&mut app, &mut system_state);
+        let mut reader = system_state.get(app.world());
+        assert_matches!(
+            reader.read().next().unwrap(),
+            SqlxEventStatus::Optional(_, Some(foo)) if foo.text == "optional"
+        );
+    }
+
+    #[test]
+    fn test_query_sync() {
+        let mut app = setup_app();
+        let mut system_state: SystemState<Query<&Foo>> =
+            SystemState::new(app.world_mut());
+
+        let sql = "INSERT INTO foos (text) VALUES ('query_sync') RETURNING *";
+        let insert = SqlxEvent::<Sqlite, Foo>::query_sync(sql);
+        app.world_mut().send_event(insert);
+
+        let mut tries = 0;
+        let mut len = system_state.get(app.world()).iter().len();
+        while len == 0 && tries < 1000 {
+            app.update();
+            len = system_state.get(app.world()).iter().len();
+            tries += 1;
+        }
+
+        let query = system_state.get(app.world());
+        assert_eq!("query_sync", query.single().text);
+    }
+
+    #[test]
+    fn test_with_sync() {
+        let mut app = setup_app();
+        let mut system_state: SystemState<Query<&Foo>> =
+            SystemState::new(app.world_mut());
+
+        let sql = "INSERT INTO foos (text) VALUES ('with_sync') RETURNING *";
+        let insert = SqlxEvent::<Sqlite, Foo>::query(sql).with_sync();
+        assert!(insert.will_sync());
+        assert!(!insert.clone().sync(false).will_sync());
+        app.world_mut().send_event(insert);
+
+        let mut tries = 0;
+        let mut len = system_state.get(app.world()).iter().len();
+        while len == 0 && tries < 1000 {
+            app.update();
+            len = system_state.get(app.world()).iter().len();
+            tries += 1;
+        }
+
+        let query = system_state.get(app.world());
+        assert_eq!("with_sync", query.single().text);
+    }
+
+    #[test]
+    fn test_sync_complete() {
+        let mut app = setup_app();
+        let mut system_state: SystemState<
+            EventReader<SqlxEventStatus<Sqlite, Foo>>,
+        > = SystemState::new(app.world_mut());
+
+        let sql = "INSERT INTO foos (text) VALUES ('complete'), ('complete') \
+                   RETURNING *";
+        let insert = SqlxEvent::<Sqlite, Foo>::query_sync(sql);
+        let id = app.world().resource::<SqlxEventIds>().next();
+        let insert = insert.with_id(id);
+        app.world_mut().send_event(insert);
+        skip_started_event(&mut app, &mut system_state);
+        wait_for_event(&mut app, &mut system_state);
+        let mut reader = system_state.get(app.world());
+        let mut events = reader.read();
+        assert_matches!(events.next().unwrap(), SqlxEventStatus::Spawn(..));
+        assert_matches!(events.next().unwrap(), SqlxEventStatus::Spawn(..));
+        let summary = SqlxSyncSummary { spawned: 2, ..default() };
+        assert_matches!(
+            events.next().unwrap(),
+            SqlxEventStatus::Complete(complete, s) if
+                *complete == id && *s == summary
+        );
+
+        let insert =
+            SqlxEvent::<Sqlite, Foo>::query_sync("SELECT * FROM missing");
+        app.world_mut().send_event(insert);
+        skip_started_event(&mut app, &mut system_state);
+        wait_for_event(&mut app, &mut system_state);
+        let mut reader = system_state.get(app.world());
+        let mut events = reader.read();
+        assert_matches!(events.next().unwrap(), SqlxEventStatus::Error(..));
+        assert_matches!(
+            events.next().unwrap(),
+            SqlxEventStatus::Complete(_, s) if s.errors == 1 && s.synced() == 0
+        );
+    }
+
+    #[test]
+    fn test_call_sync() {
+        let mut app = setup_app();
+        let mut system_state: SystemState<Query<&Foo>> =
+            SystemState::new(app.world_mut());
+
+        let text = "call_sync";
+        let insert =
+            SqlxEvent::<Sqlite, Foo>::call_sync(move |db| async move {
+                sqlx::query_as("INSERT INTO foos (text) VALUES (?) RETURNING *")
                     .bind(text)
                     .fetch_all(&db)
                     .await
@@ -364,7 +2391,7 @@ mod tests {
 
         let mut tries = 0;
         let mut len = system_state.get(app.world()).iter().len();
-        while !(len > 0) && tries < 1000 {
+        while len == 0 && tries < 1000 {
             app.update();
             len = system_state.get(app.world()).iter().len();
             tries += 1;
@@ -375,6 +2402,165 @@ mod tests {
     }
 
     #[test]
+    fn test_sync_budget() {
+        let mut app = setup_app_with(|plugin| plugin.with_sync_budget(1));
+        let mut system_state: SystemState<
+            EventReader<SqlxEventStatus<Sqlite, Foo>>,
+        > = SystemState::new(app.world_mut());
+
+        let sql = "INSERT INTO foos (text)
+                       VALUES ('budget'), ('budget'), ('budget') RETURNING *";
+        let insert = SqlxEvent::<Sqlite, Foo>::query_sync(sql);
+        let id = app.world().resource::<SqlxEventIds>().next();
+        let insert = insert.with_id(id);
+        app.world_mut().send_event(insert);
+
+        let mut spawns = Vec::new();
+        let mut done = None;
+        let mut tries = 0;
+        while done.is_none() && tries < 1000 {
+            app.update();
+            let mut reader = system_state.get(app.world());
+            let mut spawned = 0;
+            for status in reader.read() {
+                match status {
+                    SqlxEventStatus::Spawn(..) => spawned += 1,
+                    SqlxEventStatus::Done(done_id, _) if *done_id == id => {
+                        panic!("budgeted syncs only end with Complete")
+                    }
+                    SqlxEventStatus::Complete(done_id, summary)
+                        if *done_id == id =>
+                    {
+                        done = Some(summary.synced())
+                    }
+                    _ => {}
+                }
+            }
+            if spawned > 0 {
+                spawns.push(spawned);
+            }
+            tries += 1;
+        }
+
+        assert_eq!(Some(3), done);
+        assert_eq!(vec![1, 1, 1], spawns);
+        let mut query = app.world_mut().query::<&Foo>();
+        assert_eq!(3, query.iter(app.world()).len());
+    }
+
+    #[test]
+    fn test_detailed_statuses() {
+        let mut app = setup_app_with(|plugin| plugin.with_detailed_statuses());
+        let sql = "INSERT INTO foos (text)
+                       VALUES ('detailed'), ('detailed') RETURNING *";
+        let insert = SqlxEvent::<Sqlite, Foo>::query(sql);
+        let id = app.world().resource::<SqlxEventIds>().next();
+        let insert = insert.with_id(id);
+        app.world_mut().send_event(insert);
+
+        let mut kinds = Vec::new();
+        let mut tries = 0;
+        while !kinds.contains(&"return".to_string()) && tries < 1000 {
+            app.update();
+            let events =
+                app.world().resource::<Events<SqlxEventStatus<Sqlite, Foo>>>();
+            for status in events.iter_current_update_events() {
+                let summary = status.summary();
+                if summary.id == id {
+                    kinds.push(summary.kind);
+                }
+                if let SqlxEventStatus::Decoded(_, rows) = status {
+                    assert_eq!(2, *rows);
+                }
+            }
+            tries += 1;
+        }
+
+        let expected = ["start", "queued", "executing", "decoded", "return"];
+        assert_eq!(expected.map(String::from).to_vec(), kinds);
+    }
+
+    #[test]
+    fn test_frame_budget() {
+        let budget = std::time::Duration::ZERO;
+        let mut app = setup_app_with(|plugin| plugin.with_frame_budget(budget));
+        let sql = "SELECT * FROM foos WHERE text = 'frame_budget'";
+        for _ in 0..3 {
+            app.world_mut().send_event(SqlxEvent::<Sqlite, Foo>::query(sql));
+        }
+        app.update();
+        std::thread::sleep(std::time::Duration::from_millis(200));
+
+        let count = |app: &App| {
+            app.world().resource::<SqlxTasks<Sqlite, Foo>>().count()
+        };
+        assert!(count(&app) > 0);
+        let mut tries = 0;
+        while count(&app) > 0 && tries < 1000 {
+            let before = count(&app);
+            app.update();
+            assert!(before - count(&app) <= 1);
+            tries += 1;
+        }
+        assert_eq!(0, count(&app));
+    }
+
+    #[test]
+    fn test_many_tasks() {
+        let mut app = setup_app();
+        let sql = "SELECT * FROM foos WHERE text = 'many_tasks'";
+        let mut ids = Vec::new();
+        for _ in 0..200 {
+            let id = app.world().resource::<SqlxEventIds>().next();
+            let select = SqlxEvent::<Sqlite, Foo>::query(sql).with_id(id);
+            app.world_mut().send_event(select);
+            ids.push(id);
+        }
+
+        let mut returned = Vec::new();
+        let mut tries = 0;
+        while returned.len() < ids.len() && tries < 1000 {
+            app.update();
+            let events =
+                app.world().resource::<Events<SqlxEventStatus<Sqlite, Foo>>>();
+            for status in events.iter_current_update_events() {
+                if let SqlxEventStatus::Return(id, _) = status {
+                    returned.push(*id);
+                }
+            }
+            tries += 1;
+        }
+        returned.sort();
+        assert_eq!(ids, returned);
+        assert!(app.world().resource::<SqlxTasks<Sqlite, Foo>>().is_empty());
+    }
+
+    #[test]
+    fn test_sync_mode_write_only() {
+        let mut app = setup_app_with(|plugin| {
+            plugin.with_sync_mode(SqlxSyncMode::WriteOnly)
+        });
+        let mut system_state: SystemState<
+            EventReader<SqlxEventStatus<Sqlite, Foo>>,
+        > = SystemState::new(app.world_mut());
+
+        let sql = "INSERT INTO foos (text) VALUES ('write_only') RETURNING *";
+        let insert = SqlxEvent::<Sqlite, Foo>::query_sync(sql);
+        app.world_mut().send_event(insert);
+
+        skip_started_event(&mut app, &mut system_state);
+        wait_for_event(&mut app, &mut system_state);
+        let mut reader = system_state.get(app.world());
+        assert_matches!(reader.read().next().unwrap(),
+            SqlxEventStatus::Return(_, components)
+                if components[0].text == "write_only");
+        app.update();
+        let mut query = app.world_mut().query::<&Foo>();
+        assert_eq!(0, query.iter(app.world()).len());
+    }
+
+    #[test]
+    #[allow(clippy::type_complexity)]
     fn test_event_status_started() {
         let mut app = setup_app();
         let mut system_state: SystemState<(
@@ -478,5 +2664,731 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_insert() {
+        let mut app = setup_app();
+        let mut system_state: SystemState<
+            EventReader<SqlxEventStatus<Sqlite, Foo>>,
+        > = SystemState::new(app.world_mut());
+
+        let foo = Foo { id: 0, text: "insert".into() };
+        let insert = SqlxEvent::<Sqlite, Foo>::insert(&foo);
+        app.world_mut().send_event(insert);
+
+        skip_started_event(&mut app, &mut system_state);
+        wait_for_event(&mut app, &mut system_state);
+
+        let mut reader = system_state.get(app.world());
+        let mut events = reader.read();
+        assert_matches!(
+            events.next().unwrap(),
+            SqlxEventStatus::Return(_, components) if
+                components[0].text == "insert" && components[0].id != 0
+        )
+    }
+
+    #[test]
+    fn test_find_where() {
+        let mut app = setup_app();
+        let mut system_state: SystemState<
+            EventReader<SqlxEventStatus<Sqlite, Foo>>,
+        > = SystemState::new(app.world_mut());
+
+        struct FooExample {
+            id: Option<u32>,
+            text: Option<String>,
+        }
+
+        impl ToFilter for FooExample {
+            fn to_filter(&self) -> Vec<(&'static str, Option<SqlxValue>)> {
+                vec![
+                    ("id", self.id.map(Into::into)),
+                    ("text", self.text.clone().map(Into::into)),
+                ]
+            }
+        }
+
+        let text = format!("find where {}", rand::random::<u32>());
+        let pool = app.world().resource::<SqlxDatabase<Sqlite>>().pool.clone();
+        bevy::tasks::block_on(async {
+            sqlx::query("INSERT INTO foos (text) VALUES (?), (?)")
+                .bind(&text)
+                .bind(&text)
+                .execute(&pool)
+                .await
+                .unwrap()
+        });
+
+        let example = FooExample { id: None, text: Some(text.clone()) };
+        let find = SqlxEvent::<Sqlite, Foo>::find_where(example);
+        app.world_mut().send_event(find);
+        skip_started_event(&mut app, &mut system_state);
+        wait_for_event(&mut app, &mut system_state);
+        let mut reader = system_state.get(app.world());
+        let foo = match reader.read().next().unwrap() {
+            SqlxEventStatus::Return(_, foos) if foos.len() == 2 => foos[0].id,
+            status => panic!("unexpected {status:?}"),
+        };
+
+        let find = SqlxEvent::<Sqlite, Foo>::find_where([("id", foo.into())]);
+        app.world_mut().send_event(find);
+        skip_started_event(&mut app, &mut system_state);
+        wait_for_event(&mut app, &mut system_state);
+        let mut reader = system_state.get(app.world());
+        assert_matches!(
+            reader.read().next().unwrap(),
+            SqlxEventStatus::Return(_, foos) if
+                foos.len() == 1 && foos[0].id == foo && foos[0].text == text
+        );
+    }
+
+    #[test]
+    fn test_select_all_order_by_limit() {
+        let mut app = setup_app();
+        let mut system_state: SystemState<
+            EventReader<SqlxEventStatus<Sqlite, Foo>>,
+        > = SystemState::new(app.world_mut());
+
+        let pool = app.world().resource::<SqlxDatabase<Sqlite>>().pool.clone();
+        bevy::tasks::block_on(async {
+            sqlx::query("INSERT INTO foos (text) VALUES ('a'), ('b'), ('c')")
+                .execute(&pool)
+                .await
+                .unwrap()
+        });
+        let max: u32 = bevy::tasks::block_on(async {
+            sqlx::query_scalar("SELECT MAX(id) FROM foos")
+                .fetch_one(&pool)
+                .await
+                .unwrap()
+        });
+
+        let select =
+            SqlxEvent::<Sqlite, Foo>::select_all().order_by_desc("id").limit(2);
+        app.world_mut().send_event(select);
+        skip_started_event(&mut app, &mut system_state);
+        wait_for_event(&mut app, &mut system_state);
+        let mut reader = system_state.get(app.world());
+        assert_matches!(
+            reader.read().next().unwrap(),
+            SqlxEventStatus::Return(_, foos) if
+                foos.len() == 2 && foos[0].id >= max && foos[1].id < foos[0].id
+        );
+    }
+
+    #[test]
+    fn test_load_many() {
+        let mut app = setup_app();
+        let mut system_state: SystemState<
+            EventReader<SqlxEventStatus<Sqlite, Foo>>,
+        > = SystemState::new(app.world_mut());
+
+        let pool = app.world().resource::<SqlxDatabase<Sqlite>>().pool.clone();
+        let foos: Vec<Foo> = bevy::tasks::block_on(async {
+            sqlx::query_as(
+                "INSERT INTO foos (text) VALUES ('load many'), ('load many') \
+                 RETURNING *",
+            )
+            .fetch_all(&pool)
+            .await
+            .unwrap()
+        });
+
+        let mut keys: Vec<_> = foos.iter().map(|foo| foo.id).collect();
+        keys.push(u32::MAX);
+        let load = SqlxEvent::<Sqlite, Foo>::load_many(keys);
+        app.world_mut().send_event(load);
+        skip_started_event(&mut app, &mut system_state);
+        wait_for_event(&mut app, &mut system_state);
+        let mut reader = system_state.get(app.world());
+        let mut loaded = match reader.read().next().unwrap() {
+            SqlxEventStatus::Return(_, foos) => {
+                foos.iter().map(|foo| foo.id).collect::<Vec<_>>()
+            }
+            status => panic!("unexpected {status:?}"),
+        };
+        loaded.sort();
+        assert_eq!(vec![foos[0].id, foos[1].id], loaded);
+    }
+
+    #[test]
+    fn test_insert_without_returning() {
+        let mut app = setup_app();
+        let mut system_state: SystemState<
+            EventReader<SqlxEventStatus<Sqlite, Foo>>,
+        > = SystemState::new(app.world_mut());
+
+        let foo = Foo { id: 0, text: "without returning".into() };
+        let insert = SqlxEvent::<Sqlite, Foo>::insert(&foo).without_returning();
+        app.world_mut().send_event(insert);
+        skip_started_event(&mut app, &mut system_state);
+        wait_for_event(&mut app, &mut system_state);
+
+        let mut reader = system_state.get(app.world());
+        let mut events = reader.read();
+        let key = match events.next().unwrap() {
+            SqlxEventStatus::Inserted(_, key) => *key,
+            status => panic!("unexpected {status:?}"),
+        };
+        assert_matches!(
+            events.next().unwrap(),
+            SqlxEventStatus::Return(_, components) if
+                components[0].id as i64 == key &&
+                components[0].text == "without returning"
+        );
+    }
+
+    #[test]
+    fn test_load_into() {
+        let mut app = setup_app();
+        let mut system_state: SystemState<
+            EventReader<SqlxEventStatus<Sqlite, Foo>>,
+        > = SystemState::new(app.world_mut());
+
+        let pool = app.world().resource::<SqlxDatabase<Sqlite>>().pool.clone();
+        let foo: Foo = bevy::tasks::block_on(async {
+            sqlx::query_as(
+                "INSERT INTO foos (text) VALUES ('load into') RETURNING *",
+            )
+            .fetch_one(&pool)
+            .await
+            .unwrap()
+        });
+
+        let entity = app.world_mut().spawn_empty().id();
+        let load = SqlxEvent::<Sqlite, Foo>::load_into(entity, foo.id);
+        app.world_mut().send_event(load);
+        skip_started_event(&mut app, &mut system_state);
+        wait_for_event(&mut app, &mut system_state);
+        let mut reader = system_state.get(app.world());
+        assert_matches!(
+            reader.read().next().unwrap(),
+            SqlxEventStatus::Update(_, id, _) if *id == foo.id
+        );
+        app.update();
+        assert_eq!("load into", app.world().get::<Foo>(entity).unwrap().text);
+
+        app.world_mut().despawn(entity);
+        let load = SqlxEvent::<Sqlite, Foo>::load_into(entity, foo.id);
+        app.world_mut().send_event(load);
+        skip_started_event(&mut app, &mut system_state);
+        wait_for_event(&mut app, &mut system_state);
+        let mut reader = system_state.get(app.world());
+        assert_matches!(
+            reader.read().next().unwrap(),
+            SqlxEventStatus::Error(_, sqlx::Error::Configuration(_), _)
+        );
+    }
+
+    #[test]
+    fn test_load_into_despawned() {
+        for then in [SqlxDespawnedTarget::Discard, SqlxDespawnedTarget::Respawn]
+        {
+            let mut app =
+                setup_app_with(|plugin| plugin.with_despawned_target(then));
+            let mut system_state: SystemState<
+                EventReader<SqlxEventStatus<Sqlite, Foo>>,
+            > = SystemState::new(app.world_mut());
+
+            let pool =
+                app.world().resource::<SqlxDatabase<Sqlite>>().pool.clone();
+            let foo: Foo = bevy::tasks::block_on(async {
+                sqlx::query_as(
+                    "INSERT INTO foos (text) VALUES ('despawned') RETURNING *",
+                )
+                .fetch_one(&pool)
+                .await
+                .unwrap()
+            });
+
+            // Despawn the entity while the load is in flight.
+            let entity = app.world_mut().spawn_empty().id();
+            let load = SqlxEvent::<Sqlite, Foo>::load_into(entity, foo.id);
+            app.world_mut().send_event(load);
+            skip_started_event(&mut app, &mut system_state);
+            app.world_mut().despawn(entity);
+            wait_for_event(&mut app, &mut system_state);
+            let mut reader = system_state.get(app.world());
+            let kind = reader.read().next().unwrap().summary().kind;
+            app.update();
+            let mut loaded = app.world_mut().query::<&Foo>();
+            let loaded = loaded
+                .iter(app.world())
+                .filter(|loaded| loaded.id == foo.id)
+                .count();
+            let expected = match then {
+                SqlxDespawnedTarget::Discard => ("done", 0),
+                _ => ("spawn", 1),
+            };
+            assert_eq!(expected, (kind.as_str(), loaded));
+        }
+    }
+
+    #[test]
+    fn test_commands() {
+        let mut app = setup_app();
+        let mut system_state: SystemState<
+            EventReader<SqlxEventStatus<Sqlite, Foo>>,
+        > = SystemState::new(app.world_mut());
+
+        let sql = "INSERT INTO foos (text) VALUES ('commands') RETURNING *";
+        let insert = SqlxEvent::<Sqlite, Foo>::query(sql);
+        let id = app.world().resource::<SqlxEventIds>().next();
+        let insert = insert.with_id(id);
+        app.world_mut().commands().add(insert);
+        app.world_mut().flush();
+        skip_started_event(&mut app, &mut system_state);
+        wait_for_event(&mut app, &mut system_state);
+        let mut reader = system_state.get(app.world());
+        let inserted = match reader.read().next().unwrap() {
+            SqlxEventStatus::Return(returned, foos) if *returned == id => {
+                foos[0].id
+            }
+            status => panic!("unexpected {status:?}"),
+        };
+
+        let entity = app.world_mut().spawn_empty().id();
+        let select = SqlxEvent::<Sqlite, Foo>::select(inserted);
+        app.world_mut().commands().entity(entity).add(select);
+        app.world_mut().flush();
+        let mut tries = 0;
+        while app.world().get::<Foo>(entity).is_none() && tries < 1000 {
+            app.update();
+            tries += 1;
+        }
+        assert_eq!("commands", app.world().get::<Foo>(entity).unwrap().text);
+    }
+
+    #[test]
+    fn test_read_only() {
+        let mut app = setup_app_with(|plugin| plugin.with_read_only());
+        let mut system_state: SystemState<
+            EventReader<SqlxEventStatus<Sqlite, Foo>>,
+        > = SystemState::new(app.world_mut());
+
+        let sql = "DELETE FROM foos WHERE text = 'read_only' RETURNING *";
+        app.world_mut().send_event(SqlxEvent::<Sqlite, Foo>::query(sql));
+        skip_started_event(&mut app, &mut system_state);
+        wait_for_event(&mut app, &mut system_state);
+        let mut reader = system_state.get(app.world());
+        assert_matches!(
+            reader.read().next().unwrap(),
+            SqlxEventStatus::Error(_, sqlx::Error::Configuration(_), _)
+        );
+
+        let sql = "SELECT * FROM foos WHERE text = 'read_only'";
+        app.world_mut().send_event(SqlxEvent::<Sqlite, Foo>::query(sql));
+        skip_started_event(&mut app, &mut system_state);
+        wait_for_event(&mut app, &mut system_state);
+        let mut reader = system_state.get(app.world());
+        assert_matches!(
+            reader.read().next().unwrap(),
+            SqlxEventStatus::Return(..)
+        );
+    }
+
+    #[test]
+    fn test_client() {
+        let mut app =
+            setup_app_with(|plugin| plugin.with_client().with_audit());
+        let mut system_state: SystemState<
+            EventReader<SqlxEventStatus<Sqlite, Foo>>,
+        > = SystemState::new(app.world_mut());
+        let config = app.world().resource::<SqlxConfig<Sqlite, Foo>>();
+        assert!(config.read_only);
+        assert!(!config.audit);
+        let mode = SqlxSyncMode::of::<Sqlite, Foo>(app.world());
+        assert_eq!(SqlxSyncMode::ReadOnly, mode);
+
+        let foo = Foo { id: 1, text: "client".into() };
+        app.world_mut().send_event(SqlxEvent::<Sqlite, Foo>::upsert(&foo));
+        skip_started_event(&mut app, &mut system_state);
+        wait_for_event(&mut app, &mut system_state);
+        let mut reader = system_state.get(app.world());
+        assert_matches!(
+            reader.read().next().unwrap(),
+            SqlxEventStatus::Error(_, sqlx::Error::Configuration(_), _)
+        );
+    }
+
+    #[test]
+    fn test_task_pool() {
+        let pool = TaskPoolBuilder::new().num_threads(1).build();
+        let custom = SqlxTaskPool::Custom(Arc::new(pool));
+        let mut app = setup_app_with(|plugin| plugin.with_task_pool(custom));
+        IoTaskPool::get_or_init(TaskPool::new);
+        let mut system_state: SystemState<
+            EventReader<SqlxEventStatus<Sqlite, Foo>>,
+        > = SystemState::new(app.world_mut());
+
+        let sql = "SELECT * FROM foos WHERE text = 'task_pool'";
+        let query = SqlxEvent::<Sqlite, Foo>::query(sql);
+        assert_matches!(query.task_pool(), None);
+        for event in [query.clone(), query.with_task_pool(SqlxTaskPool::Io)] {
+            app.world_mut().send_event(event);
+            skip_started_event(&mut app, &mut system_state);
+            wait_for_event(&mut app, &mut system_state);
+            let mut reader = system_state.get(app.world());
+            assert_matches!(
+                reader.read().next().unwrap(),
+                SqlxEventStatus::Return(..)
+            );
+        }
+    }
+
+    #[test]
+    fn test_tenant_missing() {
+        let mut app = setup_app_with(|plugin| plugin.with_tenant("tenant_id"));
+        let mut system_state: SystemState<
+            EventReader<SqlxEventStatus<Sqlite, Foo>>,
+        > = SystemState::new(app.world_mut());
+
+        let select = SqlxEvent::<Sqlite, Foo>::select_all();
+        app.world_mut().send_event(select);
+
+        skip_started_event(&mut app, &mut system_state);
+        wait_for_event(&mut app, &mut system_state);
+
+        let mut reader = system_state.get(app.world());
+        let mut events = reader.read();
+        assert_matches!(
+            events.next().unwrap(),
+            SqlxEventStatus::Error(_, sqlx::Error::Configuration(_), _)
+        )
+    }
+
+    #[test]
+    fn test_event_status_error_resend() {
+        let mut app = setup_app();
+        let mut system_state: SystemState<
+            EventReader<SqlxEventStatus<Sqlite, Foo>>,
+        > = SystemState::new(app.world_mut());
+
+        let sql = "SELECT * FROM missing";
+        let select = SqlxEvent::<Sqlite, Foo>::query(sql).with_label("resend");
+        let id = app.world().resource::<SqlxEventIds>().next();
+        let select = select.with_id(id);
+        app.world_mut().send_event(select);
+
+        skip_started_event(&mut app, &mut system_state);
+        wait_for_event(&mut app, &mut system_state);
+        let mut reader = system_state.get(app.world());
+        let status = reader.read().next().unwrap();
+        assert_matches!(status, SqlxEventStatus::Error(..));
+        let failed = status.failed_event().unwrap().clone();
+        assert_eq!(Some(id), failed.id());
+        assert_eq!(Some("resend"), failed.label());
+
+        app.world_mut().send_event(failed);
+        app.update();
+        let mut reader = system_state.get(app.world());
+        assert_matches!(reader.read().next().unwrap(),
+            SqlxEventStatus::Start(started) if *started == id);
+    }
+
+    #[test]
+    fn test_audit() {
+        let mut app = setup_app_with(|plugin| plugin.with_audit());
+        let mut system_state: SystemState<
+            EventReader<SqlxEventStatus<Sqlite, Foo>>,
+        > = SystemState::new(app.world_mut());
+
+        let foo = Foo { id: 0, text: "audit".into() };
+        let insert =
+            SqlxEvent::<Sqlite, Foo>::insert(&foo).with_label("audit test");
+        let id = app.world().resource::<SqlxEventIds>().next();
+        let insert = insert.with_id(id);
+        app.world_mut().send_event(insert);
+
+        skip_started_event(&mut app, &mut system_state);
+        wait_for_event(&mut app, &mut system_state);
+
+        let pool = app.world().resource::<SqlxDatabase<Sqlite>>().pool.clone();
+        let (operation, label, old, new): (
+            String,
+            String,
+            Option<String>,
+            String,
+        ) = bevy::tasks::block_on(async {
+            sqlx::query_as(
+                "SELECT operation, label, old_values, new_values
+                     FROM _bevy_sqlx_audit WHERE event_id = ?",
+            )
+            .bind(id.to_string().parse::<i64>().unwrap())
+            .fetch_one(&pool)
+            .await
+            .unwrap()
+        });
+        assert_eq!("INSERT", operation);
+        assert_eq!("audit test", label);
+        assert_eq!(None, old);
+        assert_eq!(r#"[{"text":"audit"}]"#, new);
+    }
+
+    #[test]
+    fn test_event_status_throttled() {
+        let mut app = setup_app_with(|plugin| plugin.with_rate_limit(0.01, 1));
+        let mut system_state: SystemState<
+            EventReader<SqlxEventStatus<Sqlite, Foo>>,
+        > = SystemState::new(app.world_mut());
+
+        let first = SqlxEvent::<Sqlite, Foo>::query("SELECT * FROM foos");
+        let second = SqlxEvent::<Sqlite, Foo>::query("SELECT * FROM foos");
+        let ids = app.world().resource::<SqlxEventIds>();
+        let (first_id, second_id) = (ids.next(), ids.next());
+        let (first, second) =
+            (first.with_id(first_id), second.with_id(second_id));
+        app.world_mut().send_event(first);
+        app.world_mut().send_event(second);
+        app.update();
+
+        let mut reader = system_state.get(app.world());
+        let mut events = reader.read();
+        assert_matches!(events.next().unwrap(),
+            SqlxEventStatus::Start(id) if *id == first_id);
+        assert_matches!(events.next().unwrap(),
+            SqlxEventStatus::Throttled(id) if *id == second_id);
+        assert_eq!(
+            1,
+            app.world().resource::<SqlxTasks<Sqlite, Foo>>().throttled()
+        );
+    }
+
+    #[test]
+    fn test_event_status_scalar() {
+        let mut app = setup_app();
+        let mut system_state: SystemState<
+            EventReader<SqlxEventStatus<Sqlite, Foo>>,
+        > = SystemState::new(app.world_mut());
+
+        let sql = "SELECT COUNT(*) FROM (VALUES (1), (2), (3))";
+        let count = SqlxEvent::<Sqlite, Foo>::aggregate(sql);
+        app.world_mut().send_event(count);
+
+        skip_started_event(&mut app, &mut system_state);
+        wait_for_event(&mut app, &mut system_state);
+
+        let mut reader = system_state.get(app.world());
+        let mut events = reader.read();
+        assert_matches!(
+            events.next().unwrap(),
+            SqlxEventStatus::Scalar(_, SqlxValue::Int(3))
+        )
+    }
+
+    #[test]
+    fn test_flush_on_exit() {
+        let mut app = setup_app_with(|plugin| plugin.with_rate_limit(0.01, 1));
+
+        for text in ["flush 1", "flush 2"] {
+            let foo = Foo { id: 0, text: text.into() };
+            app.world_mut().send_event(SqlxEvent::<Sqlite, Foo>::insert(&foo));
+        }
+        app.update();
+        let tasks = app.world().resource::<SqlxTasks<Sqlite, Foo>>();
+        assert_eq!(1, tasks.throttled());
+
+        app.world_mut().send_event(AppExit::Success);
+        app.update();
+        let tasks = app.world().resource::<SqlxTasks<Sqlite, Foo>>();
+        assert_eq!(0, tasks.throttled());
+        assert!(tasks.is_empty());
+    }
+
+    #[test]
+    #[allow(clippy::type_complexity)]
+    fn test_connection_restored() {
+        let mut app = setup_app();
+        let mut system_state: SystemState<(
+            EventReader<SqlxConnectionStatus<Sqlite>>,
+            EventReader<SqlxEventStatus<Sqlite, Foo>>,
+        )> = SystemState::new(app.world_mut());
+
+        let mut tasks =
+            app.world_mut().resource_mut::<SqlxTasks<Sqlite, Foo>>();
+        tasks.reconnect = Some(SqlxReconnect::new());
+        let foo = Foo { id: 0, text: "restored".into() };
+        let insert = SqlxEvent::<Sqlite, Foo>::insert(&foo);
+        let id = app.world().resource::<SqlxEventIds>().next();
+        let insert = insert.with_id(id);
+        app.world_mut().send_event(insert);
+        app.update();
+        let tasks = app.world().resource::<SqlxTasks<Sqlite, Foo>>();
+        assert!(tasks.is_pending(id));
+
+        let (mut restored, mut returned) = (false, false);
+        let mut tries = 0;
+        while !returned && tries < 1000 {
+            app.update();
+            let (mut connection, mut statuses) = system_state.get(app.world());
+            restored |= connection.read().any(|status| {
+                matches!(status, SqlxConnectionStatus::Restored(_))
+            });
+            returned |= statuses.read().any(|status| {
+                matches!(status, SqlxEventStatus::Return(r, _) if *r == id)
+            });
+            tries += 1;
+        }
+        assert!(restored);
+        assert!(returned);
+    }
+
+    #[test]
+    fn test_export() {
+        let mut app = setup_app();
+        let mut system_state: SystemState<
+            EventReader<SqlxEventStatus<Sqlite, Foo>>,
+        > = SystemState::new(app.world_mut());
+
+        let sql = "SELECT 1 AS a, 'x,y' AS b UNION ALL SELECT 2, NULL";
+        for (format, expected) in [
+            (SqlxFileFormat::Csv, "a,b\n1,\"x,y\"\n2,\n"),
+            (
+                SqlxFileFormat::Json,
+                "[\n{\"a\":1,\"b\":\"x,y\"},\n{\"a\":2,\"b\":null}\n]\n",
+            ),
+        ] {
+            let path = std::env::temp_dir()
+                .join(format!("bevy_sqlx_export_{}", rand::random::<u32>()));
+            let export = SqlxEvent::<Sqlite, Foo>::export(sql, &path, format);
+            app.world_mut().send_event(export);
+
+            let mut done = None;
+            let mut tries = 0;
+            while done.is_none() && tries < 1000 {
+                app.update();
+                let mut reader = system_state.get(app.world());
+                done = reader.read().find_map(|status| match status {
+                    SqlxEventStatus::Done(_, rows) => Some(*rows),
+                    _ => None,
+                });
+                tries += 1;
+            }
+            assert_eq!(Some(2), done);
+            assert_eq!(expected, std::fs::read_to_string(&path).unwrap());
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_ddl() {
+        let mut app = setup_app();
+        let sql = "DROP TABLE IF EXISTS ddls;
+                   CREATE TABLE ddls (id INTEGER PRIMARY KEY, text TEXT);
+                   CREATE INDEX ddls_text ON ddls (text)";
+        let ddl = SqlxEvent::<Sqlite, Foo>::ddl(sql);
+        let id = app.world().resource::<SqlxEventIds>().next();
+        app.world_mut().send_event(ddl.with_id(id));
+
+        let mut changed = false;
+        let mut tries = 0;
+        while !changed && tries < 1000 {
+            app.update();
+            let events =
+                app.world().resource::<Events<SqlxEventStatus<Sqlite, Foo>>>();
+            for status in events.iter_current_update_events() {
+                match status {
+                    SqlxEventStatus::SchemaChanged(changed_id) => {
+                        assert_eq!(id, *changed_id);
+                        changed = true;
+                    }
+                    SqlxEventStatus::Error(_, err, _) => panic!("{err}"),
+                    _ => {}
+                }
+            }
+            tries += 1;
+        }
+        assert!(changed);
+
+        let pool = app.world().resource::<SqlxDatabase<Sqlite>>().pool.clone();
+        let sql = "SELECT COUNT(*) FROM sqlite_master WHERE name = 'ddls_text'";
+        let indexes: i64 =
+            bevy::tasks::block_on(sqlx::query_scalar(sql).fetch_one(&pool))
+                .unwrap();
+        assert_eq!(1, indexes);
+    }
+
+    #[test]
+    fn test_backup() {
+        let mut app = setup_app();
+        let mut system_state: SystemState<
+            EventReader<SqlxEventStatus<Sqlite, Foo>>,
+        > = SystemState::new(app.world_mut());
+        let path = std::env::temp_dir().join("bevy_sqlx_test_backup.db");
+        let _ = std::fs::remove_file(&path);
+
+        let backup = SqlxEvent::<Sqlite, Foo>::backup(&path);
+        app.world_mut().send_event(backup);
+        let mut done = None;
+        let mut tries = 0;
+        while done.is_none() && tries < 1000 {
+            app.update();
+            let mut reader = system_state.get(app.world());
+            done = reader.read().find_map(|status| match status {
+                SqlxEventStatus::Done(_, bytes) => Some(*bytes),
+                _ => None,
+            });
+            tries += 1;
+        }
+
+        let size = std::fs::metadata(&path).unwrap().len();
+        assert_eq!(Some(size), done);
+        let url = format!("sqlite:{}", path.display());
+        let sql = "SELECT name FROM sqlite_master WHERE name = 'foos'";
+        let tables: Vec<String> = bevy::tasks::block_on(async {
+            let pool: sqlx::Pool<Sqlite> =
+                sqlx::Pool::connect(&url).await.unwrap();
+            sqlx::query_scalar(sql).fetch_all(&pool).await.unwrap()
+        });
+        assert_eq!(vec!["foos"], tables);
+    }
+
+    #[test]
+    fn test_import() {
+        let mut app = setup_app();
+        let mut system_state: SystemState<
+            EventReader<SqlxEventStatus<Sqlite, Foo>>,
+        > = SystemState::new(app.world_mut());
+
+        let text = format!("import {}", rand::random::<u32>());
+        for (format, contents) in [
+            (SqlxFileFormat::Csv, format!("id,text\n0,\"{text}\"\n")),
+            (
+                SqlxFileFormat::Json,
+                format!("[{{\"id\":0,\"text\":\"{text}\"}}]"),
+            ),
+        ] {
+            let path = std::env::temp_dir()
+                .join(format!("bevy_sqlx_import_{}", rand::random::<u32>()));
+            std::fs::write(&path, contents).unwrap();
+            let import = SqlxEvent::<Sqlite, Foo>::import(&path, format);
+            app.world_mut().send_event(import);
+
+            let mut done = None;
+            let mut tries = 0;
+            while done.is_none() && tries < 1000 {
+                app.update();
+                let mut reader = system_state.get(app.world());
+                done = reader.read().find_map(|status| match status {
+                    SqlxEventStatus::Done(_, rows) => Some(*rows),
+                    _ => None,
+                });
+                tries += 1;
+            }
+            assert_eq!(Some(1), done);
+            std::fs::remove_file(path).unwrap();
+        }
+
+        let pool = app.world().resource::<SqlxDatabase<Sqlite>>().pool.clone();
+        let count: i64 = bevy::tasks::block_on(async {
+            sqlx::query_scalar("SELECT COUNT(*) FROM foos WHERE text = ?")
+                .bind(&text)
+                .fetch_one(&pool)
+                .await
+                .unwrap()
+        });
+        assert_eq!(2, count);
+    }
+
     // TODO: Add tests for multicurrent in-flight events (w/ IDs)
 }
@@ -0,0 +1,152 @@
+use crate::*;
+use bevy::prelude::*;
+use bevy::utils::{Duration, HashMap, Instant};
+use std::marker::PhantomData;
+
+/// When components synced by a [`SqlxPlugin`] are evicted
+///
+/// Components which haven't changed, or been touched with
+/// [`SqlxEviction::touch`], for `after` are removed from their entity, or
+/// the entity is despawned if `despawn` is true. A [`SqlxEvicted`] is sent
+/// for each of them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SqlxEvictionPolicy {
+    pub after: Duration,
+    pub despawn: bool,
+}
+
+/// A [`Resource`] tracking when each component `C` was last touched
+///
+/// Added by a [`SqlxPlugin`] built with
+/// [`SqlxPlugin::with_eviction`]. Changing a component touches it, so
+/// systems which only read it should touch it too:
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_sqlx::{PrimaryKey, SqlxEviction};
+/// # #[derive(Component)]
+/// # struct Foo(u32);
+/// # impl PrimaryKey for Foo {
+/// #     type Column = u32;
+/// #     fn primary_key(&self) -> Self::Column { self.0 }
+/// # }
+/// fn inspect(
+///     foos: Query<Entity, With<Foo>>,
+///     mut eviction: ResMut<SqlxEviction<Foo>>,
+/// ) {
+///     for entity in &foos {
+///         eviction.touch(entity);
+///     }
+/// }
+/// ```
+#[derive(Resource)]
+pub struct SqlxEviction<C> {
+    policy: SqlxEvictionPolicy,
+    touched: HashMap<Entity, Instant>,
+    _c: PhantomData<C>,
+}
+
+impl<C: Component + PrimaryKey> SqlxEviction<C> {
+    pub(crate) fn new(policy: SqlxEvictionPolicy) -> Self {
+        SqlxEviction { policy, touched: HashMap::new(), _c: PhantomData }
+    }
+
+    /// Mark the component of `entity` as used, postponing its eviction
+    pub fn touch(&mut self, entity: Entity) {
+        self.touched.insert(entity, Instant::now());
+    }
+
+    /// A [`System`] evicting components which weren't touched recently
+    pub fn handle_eviction(
+        mut commands: Commands,
+        mut eviction: ResMut<Self>,
+        components: Query<(Entity, Ref<C>)>,
+        mut removed: RemovedComponents<C>,
+        mut evicted: EventWriter<SqlxEvicted<C>>,
+    ) {
+        for entity in removed.read() {
+            eviction.touched.remove(&entity);
+        }
+
+        let now = Instant::now();
+        let policy = eviction.policy;
+        for (entity, component) in &components {
+            if component.is_changed() {
+                eviction.touch(entity);
+                continue;
+            }
+            let touched = *eviction.touched.entry(entity).or_insert(now);
+            if now.duration_since(touched) < policy.after {
+                continue;
+            }
+
+            eviction.touched.remove(&entity);
+            if policy.despawn {
+                commands.entity(entity).despawn();
+            } else {
+                commands.entity(entity).remove::<C>();
+            }
+            evicted.send(SqlxEvicted { entity, key: component.primary_key() });
+        }
+    }
+}
+
+/// An [`Event`] sent when a component is evicted by a
+/// [`SqlxEvictionPolicy`]
+///
+/// When the entity was despawned, `entity` no longer exists.
+#[derive(Event, Debug)]
+pub struct SqlxEvicted<C: PrimaryKey> {
+    pub entity: Entity,
+    pub key: C::Column,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{AsyncComputeTaskPool, TaskPool};
+    use bevy::utils::Duration;
+    use sqlx::{FromRow, Sqlite};
+
+    #[derive(Component, FromRow, Debug)]
+    struct Foo {
+        id: u32,
+    }
+
+    impl PrimaryKey for Foo {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    #[test]
+    fn test_eviction() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let after = Duration::from_millis(50);
+        let mut app = App::new();
+        app.add_plugins(
+            SqlxPlugin::<Sqlite, Foo>::from_url(url)
+                .with_eviction(after, false),
+        );
+
+        let touched = app.world_mut().spawn(Foo { id: 1 }).id();
+        let untouched = app.world_mut().spawn(Foo { id: 2 }).id();
+        app.update();
+        for _ in 0..3 {
+            std::thread::sleep(after / 2);
+            app.world_mut().resource_mut::<SqlxEviction<Foo>>().touch(touched);
+            app.update();
+        }
+
+        assert!(app.world().get::<Foo>(touched).is_some());
+        assert!(app.world().get::<Foo>(untouched).is_none());
+        assert!(app.world().get_entity(untouched).is_some());
+        let events = app.world().resource::<Events<SqlxEvicted<Foo>>>();
+        let evicted: Vec<_> =
+            events.get_reader().read(events).map(|e| e.key).collect();
+        assert_eq!(vec![2], evicted);
+    }
+}
@@ -0,0 +1,374 @@
+use crate::*;
+use bevy::prelude::*;
+use bevy::utils::{Duration, Instant};
+use sqlx::{Database, Executor, IntoArguments};
+use std::marker::PhantomData;
+use std::time::SystemTime;
+
+/// A component whose rows expire, like temporary world objects, buffs or
+/// session artifacts
+///
+/// Rows expire at the time in their `expires_at` column, which has to be
+/// written with [`SqlxValue::Timestamp`]s, e.g. from a [`SystemTime`] field,
+/// so it compares with the current time. Rows where it's `NULL` never
+/// expire. See [`SqlxExpiryPlugin`].
+pub trait SqlxExpiring: ToRow {
+    /// The column rows expire at
+    fn expires_at_name() -> &'static str {
+        "expires_at"
+    }
+}
+
+/// A [`Plugin`] deleting the expired rows of `C`, see [`SqlxExpiring`]
+///
+/// Every `interval`, the expired rows are deleted, and the entities of the
+/// deleted rows are despawned. A [`SqlxExpired<C>`] is sent for each of
+/// them, and a [`SqlxTableChanged`] once they're all deleted. Until then,
+/// select with [`SqlxEvent::select_unexpired`] to leave expired rows out.
+///
+/// The [`SqlxPlugin<DB, C>`] has to be added first, for its table prefix and
+/// sync mode. Deleted rows are read back with `RETURNING *`, so MySQL isn't
+/// supported.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy::utils::Duration;
+/// # use sqlx::{FromRow, Sqlite};
+/// # use bevy_sqlx::*;
+/// # #[derive(Component, FromRow)]
+/// # struct Buff { id: u32 }
+/// # impl PrimaryKey for Buff {
+/// #     type Column = u32;
+/// #     fn primary_key(&self) -> Self::Column { self.id }
+/// # }
+/// # impl ToRow for Buff {
+/// #     fn table() -> &'static str { "buffs" }
+/// #     fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+/// #         vec![("id", self.id.into())]
+/// #     }
+/// # }
+/// impl SqlxExpiring for Buff {}
+///
+/// let url = "sqlite:db/sqlite.db";
+/// App::new()
+///     .add_plugins(SqlxPlugin::<Sqlite, Buff>::from_url(url))
+///     .add_plugins(
+///         SqlxExpiryPlugin::<Sqlite, Buff>::every(Duration::from_secs(1)),
+///     );
+/// ```
+pub struct SqlxExpiryPlugin<DB, C> {
+    interval: Duration,
+    _db: PhantomData<DB>,
+    _c: PhantomData<C>,
+}
+
+impl<DB, C> SqlxExpiryPlugin<DB, C> {
+    /// Delete expired rows every `interval`
+    pub fn every(interval: Duration) -> Self {
+        SqlxExpiryPlugin { interval, _db: PhantomData, _c: PhantomData }
+    }
+}
+
+impl<DB, C> Plugin for SqlxExpiryPlugin<DB, C>
+where
+    DB: Database + Sync,
+    C: SqlxComponent<DB::Row> + SqlxExpiring,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    fn build(&self, app: &mut App) {
+        let table = app
+            .world()
+            .get_resource::<SqlxConfig<DB, C>>()
+            .map_or(C::table().into(), |config| config.table_name(C::table()));
+        app.insert_resource(SqlxExpiry::<DB, C> {
+            interval: self.interval,
+            swept: None,
+            sweeping: None,
+            table,
+            _db: PhantomData,
+            _c: PhantomData,
+        });
+        app.add_event::<SqlxExpired<C>>();
+        if !SqlxSyncMode::of::<DB, C>(app.world()).writes() {
+            return;
+        }
+        app.add_systems(
+            Update,
+            (
+                SqlxExpiry::<DB, C>::handle_sweep
+                    .before(SqlxRegistry::<DB>::handle_events),
+                SqlxExpiry::<DB, C>::handle_expired
+                    .after(SqlxRegistry::<DB>::handle_tasks),
+            ),
+        );
+    }
+}
+
+/// An [`Event`] sent for each row of `C` deleted by the
+/// [`SqlxExpiryPlugin`]
+///
+/// `entity` is the despawned entity of the row, if it was spawned.
+#[derive(Event, Debug)]
+pub struct SqlxExpired<C: PrimaryKey> {
+    pub entity: Option<Entity>,
+    pub key: C::Column,
+}
+
+/// A [`Resource`] holding when the [`SqlxExpiryPlugin`] of `C` last deleted
+/// expired rows
+#[derive(Resource)]
+pub struct SqlxExpiry<DB, C> {
+    interval: Duration,
+    swept: Option<Instant>,
+    /// The running delete
+    sweeping: Option<SqlxEventId>,
+    table: String,
+    _db: PhantomData<fn() -> DB>,
+    _c: PhantomData<fn() -> C>,
+}
+
+impl<DB, C> SqlxExpiry<DB, C> {
+    /// Delete expired rows on the next frame, instead of waiting for the
+    /// interval
+    pub fn sweep_now(&mut self) {
+        self.swept = None;
+    }
+
+    /// Return true while expired rows are being deleted
+    pub fn is_sweeping(&self) -> bool {
+        self.sweeping.is_some()
+    }
+}
+
+impl<DB, C> SqlxExpiry<DB, C>
+where
+    DB: Database + Sync,
+    C: SqlxComponent<DB::Row> + SqlxExpiring,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    /// A [`System`] deleting the expired rows once the interval elapsed
+    pub fn handle_sweep(
+        ids: Res<SqlxEventIds>,
+        mut expiry: ResMut<Self>,
+        mut events: EventWriter<SqlxEvent<DB, C>>,
+    ) {
+        let due =
+            expiry.swept.is_none_or(|swept| swept.elapsed() >= expiry.interval);
+        if !due || expiry.sweeping.is_some() {
+            return;
+        }
+        let now = SqlxValue::from(SystemTime::now());
+        let sql = format!(
+            "DELETE FROM {} WHERE {} <= {} RETURNING *",
+            expiry.table,
+            C::expires_at_name(),
+            typed_placeholder::<DB>(1, &now),
+        );
+        let id = ids.next();
+        events.send(
+            SqlxEvent::query(sql).bind(now).with_id(id).with_label("expiry"),
+        );
+        expiry.swept = Some(Instant::now());
+        expiry.sweeping = Some(id);
+    }
+
+    /// A [`System`] despawning the entities of the deleted rows
+    pub fn handle_expired(
+        mut commands: Commands,
+        components: Query<(Entity, &C)>,
+        mut expiry: ResMut<Self>,
+        mut statuses: EventReader<SqlxEventStatus<DB, C>>,
+        mut expired: EventWriter<SqlxExpired<C>>,
+        mut changes: EventWriter<SqlxTableChanged>,
+    ) {
+        for status in statuses.read() {
+            let deleted = match status {
+                SqlxEventStatus::Return(id, deleted)
+                    if Some(*id) == expiry.sweeping =>
+                {
+                    deleted
+                }
+                SqlxEventStatus::Error(id, ..)
+                    if Some(*id) == expiry.sweeping =>
+                {
+                    expiry.sweeping = None;
+                    continue;
+                }
+                _ => continue,
+            };
+            expiry.sweeping = None;
+            for row in deleted {
+                let key = row.primary_key();
+                let entity = components
+                    .iter()
+                    .find(|(_, component)| component.primary_key() == key)
+                    .map(|(entity, _)| entity);
+                if let Some(entity) = entity {
+                    commands.entity(entity).despawn_recursive();
+                }
+                expired.send(SqlxExpired { entity, key });
+            }
+            if !deleted.is_empty() {
+                changes.send(SqlxTableChanged::new(C::table()));
+            }
+        }
+    }
+}
+
+impl<DB, C> SqlxEvent<DB, C>
+where
+    DB: Database + Sync,
+    C: SqlxComponent<DB::Row> + SqlxExpiring,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    /// Select and sync the rows of `C` which haven't expired yet
+    ///
+    /// Like [`Self::query`], the table isn't prefixed.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use sqlx::{FromRow, Sqlite};
+    /// # use bevy_sqlx::*;
+    /// # #[derive(Component, FromRow)]
+    /// # struct Buff { id: u32 }
+    /// # impl PrimaryKey for Buff {
+    /// #     type Column = u32;
+    /// #     fn primary_key(&self) -> Self::Column { self.id }
+    /// # }
+    /// # impl ToRow for Buff {
+    /// #     fn table() -> &'static str { "buffs" }
+    /// #     fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+    /// #         vec![("id", self.id.into())]
+    /// #     }
+    /// # }
+    /// # impl SqlxExpiring for Buff {}
+    /// SqlxEvent::<Sqlite, Buff>::select_unexpired();
+    /// ```
+    pub fn select_unexpired() -> Self {
+        let now = SqlxValue::from(SystemTime::now());
+        let column = C::expires_at_name();
+        let sql = format!(
+            "SELECT * FROM {} WHERE {column} IS NULL OR {column} > {}",
+            C::table(),
+            typed_placeholder::<DB>(1, &now),
+        );
+        Self::query_sync(sql).bind(now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{block_on, AsyncComputeTaskPool, TaskPool};
+    use bevy::utils::Duration;
+    use sqlx::{FromRow, Sqlite};
+    use std::time::SystemTime;
+
+    #[derive(Component, FromRow, Debug)]
+    struct Buff {
+        id: u32,
+        name: String,
+    }
+
+    impl PrimaryKey for Buff {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    impl ToRow for Buff {
+        fn table() -> &'static str {
+            "expiry_buffs"
+        }
+
+        fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+            vec![("id", self.id.into()), ("name", self.name.clone().into())]
+        }
+    }
+
+    impl SqlxExpiring for Buff {}
+
+    fn names(app: &mut App) -> Vec<String> {
+        let mut query = app.world_mut().query::<&Buff>();
+        let mut names: Vec<_> =
+            query.iter(app.world()).map(|buff| buff.name.clone()).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_expiry() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let mut app = App::new();
+        app.add_plugins(SqlxPlugin::<Sqlite, Buff>::from_url(url));
+        let pool = &app.world().resource::<SqlxDatabase<Sqlite>>().pool.clone();
+        let hour = Duration::from_secs(3600);
+        block_on(async {
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS expiry_buffs (id INTEGER PRIMARY \
+                 KEY, name TEXT NOT NULL, expires_at TEXT)",
+            )
+            .execute(pool)
+            .await
+            .unwrap();
+            sqlx::query("DELETE FROM expiry_buffs")
+                .execute(pool)
+                .await
+                .unwrap();
+            let rows = [
+                ("haste", Some(SystemTime::now() + hour)),
+                ("shield", Some(SystemTime::now() - hour)),
+                ("blessing", None),
+            ];
+            for (id, (name, expires_at)) in rows.into_iter().enumerate() {
+                sqlx::query("INSERT INTO expiry_buffs VALUES (?, ?, ?)")
+                    .bind(id as u32 + 1)
+                    .bind(name)
+                    .bind(expires_at.map(SqlxValue::from))
+                    .execute(pool)
+                    .await
+                    .unwrap();
+            }
+        });
+
+        // Expired rows are left out of selects.
+        app.world_mut()
+            .send_event(SqlxEvent::<Sqlite, Buff>::select_unexpired());
+        let mut tries = 0;
+        while names(&mut app).len() < 2 && tries < 1000 {
+            app.update();
+            tries += 1;
+        }
+        assert_eq!(vec!["blessing", "haste"], names(&mut app));
+
+        // Spawn the expired row anyway, and let the plugin delete it.
+        let shield =
+            app.world_mut().spawn(Buff { id: 2, name: "shield".into() }).id();
+        app.add_plugins(SqlxExpiryPlugin::<Sqlite, Buff>::every(hour));
+        let mut tries = 0;
+        while app.world().get_entity(shield).is_some() && tries < 1000 {
+            app.update();
+            tries += 1;
+        }
+        assert_eq!(vec!["blessing", "haste"], names(&mut app));
+        let events = app.world().resource::<Events<SqlxExpired<Buff>>>();
+        let expired: Vec<_> = events
+            .get_reader()
+            .read(events)
+            .map(|expired| (expired.entity, expired.key))
+            .collect();
+        assert_eq!(vec![(Some(shield), 2)], expired);
+        let count: i64 = block_on(
+            sqlx::query_scalar("SELECT COUNT(*) FROM expiry_buffs")
+                .fetch_one(pool),
+        )
+        .unwrap();
+        assert_eq!(2, count);
+    }
+}
@@ -0,0 +1,223 @@
+use crate::*;
+use bevy::ecs::event::ManualEventReader;
+use bevy::prelude::*;
+use bevy::tasks::{block_on, poll_once, Task};
+use bevy::utils::{HashMap, HashSet};
+use sqlx::{
+    ColumnIndex, Database, Decode, Encode, Error, Executor, IntoArguments,
+    Pool, Row, Type,
+};
+use std::marker::PhantomData;
+
+/// A [`Resource`] logging each unique query of `DB`'s events, with its query
+/// plan
+///
+/// Added by a [`SqlxPlugin`] built with [`SqlxPlugin::with_explain`], and
+/// shared by every plugin using `DB`. The first time a query is seen, it's
+/// run through `EXPLAIN QUERY PLAN` on SQLite, or `EXPLAIN` on PostgreSQL,
+/// so full table scans from missing indexes show up during development.
+///
+/// Function calls, imports and backups have no query to explain.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::Sqlite;
+/// # use bevy_sqlx::SqlxQueryLog;
+/// fn scans(log: Res<SqlxQueryLog<Sqlite>>) {
+///     for (sql, plan) in log.iter() {
+///         if plan.iter().any(|line| line.starts_with("SCAN")) {
+///             warn!("{sql} scans a table");
+///         }
+///     }
+/// }
+/// ```
+#[derive(Resource)]
+pub struct SqlxQueryLog<DB: Database> {
+    seen: HashSet<String>,
+    plans: HashMap<String, Vec<String>>,
+    explaining: Vec<(String, Task<SqlxQueryPlan>)>,
+    _db: PhantomData<fn() -> DB>,
+}
+
+/// The lines of a query's plan, or the error explaining it
+type SqlxQueryPlan = Result<Vec<String>, Error>;
+
+impl<DB: Database> Default for SqlxQueryLog<DB> {
+    fn default() -> Self {
+        SqlxQueryLog {
+            seen: HashSet::new(),
+            plans: HashMap::new(),
+            explaining: Vec::new(),
+            _db: PhantomData,
+        }
+    }
+}
+
+impl<DB: Database> SqlxQueryLog<DB> {
+    /// The plan of `sql`, one line per row of the `EXPLAIN`
+    ///
+    /// Returns `None` until the plan was recorded, or if it failed.
+    pub fn get(&self, sql: &str) -> Option<&[String]> {
+        self.plans.get(sql).map(Vec::as_slice)
+    }
+
+    /// The recorded queries and their plans
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[String])> {
+        self.plans.iter().map(|(sql, plan)| (sql.as_str(), plan.as_slice()))
+    }
+
+    /// The number of recorded plans
+    pub fn len(&self) -> usize {
+        self.plans.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plans.is_empty()
+    }
+
+    /// Return true while plans are being recorded
+    pub fn is_explaining(&self) -> bool {
+        !self.explaining.is_empty()
+    }
+}
+
+impl<DB: Database + Sync> SqlxQueryLog<DB>
+where
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+    for<'r> SqlxValue: Decode<'r, DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    /// A [`System`] explaining the query of each [`SqlxEvent<DB, C>`] the
+    /// first time it's seen, and recording the finished plans
+    pub fn handle_explain<C: SqlxComponent<DB::Row>>(
+        database: Res<SqlxDatabase<DB>>,
+        config: Res<SqlxConfig<DB, C>>,
+        mut log: ResMut<Self>,
+        mut reader: Local<ManualEventReader<SqlxEvent<DB, C>>>,
+        events: Res<Events<SqlxEvent<DB, C>>>,
+    ) {
+        for event in reader.read(&events) {
+            let Some((sql, binds)) = explained(event) else {
+                continue;
+            };
+            if log.seen.insert(sql.clone()) {
+                let db = database.pool.clone();
+                let task =
+                    config.task_pool.spawn(explain(sql.clone(), binds, db));
+                log.explaining.push((sql, task));
+            }
+        }
+
+        let mut explaining = std::mem::take(&mut log.explaining);
+        explaining.retain_mut(|(sql, task)| {
+            let Some(result) = block_on(poll_once(task)) else {
+                return true;
+            };
+            match result {
+                Ok(plan) => {
+                    debug!("query plan of {sql}:\n{}", plan.join("\n"));
+                    log.plans.insert(sql.clone(), plan);
+                }
+                Err(err) => warn!("failed to explain {sql}: {err}"),
+            }
+            false
+        });
+        log.explaining.extend(explaining);
+    }
+}
+
+/// The query of `event`, and its binds
+fn explained<DB: Database, C: SqlxComponent<DB::Row>>(
+    event: &SqlxEvent<DB, C>,
+) -> Option<(String, Vec<SqlxValue>)> {
+    match &event.op {
+        SqlxEventOp::Query(sql, binds, _) => {
+            Some((sql.to_string(), binds.clone()))
+        }
+        SqlxEventOp::Statement(stmt, _) => {
+            Some((stmt.sql::<DB>(), stmt.binds()))
+        }
+        SqlxEventOp::Aggregate(sql) | SqlxEventOp::Export(sql, ..) => {
+            Some((sql.to_string(), Vec::new()))
+        }
+        SqlxEventOp::Call(_)
+        | SqlxEventOp::Import(..)
+        | SqlxEventOp::Backup(_)
+        | SqlxEventOp::Ddl(_) => None,
+    }
+}
+
+/// Explain `sql`, returning the lines of its plan
+async fn explain<DB>(
+    sql: String,
+    binds: Vec<SqlxValue>,
+    db: Pool<DB>,
+) -> SqlxQueryPlan
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+    for<'r> SqlxValue: Decode<'r, DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    // SQLite's plan rows are `(id, parent, notused, detail)`, PostgreSQL's
+    // are a single line of text.
+    let (sql, column) = if DB::NAME == "SQLite" {
+        (format!("EXPLAIN QUERY PLAN {sql}"), 3)
+    } else {
+        (format!("EXPLAIN {sql}"), 0)
+    };
+    let mut query = sqlx::query(&sql);
+    for value in binds {
+        query = query.bind(value);
+    }
+    let rows = query.fetch_all(&db).await?;
+    rows.iter()
+        .map(|row| match row.try_get::<SqlxValue, _>(column)? {
+            SqlxValue::Text(line) => Ok(line),
+            value => Ok(format!("{value:?}")),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{AsyncComputeTaskPool, TaskPool};
+    use sqlx::Sqlite;
+
+    #[test]
+    fn test_explain() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let mut app = App::new();
+        app.add_plugins(
+            SqlxPlugin::<Sqlite, SqlxDummy>::from_url(url).with_explain(),
+        );
+        let sql = "SELECT * FROM foos WHERE text = ?";
+        for _ in 0..2 {
+            let event =
+                SqlxEvent::<Sqlite, SqlxDummy>::query(sql).bind("explain");
+            app.world_mut().send_event(event);
+        }
+
+        let explaining = |app: &App| {
+            app.world().resource::<SqlxQueryLog<Sqlite>>().is_explaining()
+        };
+        app.update();
+        let mut tries = 0;
+        while explaining(&app) && tries < 1000 {
+            app.update();
+            tries += 1;
+        }
+
+        let log = app.world().resource::<SqlxQueryLog<Sqlite>>();
+        assert_eq!(1, log.len());
+        let plan = log.get(sql).unwrap();
+        assert!(plan.iter().any(|line| line.contains("foos")));
+    }
+}
@@ -0,0 +1,163 @@
+use crate::audit::{json_string, json_value};
+use crate::*;
+use bevy::tasks::futures_lite::StreamExt;
+use sqlx::{Column, ColumnIndex, Database, Decode, Encode, Error, Executor};
+use sqlx::{IntoArguments, Pool, Row, Type};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// The file format of [`SqlxEvent::export`] and [`SqlxEvent::import`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SqlxFileFormat {
+    /// A header of column names, then one line per row
+    Csv,
+    /// An array of objects, one per row
+    Json,
+}
+
+/// The number of rows processed so far by a long running event, reported
+/// with [`SqlxEventStatus::Progress`]
+#[derive(Debug, Default)]
+pub(crate) struct SqlxProgress {
+    rows: AtomicU64,
+    reported: AtomicU64,
+}
+
+impl SqlxProgress {
+    pub(crate) fn add(&self, rows: u64) {
+        self.rows.fetch_add(rows, Ordering::Relaxed);
+    }
+
+    /// The number of rows processed, if it changed since the last report
+    pub(crate) fn report(&self) -> Option<u64> {
+        let rows = self.rows.load(Ordering::Relaxed);
+        let reported = self.reported.swap(rows, Ordering::Relaxed);
+        (rows != reported).then_some(rows)
+    }
+}
+
+/// Stream the rows of `sql` to the file at `path`, returning the number of
+/// rows written
+pub(crate) async fn export<DB>(
+    sql: Arc<str>,
+    path: PathBuf,
+    format: SqlxFileFormat,
+    progress: Arc<SqlxProgress>,
+    pool: Pool<DB>,
+) -> Result<u64, Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'r> SqlxValue: Decode<'r, DB> + Type<DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    let mut file = BufWriter::new(File::create(path)?);
+    let mut rows = sqlx::query(&sql).fetch(&pool);
+    let mut count = 0;
+
+    if format == SqlxFileFormat::Json {
+        write!(file, "[")?;
+    }
+    while let Some(row) = rows.next().await {
+        let row = row?;
+        let columns = row.columns();
+        let mut values = Vec::with_capacity(columns.len());
+        for index in 0..columns.len() {
+            values.push(row.try_get::<SqlxValue, _>(index)?);
+        }
+
+        match format {
+            SqlxFileFormat::Csv => {
+                if count == 0 {
+                    let names: Vec<_> =
+                        columns.iter().map(|c| csv_field(c.name())).collect();
+                    writeln!(file, "{}", names.join(","))?;
+                }
+                let values: Vec<_> = values.iter().map(csv_value).collect();
+                writeln!(file, "{}", values.join(","))?;
+            }
+            SqlxFileFormat::Json => {
+                let object: Vec<_> = columns
+                    .iter()
+                    .zip(&values)
+                    .map(|(column, value)| {
+                        let name = json_string(column.name());
+                        format!("{name}:{}", json_value(value))
+                    })
+                    .collect();
+                let separator = if count == 0 { "" } else { "," };
+                write!(file, "{separator}\n{{{}}}", object.join(","))?;
+            }
+        }
+        count += 1;
+        progress.add(1);
+    }
+    if format == SqlxFileFormat::Json {
+        writeln!(file, "\n]")?;
+    }
+
+    file.flush()?;
+    Ok(count)
+}
+
+/// Snapshot the live SQLite database to the file at `path`, returning its
+/// size in bytes
+///
+/// The snapshot is written next to `path` with `VACUUM INTO`, then renamed
+/// over it, so `path` only ever holds a complete copy.
+pub(crate) async fn backup<DB>(
+    path: PathBuf,
+    pool: Pool<DB>,
+) -> Result<u64, Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+{
+    if DB::NAME != "SQLite" {
+        let err = format!("{} doesn't support backups", DB::NAME);
+        return Err(Error::Configuration(err.into()));
+    }
+    let mut partial = path.clone().into_os_string();
+    partial.push(".partial");
+    let partial = PathBuf::from(partial);
+    // `VACUUM INTO` refuses to overwrite a file left by a failed backup.
+    if partial.exists() {
+        std::fs::remove_file(&partial)?;
+    }
+
+    sqlx::query("VACUUM INTO ?")
+        .bind(SqlxValue::from(partial.to_string_lossy().as_ref()))
+        .execute(&pool)
+        .await?;
+    std::fs::rename(&partial, &path)?;
+    Ok(std::fs::metadata(&path)?.len())
+}
+
+pub(crate) fn csv_value(value: &SqlxValue) -> String {
+    match value {
+        SqlxValue::Null => String::new(),
+        SqlxValue::Bool(value) => value.to_string(),
+        SqlxValue::Int(value) => value.to_string(),
+        SqlxValue::Float(value) => value.to_string(),
+        SqlxValue::Text(value) => csv_field(value),
+        SqlxValue::Bytes(value) => {
+            value.iter().map(|byte| format!("{byte:02x}")).collect()
+        }
+        SqlxValue::Array(array) => csv_field(&array.literal()),
+        SqlxValue::Timestamp(micros) => rfc3339(*micros),
+    }
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.into()
+    }
+}
@@ -0,0 +1,255 @@
+use crate::*;
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+
+/// A [`Component`] built from a group of `R` rows, instead of a single one
+///
+/// One-to-many data, like the items of an inventory, land in one component
+/// of the group's entity this way. `R` is synced by a [`SqlxPlugin<DB, R>`]
+/// built [`SqlxPlugin::with_apply`] a [`SqlxGroupApply<Self>`], so its rows
+/// are merged into the component with the same [`Self::group_key`],
+/// replacing the row with the same primary key. A component is spawned for
+/// new groups.
+///
+/// There's no derive for this trait, the grouping key is declared by
+/// implementing [`Self::group_key`] by hand.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::{FromRow, Sqlite};
+/// # use bevy_sqlx::*;
+/// #[derive(Component, FromRow)]
+/// struct InventoryItem {
+///     id: u32,
+///     owner: u32,
+///     name: String,
+/// }
+/// # impl PrimaryKey for InventoryItem {
+/// #     type Column = u32;
+/// #     fn primary_key(&self) -> Self::Column { self.id }
+/// # }
+///
+/// #[derive(Component)]
+/// struct Inventory {
+///     owner: u32,
+///     items: Vec<InventoryItem>,
+/// }
+///
+/// impl SqlxFromRows<InventoryItem> for Inventory {
+///     type Key = u32;
+///
+///     fn group_key(row: &InventoryItem) -> u32 {
+///         row.owner
+///     }
+///
+///     fn from_rows(owner: u32, items: Vec<InventoryItem>) -> Self {
+///         Inventory { owner, items }
+///     }
+///
+///     fn key(&self) -> u32 {
+///         self.owner
+///     }
+///
+///     fn rows_mut(&mut self) -> &mut Vec<InventoryItem> {
+///         &mut self.items
+///     }
+/// }
+///
+/// let url = "sqlite:db/sqlite.db";
+/// App::new().add_plugins(
+///     SqlxPlugin::<Sqlite, InventoryItem>::from_url(url)
+///         .with_apply(SqlxGroupApply::<Inventory, _>::default()),
+/// );
+/// ```
+pub trait SqlxFromRows<R: PrimaryKey>: Component + Sized {
+    /// The type of the column rows are grouped by
+    type Key: Clone + Eq + Hash + Send + Sync + 'static;
+
+    /// The group `row` belongs to
+    fn group_key(row: &R) -> Self::Key;
+
+    /// Build the component of the group `key` from its `rows`
+    fn from_rows(key: Self::Key, rows: Vec<R>) -> Self;
+
+    /// The group this component was built from
+    fn key(&self) -> Self::Key;
+
+    /// The rows this component was built from
+    fn rows_mut(&mut self) -> &mut Vec<R>;
+}
+
+/// A [`SqlxApply`] merging synced rows into their [`SqlxFromRows`] group
+/// component `G`, instead of spawning them
+///
+/// The entity of each group, and the group of each row it has applied, are
+/// indexed, so a row moved to another group is removed from its previous one.
+/// Groups left without rows are kept. Rows starting a new group are reported
+/// with a [`SqlxEventStatus::Spawn`], others with a
+/// [`SqlxEventStatus::Update`].
+pub struct SqlxGroupApply<G: SqlxFromRows<R>, R: PrimaryKey> {
+    index: Arc<Mutex<SqlxGroupIndex<G::Key, R::Column>>>,
+}
+
+struct SqlxGroupIndex<K, P> {
+    /// The entity of each group, by its key
+    groups: HashMap<K, Entity>,
+    /// The group of each row, by its primary key
+    rows: HashMap<P, K>,
+}
+
+impl<G: SqlxFromRows<R>, R: PrimaryKey> Default for SqlxGroupApply<G, R> {
+    fn default() -> Self {
+        let index =
+            SqlxGroupIndex { groups: HashMap::new(), rows: HashMap::new() };
+        SqlxGroupApply { index: Arc::new(Mutex::new(index)) }
+    }
+}
+
+impl<R, G> SqlxApply<R> for SqlxGroupApply<G, R>
+where
+    R: Component + PrimaryKey,
+    R::Column: Eq + Hash + 'static,
+    G: SqlxFromRows<R>,
+{
+    fn apply(&self, row: R, ctx: &mut SqlxApplyContext<R>) -> SqlxApplied {
+        let key = G::group_key(&row);
+        let pk = row.primary_key();
+        let mut index = self.index.lock().unwrap();
+
+        let previous = index.rows.insert(pk.clone(), key.clone());
+        if let Some(previous) = previous.filter(|previous| *previous != key) {
+            if let Some(&entity) = index.groups.get(&previous) {
+                let pk = pk.clone();
+                ctx.commands.add(move |world: &mut World| {
+                    if let Some(mut group) = world.get_mut::<G>(entity) {
+                        group.rows_mut().retain(|r| r.primary_key() != pk);
+                    }
+                });
+            }
+        }
+
+        let Some(&entity) = index.groups.get(&key) else {
+            let group = G::from_rows(key.clone(), vec![row]);
+            index.groups.insert(key, ctx.commands.spawn(group).id());
+            return SqlxApplied::Spawned;
+        };
+        let index = self.index.clone();
+        ctx.commands.add(move |world: &mut World| {
+            let Some(mut group) = world.get_mut::<G>(entity) else {
+                // The group was despawned, so it's started again.
+                let group = G::from_rows(key.clone(), vec![row]);
+                let entity = world.spawn(group).id();
+                index.lock().unwrap().groups.insert(key, entity);
+                return;
+            };
+            let rows = group.rows_mut();
+            match rows.iter_mut().find(|r| r.primary_key() == pk) {
+                Some(existing) => *existing = row,
+                None => rows.push(row),
+            }
+        });
+        SqlxApplied::Updated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{AsyncComputeTaskPool, TaskPool};
+    use sqlx::{FromRow, Sqlite};
+
+    #[derive(Component, FromRow, Debug)]
+    struct Item {
+        id: u32,
+        owner: u32,
+        name: String,
+    }
+
+    impl PrimaryKey for Item {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    #[derive(Component, Debug)]
+    struct Inventory {
+        owner: u32,
+        items: Vec<Item>,
+    }
+
+    impl SqlxFromRows<Item> for Inventory {
+        type Key = u32;
+
+        fn group_key(row: &Item) -> u32 {
+            row.owner
+        }
+
+        fn from_rows(owner: u32, items: Vec<Item>) -> Self {
+            Inventory { owner, items }
+        }
+
+        fn key(&self) -> u32 {
+            self.owner
+        }
+
+        fn rows_mut(&mut self) -> &mut Vec<Item> {
+            &mut self.items
+        }
+    }
+
+    fn names(app: &mut App, owner: u32) -> Vec<String> {
+        let mut inventories = app.world_mut().query::<&Inventory>();
+        inventories
+            .iter(app.world())
+            .filter(|inventory| inventory.owner == owner)
+            .flat_map(|inventory| inventory.items.iter())
+            .map(|item| item.name.clone())
+            .collect()
+    }
+
+    fn sync(app: &mut App, sql: &'static str, done: impl Fn(&mut App) -> bool) {
+        let event = SqlxEvent::<Sqlite, Item>::query_sync(sql);
+        app.world_mut().send_event(event);
+        let mut tries = 0;
+        while !done(app) && tries < 1000 {
+            app.update();
+            tries += 1;
+        }
+    }
+
+    #[test]
+    fn test_from_rows() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let mut app = App::new();
+        app.add_plugins(
+            SqlxPlugin::<Sqlite, Item>::from_url(url)
+                .with_apply(SqlxGroupApply::<Inventory, _>::default()),
+        );
+
+        let sql = "SELECT 1 AS id, 1 AS owner, 'sword' AS name \
+                   UNION ALL SELECT 2, 1, 'shield' \
+                   UNION ALL SELECT 3, 2, 'bow'";
+        sync(&mut app, sql, |app| names(app, 2).len() == 1);
+        assert_eq!(vec!["sword", "shield"], names(&mut app, 1));
+        assert_eq!(vec!["bow"], names(&mut app, 2));
+
+        let sql = "SELECT 1 AS id, 1 AS owner, 'axe' AS name";
+        sync(&mut app, sql, |app| names(app, 1).contains(&"axe".into()));
+        assert_eq!(vec!["axe", "shield"], names(&mut app, 1));
+        let mut inventories = app.world_mut().query::<&Inventory>();
+        assert_eq!(2, inventories.iter(app.world()).len());
+        let mut items = app.world_mut().query::<&Item>();
+        assert_eq!(0, items.iter(app.world()).len());
+
+        let sql = "SELECT 3 AS id, 1 AS owner, 'bow' AS name";
+        sync(&mut app, sql, |app| names(app, 2).is_empty());
+        assert_eq!(vec!["axe", "shield", "bow"], names(&mut app, 1));
+        let mut inventories = app.world_mut().query::<&Inventory>();
+        assert_eq!(2, inventories.iter(app.world()).len());
+    }
+}
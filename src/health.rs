@@ -0,0 +1,309 @@
+use crate::*;
+use bevy::prelude::*;
+use bevy::tasks::futures_lite::future;
+use bevy::tasks::{block_on, AsyncComputeTaskPool, Task};
+use bevy::utils::{Duration, Instant};
+use sqlx::{Database, Error, Executor, IntoArguments};
+use std::marker::PhantomData;
+
+/// A [`Plugin`] pinging `DB` with a `SELECT 1` every `interval`, keeping
+/// its [`SqlxHealth<DB>`] up to date
+///
+/// A [`SqlxHealthChanged<DB>`] is sent whenever the [`SqlxHealthState`]
+/// changes, so servers can alert, or stop accepting players, before their
+/// queries start failing. A ping which takes longer than its
+/// [`Self::with_timeout`] counts as a failure.
+///
+/// ### Example
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy::utils::Duration;
+/// # use sqlx::Sqlite;
+/// # use bevy_sqlx::*;
+/// let url = "sqlite:db/sqlite.db";
+/// App::new()
+///     .add_plugins(SqlxPlugin::<Sqlite, SqlxDummy>::from_url(url))
+///     .add_plugins(
+///         SqlxHealthPlugin::<Sqlite>::every(Duration::from_secs(10))
+///             .with_slow_threshold(Duration::from_millis(250)),
+///     )
+///     .add_systems(Update, alert);
+///
+/// fn alert(mut changes: EventReader<SqlxHealthChanged<Sqlite>>) {
+///     for change in changes.read() {
+///         if change.state != SqlxHealthState::Healthy {
+///             warn!("database is {:?}", change.state);
+///         }
+///     }
+/// }
+/// ```
+pub struct SqlxHealthPlugin<DB> {
+    interval: Duration,
+    timeout: Duration,
+    slow_threshold: Option<Duration>,
+    _db: PhantomData<fn() -> DB>,
+}
+
+impl<DB> SqlxHealthPlugin<DB> {
+    /// Ping the database every `interval`
+    pub fn every(interval: Duration) -> Self {
+        SqlxHealthPlugin {
+            interval,
+            timeout: Duration::from_secs(5),
+            slow_threshold: None,
+            _db: PhantomData,
+        }
+    }
+
+    /// Fail pings which take longer than `timeout`, 5 seconds by default
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Consider the database [`SqlxHealthState::Degraded`] while pings take
+    /// longer than `threshold`
+    pub fn with_slow_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_threshold = Some(threshold);
+        self
+    }
+}
+
+impl<DB> Plugin for SqlxHealthPlugin<DB>
+where
+    DB: Database + Sync,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SqlxHealth::<DB> {
+            interval: self.interval,
+            timeout: self.timeout,
+            slow_threshold: self.slow_threshold,
+            state: None,
+            latency: None,
+            last_success: None,
+            last_error: None,
+            failures: 0,
+            pinged: None,
+            ping: None,
+            _db: PhantomData,
+        });
+        app.add_event::<SqlxHealthChanged<DB>>();
+        app.add_systems(Update, SqlxHealth::<DB>::handle_health);
+    }
+}
+
+/// How well a database is responding to the pings of a
+/// [`SqlxHealthPlugin`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SqlxHealthState {
+    /// The last ping succeeded in time
+    Healthy,
+    /// The last ping succeeded, but slower than the plugin's
+    /// [`SqlxHealthPlugin::with_slow_threshold`]
+    Degraded,
+    /// The last ping failed, or timed out
+    Unhealthy,
+}
+
+/// A [`Resource`] holding the results of the pings of the
+/// [`SqlxHealthPlugin`] of `DB`
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::Sqlite;
+/// # use bevy_sqlx::SqlxHealth;
+/// fn overlay(health: Res<SqlxHealth<Sqlite>>) {
+///     if let Some(latency) = health.latency() {
+///         info!("database latency: {latency:?}");
+///     }
+/// }
+/// ```
+#[derive(Resource, Debug)]
+pub struct SqlxHealth<DB> {
+    interval: Duration,
+    timeout: Duration,
+    slow_threshold: Option<Duration>,
+    state: Option<SqlxHealthState>,
+    latency: Option<Duration>,
+    last_success: Option<Instant>,
+    last_error: Option<String>,
+    failures: u32,
+    pinged: Option<Instant>,
+    ping: Option<Task<Result<Duration, Error>>>,
+    _db: PhantomData<fn() -> DB>,
+}
+
+impl<DB> SqlxHealth<DB> {
+    /// The state after the last ping, or `None` until the first one ends
+    pub fn state(&self) -> Option<SqlxHealthState> {
+        self.state
+    }
+
+    /// Return true if the last ping succeeded, even if it was slow
+    pub fn is_up(&self) -> bool {
+        self.state.is_some_and(|state| state != SqlxHealthState::Unhealthy)
+    }
+
+    /// How long the last successful ping took
+    pub fn latency(&self) -> Option<Duration> {
+        self.latency
+    }
+
+    /// When the last successful ping ended
+    pub fn last_success(&self) -> Option<Instant> {
+        self.last_success
+    }
+
+    /// Why the last ping failed, if it did
+    pub fn last_error(&self) -> Option<&str> {
+        self.last_error.as_deref()
+    }
+
+    /// How many pings in a row have failed
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    /// Record the result of a ping, returning the new state if it changed
+    fn record(
+        &mut self,
+        result: Result<Duration, String>,
+    ) -> Option<SqlxHealthState> {
+        let state = match result {
+            Ok(latency) => {
+                self.latency = Some(latency);
+                self.last_success = Some(Instant::now());
+                self.last_error = None;
+                self.failures = 0;
+                match self.slow_threshold {
+                    Some(threshold) if latency > threshold => {
+                        SqlxHealthState::Degraded
+                    }
+                    _ => SqlxHealthState::Healthy,
+                }
+            }
+            Err(err) => {
+                self.last_error = Some(err);
+                self.failures += 1;
+                SqlxHealthState::Unhealthy
+            }
+        };
+        let previous = self.state.replace(state);
+        (previous != Some(state)).then_some(state)
+    }
+}
+
+impl<DB> SqlxHealth<DB>
+where
+    DB: Database + Sync,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    /// A [`System`] pinging the database every interval, and sending a
+    /// [`SqlxHealthChanged`] when its state changes
+    pub fn handle_health(
+        database: Res<SqlxDatabase<DB>>,
+        mut health: ResMut<Self>,
+        mut changes: EventWriter<SqlxHealthChanged<DB>>,
+    ) {
+        let (previous, pinged, timeout) =
+            (health.state, health.pinged, health.timeout);
+        let mut result = None;
+        if let (Some(ping), Some(pinged)) = (&mut health.ping, pinged) {
+            if let Some(pong) = block_on(future::poll_once(ping)) {
+                result = Some(pong.map_err(|err| err.to_string()));
+            } else if pinged.elapsed() > timeout {
+                result = Some(Err("ping timed out".into()));
+            }
+        }
+        if let Some(result) = result {
+            health.ping = None;
+            if let Some(state) = health.record(result) {
+                changes.send(SqlxHealthChanged {
+                    state,
+                    previous,
+                    _db: PhantomData,
+                });
+            }
+        }
+
+        let due = health
+            .pinged
+            .is_none_or(|pinged| pinged.elapsed() >= health.interval);
+        if health.ping.is_none() && due {
+            let pool = database.pool.clone();
+            let ping = async move {
+                let start = Instant::now();
+                sqlx::query("SELECT 1").execute(&pool).await?;
+                Ok(start.elapsed())
+            };
+            health.pinged = Some(Instant::now());
+            health.ping = Some(AsyncComputeTaskPool::get().spawn(ping));
+        }
+    }
+}
+
+/// An [`Event`] sent when the [`SqlxHealthState`] of `DB` changes
+#[derive(Event, Debug)]
+pub struct SqlxHealthChanged<DB> {
+    /// The new state
+    pub state: SqlxHealthState,
+    /// The state before, or `None` after the first ping
+    pub previous: Option<SqlxHealthState>,
+    _db: PhantomData<fn() -> DB>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{AsyncComputeTaskPool, TaskPool};
+    use bevy::utils::Duration;
+    use sqlx::Sqlite;
+
+    fn update_until_changed(app: &mut App) -> Option<SqlxHealthState> {
+        let mut tries = 0;
+        while tries < 1000 {
+            app.update();
+            let events =
+                app.world().resource::<Events<SqlxHealthChanged<Sqlite>>>();
+            if let Some(change) = events.iter_current_update_events().next() {
+                return Some(change.state);
+            }
+            tries += 1;
+        }
+        None
+    }
+
+    #[test]
+    fn test_health() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let mut app = App::new();
+        app.add_plugins(SqlxPlugin::<Sqlite, SqlxDummy>::from_url(url));
+        app.add_plugins(SqlxHealthPlugin::<Sqlite>::every(Duration::ZERO));
+
+        assert_eq!(
+            Some(SqlxHealthState::Healthy),
+            update_until_changed(&mut app)
+        );
+        let health = app.world().resource::<SqlxHealth<Sqlite>>();
+        assert!(health.is_up());
+        assert!(health.latency().is_some());
+        assert!(health.last_success().is_some());
+
+        let pool = app.world().resource::<SqlxDatabase<Sqlite>>().pool.clone();
+        bevy::tasks::block_on(pool.close());
+        assert_eq!(
+            Some(SqlxHealthState::Unhealthy),
+            update_until_changed(&mut app)
+        );
+        let health = app.world().resource::<SqlxHealth<Sqlite>>();
+        assert!(!health.is_up());
+        assert!(health.last_error().is_some());
+        assert!(health.failures() > 0);
+    }
+}
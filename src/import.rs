@@ -0,0 +1,230 @@
+use crate::*;
+use serde::de::value::{Error as DeError, MapDeserializer};
+use serde::de::{self, DeserializeOwned, Deserializer, IntoDeserializer};
+use serde::de::{Error as _, Visitor};
+use sqlx::{Database, Encode, Error, Executor, IntoArguments, Pool, Type};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// The number of rows inserted by each transaction of an import
+pub const IMPORT_BATCH_SIZE: usize = 500;
+
+/// Insert the components decoded from the file at `path` into their table,
+/// with `prefix`, returning the number of rows inserted
+///
+/// Rows are inserted in transactions of [`IMPORT_BATCH_SIZE`], so a failed
+/// import leaves only whole batches behind.
+pub(crate) async fn import<DB, C>(
+    prefix: &str,
+    path: PathBuf,
+    format: SqlxFileFormat,
+    progress: Arc<SqlxProgress>,
+    pool: Pool<DB>,
+) -> Result<u64, Error>
+where
+    DB: Database,
+    C: ToRow + DeserializeOwned,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+{
+    let text = std::fs::read_to_string(path)?;
+    let components: Vec<C> = match format {
+        SqlxFileFormat::Csv => from_csv(&text),
+        SqlxFileFormat::Json => {
+            serde_json::from_str(&text).map_err(|err| Error::Decode(err.into()))
+        }
+    }?;
+
+    let mut count = 0;
+    for batch in components.chunks(IMPORT_BATCH_SIZE) {
+        let mut tx = pool.begin().await?;
+        for component in batch {
+            let stmt = SqlxStatement::insert(C::table(), component.to_row())
+                .prefixed(prefix);
+            let sql = stmt.sql::<DB>();
+            let mut query = sqlx::query(&sql);
+            for value in stmt.binds() {
+                query = query.bind(value);
+            }
+            query.execute(&mut *tx).await?;
+        }
+        tx.commit().await?;
+        count += batch.len() as u64;
+        progress.add(batch.len() as u64);
+    }
+    Ok(count)
+}
+
+/// Decode each record after the header into a `C`, with the header naming
+/// the fields
+fn from_csv<C: DeserializeOwned>(text: &str) -> Result<Vec<C>, Error> {
+    let mut records = csv_records(text).into_iter();
+    let Some(header) = records.next() else {
+        return Ok(Vec::new());
+    };
+    records
+        .enumerate()
+        .map(|(index, record)| {
+            if record.len() != header.len() {
+                let err = format!(
+                    "CSV record {} has {} fields, expected {}",
+                    index + 1,
+                    record.len(),
+                    header.len(),
+                );
+                return Err(Error::Decode(err.into()));
+            }
+            let fields = header
+                .iter()
+                .map(String::as_str)
+                .zip(record.iter().map(|field| CsvField(field)));
+            C::deserialize(MapDeserializer::new(fields))
+                .map_err(|err: DeError| Error::Decode(err.into()))
+        })
+        .collect()
+}
+
+/// Split CSV text into records of unquoted fields, skipping blank lines
+fn csv_records(text: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                if !record.is_empty() || !field.is_empty() {
+                    record.push(std::mem::take(&mut field));
+                    records.push(std::mem::take(&mut record));
+                }
+            }
+            c => field.push(c),
+        }
+    }
+    if !record.is_empty() || !field.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
+
+/// A single CSV field, parsed as whatever type the component expects
+///
+/// Empty fields are `None` for `Option` fields.
+struct CsvField<'a>(&'a str);
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident,)*) => {$(
+        fn $method<V: Visitor<'de>>(
+            self,
+            visitor: V,
+        ) -> Result<V::Value, Self::Error> {
+            visitor.$visit(self.0.parse().map_err(DeError::custom)?)
+        }
+    )*};
+}
+
+impl<'de> Deserializer<'de> for CsvField<'de> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_borrowed_str(self.0)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        if self.0.is_empty() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let variant: de::value::BorrowedStrDeserializer<DeError> =
+            de::value::BorrowedStrDeserializer::new(self.0);
+        variant.deserialize_enum(name, variants, visitor)
+    }
+
+    deserialize_parsed! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+    }
+
+    serde::forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, DeError> for CsvField<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Row {
+        id: u32,
+        text: String,
+        score: Option<f64>,
+    }
+
+    #[test]
+    fn test_from_csv() {
+        let text = "id,text,score\r\n1,\"a, \"\"b\"\"\",1.5\n\n2,,\n";
+        let rows: Vec<Row> = from_csv(text).unwrap();
+        assert_eq!(
+            vec![
+                Row { id: 1, text: "a, \"b\"".into(), score: Some(1.5) },
+                Row { id: 2, text: "".into(), score: None },
+            ],
+            rows
+        );
+        assert!(from_csv::<Row>("id,text,score\nx,y,z\n").is_err());
+        assert!(from_csv::<Row>("id,text,score\n1,y\n").is_err());
+    }
+}
@@ -0,0 +1,457 @@
+use crate::*;
+use bevy::prelude::*;
+use bevy::tasks::futures_lite::future;
+use bevy::tasks::{block_on, Task};
+use serde::{Deserialize, Serialize};
+use sqlx::{ColumnIndex, Database, Decode, Encode, Error, Executor};
+use sqlx::{IntoArguments, Pool, Type};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// A generated write kept in a [`SqlxJournal`] while the connection was lost
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SqlxJournalEntry {
+    /// The id of the event which was lost
+    pub id: SqlxEventId,
+    /// The statement's SQL, rendered for the database
+    pub sql: String,
+    pub binds: Vec<SqlxValue>,
+    #[serde(default)]
+    pub table: String,
+    /// When the write was journaled, in microseconds since the Unix epoch
+    #[serde(default)]
+    pub queued_at: i64,
+    /// The columns the statement writes
+    #[serde(default)]
+    pub columns: Vec<(String, SqlxValue)>,
+    /// The filters selecting the rows the statement changes, unless it's
+    /// an insert
+    #[serde(default)]
+    pub filters: Option<Vec<(String, SqlxValue)>>,
+}
+
+/// The outcome of replaying each entry, up to the first which lost the
+/// connection again
+type SqlxReplay = Vec<(SqlxEventId, Result<Option<SqlxConflict>, Error>)>;
+
+/// A [`Resource`] journaling the generated writes of `C` which were lost
+/// with the connection, to replay them once it's restored
+///
+/// Added by a [`SqlxPlugin`] built with [`SqlxPlugin::with_journal`]. The
+/// journal is a local file of JSON lines, so the writes survive the app
+/// exiting while offline, and are replayed the next time it connects.
+///
+/// Writes made with [`SqlxEvent::insert`], [`SqlxEvent::update`],
+/// [`SqlxEvent::upsert`] or [`SqlxEvent::delete`] are journaled, in the
+/// order they failed, and a [`SqlxJournalStatus::Journaled`] is sent instead
+/// of holding them with the other lost events. They're replayed one at a
+/// time, in order, without syncing their results or logging them to the
+/// [`AUDIT_TABLE`]. An entry which fails for another reason than the
+/// connection is dropped, with a [`SqlxJournalStatus::Error`].
+///
+/// With [`SqlxPlugin::with_conflict_policy`], an entry whose rows changed
+/// remotely after it was journaled is resolved by the
+/// [`SqlxConflictPolicy`] instead, and a [`SqlxJournalStatus::Conflict`] is
+/// sent.
+#[allow(clippy::type_complexity)]
+#[derive(Resource)]
+pub struct SqlxJournal<DB: Database, C> {
+    path: PathBuf,
+    len: usize,
+    replay: Option<(Task<Result<SqlxReplay, Error>>, Arc<SqlxProgress>)>,
+    reconnect: Option<SqlxReconnect>,
+    _db: PhantomData<DB>,
+    _c: PhantomData<C>,
+}
+
+impl<DB: Database, C> SqlxJournal<DB, C> {
+    /// Open the journal at `path`, counting the entries left by a previous
+    /// run
+    pub(crate) fn new(path: PathBuf) -> Self {
+        let len = read_entries(&path).map_or(0, |entries| entries.len());
+        SqlxJournal {
+            path,
+            len,
+            replay: None,
+            reconnect: None,
+            _db: PhantomData,
+            _c: PhantomData,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The number of entries waiting to be replayed
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Return true while the entries are being replayed
+    pub fn is_replaying(&self) -> bool {
+        self.replay.is_some()
+    }
+
+    /// Read the entries waiting to be replayed, oldest first
+    pub fn entries(&self) -> Result<Vec<SqlxJournalEntry>, Error> {
+        read_entries(&self.path)
+    }
+
+    /// Append `entry` to the end of the journal
+    pub(crate) fn append(
+        &mut self,
+        entry: &SqlxJournalEntry,
+    ) -> io::Result<()> {
+        let mut file =
+            OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Remove the first `n` entries from the journal
+    fn remove_front(&mut self, n: usize) -> io::Result<()> {
+        let text = fs::read_to_string(&self.path)?;
+        let rest: String = text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .skip(n)
+            .map(|line| format!("{line}\n"))
+            .collect();
+        if rest.is_empty() {
+            fs::remove_file(&self.path)?;
+        } else {
+            fs::write(&self.path, rest)?;
+        }
+        self.len = self.len.saturating_sub(n);
+        Ok(())
+    }
+}
+
+impl<DB, C> SqlxJournal<DB, C>
+where
+    DB: Database + Sync,
+    C: SqlxComponent<DB::Row>,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+    for<'r> SqlxValue: Decode<'r, DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    /// A [`System`] journaling the generated writes held while the
+    /// connection is lost, and replaying them once it's available
+    pub fn handle_journal(
+        database: Res<SqlxDatabase<DB>>,
+        credentials: Option<Res<SqlxCredentials<DB>>>,
+        config: Res<SqlxConfig<DB, C>>,
+        tenant: Option<Res<TenantId>>,
+        mut tasks: ResMut<SqlxTasks<DB, C>>,
+        mut journal: ResMut<Self>,
+        mut status: EventWriter<SqlxJournalStatus<DB, C>>,
+    ) {
+        let tenant = tenant.as_deref();
+        tasks.lost.retain(|event| {
+            let Some(Ok(entry)) = event.journal_entry(&config, tenant) else {
+                return true;
+            };
+            match journal.append(&entry) {
+                Ok(()) => {
+                    status.send(SqlxJournalStatus::Journaled(
+                        entry.id,
+                        PhantomData,
+                    ));
+                    false
+                }
+                Err(err) => {
+                    status.send(SqlxJournalStatus::Error(
+                        Some(entry.id),
+                        err.into(),
+                    ));
+                    true
+                }
+            }
+        });
+
+        if let Some((task, progress)) = &mut journal.replay {
+            if let Some(rows) = progress.report() {
+                status.send(SqlxJournalStatus::Progress(rows, PhantomData));
+            }
+            let Some(result) = block_on(future::poll_once(task)) else {
+                return;
+            };
+            journal.replay = None;
+            journal.replayed(result, &mut status);
+            return;
+        }
+
+        if journal.is_empty() || tasks.is_connection_lost() {
+            return;
+        }
+        if let Some(reconnect) = &mut journal.reconnect {
+            let provider = credentials.as_ref().map(|c| &c.provider);
+            if !reconnect.poll(&database.pool, provider) {
+                return;
+            }
+            journal.reconnect = None;
+        }
+
+        let len = journal.len as u64;
+        status.send(SqlxJournalStatus::Replaying(len, PhantomData));
+        let progress = Arc::new(SqlxProgress::default());
+        let path = journal.path.clone();
+        let pool = database.pool.clone();
+        let conflicts = config
+            .conflict_column
+            .map(|column| (column, config.conflict_policy.clone()));
+        let task = replay(path, progress.clone(), pool, conflicts);
+        let task = config.task_pool.spawn(task);
+        journal.replay = Some((task, progress));
+    }
+
+    /// Drop the replayed entries, and send their statuses
+    fn replayed(
+        &mut self,
+        result: Result<SqlxReplay, Error>,
+        status: &mut EventWriter<SqlxJournalStatus<DB, C>>,
+    ) {
+        let results = match result {
+            Ok(results) => results,
+            Err(err) => {
+                status.send(SqlxJournalStatus::Error(None, err));
+                self.reconnect = Some(SqlxReconnect::new());
+                return;
+            }
+        };
+
+        let mut replayed = 0;
+        let mut removed = 0;
+        for (id, result) in results {
+            match result {
+                Ok(None) => replayed += 1,
+                Ok(Some(conflict)) => {
+                    status.send(SqlxJournalStatus::Conflict(conflict));
+                    replayed += 1;
+                }
+                Err(err) if is_connection_error(&err) => {
+                    self.reconnect = Some(SqlxReconnect::new());
+                    break;
+                }
+                Err(err) => {
+                    status.send(SqlxJournalStatus::Error(Some(id), err));
+                }
+            }
+            removed += 1;
+        }
+        if let Err(err) = self.remove_front(removed) {
+            status.send(SqlxJournalStatus::Error(None, err.into()));
+        }
+        status.send(SqlxJournalStatus::Replayed(replayed, PhantomData));
+    }
+}
+
+/// An [`Event`] sent as a [`SqlxJournal`] journals and replays writes
+#[derive(Event, Debug)]
+pub enum SqlxJournalStatus<DB, C> {
+    /// The lost write of an event was journaled
+    Journaled(SqlxEventId, PhantomData<(DB, C)>),
+    /// Replaying this many entries started
+    Replaying(u64, PhantomData<(DB, C)>),
+    /// This many entries were replayed so far
+    Progress(u64, PhantomData<(DB, C)>),
+    /// The replay finished, with this many entries replayed successfully,
+    /// conflicts included
+    Replayed(u64, PhantomData<(DB, C)>),
+    /// An entry conflicted with the remote rows, and was resolved
+    Conflict(SqlxConflict),
+    /// An entry failed and was dropped, or the journal itself failed
+    Error(Option<SqlxEventId>, Error),
+}
+
+/// Read the entries of the journal at `path`, which may not exist yet
+fn read_entries(path: &Path) -> Result<Vec<SqlxJournalEntry>, Error> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err.into()),
+    };
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::from_str(line).map_err(|err| Error::Decode(err.into()))
+        })
+        .collect()
+}
+
+/// Execute the entries of the journal at `path` in order, stopping at the
+/// first which loses the connection
+///
+/// With a conflict `column` and policy, conflicting entries are resolved
+/// instead of executed.
+async fn replay<DB>(
+    path: PathBuf,
+    progress: Arc<SqlxProgress>,
+    pool: Pool<DB>,
+    conflicts: Option<(&'static str, SqlxConflictPolicy)>,
+) -> Result<SqlxReplay, Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+    for<'r> SqlxValue: Decode<'r, DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    let mut results = Vec::new();
+    for entry in read_entries(&path)? {
+        let result = replay_entry(&entry, &conflicts, &pool).await;
+        let lost = matches!(&result, Err(err) if is_connection_error(err));
+        results.push((entry.id, result));
+        if lost {
+            break;
+        }
+        progress.add(1);
+    }
+    Ok(results)
+}
+
+/// Execute `entry`, unless it conflicts
+async fn replay_entry<DB>(
+    entry: &SqlxJournalEntry,
+    conflicts: &Option<(&'static str, SqlxConflictPolicy)>,
+    pool: &Pool<DB>,
+) -> Result<Option<SqlxConflict>, Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+    for<'r> SqlxValue: Decode<'r, DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    if let Some((column, policy)) = conflicts {
+        let conflict = resolve_conflict(entry, (column, policy), pool).await?;
+        if conflict.is_some() {
+            return Ok(conflict);
+        }
+    }
+    let mut query = sqlx::query(&entry.sql);
+    for value in entry.binds.iter().cloned() {
+        query = query.bind(value);
+    }
+    query.execute(pool).await?;
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{block_on, AsyncComputeTaskPool, TaskPool};
+    use sqlx::{FromRow, Sqlite};
+
+    #[derive(Component, FromRow, Debug)]
+    struct Foo {
+        id: u32,
+        text: String,
+    }
+
+    impl PrimaryKey for Foo {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    impl ToRow for Foo {
+        fn table() -> &'static str {
+            "foos"
+        }
+
+        fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+            vec![("text", self.text.clone().into())]
+        }
+    }
+
+    #[test]
+    fn test_journal_replay() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let path = std::env::temp_dir().join("bevy_sqlx_test_journal.jsonl");
+        let _ = std::fs::remove_file(&path);
+
+        // Entries left by a previous run, the second of which fails.
+        let foo = Foo { id: 0, text: "journaled".into() };
+        let ids = SqlxEventIds::default();
+        let event = SqlxEvent::<Sqlite, Foo>::insert(&foo).with_id(ids.next());
+        let config = SqlxConfig::<Sqlite, Foo>::default();
+        let inserted = event.journal_entry(&config, None).unwrap().unwrap();
+        let failed = SqlxJournalEntry {
+            id: ids.next(),
+            sql: "INSERT INTO missing VALUES (1)".into(),
+            binds: vec![],
+            table: "missing".into(),
+            queued_at: 0,
+            columns: vec![],
+            filters: None,
+        };
+        let mut journal = SqlxJournal::<Sqlite, Foo>::new(path.clone());
+        journal.append(&inserted).unwrap();
+        journal.append(&failed).unwrap();
+        assert_eq!(
+            vec![inserted.clone(), failed.clone()],
+            journal.entries().unwrap()
+        );
+
+        let mut app = App::new();
+        app.add_plugins(
+            SqlxPlugin::<Sqlite, Foo>::from_url(url).with_journal(&path),
+        );
+        assert_eq!(2, app.world().resource::<SqlxJournal<Sqlite, Foo>>().len());
+
+        let mut statuses = Vec::new();
+        let mut tries = 0;
+        while !app.world().resource::<SqlxJournal<Sqlite, Foo>>().is_empty()
+            && tries < 1000
+        {
+            app.update();
+            let events = app
+                .world()
+                .resource::<Events<SqlxJournalStatus<Sqlite, Foo>>>();
+            statuses.extend(events.iter_current_update_events().map(
+                |s| match s {
+                    SqlxJournalStatus::Replaying(n, _) => {
+                        format!("replaying {n}")
+                    }
+                    SqlxJournalStatus::Replayed(n, _) => {
+                        format!("replayed {n}")
+                    }
+                    SqlxJournalStatus::Error(id, _) => format!("error {id:?}"),
+                    _ => String::new(),
+                },
+            ));
+            tries += 1;
+        }
+        statuses.retain(|status| !status.is_empty());
+        let error = format!("error {:?}", Some(failed.id));
+        assert_eq!(vec!["replaying 2", &error, "replayed 1"], statuses);
+        assert!(!path.exists());
+
+        let pool = app.world().resource::<SqlxDatabase<Sqlite>>().pool.clone();
+        let count: i64 = block_on(async {
+            sqlx::query_scalar("SELECT COUNT(*) FROM foos WHERE text = ?")
+                .bind("journaled")
+                .fetch_one(&pool)
+                .await
+                .unwrap()
+        });
+        assert!(count >= 1);
+    }
+}
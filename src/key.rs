@@ -0,0 +1,179 @@
+use crate::*;
+use bevy::prelude::*;
+use sqlx::Type;
+use sqlx::{ColumnIndex, Database, Decode, Encode, Executor, IntoArguments};
+use std::marker::PhantomData;
+
+/// A [`Component`] holding only the primary key of a `C`
+///
+/// With a [`SqlxKeyPlugin<DB, C>`] added, spawning an entity with a key
+/// loads its row, and inserts the full `C` onto the entity with
+/// [`SqlxEvent::load_into`]. Changing the key loads the new row. This allows
+/// cheap placeholder entities, like map pins or distant NPCs, which hydrate
+/// on demand.
+///
+/// ### Example
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::{FromRow, Sqlite};
+/// # use bevy_sqlx::*;
+/// # #[derive(Component, FromRow)]
+/// # struct Foo { id: u32, text: String }
+/// # impl PrimaryKey for Foo {
+/// #     type Column = u32;
+/// #     fn primary_key(&self) -> Self::Column { self.id }
+/// # }
+/// # impl ToRow for Foo {
+/// #     fn table() -> &'static str { "foos" }
+/// #     fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+/// #         vec![("text", self.text.clone().into())]
+/// #     }
+/// # }
+/// let url = "sqlite:db/sqlite.db";
+/// let mut app = App::new();
+/// app.add_plugins(SqlxPlugin::<Sqlite, Foo>::from_url(url))
+///     .add_plugins(SqlxKeyPlugin::<Sqlite, Foo>::default());
+/// app.world_mut().spawn(SqlxKey::<Foo>::new(1));
+/// ```
+#[derive(Component)]
+pub struct SqlxKey<C: PrimaryKey + 'static> {
+    pub key: C::Column,
+    _c: PhantomData<fn() -> C>,
+}
+
+impl<C: PrimaryKey + 'static> SqlxKey<C> {
+    pub fn new(key: C::Column) -> Self {
+        SqlxKey { key, _c: PhantomData }
+    }
+
+    /// A [`System`] loading the components of new or changed keys
+    pub fn handle_keys<DB>(
+        keys: Query<(Entity, &Self), Changed<Self>>,
+        mut events: EventWriter<SqlxEvent<DB, C>>,
+    ) where
+        DB: Database + Sync,
+        C: SqlxComponent<DB::Row> + ToRow,
+        C::Column: Into<SqlxValue>,
+        for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+        for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    {
+        for (entity, key) in &keys {
+            events.send(SqlxEvent::load_into(entity, key.key.clone()));
+        }
+    }
+}
+
+/// A [`Plugin`] hydrating entities spawned with a [`SqlxKey<C>`]
+///
+/// See [`SqlxKey`] for more information.
+pub struct SqlxKeyPlugin<DB, C> {
+    _db: PhantomData<DB>,
+    _c: PhantomData<C>,
+}
+
+impl<DB, C> Default for SqlxKeyPlugin<DB, C> {
+    fn default() -> Self {
+        SqlxKeyPlugin { _db: PhantomData, _c: PhantomData }
+    }
+}
+
+impl<DB, C> Plugin for SqlxKeyPlugin<DB, C>
+where
+    DB: Database + Sync,
+    C: SqlxComponent<DB::Row> + ToRow,
+    C::Column: Into<SqlxValue>,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+    for<'r> SqlxValue: Decode<'r, DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    fn build(&self, app: &mut App) {
+        if !SqlxSyncMode::of::<DB, C>(app.world()).reads() {
+            return;
+        }
+        app.add_systems(
+            Update,
+            SqlxKey::<C>::handle_keys::<DB>
+                .before(SqlxRegistry::<DB>::handle_events),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{block_on, AsyncComputeTaskPool, TaskPool};
+    use sqlx::{FromRow, Sqlite};
+
+    #[derive(Component, FromRow, Debug)]
+    struct Foo {
+        id: u32,
+        text: String,
+    }
+
+    impl PrimaryKey for Foo {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    impl ToRow for Foo {
+        fn table() -> &'static str {
+            "foos"
+        }
+
+        fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+            vec![("text", self.text.clone().into())]
+        }
+    }
+
+    #[test]
+    fn test_key() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let mut app = App::new();
+        app.add_plugins(SqlxPlugin::<Sqlite, Foo>::from_url(url));
+        app.add_plugins(SqlxKeyPlugin::<Sqlite, Foo>::default());
+
+        let pool = app.world().resource::<SqlxDatabase<Sqlite>>().pool.clone();
+        let insert = "INSERT INTO foos (text) VALUES (?) RETURNING *";
+        let (first, second): (Foo, Foo) = block_on(async {
+            (
+                sqlx::query_as(insert)
+                    .bind("first key")
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap(),
+                sqlx::query_as(insert)
+                    .bind("second key")
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap(),
+            )
+        });
+
+        let entity = app.world_mut().spawn(SqlxKey::<Foo>::new(first.id)).id();
+        let text = |app: &App| {
+            app.world().get::<Foo>(entity).map(|foo| foo.text.clone())
+        };
+        let mut tries = 0;
+        while text(&app).is_none() && tries < 1000 {
+            app.update();
+            tries += 1;
+        }
+        assert_eq!(Some("first key".into()), text(&app));
+
+        app.world_mut().get_mut::<SqlxKey<Foo>>(entity).unwrap().key =
+            second.id;
+        let mut tries = 0;
+        while text(&app).as_deref() != Some("second key") && tries < 1000 {
+            app.update();
+            tries += 1;
+        }
+        assert_eq!(Some("second key".into()), text(&app));
+    }
+}
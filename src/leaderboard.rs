@@ -0,0 +1,272 @@
+use crate::*;
+use bevy::prelude::*;
+use bevy::utils::Duration;
+use sqlx::{Database, Encode, Executor, IntoArguments, Type};
+use std::marker::PhantomData;
+
+/// A [`Plugin`] keeping a [`Leaderboard<C>`] of the top rows of `C`'s table
+///
+/// The leaderboard is loaded when the app starts, and refreshed every
+/// [`Self::every`] or whenever [`Leaderboard::refresh`] is called. Queries
+/// are sent as [`SqlxEvent`]s, so a [`SqlxPlugin<DB, C>`] must be added
+/// too.
+///
+/// ### Example
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy::utils::Duration;
+/// # use sqlx::{FromRow, Sqlite};
+/// # use bevy_sqlx::*;
+/// # #[derive(Component, FromRow, Clone)]
+/// # struct Foo { id: u32 }
+/// # impl PrimaryKey for Foo {
+/// #     type Column = u32;
+/// #     fn primary_key(&self) -> Self::Column { self.id }
+/// # }
+/// # impl ToRow for Foo {
+/// #     fn table() -> &'static str { "foos" }
+/// #     fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+/// #         vec![("id", self.id.into())]
+/// #     }
+/// # }
+/// let url = "sqlite:db/sqlite.db";
+/// App::new()
+///     .add_plugins(DefaultPlugins)
+///     .add_plugins(SqlxPlugin::<Sqlite, Foo>::from_url(&url))
+///     .add_plugins(
+///         SqlxLeaderboardPlugin::<Sqlite, Foo>::new("id", 10)
+///             .every(Duration::from_secs(30)),
+///     )
+///     .add_systems(Update, top)
+///     .run();
+///
+/// fn top(leaderboard: Res<Leaderboard<Foo>>) {
+///     if let Some(first) = leaderboard.first() {
+///         println!("#1 is {}", first.id);
+///     }
+/// }
+/// ```
+pub struct SqlxLeaderboardPlugin<DB: Database, C: SqlxComponent<DB::Row>> {
+    order_by: &'static str,
+    descending: bool,
+    limit: u64,
+    every: Option<Duration>,
+    _db: PhantomData<DB>,
+    _c: PhantomData<C>,
+}
+
+impl<DB: Database, C: SqlxComponent<DB::Row>> SqlxLeaderboardPlugin<DB, C> {
+    /// Rank the top `limit` rows by `order_by`, highest first
+    pub fn new(order_by: &'static str, limit: u64) -> Self {
+        SqlxLeaderboardPlugin {
+            order_by,
+            descending: true,
+            limit,
+            every: None,
+            _db: PhantomData,
+            _c: PhantomData,
+        }
+    }
+
+    /// Rank rows lowest first instead, e.g. for best times
+    pub fn ascending(mut self) -> Self {
+        self.descending = false;
+        self
+    }
+
+    /// Refresh the leaderboard periodically
+    pub fn every(mut self, period: Duration) -> Self {
+        self.every = Some(period);
+        self
+    }
+}
+
+impl<DB, C> Plugin for SqlxLeaderboardPlugin<DB, C>
+where
+    DB: Database + Sync,
+    C: SqlxComponent<DB::Row> + ToRow + Clone,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+{
+    fn build(&self, app: &mut App) {
+        let mut select = SqlxStatement::select(C::table()).limit(self.limit);
+        select = if self.descending {
+            select.order_by_desc(self.order_by)
+        } else {
+            select.order_by(self.order_by)
+        };
+
+        app.insert_resource(Leaderboard::<C> {
+            entries: Vec::new(),
+            select,
+            timer: self
+                .every
+                .map(|period| Timer::new(period, TimerMode::Repeating)),
+            refresh: true,
+            pending: None,
+        });
+        app.add_systems(
+            Update,
+            (
+                Leaderboard::<C>::handle_statuses::<DB>,
+                Leaderboard::<C>::handle_refresh::<DB>,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// A [`Resource`] of the top rows of `C`'s table, in rank order
+///
+/// See [`SqlxLeaderboardPlugin`] for more information.
+#[derive(Resource)]
+pub struct Leaderboard<C> {
+    entries: Vec<C>,
+    select: SqlxStatement,
+    timer: Option<Timer>,
+    refresh: bool,
+    pending: Option<SqlxEventId>,
+}
+
+impl<C> Leaderboard<C> {
+    /// Request the leaderboard be reloaded from the database
+    pub fn refresh(&mut self) {
+        self.refresh = true;
+    }
+
+    /// Return true while the leaderboard is being reloaded
+    pub fn is_loading(&self) -> bool {
+        self.pending.is_some()
+    }
+}
+
+impl<C> std::ops::Deref for Leaderboard<C> {
+    type Target = [C];
+
+    fn deref(&self) -> &[C] {
+        &self.entries
+    }
+}
+
+impl<C: Send + Sync + 'static> Leaderboard<C> {
+    /// A [`System`] sending the leaderboard's select when it's due
+    pub fn handle_refresh<DB>(
+        time: Option<Res<Time>>,
+        ids: Res<SqlxEventIds>,
+        mut leaderboard: ResMut<Self>,
+        mut events: EventWriter<SqlxEvent<DB, C>>,
+    ) where
+        DB: Database + Sync,
+        C: SqlxComponent<DB::Row>,
+        for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+        for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    {
+        let leaderboard = &mut *leaderboard;
+        if let (Some(timer), Some(time)) = (&mut leaderboard.timer, time) {
+            if timer.tick(time.delta()).just_finished() {
+                leaderboard.refresh = true;
+            }
+        }
+
+        if leaderboard.refresh && leaderboard.pending.is_none() {
+            let select = leaderboard.select.clone();
+            let id = ids.next();
+            let event = SqlxEvent::<DB, C>::statement(select)
+                .with_label("leaderboard")
+                .with_id(id);
+            leaderboard.pending = Some(id);
+            leaderboard.refresh = false;
+            events.send(event);
+        }
+    }
+
+    /// A [`System`] replacing the leaderboard's entries when its select
+    /// returns
+    pub fn handle_statuses<DB>(
+        mut leaderboard: ResMut<Self>,
+        mut statuses: EventReader<SqlxEventStatus<DB, C>>,
+    ) where
+        DB: Database + Sync,
+        C: SqlxComponent<DB::Row> + Clone,
+    {
+        for status in statuses.read() {
+            if leaderboard.pending != Some(status.id()) {
+                continue;
+            }
+            match status {
+                SqlxEventStatus::Return(_, entries) => {
+                    leaderboard.entries = entries.clone();
+                    leaderboard.pending = None;
+                }
+                SqlxEventStatus::Error(..) => {
+                    leaderboard.pending = None;
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{block_on, AsyncComputeTaskPool, TaskPool};
+    use sqlx::{FromRow, Sqlite};
+
+    #[derive(Component, FromRow, Debug, Clone)]
+    struct Foo {
+        id: u32,
+    }
+
+    impl PrimaryKey for Foo {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    impl ToRow for Foo {
+        fn table() -> &'static str {
+            "foos"
+        }
+
+        fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+            vec![("id", self.id.into())]
+        }
+    }
+
+    #[test]
+    fn test_leaderboard() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let mut app = App::new();
+        app.add_plugins(SqlxPlugin::<Sqlite, Foo>::from_url(url));
+        app.add_plugins(SqlxLeaderboardPlugin::<Sqlite, Foo>::new("id", 2));
+
+        let pool = app.world().resource::<SqlxDatabase<Sqlite>>().pool.clone();
+        block_on(async {
+            for _ in 0..3 {
+                sqlx::query("INSERT INTO foos (text) VALUES ('leaderboard')")
+                    .execute(&pool)
+                    .await
+                    .unwrap();
+            }
+        });
+
+        let mut tries = 0;
+        app.update();
+        while app.world().resource::<Leaderboard<Foo>>().is_loading()
+            && tries < 1000
+        {
+            app.update();
+            tries += 1;
+        }
+
+        let leaderboard = app.world().resource::<Leaderboard<Foo>>();
+        assert_eq!(2, leaderboard.len());
+        assert!(leaderboard[0].id > leaderboard[1].id);
+    }
+}
@@ -5,7 +5,7 @@
 //! ### Setup
 //!
 //! - Define a [`Component`](bevy::prelude::Component) with
-//! [`FromRow`](sqlx::FromRow) and [`PrimaryKey`]
+//!   [`FromRow`](sqlx::FromRow) and [`PrimaryKey`]
 //!
 //! ```
 //! # use bevy::prelude::*;
@@ -47,7 +47,7 @@
 //! ### Usage (return component directly)
 //!
 //! - Send events with [`SqlxEvent::query`] or [`SqlxEvent::call`] to query
-//! the database
+//!   the database
 //!
 //! ```
 //! # use bevy::prelude::*;
@@ -100,7 +100,7 @@
 //!
 //!
 //! - Send events with [`SqlxEvent::query_sync`] or [`SqlxEvent::call_sync`] to
-//! query the database
+//!   query the database
 //!
 //! ```
 //! # use bevy::prelude::*;
@@ -146,7 +146,7 @@
 //! ```
 //!
 //! - And/or, respond to [`SqlxEventStatus::Spawn`] and [`SqlxEventStatus::Update`]
-//! events
+//!   events
 //!
 //! ```
 //! # use bevy::prelude::*;
@@ -178,16 +178,189 @@
 //! }
 //! ```
 
+mod activity;
+pub use self::activity::*;
+
+mod apply;
+pub use self::apply::*;
+
+pub mod audit;
+pub use self::audit::*;
+
+mod autosave;
+pub use self::autosave::*;
+
+pub mod channel;
+pub use self::channel::*;
+
+mod collection;
+pub use self::collection::*;
+
 pub mod component;
 pub use self::component::*;
 
+pub mod condition;
+pub use self::condition::*;
+
+mod config_table;
+pub use self::config_table::*;
+
+mod conflict;
+pub use self::conflict::*;
+
+mod connection;
+pub use self::connection::*;
+
+mod copy;
+pub use self::copy::*;
+
+mod dead_letter;
+pub use self::dead_letter::*;
+
+mod error;
+pub use self::error::*;
+
 pub mod event;
 pub use self::event::*;
+
+mod eviction;
+pub use self::eviction::*;
+
+mod expiry;
+pub use self::expiry::*;
+
+mod explain;
+pub use self::explain::*;
+
+mod export;
+pub use self::export::*;
+
+mod group;
+pub use self::group::*;
+
+mod health;
+pub use self::health::*;
+
+mod import;
+pub use self::import::*;
+
+mod journal;
+pub use self::journal::*;
+
+mod credentials;
+pub use self::credentials::*;
+
 mod database;
 pub use self::database::*;
 
+mod key;
+pub use self::key::*;
+
+mod leaderboard;
+pub use self::leaderboard::*;
+
+mod link;
+pub use self::link::*;
+
+mod load;
+pub use self::load::*;
+
+pub mod lock;
+pub use self::lock::*;
+
+mod matching;
+pub use self::matching::*;
+
+mod migration;
+pub use self::migration::*;
+
+mod persist;
+pub use self::persist::*;
+
 mod plugin;
 pub use self::plugin::*;
 
+#[cfg(feature = "postgres")]
+mod postgres;
+
+mod prefix;
+pub use self::prefix::*;
+
+pub mod recorder;
+pub use self::recorder::*;
+
+mod registry;
+pub use self::registry::*;
+
+mod relation;
+pub use self::relation::*;
+
+pub mod resource;
+pub use self::resource::*;
+
+mod schedule;
+pub use self::schedule::*;
+
+pub mod statement;
+pub use self::statement::*;
+
+pub mod session;
+pub use self::session::*;
+
+#[cfg(feature = "asset")]
+mod settings;
+#[cfg(feature = "asset")]
+pub use self::settings::*;
+
+mod snapshot;
+pub use self::snapshot::*;
+
+mod spatial;
+pub use self::spatial::*;
+
+mod spawn;
+pub use self::spawn::*;
+
+mod split;
+pub use self::split::*;
+
+#[cfg(feature = "sqlite")]
+mod sqlite;
+#[cfg(feature = "sqlite")]
+pub use self::sqlite::*;
+
+pub mod state;
+pub use self::state::*;
+
+mod streaming;
+pub use self::streaming::*;
+
+mod subscription;
+pub use self::subscription::*;
+
+mod swap;
+pub use self::swap::*;
+
+mod task_pool;
+pub use self::task_pool::*;
+
 mod tasks;
 pub use self::tasks::*;
+
+mod telemetry;
+pub use self::telemetry::*;
+
+mod tenant;
+pub use self::tenant::*;
+
+mod throttle;
+pub use self::throttle::*;
+
+mod timeout;
+pub(crate) use self::timeout::*;
+
+mod tls;
+pub use self::tls::*;
+
+mod write_back;
+pub use self::write_back::*;
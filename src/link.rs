@@ -0,0 +1,417 @@
+use crate::*;
+use bevy::ecs::world::Command;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use sqlx::{ColumnIndex, Database, Decode, Encode, Executor};
+use sqlx::{IntoArguments, Type};
+use std::marker::PhantomData;
+
+/// A component linked to many `T`s, and the other way around, through a
+/// join table
+///
+/// ### Example
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::FromRow;
+/// # use bevy_sqlx::*;
+/// #[derive(Component, FromRow)]
+/// struct Foo { id: u32 }
+/// # impl PrimaryKey for Foo {
+/// #     type Column = u32;
+/// #     fn primary_key(&self) -> Self::Column { self.id }
+/// # }
+/// #[derive(Component, FromRow)]
+/// struct Tag { id: u32, name: String }
+/// # impl PrimaryKey for Tag {
+/// #     type Column = u32;
+/// #     fn primary_key(&self) -> Self::Column { self.id }
+/// # }
+///
+/// impl SqlxManyToMany<Tag> for Foo {
+///     fn join_table() -> &'static str {
+///         "foo_tags"
+///     }
+///
+///     fn join_columns() -> (&'static str, &'static str) {
+///         ("foo_id", "tag_id")
+///     }
+/// }
+/// ```
+pub trait SqlxManyToMany<T: PrimaryKey>: PrimaryKey {
+    /// The name of the join table
+    fn join_table() -> &'static str;
+
+    /// The names of the join table's columns referencing `Self`'s and `T`'s
+    /// primary keys
+    fn join_columns() -> (&'static str, &'static str);
+}
+
+/// A [`Component`] of the primary keys of the `T`s linked to the entity's
+/// `C`, see [`SqlxManyToMany`]
+///
+/// It's inserted and kept up to date by the [`SqlxLink`] commands:
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::{FromRow, Sqlite};
+/// # use bevy_sqlx::*;
+/// # #[derive(Component, FromRow)]
+/// # struct Foo { id: u32 }
+/// # impl PrimaryKey for Foo {
+/// #     type Column = u32;
+/// #     fn primary_key(&self) -> Self::Column { self.id }
+/// # }
+/// # #[derive(Component, FromRow)]
+/// # struct Tag { id: u32, name: String }
+/// # impl PrimaryKey for Tag {
+/// #     type Column = u32;
+/// #     fn primary_key(&self) -> Self::Column { self.id }
+/// # }
+/// # impl ToRow for Tag {
+/// #     fn table() -> &'static str { "tags" }
+/// #     fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+/// #         vec![("name", self.name.clone().into())]
+/// #     }
+/// # }
+/// # impl SqlxManyToMany<Tag> for Foo {
+/// #     fn join_table() -> &'static str { "foo_tags" }
+/// #     fn join_columns() -> (&'static str, &'static str) {
+/// #         ("foo_id", "tag_id")
+/// #     }
+/// # }
+/// fn tag(mut commands: Commands, foos: Query<Entity, Added<Foo>>) {
+///     for foo in &foos {
+///         commands.add(SqlxLinks::<Foo, Tag>::add::<Sqlite>(foo, 1));
+///     }
+/// }
+/// ```
+#[derive(Component)]
+pub struct SqlxLinks<C, T: PrimaryKey> {
+    pub keys: Vec<T::Column>,
+    _c: PhantomData<fn() -> C>,
+}
+
+impl<C, T: PrimaryKey> Default for SqlxLinks<C, T> {
+    fn default() -> Self {
+        SqlxLinks { keys: Vec::new(), _c: PhantomData }
+    }
+}
+
+impl<C: SqlxManyToMany<T>, T: PrimaryKey> SqlxLinks<C, T> {
+    /// Return true if the `T` with primary key `key` is linked
+    pub fn contains(&self, key: &T::Column) -> bool {
+        self.keys.contains(key)
+    }
+
+    /// Construct a [`Command`] syncing every `T` linked to `owner`'s `C`,
+    /// and replacing its [`SqlxLinks`] with their keys
+    pub fn load<DB>(owner: Entity) -> SqlxLink<DB, C, T> {
+        SqlxLink::new(owner, SqlxLinkOp::Load)
+    }
+
+    /// Construct a [`Command`] linking `owner`'s `C` to the `T` with
+    /// primary key `key`
+    pub fn add<DB>(owner: Entity, key: T::Column) -> SqlxLink<DB, C, T> {
+        SqlxLink::new(owner, SqlxLinkOp::Add(key))
+    }
+
+    /// Construct a [`Command`] unlinking `owner`'s `C` from the `T` with
+    /// primary key `key`
+    pub fn remove<DB>(owner: Entity, key: T::Column) -> SqlxLink<DB, C, T> {
+        SqlxLink::new(owner, SqlxLinkOp::Remove(key))
+    }
+}
+
+enum SqlxLinkOp<T: PrimaryKey> {
+    Load,
+    Add(T::Column),
+    Remove(T::Column),
+}
+
+/// A [`Command`] changing the links of an entity's `C` in the join table,
+/// and its [`SqlxLinks`], from [`SqlxLinks::load`], [`SqlxLinks::add`] or
+/// [`SqlxLinks::remove`]
+///
+/// Nothing is done if the entity has no `C`.
+pub struct SqlxLink<DB, C, T: PrimaryKey> {
+    owner: Entity,
+    op: SqlxLinkOp<T>,
+    _db: PhantomData<fn() -> DB>,
+    _c: PhantomData<fn() -> C>,
+}
+
+impl<DB, C, T: PrimaryKey> SqlxLink<DB, C, T> {
+    fn new(owner: Entity, op: SqlxLinkOp<T>) -> Self {
+        SqlxLink { owner, op, _db: PhantomData, _c: PhantomData }
+    }
+}
+
+impl<DB, C, T> Command for SqlxLink<DB, C, T>
+where
+    DB: Database + Sync,
+    C: Component + SqlxManyToMany<T>,
+    C::Column: Into<SqlxValue>,
+    T: SqlxComponent<DB::Row> + ToRow,
+    T::Column: Into<SqlxValue>,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    fn apply(self, world: &mut World) {
+        let Some(owner) = world.get::<C>(self.owner) else {
+            warn!("{} has no {}", self.owner, std::any::type_name::<C>());
+            return;
+        };
+        let pk = owner.primary_key();
+        let join = C::join_table();
+        let (owner_column, column) = C::join_columns();
+        let mut owner = world.entity_mut(self.owner);
+        if !owner.contains::<SqlxLinks<C, T>>() {
+            owner.insert(SqlxLinks::<C, T>::default());
+        }
+        let mut links = owner.get_mut::<SqlxLinks<C, T>>().unwrap();
+
+        let event = match self.op {
+            SqlxLinkOp::Load => {
+                links.keys.clear();
+                let sql = format!(
+                    "SELECT {table}.* FROM {table} JOIN {join} \
+                     ON {join}.{column} = {table}.{key} \
+                     WHERE {join}.{owner_column} = {}",
+                    placeholder::<DB>(1),
+                    table = T::table(),
+                    key = T::primary_key_name(),
+                );
+                let id = world.resource::<SqlxEventIds>().next();
+                let event =
+                    SqlxEvent::<DB, T>::query_sync(sql).bind(pk).with_id(id);
+                if let Some(mut loads) =
+                    world.get_resource_mut::<SqlxLinkLoads<C, T>>()
+                {
+                    loads.pending.insert(id, self.owner);
+                }
+                event
+            }
+            SqlxLinkOp::Add(key) => {
+                if !links.contains(&key) {
+                    links.keys.push(key.clone());
+                }
+                let row = vec![(owner_column, pk.into()), (column, key.into())];
+                SqlxEvent::statement(SqlxStatement::insert(join, row))
+            }
+            SqlxLinkOp::Remove(key) => {
+                links.keys.retain(|linked| *linked != key);
+                let stmt = SqlxStatement::delete(join)
+                    .filter(owner_column, pk)
+                    .filter(column, key);
+                SqlxEvent::statement(stmt)
+            }
+        };
+        world.send_event(event);
+    }
+}
+
+/// A [`Resource`] of the entities whose [`SqlxLinks::load`] is running
+#[derive(Resource)]
+struct SqlxLinkLoads<C, T> {
+    pending: HashMap<SqlxEventId, Entity>,
+    _c: PhantomData<fn() -> (C, T)>,
+}
+
+impl<C, T> Default for SqlxLinkLoads<C, T> {
+    fn default() -> Self {
+        SqlxLinkLoads { pending: HashMap::new(), _c: PhantomData }
+    }
+}
+
+/// A [`Plugin`] filling in the [`SqlxLinks`] loaded with
+/// [`SqlxLinks::load`], see [`SqlxManyToMany`]
+///
+/// The [`SqlxPlugin`] of `T` must be added too.
+pub struct SqlxManyToManyPlugin<DB, C, T> {
+    _db: PhantomData<DB>,
+    _c: PhantomData<C>,
+    _t: PhantomData<T>,
+}
+
+impl<DB, C, T> Default for SqlxManyToManyPlugin<DB, C, T> {
+    fn default() -> Self {
+        SqlxManyToManyPlugin {
+            _db: PhantomData,
+            _c: PhantomData,
+            _t: PhantomData,
+        }
+    }
+}
+
+impl<DB, C, T> Plugin for SqlxManyToManyPlugin<DB, C, T>
+where
+    DB: Database + Sync,
+    C: Component + SqlxManyToMany<T>,
+    T: SqlxComponent<DB::Row>,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+    for<'r> SqlxValue: Decode<'r, DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SqlxLinkLoads<C, T>>();
+        app.add_systems(
+            Update,
+            handle_links::<DB, C, T>
+                .after(SqlxRegistry::<DB>::handle_events)
+                .after(SqlxRegistry::<DB>::handle_tasks),
+        );
+    }
+}
+
+/// Add the keys of the `T`s synced by a [`SqlxLinks::load`] to the owner's
+/// [`SqlxLinks`]
+fn handle_links<DB, C, T>(
+    mut loads: ResMut<SqlxLinkLoads<C, T>>,
+    mut links: Query<&mut SqlxLinks<C, T>>,
+    tasks: Res<SqlxTasks<DB, T>>,
+    mut statuses: EventReader<SqlxEventStatus<DB, T>>,
+) where
+    DB: Database + Sync,
+    C: Component + SqlxManyToMany<T>,
+    T: SqlxComponent<DB::Row>,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    for status in statuses.read() {
+        let (id, key) = match status {
+            SqlxEventStatus::Spawn(id, key, _)
+            | SqlxEventStatus::Update(id, key, _) => (id, key),
+            _ => continue,
+        };
+        let Some(owner) = loads.pending.get(id) else {
+            continue;
+        };
+        if let Ok(mut links) = links.get_mut(*owner) {
+            if !links.contains(key) {
+                links.keys.push(key.clone());
+            }
+        }
+    }
+    loads.pending.retain(|id, _| tasks.is_pending(*id));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{block_on, AsyncComputeTaskPool, TaskPool};
+    use sqlx::{FromRow, Sqlite};
+
+    #[derive(Component, FromRow, Debug)]
+    struct Foo {
+        id: u32,
+    }
+
+    impl PrimaryKey for Foo {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    impl SqlxManyToMany<Tag> for Foo {
+        fn join_table() -> &'static str {
+            "link_foo_tags"
+        }
+
+        fn join_columns() -> (&'static str, &'static str) {
+            ("foo_id", "tag_id")
+        }
+    }
+
+    #[derive(Component, FromRow, Debug)]
+    struct Tag {
+        id: u32,
+        name: String,
+    }
+
+    impl PrimaryKey for Tag {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    impl ToRow for Tag {
+        fn table() -> &'static str {
+            "link_tags"
+        }
+
+        fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+            vec![("id", self.id.into()), ("name", self.name.clone().into())]
+        }
+    }
+
+    fn linked(app: &App) -> Vec<u32> {
+        let pool = &app.world().resource::<SqlxDatabase<Sqlite>>().pool;
+        let sql = "SELECT tag_id FROM link_foo_tags WHERE foo_id = 5 \
+                   ORDER BY tag_id";
+        block_on(sqlx::query_scalar(sql).fetch_all(pool)).unwrap()
+    }
+
+    fn keys(app: &App, foo: Entity) -> Vec<u32> {
+        let links = app.world().get::<SqlxLinks<Foo, Tag>>(foo);
+        let mut keys = links.map_or(Vec::new(), |links| links.keys.clone());
+        keys.sort();
+        keys
+    }
+
+    #[test]
+    fn test_links() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let mut app = App::new();
+        app.add_plugins(SqlxPlugin::<Sqlite, Tag>::from_url(url));
+        app.add_plugins(SqlxManyToManyPlugin::<Sqlite, Foo, Tag>::default());
+        let pool = &app.world().resource::<SqlxDatabase<Sqlite>>().pool;
+        block_on(async {
+            for sql in [
+                "CREATE TABLE IF NOT EXISTS link_tags \
+                 (id INTEGER PRIMARY KEY, name TEXT NOT NULL)",
+                "CREATE TABLE IF NOT EXISTS link_foo_tags \
+                 (foo_id INTEGER NOT NULL, tag_id INTEGER NOT NULL)",
+                "DELETE FROM link_tags",
+                "DELETE FROM link_foo_tags",
+                "INSERT INTO link_tags VALUES (1, 'a'), (2, 'b'), (3, 'c')",
+                "INSERT INTO link_foo_tags VALUES (5, 1), (5, 2), (6, 3)",
+            ] {
+                sqlx::query(sql).execute(pool).await.unwrap();
+            }
+        });
+
+        let foo = app.world_mut().spawn(Foo { id: 5 }).id();
+        app.world_mut()
+            .commands()
+            .add(SqlxLinks::<Foo, Tag>::load::<Sqlite>(foo));
+        app.world_mut().flush();
+        let mut tries = 0;
+        while keys(&app, foo).len() < 2 && tries < 1000 {
+            app.update();
+            tries += 1;
+        }
+        assert_eq!(vec![1, 2], keys(&app, foo));
+        let mut tags = app.world_mut().query::<&Tag>();
+        assert_eq!(2, tags.iter(app.world()).len());
+
+        let add = SqlxLinks::<Foo, Tag>::add::<Sqlite>(foo, 3);
+        let remove = SqlxLinks::<Foo, Tag>::remove::<Sqlite>(foo, 1);
+        app.world_mut().commands().add(add);
+        app.world_mut().commands().add(remove);
+        app.world_mut().flush();
+        assert_eq!(vec![2, 3], keys(&app, foo));
+        let mut tries = 0;
+        while linked(&app) != vec![2, 3] && tries < 1000 {
+            app.update();
+            tries += 1;
+        }
+        assert_eq!(vec![2, 3], linked(&app));
+    }
+}
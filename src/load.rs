@@ -0,0 +1,246 @@
+use crate::*;
+use bevy::prelude::*;
+use bevy::state::state::FreelyMutableState;
+use sqlx::Type;
+use sqlx::{ColumnIndex, Database, Decode, Encode, Executor, IntoArguments};
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// A [`Plugin`] running an initial synchronizing query while the app is in
+/// a loading [`States`] variant
+///
+/// Upon entering the `loading` state the query is sent with
+/// [`SqlxEvent::query_sync`]. Once every load registered for that state is
+/// finished, the app moves on to the `next` state. A load which fails still
+/// finishes, its [`SqlxEventStatus::Error`] is sent as usual.
+///
+/// Any number of loads may be added, for any number of components. See
+/// [`SqlxLoads`] for their progress.
+///
+/// ### Example
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::{FromRow, Sqlite};
+/// # use bevy_sqlx::*;
+/// # #[derive(Component, FromRow)]
+/// # struct Foo { id: u32 }
+/// # impl PrimaryKey for Foo {
+/// #     type Column = u32;
+/// #     fn primary_key(&self) -> Self::Column { self.id }
+/// # }
+/// #[derive(States, Clone, PartialEq, Eq, Hash, Debug, Default)]
+/// enum GameState {
+///     #[default]
+///     Loading,
+///     Playing,
+/// }
+///
+/// let url = "sqlite:db/sqlite.db";
+/// App::new()
+///     .add_plugins(DefaultPlugins)
+///     .add_plugins(SqlxPlugin::<Sqlite, Foo>::from_url(url))
+///     .init_state::<GameState>()
+///     .add_plugins(SqlxLoadState::<Sqlite, Foo, _>::new(
+///         "SELECT * FROM foos",
+///         GameState::Loading,
+///         GameState::Playing,
+///     ))
+///     .run();
+/// ```
+pub struct SqlxLoadState<DB: Database, C: SqlxComponent<DB::Row>, S: States> {
+    sql: Arc<str>,
+    loading: S,
+    next: S,
+    _db: PhantomData<DB>,
+    _c: PhantomData<C>,
+}
+
+impl<DB: Database, C: SqlxComponent<DB::Row>, S: States>
+    SqlxLoadState<DB, C, S>
+{
+    /// Load the rows of `sql` while in the `loading` state, then move on to
+    /// `next`
+    pub fn new(sql: &str, loading: S, next: S) -> Self {
+        SqlxLoadState {
+            sql: sql.into(),
+            loading,
+            next,
+            _db: PhantomData,
+            _c: PhantomData,
+        }
+    }
+}
+
+impl<DB, C, S> Plugin for SqlxLoadState<DB, C, S>
+where
+    DB: Database + Sync,
+    C: SqlxComponent<DB::Row>,
+    S: FreelyMutableState,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+    for<'r> SqlxValue: Decode<'r, DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    fn build(&self, app: &mut App) {
+        if !app.world().contains_resource::<SqlxLoads<S>>() {
+            app.insert_resource(SqlxLoads::<S> { loads: Vec::new() });
+            app.add_systems(Update, SqlxLoads::<S>::handle_loads);
+        }
+
+        let mut loads = app.world_mut().resource_mut::<SqlxLoads<S>>();
+        let index = loads.loads.len();
+        loads.loads.push(SqlxLoad {
+            loading: self.loading.clone(),
+            next: self.next.clone(),
+            id: None,
+            started: false,
+            finished: false,
+        });
+
+        let sql = self.sql.clone();
+        app.add_systems(
+            OnEnter(self.loading.clone()),
+            move |ids: Res<SqlxEventIds>,
+                  mut loads: ResMut<SqlxLoads<S>>,
+                  mut events: EventWriter<SqlxEvent<DB, C>>| {
+                let id = ids.next();
+                let event =
+                    SqlxEvent::<DB, C>::query_sync(sql.to_string()).with_id(id);
+                let load = &mut loads.loads[index];
+                load.id = Some(id);
+                load.started = false;
+                load.finished = false;
+                events.send(event);
+            },
+        );
+        app.add_systems(
+            Update,
+            (move |mut loads: ResMut<SqlxLoads<S>>,
+                   tasks: Res<SqlxTasks<DB, C>>,
+                   mut statuses: EventReader<SqlxEventStatus<DB, C>>| {
+                let load = &mut loads.loads[index];
+                let Some(id) = load.id else { return };
+                for status in statuses.read() {
+                    if let SqlxEventStatus::Start(started) = status {
+                        load.started |= *started == id;
+                    }
+                }
+                load.finished = load.started && !tasks.is_pending(id);
+            })
+            .before(SqlxLoads::<S>::handle_loads)
+            .after(SqlxRegistry::<DB>::handle_tasks),
+        );
+    }
+
+    fn is_unique(&self) -> bool {
+        false
+    }
+}
+
+struct SqlxLoad<S> {
+    loading: S,
+    next: S,
+    id: Option<SqlxEventId>,
+    started: bool,
+    finished: bool,
+}
+
+/// A [`Resource`] tracking every [`SqlxLoadState`] of the states `S`
+#[derive(Resource)]
+pub struct SqlxLoads<S: States> {
+    loads: Vec<SqlxLoad<S>>,
+}
+
+impl<S: FreelyMutableState> SqlxLoads<S> {
+    /// The number of loads for the `loading` state, e.g. for a progress bar
+    pub fn total(&self, loading: &S) -> usize {
+        self.loads.iter().filter(|l| &l.loading == loading).count()
+    }
+
+    /// The number of loads for the `loading` state which are finished
+    pub fn finished(&self, loading: &S) -> usize {
+        self.loads
+            .iter()
+            .filter(|l| &l.loading == loading && l.finished)
+            .count()
+    }
+
+    /// A [`System`] moving on to the next state once every load for the
+    /// current state is finished
+    pub fn handle_loads(
+        state: Res<State<S>>,
+        mut next_state: ResMut<NextState<S>>,
+        loads: Res<SqlxLoads<S>>,
+    ) {
+        let mut current =
+            loads.loads.iter().filter(|l| &l.loading == state.get());
+        if current.clone().all(|l| l.finished) {
+            if let Some(load) = current.next() {
+                next_state.set(load.next.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::state::app::StatesPlugin;
+    use bevy::tasks::{AsyncComputeTaskPool, TaskPool};
+    use sqlx::{FromRow, Sqlite};
+
+    #[derive(Component, FromRow, Debug)]
+    struct Foo {
+        id: u32,
+    }
+
+    impl PrimaryKey for Foo {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    #[derive(States, Clone, PartialEq, Eq, Hash, Debug, Default)]
+    enum GameState {
+        #[default]
+        Loading,
+        Playing,
+    }
+
+    #[test]
+    fn test_load_state() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let mut app = App::new();
+        app.add_plugins(StatesPlugin);
+        app.add_plugins(SqlxPlugin::<Sqlite, Foo>::from_url(url));
+        app.init_state::<GameState>();
+        for sql in ["SELECT * FROM foos", "SELECT * FROM foos WHERE 0"] {
+            app.add_plugins(SqlxLoadState::<Sqlite, Foo, _>::new(
+                sql,
+                GameState::Loading,
+                GameState::Playing,
+            ));
+        }
+
+        let mut tries = 0;
+        app.update();
+        while *app.world().resource::<State<GameState>>() == GameState::Loading
+            && tries < 1000
+        {
+            app.update();
+            tries += 1;
+        }
+
+        let loads = app.world().resource::<SqlxLoads<GameState>>();
+        assert_eq!(2, loads.finished(&GameState::Loading));
+        assert_eq!(
+            GameState::Playing,
+            **app.world().resource::<State<GameState>>()
+        );
+    }
+}
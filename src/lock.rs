@@ -0,0 +1,395 @@
+//! Locks shared by every app using the same database
+//!
+//! The [`SqlxLockPlugin`] lets multiple server instances coordinate exclusive
+//! ownership of shards or regions. Send [`SqlxLock::acquire`] and
+//! [`SqlxLock::release`] events with an `i64` key, and read the outcome from
+//! [`SqlxLockStatus`] events. Acquiring never waits, a lock held elsewhere
+//! sends [`SqlxLockStatus::Busy`] instead.
+//!
+//! On Postgres these are session level advisory locks, each held on its own
+//! pooled connection until released. Other databases insert a row into the
+//! [`LOCK_TABLE`], owned by the app which acquired it.
+//!
+//! | column        | value                                        |
+//! | ------------- | -------------------------------------------- |
+//! | `lock_key`    | the key of the lock                          |
+//! | `owner`       | a random 128-bit token of the owning app     |
+//! | `acquired_at` | when it was acquired, in seconds since epoch |
+//!
+//! Unlike advisory locks, these rows outlive an app which exits without
+//! releasing them.
+//!
+//! ### Example
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use bevy_sqlx::lock::*;
+//! fn claim(mut locks: EventWriter<SqlxLock>) {
+//!     locks.send(SqlxLock::acquire(3));
+//! }
+//!
+//! fn claimed(mut statuses: EventReader<SqlxLockStatus>) {
+//!     for status in statuses.read() {
+//!         if let SqlxLockStatus::Acquired(shard) = status {
+//!             println!("simulating shard {shard}");
+//!         }
+//!     }
+//! }
+//! ```
+use crate::*;
+use bevy::prelude::*;
+use bevy::tasks::futures_lite::future;
+use bevy::tasks::{block_on, AsyncComputeTaskPool, Task};
+use bevy::utils::synccell::SyncCell;
+use bevy::utils::HashMap;
+use rand::RngCore;
+use sqlx::pool::PoolConnection;
+use sqlx::{Database, Encode, Error, Executor, IntoArguments, Pool, Type};
+use std::fmt::Write;
+use std::marker::PhantomData;
+use std::time::SystemTime;
+
+/// The name of the table locks are stored in, on databases without
+/// advisory locks
+pub const LOCK_TABLE: &str = "_bevy_sqlx_locks";
+
+/// A [`Plugin`] handling [`SqlxLock`] events
+///
+/// On databases other than Postgres, the [`LOCK_TABLE`] is created when the
+/// plugin is built, if it doesn't exist yet. See the [`lock`](crate::lock)
+/// module for more information.
+pub struct SqlxLockPlugin<DB: Database> {
+    _db: PhantomData<DB>,
+}
+
+impl<DB: Database> Default for SqlxLockPlugin<DB> {
+    fn default() -> Self {
+        SqlxLockPlugin { _db: PhantomData }
+    }
+}
+
+impl<DB: Database + Sync> Plugin for SqlxLockPlugin<DB>
+where
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+{
+    fn build(&self, app: &mut App) {
+        if !is_postgres::<DB>() {
+            let pool = &app.world().resource::<SqlxDatabase<DB>>().pool;
+            block_on(create_lock_table(pool)).unwrap();
+        }
+        app.insert_resource(SqlxLocks::<DB>::default());
+        app.add_event::<SqlxLock>();
+        app.add_event::<SqlxLockStatus>();
+        app.add_systems(Update, SqlxLocks::<DB>::handle_locks);
+    }
+}
+
+/// Create the [`LOCK_TABLE`] unless it already exists
+async fn create_lock_table<DB>(pool: &Pool<DB>) -> Result<(), Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    let sql = format!(
+        "CREATE TABLE IF NOT EXISTS {LOCK_TABLE} (
+            lock_key     BIGINT      PRIMARY KEY,
+            owner        VARCHAR(32) NOT NULL,
+            acquired_at  BIGINT      NOT NULL
+        )"
+    );
+    sqlx::query(&sql).execute(pool).await.map(|_| ())
+}
+
+/// An [`Event`] acquiring or releasing a lock
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SqlxLock {
+    Acquire(i64),
+    Release(i64),
+}
+
+impl SqlxLock {
+    /// Try to acquire the lock `key`, sending [`SqlxLockStatus::Acquired`]
+    /// or [`SqlxLockStatus::Busy`]
+    pub fn acquire(key: i64) -> Self {
+        SqlxLock::Acquire(key)
+    }
+
+    /// Release the held lock `key`, sending [`SqlxLockStatus::Released`]
+    pub fn release(key: i64) -> Self {
+        SqlxLock::Release(key)
+    }
+}
+
+/// An [`Event`] sent once a [`SqlxLock`] is handled
+#[derive(Event, Debug)]
+pub enum SqlxLockStatus {
+    /// The lock is now held by this app
+    Acquired(i64),
+    /// The lock is held by another app, or connection
+    Busy(i64),
+    /// The lock is no longer held by this app
+    Released(i64),
+    Error(i64, Error),
+}
+
+/// What a lock task finished with
+enum SqlxLockOutput<DB: Database> {
+    /// The lock was acquired, on the connection holding it
+    Acquired(i64, Option<PoolConnection<DB>>),
+    Status(SqlxLockStatus),
+}
+
+/// A [`Resource`] of the locks held by this app
+#[derive(Resource)]
+pub struct SqlxLocks<DB: Database> {
+    owner: String,
+    /// Connections aren't [`Sync`], but only [`Self::handle_locks`] uses
+    /// them
+    held: HashMap<i64, Option<SyncCell<PoolConnection<DB>>>>,
+    tasks: Vec<Task<SqlxLockOutput<DB>>>,
+}
+
+impl<DB: Database> Default for SqlxLocks<DB> {
+    fn default() -> Self {
+        let mut bytes = [0; 16];
+        rand::rngs::OsRng.fill_bytes(&mut bytes);
+        let owner = bytes.iter().fold(String::new(), |mut owner, byte| {
+            write!(owner, "{byte:02x}").unwrap();
+            owner
+        });
+        SqlxLocks { owner, held: HashMap::new(), tasks: Vec::new() }
+    }
+}
+
+impl<DB: Database> SqlxLocks<DB> {
+    /// The token owning this app's rows in the [`LOCK_TABLE`]
+    pub fn owner(&self) -> &str {
+        &self.owner
+    }
+
+    /// Return true if this app holds the lock `key`
+    pub fn is_held(&self, key: i64) -> bool {
+        self.held.contains_key(&key)
+    }
+
+    /// The keys of the locks held by this app
+    pub fn held(&self) -> impl Iterator<Item = i64> + '_ {
+        self.held.keys().copied()
+    }
+}
+
+impl<DB: Database + Sync> SqlxLocks<DB>
+where
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+{
+    /// A [`System`] spawning a task for each [`SqlxLock`], and sending the
+    /// [`SqlxLockStatus`] of finished ones
+    pub fn handle_locks(
+        database: Res<SqlxDatabase<DB>>,
+        mut locks: ResMut<Self>,
+        mut events: EventReader<SqlxLock>,
+        mut status: EventWriter<SqlxLockStatus>,
+    ) {
+        for event in events.read() {
+            let pool = database.pool.clone();
+            let owner = locks.owner.clone();
+            let task = match *event {
+                SqlxLock::Acquire(key) if locks.is_held(key) => {
+                    status.send(SqlxLockStatus::Acquired(key));
+                    continue;
+                }
+                SqlxLock::Acquire(key) => {
+                    AsyncComputeTaskPool::get().spawn(async move {
+                        match acquire(key, &owner, pool).await {
+                            Ok(Some(conn)) => {
+                                SqlxLockOutput::Acquired(key, conn)
+                            }
+                            Ok(None) => SqlxLockOutput::Status(
+                                SqlxLockStatus::Busy(key),
+                            ),
+                            Err(err) => SqlxLockOutput::Status(
+                                SqlxLockStatus::Error(key, err),
+                            ),
+                        }
+                    })
+                }
+                SqlxLock::Release(key) => {
+                    let Some(conn) = locks.held.remove(&key) else {
+                        let err = "lock isn't held".into();
+                        let err = Error::Configuration(err);
+                        status.send(SqlxLockStatus::Error(key, err));
+                        continue;
+                    };
+                    let conn = conn.map(SyncCell::to_inner);
+                    AsyncComputeTaskPool::get().spawn(async move {
+                        let status =
+                            match release(key, &owner, conn, pool).await {
+                                Ok(()) => SqlxLockStatus::Released(key),
+                                Err(err) => SqlxLockStatus::Error(key, err),
+                            };
+                        SqlxLockOutput::Status(status)
+                    })
+                }
+            };
+            locks.tasks.push(task);
+        }
+
+        let mut finished = Vec::new();
+        locks.tasks.retain_mut(|task| {
+            block_on(future::poll_once(task))
+                .map(|output| finished.push(output))
+                .is_none()
+        });
+        for output in finished {
+            match output {
+                SqlxLockOutput::Acquired(key, conn) => {
+                    locks.held.insert(key, conn.map(SyncCell::new));
+                    status.send(SqlxLockStatus::Acquired(key));
+                }
+                SqlxLockOutput::Status(output) => {
+                    status.send(output);
+                }
+            }
+        }
+    }
+}
+
+/// Try to acquire the lock `key`, returning the connection holding it on
+/// Postgres, or `None` if it's held elsewhere
+async fn acquire<DB>(
+    key: i64,
+    owner: &str,
+    pool: Pool<DB>,
+) -> Result<Option<Option<PoolConnection<DB>>>, Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+{
+    if is_postgres::<DB>() {
+        let mut conn = pool.acquire().await?;
+        let acquired = sqlx::query("SELECT 1 WHERE pg_try_advisory_lock($1)")
+            .bind(SqlxValue::from(key))
+            .fetch_optional(&mut *conn)
+            .await?;
+        return Ok(acquired.map(|_| Some(conn)));
+    }
+
+    let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH);
+    let now = now.map_or(0, |now| now.as_secs() as i64);
+    let sql = format!(
+        "INSERT INTO {LOCK_TABLE} (lock_key, owner, acquired_at)
+            VALUES ({}, {}, {})
+            ON CONFLICT DO NOTHING
+            RETURNING lock_key",
+        placeholder::<DB>(1),
+        placeholder::<DB>(2),
+        placeholder::<DB>(3),
+    );
+    let acquired = sqlx::query(&sql)
+        .bind(SqlxValue::from(key))
+        .bind(SqlxValue::from(owner))
+        .bind(SqlxValue::from(now))
+        .fetch_optional(&pool)
+        .await?;
+    Ok(acquired.map(|_| None))
+}
+
+/// Release the lock `key`, held on `conn` on Postgres
+async fn release<DB>(
+    key: i64,
+    owner: &str,
+    conn: Option<PoolConnection<DB>>,
+    pool: Pool<DB>,
+) -> Result<(), Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+{
+    if let Some(mut conn) = conn {
+        sqlx::query("SELECT pg_advisory_unlock($1)")
+            .bind(SqlxValue::from(key))
+            .execute(&mut *conn)
+            .await?;
+        return Ok(());
+    }
+
+    let sql = format!(
+        "DELETE FROM {LOCK_TABLE} WHERE lock_key = {} AND owner = {}",
+        placeholder::<DB>(1),
+        placeholder::<DB>(2),
+    );
+    sqlx::query(&sql)
+        .bind(SqlxValue::from(key))
+        .bind(SqlxValue::from(owner))
+        .execute(&pool)
+        .await
+        .map(|_| ())
+}
+
+fn is_postgres<DB: Database>() -> bool {
+    DB::NAME == "PostgreSQL"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::tasks::TaskPool;
+    use sqlx::Sqlite;
+
+    fn setup_app(url: &str) -> App {
+        let mut app = App::new();
+        app.add_plugins(SqlxPlugin::<Sqlite, SqlxDummy>::from_url(url));
+        app.add_plugins(SqlxLockPlugin::<Sqlite>::default());
+        app
+    }
+
+    fn send(app: &mut App, lock: SqlxLock) -> SqlxLockStatus {
+        app.world_mut().send_event(lock);
+        let mut tries = 0;
+        loop {
+            app.update();
+            let mut events =
+                app.world_mut().resource_mut::<Events<SqlxLockStatus>>();
+            if let Some(status) = events.drain().next() {
+                return status;
+            }
+            tries += 1;
+            assert!(tries < 1000);
+        }
+    }
+
+    #[test]
+    fn test_locks() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let mut a = setup_app(url);
+        let mut b = setup_app(url);
+        let pool = a.world().resource::<SqlxDatabase<Sqlite>>().pool.clone();
+        let sql = format!("DELETE FROM {LOCK_TABLE} WHERE lock_key = 7");
+        block_on(sqlx::query(&sql).execute(&pool)).unwrap();
+
+        let status = send(&mut a, SqlxLock::acquire(7));
+        assert!(matches!(status, SqlxLockStatus::Acquired(7)));
+        assert!(a.world().resource::<SqlxLocks<Sqlite>>().is_held(7));
+        let status = send(&mut b, SqlxLock::acquire(7));
+        assert!(matches!(status, SqlxLockStatus::Busy(7)));
+        let status = send(&mut b, SqlxLock::release(7));
+        assert!(matches!(status, SqlxLockStatus::Error(7, _)));
+
+        let status = send(&mut a, SqlxLock::release(7));
+        assert!(matches!(status, SqlxLockStatus::Released(7)));
+        assert!(!a.world().resource::<SqlxLocks<Sqlite>>().is_held(7));
+        let status = send(&mut b, SqlxLock::acquire(7));
+        assert!(matches!(status, SqlxLockStatus::Acquired(7)));
+        send(&mut b, SqlxLock::release(7));
+    }
+}
@@ -0,0 +1,169 @@
+use crate::*;
+use bevy::prelude::*;
+use std::fmt;
+
+/// A strategy finding the entity a synced component belongs to, for
+/// [`SqlxDefaultApply`]
+///
+/// The default, [`SqlxPrimaryKeyMatch`], finds the spawned `C` with the same
+/// primary key. [`SqlxNaturalKeyMatch`] compares another key of the rows,
+/// and [`SqlxKeyMatch`] finds entities by their [`SqlxKey<C>`]. Implement
+/// this to match components some other way, and build the plugin
+/// [`SqlxPlugin::with_match`].
+///
+/// When no entity is found, the component is spawned.
+pub trait SqlxMatch<C: Component + PrimaryKey>: Send + Sync + 'static {
+    /// The entity `component` is synced onto, if any
+    fn find(&self, component: &C, ctx: &SqlxApplyContext<C>) -> Option<Entity>;
+}
+
+impl<C: Component + PrimaryKey> fmt::Debug for dyn SqlxMatch<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SqlxMatch").finish_non_exhaustive()
+    }
+}
+
+/// The default [`SqlxMatch`], finding the spawned component with the same
+/// primary key
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SqlxPrimaryKeyMatch;
+
+impl<C: Component + PrimaryKey> SqlxMatch<C> for SqlxPrimaryKeyMatch {
+    fn find(&self, component: &C, ctx: &SqlxApplyContext<C>) -> Option<Entity> {
+        ctx.find(&component.primary_key())
+    }
+}
+
+/// A [`SqlxMatch`] finding the spawned component with the same natural
+/// key, like a unique name, instead of the same primary key
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::{FromRow, Sqlite};
+/// # use bevy_sqlx::*;
+/// #[derive(Component, FromRow)]
+/// struct Player {
+///     id: u32,
+///     name: String,
+/// }
+/// # impl PrimaryKey for Player {
+/// #     type Column = u32;
+/// #     fn primary_key(&self) -> Self::Column { self.id }
+/// # }
+///
+/// let url = "sqlite:db/sqlite.db";
+/// let by_name = SqlxNaturalKeyMatch::new(|p: &Player| p.name.clone());
+/// SqlxPlugin::<Sqlite, Player>::from_url(url).with_match(by_name);
+/// ```
+pub struct SqlxNaturalKeyMatch<F>(F);
+
+impl<F> SqlxNaturalKeyMatch<F> {
+    /// Match components with equal keys, as returned by `key`
+    pub fn new(key: F) -> Self {
+        SqlxNaturalKeyMatch(key)
+    }
+}
+
+impl<C, F, K> SqlxMatch<C> for SqlxNaturalKeyMatch<F>
+where
+    C: Component + PrimaryKey,
+    F: Fn(&C) -> K + Send + Sync + 'static,
+    K: PartialEq,
+{
+    fn find(&self, component: &C, ctx: &SqlxApplyContext<C>) -> Option<Entity> {
+        let key = (self.0)(component);
+        ctx.spawned
+            .iter()
+            .find(|(_, spawned)| (self.0)(spawned) == key)
+            .map(|(entity, _)| entity)
+    }
+}
+
+/// A [`SqlxMatch`] finding the entity with a [`SqlxKey<C>`] of the
+/// component's primary key, or else the spawned component with it
+///
+/// Unlike the [`SqlxKeyPlugin`], which loads each key's row on its own,
+/// this fills placeholder entities with the rows of any query.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SqlxKeyMatch;
+
+impl<C: Component + PrimaryKey> SqlxMatch<C> for SqlxKeyMatch {
+    fn find(&self, component: &C, ctx: &SqlxApplyContext<C>) -> Option<Entity> {
+        let pk = component.primary_key();
+        ctx.keys
+            .iter()
+            .find(|(_, key)| key.key == pk)
+            .map(|(entity, _)| entity)
+            .or_else(|| ctx.find(&pk))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{AsyncComputeTaskPool, TaskPool};
+    use sqlx::{FromRow, Sqlite};
+
+    #[derive(Component, FromRow, Debug)]
+    struct Foo {
+        id: u32,
+        name: String,
+    }
+
+    impl PrimaryKey for Foo {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    fn sync(app: &mut App) {
+        let sql = "SELECT 1 AS id, 'one' AS name";
+        let event = SqlxEvent::<Sqlite, Foo>::query_sync(sql);
+        app.world_mut().send_event(event);
+        let mut tries = 0;
+        let mut done = false;
+        while !done && tries < 1000 {
+            app.update();
+            let events =
+                app.world().resource::<Events<SqlxEventStatus<Sqlite, Foo>>>();
+            done = events
+                .iter_current_update_events()
+                .any(|status| matches!(status, SqlxEventStatus::Complete(..)));
+            tries += 1;
+        }
+    }
+
+    #[test]
+    fn test_natural_key_match() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let by_name = SqlxNaturalKeyMatch::new(|foo: &Foo| foo.name.clone());
+        let mut app = App::new();
+        app.add_plugins(
+            SqlxPlugin::<Sqlite, Foo>::from_url(url).with_match(by_name),
+        );
+        let foo = Foo { id: 7, name: "one".into() };
+        let entity = app.world_mut().spawn(foo).id();
+
+        sync(&mut app);
+        assert_eq!(1, app.world().get::<Foo>(entity).unwrap().id);
+        assert_eq!(1, app.world_mut().query::<&Foo>().iter(app.world()).len());
+    }
+
+    #[test]
+    fn test_key_match() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let mut app = App::new();
+        app.add_plugins(
+            SqlxPlugin::<Sqlite, Foo>::from_url(url).with_match(SqlxKeyMatch),
+        );
+        let entity = app.world_mut().spawn(SqlxKey::<Foo>::new(1)).id();
+
+        sync(&mut app);
+        assert_eq!("one", app.world().get::<Foo>(entity).unwrap().name);
+        assert_eq!(1, app.world_mut().query::<&Foo>().iter(app.world()).len());
+    }
+}
@@ -0,0 +1,291 @@
+use crate::*;
+use bevy::utils::HashMap;
+use sqlx::{
+    Column, ColumnIndex, Database, Decode, Encode, Error, Executor, FromRow,
+    IntoArguments, Row, Type,
+};
+use std::any::Any;
+use std::fmt;
+use std::marker::PhantomData;
+
+/// A migrated row, as the type of its version
+type SqlxMigrated = Box<dyn Any + Send>;
+
+/// Decode a row of an old version
+type SqlxMigrationDecode<DB> =
+    fn(&<DB as Database>::Row) -> Result<SqlxMigrated, Error>;
+
+/// Migrate a row to the next version
+type SqlxMigrationStep =
+    Box<dyn Fn(SqlxMigrated) -> Result<SqlxMigrated, Error> + Send + Sync>;
+
+/// A registry of Rust functions migrating rows of `C` saved by older
+/// versions of the app, applied when they're loaded
+///
+/// Each row stores the schema version it was saved with in a version
+/// column, which [`ToRow::to_row`] should write the current version to.
+/// Rows of an older version are decoded as the type registered for that
+/// version, and migrated one version at a time up to `C`. This keeps old
+/// saves loading, where a SQL migration can't express the change.
+///
+/// Build the [`SqlxPlugin`] [`SqlxPlugin::with_migrations`] to apply them to
+/// every synced event.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::{FromRow, Sqlite};
+/// # use bevy_sqlx::*;
+/// #[derive(FromRow)]
+/// struct FooV2 {
+///     id: u32,
+///     name: String,
+/// }
+///
+/// #[derive(Component, FromRow)]
+/// struct Foo {
+///     id: u32,
+///     first_name: String,
+///     last_name: String,
+/// }
+/// # impl PrimaryKey for Foo {
+/// #     type Column = u32;
+/// #     fn primary_key(&self) -> Self::Column { self.id }
+/// # }
+///
+/// fn migrate_v2_to_v3(old: FooV2) -> Foo {
+///     let (first, last) = old.name.split_once(' ').unwrap_or((&old.name, ""));
+///     Foo { id: old.id, first_name: first.into(), last_name: last.into() }
+/// }
+///
+/// let migrations = SqlxMigrations::<Sqlite, Foo>::new("schema_version", 3)
+///     .with_migration(2, migrate_v2_to_v3);
+/// SqlxPlugin::<Sqlite, Foo>::from_url("sqlite:db/sqlite.db")
+///     .with_migrations(migrations);
+/// ```
+pub struct SqlxMigrations<DB: Database, C> {
+    column: &'static str,
+    version: u32,
+    decodes: HashMap<u32, SqlxMigrationDecode<DB>>,
+    steps: HashMap<u32, SqlxMigrationStep>,
+    _c: PhantomData<fn() -> C>,
+}
+
+impl<DB: Database, C> fmt::Debug for SqlxMigrations<DB, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut versions: Vec<_> = self.steps.keys().collect();
+        versions.sort();
+        f.debug_struct("SqlxMigrations")
+            .field("column", &self.column)
+            .field("version", &self.version)
+            .field("migrations", &versions)
+            .finish()
+    }
+}
+
+impl<DB: Database, C: SqlxComponent<DB::Row>> SqlxMigrations<DB, C> {
+    /// Read rows' versions from `column`, migrating them up to `version`
+    pub fn new(column: &'static str, version: u32) -> Self {
+        SqlxMigrations {
+            column,
+            version,
+            decodes: HashMap::new(),
+            steps: HashMap::new(),
+            _c: PhantomData,
+        }
+    }
+
+    /// The column rows store their version in
+    pub fn column(&self) -> &'static str {
+        self.column
+    }
+
+    /// The current version, decoded as `C`
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Decode rows of version `from` as `Old`, and migrate them to version
+    /// `from + 1` with `migrate`
+    ///
+    /// `New` is `C` for the current version, or the `Old` type of the
+    /// migration from the next version.
+    pub fn with_migration<Old, New>(
+        mut self,
+        from: u32,
+        migrate: impl Fn(Old) -> New + Send + Sync + 'static,
+    ) -> Self
+    where
+        Old: for<'r> FromRow<'r, DB::Row> + Send + 'static,
+        New: Send + 'static,
+    {
+        self.decodes.insert(from, |row| {
+            Ok(Box::new(Old::from_row(row)?) as SqlxMigrated)
+        });
+        self.steps.insert(
+            from,
+            Box::new(move |old: SqlxMigrated| {
+                let old = old.downcast::<Old>().map_err(|_| {
+                    let err = format!("migration from version {from}");
+                    Error::Decode(format!("unexpected type in {err}").into())
+                })?;
+                Ok(Box::new(migrate(*old)) as SqlxMigrated)
+            }),
+        );
+        self
+    }
+
+    /// Decode `row` as `C`, migrating it from its version
+    pub fn decode(&self, row: &DB::Row) -> Result<C, Error>
+    where
+        for<'r> SqlxValue: Decode<'r, DB> + Type<DB>,
+        usize: ColumnIndex<DB::Row>,
+    {
+        let index = row
+            .columns()
+            .iter()
+            .position(|column| column.name() == self.column)
+            .ok_or_else(|| Error::ColumnNotFound(self.column.into()))?;
+        let version = match row.try_get::<SqlxValue, _>(index)? {
+            SqlxValue::Int(version) => version as u32,
+            value => {
+                let err = format!("{value:?} isn't a version");
+                return Err(Error::Decode(err.into()));
+            }
+        };
+        if version == self.version {
+            return C::from_row(row);
+        }
+        if version > self.version {
+            let err = format!(
+                "version {version} is newer than the current {}",
+                self.version
+            );
+            return Err(Error::Decode(err.into()));
+        }
+
+        let missing = |version| {
+            let err = format!("no migration from version {version}");
+            Error::Decode(err.into())
+        };
+        let decode =
+            self.decodes.get(&version).ok_or_else(|| missing(version))?;
+        let mut migrated = decode(row)?;
+        for from in version..self.version {
+            let step = self.steps.get(&from).ok_or_else(|| missing(from))?;
+            migrated = step(migrated)?;
+        }
+        migrated.downcast::<C>().map(|c| *c).map_err(|_| {
+            let err =
+                format!("migration to version {} isn't a C", self.version);
+            Error::Decode(err.into())
+        })
+    }
+}
+
+/// Fetch the rows of `sql`, decoding them with `migrations`
+pub(crate) async fn fetch_migrated<DB, C>(
+    sql: &str,
+    binds: Vec<SqlxValue>,
+    migrations: &SqlxMigrations<DB, C>,
+    conn: &mut DB::Connection,
+) -> Result<Vec<C>, Error>
+where
+    DB: Database,
+    C: SqlxComponent<DB::Row>,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+    for<'r> SqlxValue: Decode<'r, DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    let mut query = sqlx::query(sql);
+    for value in binds {
+        query = query.bind(value);
+    }
+    let rows = query.fetch_all(conn).await?;
+    rows.iter().map(|row| migrations.decode(row)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{AsyncComputeTaskPool, TaskPool};
+    use sqlx::{FromRow, Sqlite};
+
+    #[derive(FromRow)]
+    struct FooV1 {
+        id: u32,
+        text: String,
+    }
+
+    #[derive(FromRow)]
+    struct FooV2 {
+        id: u32,
+        words: String,
+    }
+
+    #[derive(Component, FromRow, Debug)]
+    struct Foo {
+        id: u32,
+        first: String,
+        rest: String,
+    }
+
+    impl PrimaryKey for Foo {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    fn migrate_v2_to_v3(old: FooV2) -> Foo {
+        let (first, rest) =
+            old.words.split_once(' ').unwrap_or((&old.words, ""));
+        Foo { id: old.id, first: first.into(), rest: rest.into() }
+    }
+
+    #[test]
+    fn test_migrations() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let migrations = SqlxMigrations::<Sqlite, Foo>::new("version", 3)
+            .with_migration(1, |old: FooV1| FooV2 {
+                id: old.id,
+                words: old.text.to_uppercase(),
+            })
+            .with_migration(2, migrate_v2_to_v3);
+        let url = "sqlite:db/sqlite.db";
+        let mut app = App::new();
+        app.add_plugins(
+            SqlxPlugin::<Sqlite, Foo>::from_url(url)
+                .with_migrations(migrations),
+        );
+
+        let sql = "SELECT 1 AS id, 1 AS version, 'old save' AS text, \
+                   NULL AS words, NULL AS first, NULL AS rest \
+                   UNION ALL SELECT 2, 2, NULL, 'newer save', NULL, NULL \
+                   UNION ALL SELECT 3, 3, NULL, NULL, 'current', 'save'";
+        let event = SqlxEvent::<Sqlite, Foo>::query_sync(sql);
+        app.world_mut().send_event(event);
+        let mut foos = app.world_mut().query::<&Foo>();
+        let mut tries = 0;
+        while foos.iter(app.world()).len() < 3 && tries < 1000 {
+            app.update();
+            tries += 1;
+        }
+
+        let mut foos: Vec<_> = foos
+            .iter(app.world())
+            .map(|foo| (foo.id, foo.first.as_str(), foo.rest.as_str()))
+            .collect();
+        foos.sort();
+        assert_eq!(
+            vec![
+                (1, "OLD", "SAVE"),
+                (2, "newer", "save"),
+                (3, "current", "save")
+            ],
+            foos
+        );
+    }
+}
@@ -0,0 +1,406 @@
+use crate::*;
+use bevy::ecs::query::QueryFilter;
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+use sqlx::{Database, Encode, Executor, IntoArguments, Type};
+use std::marker::PhantomData;
+
+/// A [`Plugin`] registering `C` for the [`SqlxEntityCommandsExt`] verbs of
+/// `DB`
+///
+/// Add it once per component type which should be persisted, next to the
+/// component's [`SqlxPlugin`].
+///
+/// ### Example
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::{FromRow, Sqlite};
+/// # use bevy_sqlx::*;
+/// # #[derive(Component, FromRow)]
+/// # struct Foo { id: u32, text: String }
+/// # impl PrimaryKey for Foo {
+/// #     type Column = u32;
+/// #     fn primary_key(&self) -> Self::Column { self.id }
+/// # }
+/// # impl ToRow for Foo {
+/// #     fn table() -> &'static str { "foos" }
+/// #     fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+/// #         vec![("id", self.id.into()), ("text", self.text.clone().into())]
+/// #     }
+/// # }
+/// let url = "sqlite:db/sqlite.db";
+/// App::new()
+///     .add_plugins(SqlxPlugin::<Sqlite, Foo>::from_url(url))
+///     .add_plugins(SqlxPersistPlugin::<Sqlite, Foo>::default());
+///
+/// fn save(mut commands: Commands, foos: Query<Entity, With<Foo>>) {
+///     for entity in &foos {
+///         commands.entity(entity).persist::<Sqlite>();
+///     }
+/// }
+/// ```
+pub struct SqlxPersistPlugin<DB, C> {
+    _db: PhantomData<DB>,
+    _c: PhantomData<C>,
+}
+
+impl<DB, C> Default for SqlxPersistPlugin<DB, C> {
+    fn default() -> Self {
+        SqlxPersistPlugin { _db: PhantomData, _c: PhantomData }
+    }
+}
+
+impl<DB, C> Plugin for SqlxPersistPlugin<DB, C>
+where
+    DB: Database + Sync,
+    C: SqlxComponent<DB::Row> + ToRow,
+    C::Column: Into<SqlxValue>,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SqlxPersistence<DB>>();
+        let mut persistence =
+            app.world_mut().resource_mut::<SqlxPersistence<DB>>();
+        persistence.components.push(SqlxPersistVerbs {
+            persist: |world, entity| {
+                if let Some(component) = world.get::<C>(entity) {
+                    let event = SqlxEvent::<DB, C>::upsert(component);
+                    world.send_event(event);
+                }
+            },
+            refresh: |world, entity| {
+                if let Some(component) = world.get::<C>(entity) {
+                    let pk = component.primary_key();
+                    let event = SqlxEvent::<DB, C>::load_into(entity, pk);
+                    world.send_event(event);
+                }
+            },
+            delete_row: |world, entity| {
+                if let Some(component) = world.get::<C>(entity) {
+                    let event = SqlxEvent::<DB, C>::delete(component);
+                    world.send_event(event);
+                }
+            },
+        });
+    }
+}
+
+/// A [`Resource`] of the component types registered with a
+/// [`SqlxPersistPlugin`] for `DB`
+#[derive(Resource)]
+pub struct SqlxPersistence<DB> {
+    components: Vec<SqlxPersistVerbs>,
+    _db: PhantomData<fn() -> DB>,
+}
+
+impl<DB> Default for SqlxPersistence<DB> {
+    fn default() -> Self {
+        SqlxPersistence { components: Vec::new(), _db: PhantomData }
+    }
+}
+
+/// Send one component type's event for an entity, if it has the component
+type SqlxPersistVerb = fn(&mut World, Entity);
+
+struct SqlxPersistVerbs {
+    persist: SqlxPersistVerb,
+    refresh: SqlxPersistVerb,
+    delete_row: SqlxPersistVerb,
+}
+
+impl<DB: Database + Sync> SqlxPersistence<DB> {
+    /// Run `verb` of every registered component type on `entity`
+    fn run(
+        world: &mut World,
+        entity: Entity,
+        verb: fn(&SqlxPersistVerbs) -> SqlxPersistVerb,
+    ) {
+        let Some(persistence) = world.get_resource::<Self>() else {
+            warn!("no SqlxPersistPlugin for {}", DB::NAME);
+            return;
+        };
+        if world.get::<SqlxSkipSync>(entity).is_some() {
+            return;
+        }
+        let verbs: Vec<_> = persistence.components.iter().map(verb).collect();
+        for verb in verbs {
+            verb(world, entity);
+        }
+    }
+}
+
+/// Persistence verbs for [`EntityCommands`], sending the generated event of
+/// each of the entity's components registered with a [`SqlxPersistPlugin`]
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::Sqlite;
+/// # use bevy_sqlx::SqlxEntityCommandsExt;
+/// fn reload(mut commands: Commands, player: Query<Entity, With<Name>>) {
+///     commands.entity(player.single()).refresh::<Sqlite>();
+/// }
+/// ```
+pub trait SqlxEntityCommandsExt {
+    /// Upsert the entity's components, see [`SqlxEvent::upsert`]
+    fn persist<DB: Database + Sync>(&mut self) -> &mut Self;
+
+    /// Select the entity's components again by primary key, and insert them
+    /// over the current ones, see [`SqlxEvent::load_into`]
+    fn refresh<DB: Database + Sync>(&mut self) -> &mut Self;
+
+    /// Delete the rows of the entity's components, see
+    /// [`SqlxEvent::delete`]
+    ///
+    /// The entity and its components are left as they are.
+    fn delete_row<DB: Database + Sync>(&mut self) -> &mut Self;
+}
+
+impl SqlxEntityCommandsExt for EntityCommands<'_> {
+    fn persist<DB: Database + Sync>(&mut self) -> &mut Self {
+        self.add(|entity, world: &mut World| {
+            SqlxPersistence::<DB>::run(world, entity, |verbs| verbs.persist);
+        })
+    }
+
+    fn refresh<DB: Database + Sync>(&mut self) -> &mut Self {
+        self.add(|entity, world: &mut World| {
+            SqlxPersistence::<DB>::run(world, entity, |verbs| verbs.refresh);
+        })
+    }
+
+    fn delete_row<DB: Database + Sync>(&mut self) -> &mut Self {
+        self.add(|entity, world: &mut World| {
+            let verb = |verbs: &SqlxPersistVerbs| verbs.delete_row;
+            SqlxPersistence::<DB>::run(world, entity, verb);
+        })
+    }
+}
+
+/// Batched persistence for [`Commands`] and the [`World`], writing the
+/// components of every entity matching a query filter at once
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::{FromRow, Sqlite};
+/// # use bevy_sqlx::*;
+/// # #[derive(Component, FromRow)]
+/// # struct Foo { id: u32, text: String }
+/// # impl PrimaryKey for Foo {
+/// #     type Column = u32;
+/// #     fn primary_key(&self) -> Self::Column { self.id }
+/// # }
+/// # impl ToRow for Foo {
+/// #     fn table() -> &'static str { "foos" }
+/// #     fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+/// #         vec![("id", self.id.into()), ("text", self.text.clone().into())]
+/// #     }
+/// # }
+/// #[derive(Component)]
+/// struct Dirty;
+///
+/// fn save(mut commands: Commands) {
+///     commands.persist_matching::<Sqlite, Foo, With<Dirty>>();
+/// }
+/// ```
+pub trait SqlxPersistMatchingExt {
+    /// Upsert the `C` of every entity matching `F` in a single event, see
+    /// [`SqlxEvent::upsert_many`]
+    fn persist_matching<DB, C, F>(&mut self) -> &mut Self
+    where
+        DB: Database + Sync,
+        C: SqlxComponent<DB::Row> + ToRow,
+        F: QueryFilter + 'static,
+        for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+        for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+        for<'q> SqlxValue: Encode<'q, DB> + Type<DB>;
+}
+
+impl SqlxPersistMatchingExt for World {
+    fn persist_matching<DB, C, F>(&mut self) -> &mut Self
+    where
+        DB: Database + Sync,
+        C: SqlxComponent<DB::Row> + ToRow,
+        F: QueryFilter + 'static,
+        for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+        for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+        for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+    {
+        let mut query = self.query_filtered::<&C, (F, Without<SqlxSkipSync>)>();
+        let rows: SqlxCopyRows = query.iter(self).map(C::to_row).collect();
+        self.send_event(SqlxEvent::<DB, C>::upsert_rows(rows));
+        self
+    }
+}
+
+impl SqlxPersistMatchingExt for Commands<'_, '_> {
+    fn persist_matching<DB, C, F>(&mut self) -> &mut Self
+    where
+        DB: Database + Sync,
+        C: SqlxComponent<DB::Row> + ToRow,
+        F: QueryFilter + 'static,
+        for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+        for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+        for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+    {
+        self.add(|world: &mut World| {
+            world.persist_matching::<DB, C, F>();
+        });
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{block_on, AsyncComputeTaskPool, TaskPool};
+    use sqlx::{FromRow, Pool, Sqlite};
+
+    #[derive(Component, FromRow, Debug)]
+    struct Bar {
+        id: u32,
+        text: String,
+    }
+
+    impl PrimaryKey for Bar {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    impl ToRow for Bar {
+        fn table() -> &'static str {
+            "persists_matching"
+        }
+
+        fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+            vec![("id", self.id.into()), ("text", self.text.clone().into())]
+        }
+    }
+
+    #[derive(Component, FromRow, Debug)]
+    struct Foo {
+        id: u32,
+        text: String,
+    }
+
+    impl PrimaryKey for Foo {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    impl ToRow for Foo {
+        fn table() -> &'static str {
+            "persists"
+        }
+
+        fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+            vec![("id", self.id.into()), ("text", self.text.clone().into())]
+        }
+    }
+
+    fn text(pool: &Pool<Sqlite>) -> Option<String> {
+        let sql = "SELECT text FROM persists WHERE id = 1";
+        block_on(sqlx::query_scalar(sql).fetch_optional(pool)).unwrap()
+    }
+
+    fn update_until(app: &mut App, mut done: impl FnMut(&mut App) -> bool) {
+        let mut tries = 0;
+        while !done(app) && tries < 1000 {
+            app.update();
+            tries += 1;
+        }
+    }
+
+    #[test]
+    fn test_persist() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let pool: Pool<Sqlite> = block_on(Pool::connect(url)).unwrap();
+        block_on(async {
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS persists (id INTEGER PRIMARY KEY, \
+                 text TEXT NOT NULL)",
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+            sqlx::query("DELETE FROM persists").execute(&pool).await.unwrap();
+        });
+
+        let mut app = App::new();
+        app.add_plugins(SqlxPlugin::<Sqlite, Foo>::from_url(url));
+        app.add_plugins(SqlxPersistPlugin::<Sqlite, Foo>::default());
+        let foo = Foo { id: 1, text: "persisted".into() };
+        let entity = app.world_mut().spawn(foo).id();
+        app.world_mut().commands().entity(entity).persist::<Sqlite>();
+        app.world_mut().flush();
+        update_until(&mut app, |_| text(&pool).is_some());
+        assert_eq!(Some("persisted".into()), text(&pool));
+
+        let sql = "UPDATE persists SET text = 'refreshed' WHERE id = 1";
+        block_on(sqlx::query(sql).execute(&pool)).unwrap();
+        app.world_mut().commands().entity(entity).refresh::<Sqlite>();
+        app.world_mut().flush();
+        update_until(&mut app, |app| {
+            app.world().get::<Foo>(entity).unwrap().text == "refreshed"
+        });
+        assert_eq!("refreshed", app.world().get::<Foo>(entity).unwrap().text);
+
+        app.world_mut().commands().entity(entity).delete_row::<Sqlite>();
+        app.world_mut().flush();
+        update_until(&mut app, |_| text(&pool).is_none());
+        assert_eq!(None, text(&pool));
+        assert!(app.world().get::<Foo>(entity).is_some());
+    }
+
+    #[derive(Component)]
+    struct Dirty;
+
+    #[test]
+    fn test_persist_matching() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let pool: Pool<Sqlite> = block_on(Pool::connect(url)).unwrap();
+        block_on(async {
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS persists_matching \
+                 (id INTEGER PRIMARY KEY, text TEXT NOT NULL)",
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+            sqlx::query("DELETE FROM persists_matching")
+                .execute(&pool)
+                .await
+                .unwrap();
+        });
+
+        let mut app = App::new();
+        app.add_plugins(SqlxPlugin::<Sqlite, Bar>::from_url(url));
+        for id in 1..=3 {
+            let bar = Bar { id, text: format!("bar {id}") };
+            if id == 2 {
+                app.world_mut().spawn(bar);
+            } else {
+                app.world_mut().spawn((bar, Dirty));
+            }
+        }
+        app.world_mut()
+            .commands()
+            .persist_matching::<Sqlite, Bar, With<Dirty>>();
+        app.world_mut().flush();
+
+        let ids = || -> Vec<u32> {
+            let sql = "SELECT id FROM persists_matching ORDER BY id";
+            block_on(sqlx::query_scalar(sql).fetch_all(&pool)).unwrap()
+        };
+        update_until(&mut app, |_| !ids().is_empty());
+        assert_eq!(vec![1, 3], ids());
+    }
+}
@@ -1,23 +1,74 @@
 use crate::*;
 use bevy::prelude::*;
 use bevy::tasks::block_on;
-use sqlx::{Database, Executor, IntoArguments, Pool};
+use bevy::utils::Duration;
+use sqlx::{
+    ColumnIndex, Connection, Database, Decode, Encode, Executor, IntoArguments,
+    Pool, Type,
+};
 use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 /// A [`Plugin`](bevy::prelude::Plugin) to add to an
 /// [`App`](bevy::prelude::App)
 ///
 /// This plugin sets up and manages the following:
 /// - A [`SqlxDatabase<DB>`] resource
+/// - A [`SqlxConfig<DB, C>`] resource
 /// - A [`SqlxTasks<DB::Row, C>`] resource
+/// - A [`SqlxEventRegistry<DB, C>`] resource, and its
+///   [`SqlxEventRegistry<DB, C>::handle_registry`] system, registered in the
+///   [`SqlxRegistry<DB>`]
 /// - [`SqlxEvent<DB, C>`] events
-/// - A [`SqlxEvent<DB, C>::handle_events`] system
-/// - A [`SqlxTasks<DB, C>::handle_tasks`] system
+/// - A [`SqlxActivity`] resource, shared by every plugin, and its reflected
+///   types
+/// - A [`SqlxRegistry<DB>`] resource, shared by every plugin using `DB`,
+///   with its [`SqlxRegistry<DB>::handle_events`] and
+///   [`SqlxRegistry<DB>::handle_tasks`] systems
+/// - A [`SqlxConnectionState<DB>`] resource, shared by every plugin using
+///   `DB`, with [`SqlxConnected<DB>`] and [`SqlxDisconnected<DB>`] events and
+///   its [`SqlxConnectionState<DB>::handle_connection`] system
+/// - A [`SqlxSwap<DB>`] resource, shared by every plugin using `DB`, with
+///   [`SqlxSwapDatabase<DB>`] and [`SqlxSwapStatus<DB>`] events and its
+///   [`SqlxSwap<DB>::handle_swap`] system
+/// - A [`SqlxSnapshots<DB>`] resource, shared by every plugin using `DB`,
+///   with [`SqlxSnapshotLoaded<DB>`] events and its
+///   [`SqlxSnapshots<DB>::handle_snapshots`] system
+/// - A [`SqlxSchedules<DB, C>`] resource, with the schedules of
+///   [`Self::with_schedule`]
+/// - [`SqlxSubscription<DB, C>::handle_subscriptions`],
+///   [`SqlxSchedules<DB, C>::handle_schedules`],
+///   [`SqlxEvent<DB, C>::handle_events`], [`SqlxTasks<DB, C>::handle_tasks`],
+///   [`SqlxTasks<DB, C>::handle_reconnect`] and
+///   [`SqlxTasks<DB, C>::flush_on_exit`] systems, registered in the
+///   [`SqlxRegistry<DB>`]
+/// - With [`Self::with_eviction`], a [`SqlxEviction<C>`] resource,
+///   [`SqlxEvicted<C>`] events and a [`SqlxEviction<C>::handle_eviction`]
+///   system
+/// - With [`Self::with_sync_budget`], synced components are spread over
+///   multiple frames
+/// - With [`Self::with_frame_budget`], finished tasks are spread over
+///   multiple frames
+/// - With [`Self::with_explain`] in debug builds, a [`SqlxQueryLog<DB>`]
+///   resource, shared by every plugin using `DB`, and its
+///   [`SqlxQueryLog<DB>::handle_explain`] system
+/// - With [`Self::from_credentials`], a [`SqlxCredentials<DB>`] resource and
+///   its [`SqlxCredentials<DB>::handle_refresh`] system
+/// - With [`Self::with_error_handler`], a [`SqlxErrorHandler`] resource
+/// - With [`Self::with_dead_letters`], a [`SqlxDeadLetters<DB, C>`] resource
+///   and a [`SqlxDeadLetters<DB, C>::handle_retries`] system
+/// - With [`Self::with_journal`], a [`SqlxJournal<DB, C>`] resource,
+///   [`SqlxJournalStatus<DB, C>`] events and a
+///   [`SqlxJournal<DB, C>::handle_journal`] system
 //
 // TODO: test multiple of these at once
 pub struct SqlxPlugin<DB: Database, C: SqlxComponent<DB::Row>> {
-    pool: Pool<DB>,
-    _c: PhantomData<C>,
+    pub(crate) pool: Pool<DB>,
+    config: SqlxConfig<DB, C>,
+    error_handler: Option<SqlxErrorHandler>,
+    credentials: Option<SqlxCredentialsProvider>,
+    schedules: Vec<(&'static str, SqlxSchedule<DB, C>)>,
 }
 
 impl<DB: Database, C: SqlxComponent<DB::Row>> SqlxPlugin<DB, C> {
@@ -35,7 +86,13 @@ impl<DB: Database, C: SqlxComponent<DB::Row>> SqlxPlugin<DB, C> {
     /// SqlxPlugin::<Sqlite, SqlxDummy>::from_pool(pool);
     /// ```
     pub fn from_pool(pool: Pool<DB>) -> Self {
-        SqlxPlugin { pool, _c: PhantomData }
+        SqlxPlugin {
+            pool,
+            config: SqlxConfig::default(),
+            error_handler: None,
+            credentials: None,
+            schedules: Vec::new(),
+        }
     }
 
     /// Build a plugin with a new connection from the given `url`
@@ -48,7 +105,830 @@ impl<DB: Database, C: SqlxComponent<DB::Row>> SqlxPlugin<DB, C> {
     /// ```
     pub fn from_url(url: &str) -> Self {
         let pool = block_on(async { Pool::connect(url).await.unwrap() });
-        SqlxPlugin { pool, _c: PhantomData }
+        Self::from_pool(pool)
+    }
+
+    /// Build a plugin with a new connection using `options`
+    ///
+    /// The driver's connect options express settings URLs can't, like
+    /// `PgConnectOptions::socket` for unix socket connections,
+    /// `PgConnectOptions::application_name`, or SQLite pragmas.
+    ///
+    /// ```
+    /// use sqlx::Sqlite;
+    /// use sqlx::sqlite::SqliteConnectOptions;
+    /// use bevy_sqlx::{SqlxPlugin, SqlxDummy};
+    ///
+    /// let options = SqliteConnectOptions::new()
+    ///     .filename("db/sqlite.db")
+    ///     .pragma("cache_size", "-4096");
+    /// SqlxPlugin::<Sqlite, SqlxDummy>::from_options(options);
+    /// ```
+    pub fn from_options(
+        options: <DB::Connection as Connection>::Options,
+    ) -> Self {
+        let pool =
+            block_on(async { Pool::connect_with(options).await.unwrap() });
+        Self::from_pool(pool)
+    }
+
+    /// Build a plugin with a new connection from the URL read from
+    /// `credentials`
+    ///
+    /// The URL is read again before reconnecting, and every
+    /// [`Self::with_credentials_refresh`] interval. See
+    /// [`SqlxCredentialsProvider`] for more information.
+    ///
+    /// ```
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxPlugin, SqlxDummy, SqlxCredentialsProvider};
+    ///
+    /// let credentials = SqlxCredentialsProvider::callback(|| {
+    ///     Ok("sqlite:db/sqlite.db".into())
+    /// });
+    /// SqlxPlugin::<Sqlite, SqlxDummy>::from_credentials(credentials);
+    /// ```
+    pub fn from_credentials(credentials: SqlxCredentialsProvider) -> Self {
+        let options = credentials.options::<DB>().unwrap();
+        SqlxPlugin {
+            credentials: Some(credentials),
+            ..Self::from_options(options)
+        }
+    }
+
+    /// Read the URL from the plugin's [`SqlxCredentialsProvider`] again
+    /// every `interval`, so new connections use rotated credentials
+    ///
+    /// This only has an effect on plugins built [`Self::from_credentials`].
+    ///
+    /// ```
+    /// use bevy::utils::Duration;
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxPlugin, SqlxDummy, SqlxCredentialsProvider};
+    ///
+    /// # std::env::set_var("DATABASE_URL", "sqlite:db/sqlite.db");
+    /// let credentials = SqlxCredentialsProvider::env("DATABASE_URL");
+    /// SqlxPlugin::<Sqlite, SqlxDummy>::from_credentials(credentials)
+    ///     .with_credentials_refresh(Duration::from_secs(600));
+    /// ```
+    pub fn with_credentials_refresh(mut self, interval: Duration) -> Self {
+        self.config.credentials_refresh = Some(interval);
+        self
+    }
+
+    /// Scope generated statements to the [`TenantId`] resource
+    ///
+    /// Selects, updates and deletes are filtered on `column`, and inserts
+    /// write the tenant to it. Sending a generated statement without a
+    /// [`TenantId`] resource results in a [`SqlxEventStatus::Error`].
+    ///
+    /// ```
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxPlugin, SqlxDummy};
+    ///
+    /// SqlxPlugin::<Sqlite, SqlxDummy>::from_url("sqlite:db/sqlite.db")
+    ///     .with_tenant("tenant_id");
+    /// ```
+    pub fn with_tenant(mut self, column: &'static str) -> Self {
+        self.config.tenant_column = Some(column);
+        self
+    }
+
+    /// Prefix the tables of generated statements, e.g. with `dev_`
+    ///
+    /// This overrides the [`SqlxTablePrefix`] resource, and applies to
+    /// imports and [`SqlxEvent::copy_in`] too. Raw SQL is never rewritten.
+    ///
+    /// ```
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxPlugin, SqlxDummy};
+    ///
+    /// SqlxPlugin::<Sqlite, SqlxDummy>::from_url("sqlite:db/sqlite.db")
+    ///     .with_table_prefix("dev_");
+    /// ```
+    pub fn with_table_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.config.table_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Have the database give up on statements which run longer than
+    /// `timeout`
+    ///
+    /// The timeout is set on the connection each event runs on, as
+    /// `statement_timeout` on Postgres and `busy_timeout` on SQLite, and
+    /// restored afterwards. It applies to queries and generated statements,
+    /// not to calls, imports, exports or backups, and can be overridden with
+    /// [`SqlxEvent::with_statement_timeout`].
+    ///
+    /// ```
+    /// use bevy::utils::Duration;
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxPlugin, SqlxDummy};
+    ///
+    /// SqlxPlugin::<Sqlite, SqlxDummy>::from_url("sqlite:db/sqlite.db")
+    ///     .with_statement_timeout(Duration::from_secs(2));
+    /// ```
+    pub fn with_statement_timeout(mut self, timeout: Duration) -> Self {
+        self.config.statement_timeout = Some(timeout);
+        self
+    }
+
+    /// Qualify the tables of generated statements with `schema`, e.g.
+    /// `staging.foos`
+    ///
+    /// This lets several games or environments share one Postgres database
+    /// with a schema each, and applies to imports and [`SqlxEvent::copy_in`]
+    /// too. Raw SQL is never rewritten, see `with_search_path` for
+    /// Postgres. On SQLite, the schema can be an attached database.
+    ///
+    /// ```
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxPlugin, SqlxDummy};
+    ///
+    /// SqlxPlugin::<Sqlite, SqlxDummy>::from_url("sqlite:db/sqlite.db")
+    ///     .with_schema("main");
+    /// ```
+    pub fn with_schema(mut self, schema: impl Into<String>) -> Self {
+        self.config.schema = Some(schema.into());
+        self
+    }
+
+    /// Log every generated write to the [`AUDIT_TABLE`]
+    ///
+    /// The table is created when the plugin is built, if it doesn't exist
+    /// yet. If it can't be created, a warning is logged and writes aren't
+    /// audited. See the [`audit`](crate::audit) module for more information.
+    ///
+    /// ```
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxPlugin, SqlxDummy};
+    ///
+    /// SqlxPlugin::<Sqlite, SqlxDummy>::from_url("sqlite:db/sqlite.db")
+    ///     .with_audit();
+    /// ```
+    pub fn with_audit(mut self) -> Self {
+        self.config.audit = true;
+        self
+    }
+
+    /// Dispatch at most `per_second` events each second, after an initial
+    /// `burst`
+    ///
+    /// See [`SqlxRateLimit`] for more information.
+    ///
+    /// ```
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxPlugin, SqlxDummy};
+    ///
+    /// SqlxPlugin::<Sqlite, SqlxDummy>::from_url("sqlite:db/sqlite.db")
+    ///     .with_rate_limit(10., 20);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `per_second` isn't positive, or `burst` is 0, see
+    /// [`SqlxRateLimit::new`].
+    pub fn with_rate_limit(mut self, per_second: f32, burst: u32) -> Self {
+        self.config.rate_limit = Some(SqlxRateLimit::new(per_second, burst));
+        self
+    }
+
+    /// Wait at most `timeout` for pending events to finish when the app
+    /// exits, instead of the default 5 seconds
+    ///
+    /// See [`SqlxTasks::flush_on_exit`] for more information.
+    ///
+    /// ```
+    /// use bevy::utils::Duration;
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxPlugin, SqlxDummy};
+    ///
+    /// SqlxPlugin::<Sqlite, SqlxDummy>::from_url("sqlite:db/sqlite.db")
+    ///     .with_flush_timeout(Duration::from_secs(1));
+    /// ```
+    pub fn with_flush_timeout(mut self, timeout: Duration) -> Self {
+        self.config.flush_timeout = timeout;
+        self
+    }
+
+    /// Remove components which haven't been touched for `after`, despawning
+    /// their entities too if `despawn` is true
+    ///
+    /// This bounds the memory used by long sessions against big tables. See
+    /// [`SqlxEvictionPolicy`] for more information.
+    ///
+    /// ```
+    /// use bevy::utils::Duration;
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxPlugin, SqlxDummy};
+    ///
+    /// SqlxPlugin::<Sqlite, SqlxDummy>::from_url("sqlite:db/sqlite.db")
+    ///     .with_eviction(Duration::from_secs(300), true);
+    /// ```
+    pub fn with_eviction(mut self, after: Duration, despawn: bool) -> Self {
+        self.config.eviction = Some(SqlxEvictionPolicy { after, despawn });
+        self
+    }
+
+    /// Spawn or update at most `rows_per_frame` synced components each frame
+    ///
+    /// Syncing tens of thousands of rows at once stalls a frame, so the
+    /// remaining components are queued for the following frames instead. The
+    /// [`SqlxEventStatus::Complete`] is sent once all of an event's
+    /// components are synced, like without a budget.
+    ///
+    /// ```
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxPlugin, SqlxDummy};
+    ///
+    /// SqlxPlugin::<Sqlite, SqlxDummy>::from_url("sqlite:db/sqlite.db")
+    ///     .with_sync_budget(1000);
+    /// ```
+    pub fn with_sync_budget(mut self, rows_per_frame: usize) -> Self {
+        self.config.sync_budget = Some(rows_per_frame.max(1));
+        self
+    }
+
+    /// Stop handling finished tasks once `budget` has passed in a frame
+    ///
+    /// When many queries finish at once, applying all of their results
+    /// spikes the frame time, so the remaining tasks are handled in the
+    /// following frames instead. Combined with [`Self::with_sync_budget`],
+    /// queued components are also left for the next frame once it's spent.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxPlugin, SqlxDummy};
+    ///
+    /// SqlxPlugin::<Sqlite, SqlxDummy>::from_url("sqlite:db/sqlite.db")
+    ///     .with_frame_budget(Duration::from_millis(1));
+    /// ```
+    pub fn with_frame_budget(mut self, budget: Duration) -> Self {
+        self.config.frame_budget = Some(budget);
+        self
+    }
+
+    /// Also send [`SqlxEventStatus::Queued`], [`SqlxEventStatus::Executing`]
+    /// and [`SqlxEventStatus::Decoded`] for every event, so loading screens
+    /// can show how far long operations got
+    ///
+    /// ```
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxPlugin, SqlxDummy};
+    ///
+    /// SqlxPlugin::<Sqlite, SqlxDummy>::from_url("sqlite:db/sqlite.db")
+    ///     .with_detailed_statuses();
+    /// ```
+    pub fn with_detailed_statuses(mut self) -> Self {
+        self.config.detailed_statuses = true;
+        self
+    }
+
+    /// Record the query plan of each unique query in a [`SqlxQueryLog`],
+    /// to find missing indexes during development
+    ///
+    /// This only has an effect in debug builds.
+    ///
+    /// ```
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxPlugin, SqlxDummy};
+    ///
+    /// SqlxPlugin::<Sqlite, SqlxDummy>::from_url("sqlite:db/sqlite.db")
+    ///     .with_explain();
+    /// ```
+    pub fn with_explain(mut self) -> Self {
+        self.config.explain = true;
+        self
+    }
+
+    /// Keep the events which failed in a [`SqlxDeadLetters`] resource, to
+    /// retry or drop them later
+    ///
+    /// ```
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxPlugin, SqlxDummy};
+    ///
+    /// SqlxPlugin::<Sqlite, SqlxDummy>::from_url("sqlite:db/sqlite.db")
+    ///     .with_dead_letters();
+    /// ```
+    pub fn with_dead_letters(mut self) -> Self {
+        self.config.dead_letters = true;
+        self
+    }
+
+    /// Reject every event which isn't a `SELECT` with a
+    /// [`SqlxEventStatus::Error`], protecting reference data from accidental
+    /// writes
+    ///
+    /// Unlike [`SqlxSyncMode::ReadOnly`], which only leaves out the systems
+    /// writing components back, this checks the events themselves. Events
+    /// made with [`SqlxEvent::call`] can't be inspected, and aren't checked.
+    ///
+    /// ```
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxPlugin, SqlxDummy};
+    ///
+    /// SqlxPlugin::<Sqlite, SqlxDummy>::from_url("sqlite:db/sqlite.db")
+    ///     .with_read_only();
+    /// ```
+    pub fn with_read_only(mut self) -> Self {
+        self.config.read_only = true;
+        self
+    }
+
+    /// Run events on `task_pool`, instead of the [`AsyncComputeTaskPool`],
+    /// see [`SqlxTaskPool`]
+    ///
+    /// [`AsyncComputeTaskPool`]: bevy::tasks::AsyncComputeTaskPool
+    ///
+    /// ```
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxPlugin, SqlxDummy, SqlxTaskPool};
+    ///
+    /// SqlxPlugin::<Sqlite, SqlxDummy>::from_url("sqlite:db/sqlite.db")
+    ///     .with_task_pool(SqlxTaskPool::Io);
+    /// ```
+    pub fn with_task_pool(mut self, task_pool: SqlxTaskPool) -> Self {
+        self.config.task_pool = task_pool;
+        self
+    }
+
+    /// Never write to the database from this app, for client builds of a
+    /// game whose server owns the shared database
+    ///
+    /// Reads and syncs keep working. Everything else is restricted the
+    /// following way:
+    /// - The plugin is [`Self::with_read_only`], so write events are
+    ///   rejected
+    /// - A [`SqlxSyncMode::Bidirectional`] plugin is
+    ///   [`SqlxSyncMode::ReadOnly`], so changes aren't written back
+    /// - [`Self::with_audit`] and [`Self::with_journal`] are ignored
+    ///
+    /// Building the crate with the `client` feature does this for every
+    /// plugin, and also leaves out the [`SqlxAutosavePlugin`]'s system, so a
+    /// client build can't write by mistake.
+    ///
+    /// ```
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxPlugin, SqlxDummy};
+    ///
+    /// SqlxPlugin::<Sqlite, SqlxDummy>::from_url("sqlite:db/sqlite.db")
+    ///     .with_client();
+    /// ```
+    pub fn with_client(mut self) -> Self {
+        self.config.client = true;
+        self
+    }
+
+    /// Only sync `C` in the given direction, see [`SqlxSyncMode`]
+    ///
+    /// ```
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxPlugin, SqlxDummy, SqlxSyncMode};
+    ///
+    /// SqlxPlugin::<Sqlite, SqlxDummy>::from_url("sqlite:db/sqlite.db")
+    ///     .with_sync_mode(SqlxSyncMode::ReadOnly);
+    /// ```
+    pub fn with_sync_mode(mut self, mode: SqlxSyncMode) -> Self {
+        self.config.sync_mode = mode;
+        self
+    }
+
+    /// Choose what's done with components loaded into an entity which was
+    /// despawned in the meantime, see [`SqlxDespawnedTarget`]
+    ///
+    /// ```
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxPlugin, SqlxDummy, SqlxDespawnedTarget};
+    ///
+    /// SqlxPlugin::<Sqlite, SqlxDummy>::from_url("sqlite:db/sqlite.db")
+    ///     .with_despawned_target(SqlxDespawnedTarget::Respawn);
+    /// ```
+    pub fn with_despawned_target(mut self, then: SqlxDespawnedTarget) -> Self {
+        self.config.despawned_target = then;
+        self
+    }
+
+    /// Spawn synced components as children of `parent`
+    ///
+    /// See [`SqlxSpawnTarget`] for more information.
+    ///
+    /// ```
+    /// use bevy::prelude::*;
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxPlugin, SqlxDummy};
+    ///
+    /// let mut app = App::new();
+    /// let level = app.world_mut().spawn_empty().id();
+    /// app.add_plugins(
+    ///     SqlxPlugin::<Sqlite, SqlxDummy>::from_url("sqlite:db/sqlite.db")
+    ///         .with_spawn_parent(level),
+    /// );
+    /// ```
+    pub fn with_spawn_parent(mut self, parent: Entity) -> Self {
+        self.config.spawn_target.parent = Some(SqlxSpawnParent::Entity(parent));
+        self
+    }
+
+    /// Spawn synced components as children of the entity with the marker
+    /// component `M`, which doesn't need to exist yet
+    ///
+    /// Without such an entity, they're spawned at the root of the world. See
+    /// [`SqlxSpawnTarget`] for more information.
+    ///
+    /// ```
+    /// use bevy::prelude::*;
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxPlugin, SqlxDummy};
+    ///
+    /// #[derive(Component)]
+    /// struct Level;
+    ///
+    /// SqlxPlugin::<Sqlite, SqlxDummy>::from_url("sqlite:db/sqlite.db")
+    ///     .with_spawn_anchor::<Level>();
+    /// ```
+    pub fn with_spawn_anchor<M: Component>(mut self) -> Self {
+        let anchor = SqlxSpawnParent::Anchor(|world| {
+            world.query_filtered::<Entity, With<M>>().iter(world).next()
+        });
+        self.config.spawn_target.parent = Some(anchor);
+        self
+    }
+
+    /// Insert a clone of `markers` on the entities of synced components
+    /// when they're spawned
+    ///
+    /// ```
+    /// use bevy::prelude::*;
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxPlugin, SqlxDummy};
+    ///
+    /// #[derive(Component, Clone)]
+    /// struct Loaded;
+    ///
+    /// SqlxPlugin::<Sqlite, SqlxDummy>::from_url("sqlite:db/sqlite.db")
+    ///     .with_spawn_markers((Loaded, Name::new("dummy")));
+    /// ```
+    pub fn with_spawn_markers(mut self, markers: impl Bundle + Clone) -> Self {
+        self.config.spawn_target.insert(markers);
+        self
+    }
+
+    /// Apply synced components with `apply`, instead of spawning or
+    /// updating them with [`SqlxDefaultApply`]
+    ///
+    /// See [`SqlxApply`] for more information.
+    ///
+    /// ```
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxPlugin, SqlxDummy, SqlxDefaultApply};
+    ///
+    /// SqlxPlugin::<Sqlite, SqlxDummy>::from_url("sqlite:db/sqlite.db")
+    ///     .with_apply(SqlxDefaultApply);
+    /// ```
+    pub fn with_apply(mut self, apply: impl SqlxApply<C>) -> Self {
+        self.config.apply = Arc::new(apply);
+        self
+    }
+
+    /// Find the entity of each synced component with `matcher`, instead of
+    /// by its primary key with [`SqlxPrimaryKeyMatch`]
+    ///
+    /// See [`SqlxMatch`] for more information.
+    ///
+    /// ```
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxPlugin, SqlxDummy, SqlxKeyMatch};
+    ///
+    /// SqlxPlugin::<Sqlite, SqlxDummy>::from_url("sqlite:db/sqlite.db")
+    ///     .with_match(SqlxKeyMatch);
+    /// ```
+    pub fn with_match(mut self, matcher: impl SqlxMatch<C>) -> Self {
+        self.config.matcher = Arc::new(matcher);
+        self
+    }
+
+    /// Migrate rows saved by older versions of the app with `migrations`
+    /// when they're synced
+    ///
+    /// See [`SqlxMigrations`] for more information.
+    ///
+    /// ```
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxPlugin, SqlxDummy, SqlxMigrations};
+    ///
+    /// let migrations = SqlxMigrations::new("schema_version", 1);
+    /// SqlxPlugin::<Sqlite, SqlxDummy>::from_url("sqlite:db/sqlite.db")
+    ///     .with_migrations(migrations);
+    /// ```
+    pub fn with_migrations(
+        mut self,
+        migrations: SqlxMigrations<DB, C>,
+    ) -> Self {
+        self.config.migrations = Some(Arc::new(migrations));
+        self
+    }
+
+    /// Journal the generated writes lost with the connection to the file at
+    /// `path`, and replay them once it's restored
+    ///
+    /// See [`SqlxJournal`] for more information.
+    ///
+    /// ```
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxPlugin, SqlxDummy};
+    ///
+    /// SqlxPlugin::<Sqlite, SqlxDummy>::from_url("sqlite:db/sqlite.db")
+    ///     .with_journal("db/journal.jsonl");
+    /// ```
+    pub fn with_journal(mut self, path: impl AsRef<Path>) -> Self {
+        self.config.journal = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Resolve journaled writes whose rows changed remotely after they were
+    /// queued with `policy`, comparing the timestamp `column`
+    ///
+    /// See [`SqlxConflictPolicy`] for more information.
+    ///
+    /// ```
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxPlugin, SqlxDummy, SqlxConflictPolicy};
+    ///
+    /// SqlxPlugin::<Sqlite, SqlxDummy>::from_url("sqlite:db/sqlite.db")
+    ///     .with_journal("db/journal.jsonl")
+    ///     .with_conflict_policy("updated_at", SqlxConflictPolicy::default());
+    /// ```
+    pub fn with_conflict_policy(
+        mut self,
+        column: &'static str,
+        policy: SqlxConflictPolicy,
+    ) -> Self {
+        self.config.conflict_column = Some(column);
+        self.config.conflict_policy = policy;
+        self
+    }
+
+    /// Call `handler` with every [`SqlxEventStatus::Error`]
+    ///
+    /// The [`SqlxErrorHandler`] is shared by every plugin, of any database
+    /// or component, so it only needs to be given to one of them.
+    ///
+    /// ```
+    /// use bevy::prelude::*;
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxPlugin, SqlxDummy, SqlxErrorContext};
+    ///
+    /// SqlxPlugin::<Sqlite, SqlxDummy>::from_url("sqlite:db/sqlite.db")
+    ///     .with_error_handler(|err: &SqlxErrorContext| {
+    ///         error!("{} event {} failed: {}", err.component, err.id, err.error);
+    ///     });
+    /// ```
+    pub fn with_error_handler(
+        mut self,
+        handler: impl Fn(&SqlxErrorContext) + Send + Sync + 'static,
+    ) -> Self {
+        self.error_handler = Some(SqlxErrorHandler::new(handler));
+        self
+    }
+
+    /// Send the event of `schedule` every interval, see [`SqlxSchedule`]
+    ///
+    /// The schedule is enabled and disabled by `name` at runtime, with the
+    /// [`SqlxSchedules<DB, C>`] resource.
+    ///
+    /// ```
+    /// use bevy::utils::Duration;
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxEvent, SqlxDummy, SqlxPlugin, SqlxSchedule};
+    ///
+    /// let refresh = SqlxSchedule::every(Duration::from_secs(10), || {
+    ///     SqlxEvent::query("UPDATE stats SET total = (SELECT SUM(score) FROM foos)")
+    /// });
+    /// SqlxPlugin::<Sqlite, SqlxDummy>::from_url("sqlite:db/sqlite.db")
+    ///     .with_schedule("refresh_stats", refresh);
+    /// ```
+    pub fn with_schedule(
+        mut self,
+        name: &'static str,
+        schedule: SqlxSchedule<DB, C>,
+    ) -> Self {
+        self.schedules.retain(|(other, _)| *other != name);
+        self.schedules.push((name, schedule));
+        self
+    }
+}
+
+/// A [`Resource`](bevy::prelude::Resource) holding the options a
+/// [`SqlxPlugin`] was built with
+#[derive(Resource, Debug)]
+pub struct SqlxConfig<DB: Database, C: SqlxComponent<DB::Row>> {
+    /// The column generated statements are scoped to the [`TenantId`] by
+    pub tenant_column: Option<&'static str>,
+    /// The prefix of the tables of generated statements
+    pub table_prefix: Option<String>,
+    /// The schema the tables of generated statements are qualified with
+    pub schema: Option<String>,
+    /// Whether generated writes are logged to the [`AUDIT_TABLE`]
+    pub audit: bool,
+    /// The limit on how many events are dispatched per second
+    pub rate_limit: Option<SqlxRateLimit>,
+    /// How long to wait for pending events when the app exits
+    pub flush_timeout: Duration,
+    /// How long the database lets a statement run, see
+    /// [`SqlxPlugin::with_statement_timeout`]
+    pub statement_timeout: Option<Duration>,
+    /// When synced components are evicted
+    pub eviction: Option<SqlxEvictionPolicy>,
+    /// How many synced components are spawned or updated per frame
+    pub sync_budget: Option<usize>,
+    /// How long finished tasks are handled for per frame
+    pub frame_budget: Option<Duration>,
+    /// Whether queued, executing and decoded statuses are sent
+    pub detailed_statuses: bool,
+    /// Whether query plans are recorded in the [`SqlxQueryLog`]
+    pub explain: bool,
+    /// Whether failed events are kept in the [`SqlxDeadLetters`]
+    pub dead_letters: bool,
+    /// Which directions `C` is synced in
+    pub sync_mode: SqlxSyncMode,
+    /// What's done when the target of an event is despawned before it ends
+    pub despawned_target: SqlxDespawnedTarget,
+    /// Whether events which aren't a `SELECT` are rejected
+    pub read_only: bool,
+    /// Whether the plugin never writes, see [`SqlxPlugin::with_client`]
+    pub client: bool,
+    /// Where synced components are spawned
+    pub spawn_target: SqlxSpawnTarget,
+    /// What's done with synced components
+    pub apply: Arc<dyn SqlxApply<C>>,
+    /// How synced components find their entity
+    pub matcher: Arc<dyn SqlxMatch<C>>,
+    /// The file generated writes are journaled to while offline
+    pub journal: Option<PathBuf>,
+    /// The timestamp column journaled writes are checked for conflicts on
+    pub conflict_column: Option<&'static str>,
+    /// How conflicting journaled writes are resolved
+    pub conflict_policy: SqlxConflictPolicy,
+    /// The task pool events run on
+    pub task_pool: SqlxTaskPool,
+    /// How often the URL is read from the [`SqlxCredentialsProvider`] again
+    pub credentials_refresh: Option<Duration>,
+    /// How rows saved by older versions are migrated
+    pub migrations: Option<Arc<SqlxMigrations<DB, C>>>,
+    _db: PhantomData<DB>,
+    _c: PhantomData<C>,
+}
+
+impl<DB: Database, C: SqlxComponent<DB::Row>> Default for SqlxConfig<DB, C> {
+    fn default() -> Self {
+        SqlxConfig {
+            tenant_column: None,
+            table_prefix: None,
+            schema: None,
+            audit: false,
+            rate_limit: None,
+            flush_timeout: Duration::from_secs(5),
+            statement_timeout: None,
+            eviction: None,
+            sync_budget: None,
+            frame_budget: None,
+            detailed_statuses: false,
+            explain: false,
+            dead_letters: false,
+            sync_mode: SqlxSyncMode::Bidirectional,
+            despawned_target: SqlxDespawnedTarget::default(),
+            read_only: false,
+            client: false,
+            spawn_target: SqlxSpawnTarget::default(),
+            apply: Arc::new(SqlxDefaultApply),
+            matcher: Arc::new(SqlxPrimaryKeyMatch),
+            journal: None,
+            conflict_column: None,
+            conflict_policy: SqlxConflictPolicy::default(),
+            task_pool: SqlxTaskPool::default(),
+            credentials_refresh: None,
+            migrations: None,
+            _db: PhantomData,
+            _c: PhantomData,
+        }
+    }
+}
+
+impl<DB: Database, C: SqlxComponent<DB::Row>> Clone for SqlxConfig<DB, C> {
+    fn clone(&self) -> Self {
+        SqlxConfig {
+            tenant_column: self.tenant_column,
+            table_prefix: self.table_prefix.clone(),
+            schema: self.schema.clone(),
+            audit: self.audit,
+            rate_limit: self.rate_limit,
+            flush_timeout: self.flush_timeout,
+            statement_timeout: self.statement_timeout,
+            eviction: self.eviction,
+            sync_budget: self.sync_budget,
+            frame_budget: self.frame_budget,
+            detailed_statuses: self.detailed_statuses,
+            explain: self.explain,
+            dead_letters: self.dead_letters,
+            sync_mode: self.sync_mode,
+            despawned_target: self.despawned_target,
+            read_only: self.read_only,
+            client: self.client,
+            spawn_target: self.spawn_target.clone(),
+            apply: self.apply.clone(),
+            matcher: self.matcher.clone(),
+            journal: self.journal.clone(),
+            conflict_column: self.conflict_column,
+            conflict_policy: self.conflict_policy.clone(),
+            task_pool: self.task_pool.clone(),
+            credentials_refresh: self.credentials_refresh,
+            migrations: self.migrations.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+impl<DB: Database, C: SqlxComponent<DB::Row>> SqlxConfig<DB, C> {
+    /// The name of `table` with this config's schema and prefix
+    pub fn table_name(&self, table: &str) -> String {
+        let prefix = self.table_prefix.as_deref().unwrap_or("");
+        match &self.schema {
+            Some(schema) => format!("{schema}.{prefix}{table}"),
+            None => format!("{prefix}{table}"),
+        }
+    }
+
+    /// Restrict this config to reads, see [`SqlxPlugin::with_client`]
+    fn restrict_to_reads(mut self) -> Self {
+        self.client = true;
+        self.read_only = true;
+        if self.sync_mode == SqlxSyncMode::Bidirectional {
+            self.sync_mode = SqlxSyncMode::ReadOnly;
+        }
+        self.audit = false;
+        self.journal = None;
+        self
+    }
+}
+
+/// The directions a [`SqlxPlugin`]'s component is synced in
+///
+/// - [`Self::ReadOnly`] components are only loaded from the database, like
+///   static content tables. Systems writing them back, like
+///   the [`SqlxWriteBackPlugin`]'s, aren't added.
+/// - [`Self::WriteOnly`] components are only written to the database, like
+///   telemetry. Synced events return their components instead of spawning
+///   them, and systems loading them, like [`SqlxSubscription`]s and
+///   [`SqlxKey`]s, aren't added.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SqlxSyncMode {
+    ReadOnly,
+    WriteOnly,
+    #[default]
+    Bidirectional,
+}
+
+/// What's done with the component loaded for an event's
+/// [`SqlxEvent::target`] when the entity was despawned before the event
+/// finished, e.g. by [`SqlxEvent::load_into`] or a
+/// [`SqlxEntityCommandsExt::refresh`]
+///
+/// - [`Self::Discard`] drops the component, sending an
+///   [`SqlxEventStatus::Done`] with no rows.
+/// - [`Self::Respawn`] spawns a new entity with it, where the plugin's
+///   [`SqlxSpawnTarget`] says, sending an [`SqlxEventStatus::Spawn`].
+/// - [`Self::Error`] sends an [`SqlxEventStatus::Error`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SqlxDespawnedTarget {
+    Discard,
+    Respawn,
+    #[default]
+    Error,
+}
+
+impl SqlxSyncMode {
+    /// The mode of the [`SqlxPlugin<DB, C>`] added to `world`, or
+    /// [`Self::Bidirectional`] if there's none
+    pub fn of<DB, C>(world: &World) -> Self
+    where
+        DB: Database + Sync,
+        C: SqlxComponent<DB::Row>,
+    {
+        world
+            .get_resource::<SqlxConfig<DB, C>>()
+            .map_or(SqlxSyncMode::default(), |config| config.sync_mode)
+    }
+
+    /// Return true if components are loaded from the database
+    pub fn reads(&self) -> bool {
+        *self != SqlxSyncMode::WriteOnly
+    }
+
+    /// Return true if components are written to the database
+    pub fn writes(&self) -> bool {
+        *self != SqlxSyncMode::ReadOnly
     }
 }
 
@@ -57,13 +937,86 @@ impl<DB: Database + Sync, C: SqlxComponent<DB::Row>> Plugin
 where
     for<'c> &'c mut <DB as Database>::Connection: Executor<'c, Database = DB>,
     for<'q> <DB as Database>::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+    for<'r> SqlxValue: Decode<'r, DB>,
+    usize: ColumnIndex<DB::Row>,
 {
     fn build(&self, app: &mut App) {
+        let mut config = self.config.clone();
+        if config.client || cfg!(feature = "client") {
+            config = config.restrict_to_reads();
+        }
+        if config.table_prefix.is_none() {
+            let prefix = app.world().get_resource::<SqlxTablePrefix>();
+            config.table_prefix = prefix.map(|prefix| prefix.0.clone());
+        }
+        if config.audit {
+            if let Err(err) = block_on(create_audit_table(&self.pool)) {
+                warn!("failed to create {AUDIT_TABLE}, not auditing: {err}");
+                config.audit = false;
+            }
+        }
         app.insert_resource(SqlxDatabase { pool: self.pool.clone() });
+        app.insert_resource(config.clone());
         app.insert_resource(SqlxTasks::<DB, C>::default());
+        app.init_resource::<SqlxEventIds>();
+        app.init_resource::<SqlxEventRegistry<DB, C>>();
         app.add_event::<SqlxEvent<DB, C>>();
         app.add_event::<SqlxEventStatus<DB, C>>();
-        app.add_systems(Update, SqlxEvent::<DB, C>::handle_events);
-        app.add_systems(Update, SqlxTasks::<DB, C>::handle_tasks);
+        app.add_event::<SqlxTableChanged>();
+        app.add_event::<SqlxConnectionStatus<DB>>();
+        app.init_resource::<SqlxActivity>();
+        app.register_type::<SqlxActivity>();
+        app.register_type::<SqlxEventSummary>();
+        app.register_type::<SqlxStatusSummary>();
+        let mut schedules = SqlxSchedules::<DB, C>::default();
+        for (name, schedule) in &self.schedules {
+            schedules.insert(name, schedule.clone());
+        }
+        app.insert_resource(schedules);
+        SqlxRegistry::<DB>::register::<C>(app);
+        if let Some(policy) =
+            config.eviction.filter(|_| config.sync_mode.reads())
+        {
+            app.insert_resource(SqlxEviction::<C>::new(policy));
+            app.add_event::<SqlxEvicted<C>>();
+            app.add_systems(
+                Update,
+                SqlxEviction::<C>::handle_eviction
+                    .after(SqlxRegistry::<DB>::handle_tasks),
+            );
+        }
+        if let Some(handler) = &self.error_handler {
+            app.insert_resource(handler.clone());
+        }
+        if let Some(provider) = &self.credentials {
+            let refresh = config.credentials_refresh;
+            app.insert_resource(SqlxCredentials::<DB>::new(
+                provider.clone(),
+                refresh,
+            ));
+            app.add_systems(Update, SqlxCredentials::<DB>::handle_refresh);
+        }
+        if let Some(path) = &config.journal {
+            app.insert_resource(SqlxJournal::<DB, C>::new(path.clone()));
+            app.add_event::<SqlxJournalStatus<DB, C>>();
+            app.add_systems(
+                Update,
+                SqlxJournal::<DB, C>::handle_journal
+                    .after(SqlxRegistry::<DB>::handle_tasks),
+            );
+        }
+        if cfg!(debug_assertions) && config.explain {
+            app.init_resource::<SqlxQueryLog<DB>>();
+            app.add_systems(Update, SqlxQueryLog::<DB>::handle_explain::<C>);
+        }
+        if config.dead_letters {
+            app.init_resource::<SqlxDeadLetters<DB, C>>();
+            app.add_systems(
+                Update,
+                SqlxDeadLetters::<DB, C>::handle_retries
+                    .before(SqlxRegistry::<DB>::handle_events),
+            );
+        }
     }
 }
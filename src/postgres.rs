@@ -0,0 +1,37 @@
+use crate::*;
+use sqlx::postgres::PgRow;
+use sqlx::Postgres;
+
+impl<C: SqlxComponent<PgRow>> SqlxPlugin<Postgres, C> {
+    /// Set the `search_path` of every connection to `schemas`, so raw SQL
+    /// finds unqualified tables in them
+    ///
+    /// The pool is reconnected with the new setting. Generated statements
+    /// are qualified with [`Self::with_schema`] instead, which doesn't
+    /// depend on the connection.
+    ///
+    /// ```no_run
+    /// use sqlx::Postgres;
+    /// use bevy_sqlx::{SqlxPlugin, SqlxDummy};
+    ///
+    /// let url = "postgres://localhost/game";
+    /// SqlxPlugin::<Postgres, SqlxDummy>::from_url(url)
+    ///     .with_search_path(&["staging", "public"])
+    ///     .with_schema("staging");
+    /// ```
+    pub fn with_search_path(mut self, schemas: &[&str]) -> Self {
+        let search_path = schemas
+            .iter()
+            .map(|schema| format!("\"{}\"", schema.replace('"', "\"\"")))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let connect = (*self.pool.connect_options())
+            .clone()
+            .options([("search_path", search_path)]);
+        let options = self.pool.options().clone();
+        let pool = bevy::tasks::block_on(options.connect_with(connect));
+        bevy::tasks::block_on(self.pool.close());
+        self.pool = pool.unwrap();
+        self
+    }
+}
@@ -0,0 +1,101 @@
+use bevy::prelude::*;
+
+/// A [`Resource`] prefixing the tables of every generated statement, so
+/// several environments can share one database
+///
+/// Each [`SqlxPlugin`](crate::SqlxPlugin) added after this resource is
+/// inserted uses its prefix, unless built with
+/// [`SqlxPlugin::with_table_prefix`](crate::SqlxPlugin::with_table_prefix).
+/// The [`SqlxAutosavePlugin`](crate::SqlxAutosavePlugin) and
+/// [`SqlxResourcePlugin::row`](crate::SqlxResourcePlugin::row) use it too.
+/// Like [`TenantId`](crate::TenantId) scoping, raw SQL from
+/// [`SqlxEvent::query`](crate::SqlxEvent::query) and
+/// [`SqlxEvent::call`](crate::SqlxEvent::call) is never rewritten.
+///
+/// ### Example
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::Sqlite;
+/// # use bevy_sqlx::{SqlxPlugin, SqlxDummy, SqlxTablePrefix};
+/// let url = "sqlite:db/sqlite.db";
+/// App::new()
+///     .insert_resource(SqlxTablePrefix("staging_".into()))
+///     .add_plugins(SqlxPlugin::<Sqlite, SqlxDummy>::from_url(&url));
+/// ```
+#[derive(Resource, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SqlxTablePrefix(pub String);
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{block_on, AsyncComputeTaskPool, TaskPool};
+    use sqlx::{FromRow, Pool, Sqlite};
+
+    #[derive(Component, FromRow, Debug)]
+    struct Foo {
+        id: u32,
+        text: String,
+    }
+
+    impl PrimaryKey for Foo {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    impl ToRow for Foo {
+        fn table() -> &'static str {
+            "prefixeds"
+        }
+
+        fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+            vec![("id", self.id.into()), ("text", self.text.clone().into())]
+        }
+    }
+
+    fn count(pool: &Pool<Sqlite>) -> i64 {
+        let sql = "SELECT COUNT(*) FROM dev_prefixeds";
+        block_on(sqlx::query_scalar(sql).fetch_one(pool)).unwrap()
+    }
+
+    #[test]
+    fn test_table_prefix() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let pool: Pool<Sqlite> = block_on(Pool::connect(url)).unwrap();
+        block_on(async {
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS dev_prefixeds \
+                 (id INTEGER PRIMARY KEY, text TEXT NOT NULL)",
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+            sqlx::query("DELETE FROM dev_prefixeds")
+                .execute(&pool)
+                .await
+                .unwrap();
+        });
+
+        let mut app = App::new();
+        app.insert_resource(SqlxTablePrefix("dev_".into()));
+        app.add_plugins(SqlxPlugin::<Sqlite, Foo>::from_url(url));
+        let config = app.world().resource::<SqlxConfig<Sqlite, Foo>>();
+        assert_eq!("dev_prefixeds", config.table_name(Foo::table()));
+
+        let foo = Foo { id: 1, text: "one".into() };
+        app.world_mut().send_event(SqlxEvent::<Sqlite, Foo>::insert(&foo));
+        let foos = (2..=10).map(|id| Foo { id, text: id.to_string() });
+        let event = SqlxEvent::<Sqlite, Foo>::copy_in(foos.collect());
+        app.world_mut().send_event(event);
+        let mut tries = 0;
+        while count(&pool) < 10 && tries < 1000 {
+            app.update();
+            tries += 1;
+        }
+        assert_eq!(10, count(&pool));
+    }
+}
@@ -0,0 +1,649 @@
+//! An opt-in log of Bevy [`Event`]s
+//!
+//! The [`SqlxRecorderPlugin`] appends every event of the selected types to
+//! the [`EVENT_TABLE`], for audit trails and analytics of gameplay events.
+//! Events are buffered, and written in a single transaction every interval,
+//! or as soon as a batch is full.
+//!
+//! | column        | value                                          |
+//! | ------------- | ---------------------------------------------- |
+//! | `name`        | the type name of the event                     |
+//! | `tick`        | the [`FrameCount`] the event was recorded on   |
+//! | `payload`     | the JSON serialized event                      |
+//! | `recorded_at` | the time the event was recorded                |
+//!
+//! The [`SqlxReplayPlugin`] streams the recorded events back into the app,
+//! sending them again at their original tick offsets, or scaled ones, so
+//! the database becomes a replay and debugging source.
+//!
+//! ### Example
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use bevy::utils::Duration;
+//! # use serde::Serialize;
+//! # use sqlx::Sqlite;
+//! # use bevy_sqlx::{SqlxPlugin, SqlxDummy};
+//! # use bevy_sqlx::recorder::SqlxRecorderPlugin;
+//! #[derive(Event, Serialize)]
+//! struct Scored {
+//!     player: u32,
+//!     points: u32,
+//! }
+//!
+//! let url = "sqlite:db/sqlite.db";
+//! App::new()
+//!     .add_plugins(SqlxPlugin::<Sqlite, SqlxDummy>::from_url(url))
+//!     .add_event::<Scored>()
+//!     .add_plugins(
+//!         SqlxRecorderPlugin::<Sqlite>::every(Duration::from_secs(5))
+//!             .with::<Scored>(),
+//!     );
+//! ```
+//!
+//! Replaying them later:
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use serde::Deserialize;
+//! # use sqlx::Sqlite;
+//! # use bevy_sqlx::{SqlxPlugin, SqlxDummy};
+//! # use bevy_sqlx::recorder::{SqlxReplay, SqlxReplayPlugin};
+//! #[derive(Event, Deserialize)]
+//! struct Scored {
+//!     player: u32,
+//!     points: u32,
+//! }
+//!
+//! fn replay_at_double_speed(mut replay: ResMut<SqlxReplay<Sqlite>>) {
+//!     replay.play(2.);
+//! }
+//!
+//! let url = "sqlite:db/sqlite.db";
+//! App::new()
+//!     .add_plugins(SqlxPlugin::<Sqlite, SqlxDummy>::from_url(url))
+//!     .add_event::<Scored>()
+//!     .add_plugins(SqlxReplayPlugin::<Sqlite>::default().with::<Scored>())
+//!     .add_systems(Startup, replay_at_double_speed);
+//! ```
+use crate::*;
+use bevy::core::{FrameCount, FrameCountPlugin};
+use bevy::prelude::*;
+use bevy::tasks::futures_lite::future;
+use bevy::tasks::{block_on, AsyncComputeTaskPool, Task};
+use bevy::utils::{Duration, Instant};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sqlx::{ColumnIndex, Database, Decode, Encode, Error, Executor};
+use sqlx::{IntoArguments, Pool, Type};
+use std::collections::VecDeque;
+use std::fmt;
+use std::marker::PhantomData;
+use std::time::SystemTime;
+
+/// The name of the table recorded events are appended to
+pub const EVENT_TABLE: &str = "_bevy_sqlx_events";
+
+/// A [`Plugin`] recording the events of the selected types in the
+/// [`EVENT_TABLE`]
+///
+/// Each event type is selected with [`Self::with`]. The recorded events are
+/// written every `interval`, or once [`Self::with_batch_size`] of them are
+/// buffered. If the last batch hasn't been written yet, the next one waits
+/// for it. The table is created when the plugin is built, if it doesn't
+/// exist yet.
+///
+/// See the [`recorder`](crate::recorder) module for more information.
+pub struct SqlxRecorderPlugin<DB> {
+    interval: Duration,
+    batch_size: usize,
+    recorders: Vec<fn(&mut App)>,
+    _db: PhantomData<fn() -> DB>,
+}
+
+impl<DB: Database> SqlxRecorderPlugin<DB> {
+    /// Write the recorded events every `interval`
+    pub fn every(interval: Duration) -> Self {
+        SqlxRecorderPlugin {
+            interval,
+            batch_size: 256,
+            recorders: Vec::new(),
+            _db: PhantomData,
+        }
+    }
+
+    /// Record the events `E`
+    pub fn with<E: Event + Serialize>(mut self) -> Self {
+        self.recorders.push(|app| {
+            app.add_systems(Last, record::<DB, E>);
+        });
+        self
+    }
+
+    /// Write the recorded events as soon as `batch_size` of them are
+    /// buffered, 256 by default
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+}
+
+impl<DB> Plugin for SqlxRecorderPlugin<DB>
+where
+    DB: Database + Sync,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+{
+    fn build(&self, app: &mut App) {
+        let pool = &app.world().resource::<SqlxDatabase<DB>>().pool;
+        block_on(create_event_table(pool)).unwrap();
+        if !app.is_plugin_added::<FrameCountPlugin>() {
+            app.add_plugins(FrameCountPlugin);
+        }
+        app.insert_resource(SqlxRecorder::<DB> {
+            interval: self.interval,
+            batch_size: self.batch_size,
+            written: Instant::now(),
+            buffer: Vec::new(),
+            task: None,
+            _db: PhantomData,
+        });
+        // Client builds never write, see `SqlxPlugin::with_client`.
+        if cfg!(feature = "client") {
+            return;
+        }
+        for recorder in &self.recorders {
+            recorder(app);
+        }
+        app.add_systems(Update, SqlxRecorder::<DB>::handle_recorder);
+    }
+}
+
+/// Create the [`EVENT_TABLE`] unless it already exists
+async fn create_event_table<DB>(pool: &Pool<DB>) -> Result<(), Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    let sql = format!(
+        "CREATE TABLE IF NOT EXISTS {EVENT_TABLE} (
+            name        VARCHAR(255) NOT NULL,
+            tick        BIGINT       NOT NULL,
+            payload     TEXT         NOT NULL,
+            recorded_at TIMESTAMP    NOT NULL
+        )"
+    );
+    sqlx::query(&sql).execute(pool).await.map(|_| ())
+}
+
+/// A [`System`] buffering the events `E` in the [`SqlxRecorder`]
+fn record<DB: Database, E: Event + Serialize>(
+    frame: Res<FrameCount>,
+    mut recorder: ResMut<SqlxRecorder<DB>>,
+    mut events: EventReader<E>,
+) {
+    let name = std::any::type_name::<E>();
+    for event in events.read() {
+        let payload = match serde_json::to_string(event) {
+            Ok(payload) => payload,
+            Err(err) => {
+                warn!("failed to record {name}: {err}");
+                continue;
+            }
+        };
+        let columns = vec![
+            ("name", name.into()),
+            ("tick", frame.0.into()),
+            ("payload", payload.into()),
+            ("recorded_at", SystemTime::now().into()),
+        ];
+        recorder.buffer.push(SqlxStatement::insert(EVENT_TABLE, columns));
+    }
+}
+
+/// A [`Resource`] holding the recorded events of the [`SqlxRecorderPlugin`]
+/// of `DB`, until they're written
+#[derive(Resource)]
+pub struct SqlxRecorder<DB> {
+    interval: Duration,
+    batch_size: usize,
+    written: Instant,
+    buffer: Vec<SqlxStatement>,
+    task: Option<Task<Result<u64, Error>>>,
+    _db: PhantomData<fn() -> DB>,
+}
+
+impl<DB> SqlxRecorder<DB> {
+    /// The number of recorded events which haven't been written yet
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Return true if every recorded event was written
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty() && self.task.is_none()
+    }
+
+    /// Return true while a batch is being written
+    pub fn is_writing(&self) -> bool {
+        self.task.is_some()
+    }
+
+    /// Write the recorded events on the next frame, instead of waiting for
+    /// the interval
+    pub fn write_now(&mut self) {
+        self.written = Instant::now() - self.interval;
+    }
+}
+
+impl<DB> SqlxRecorder<DB>
+where
+    DB: Database + Sync,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+{
+    /// A [`System`] finishing the running batch, and writing the next one
+    /// once the interval elapsed or the batch is full
+    pub fn handle_recorder(
+        database: Res<SqlxDatabase<DB>>,
+        mut recorder: ResMut<Self>,
+    ) {
+        if let Some(task) = &mut recorder.task {
+            let Some(result) = block_on(future::poll_once(task)) else {
+                return;
+            };
+            recorder.task = None;
+            if let Err(err) = result {
+                warn!("failed to write recorded events: {err}");
+            }
+        }
+        let full = recorder.buffer.len() >= recorder.batch_size;
+        if recorder.buffer.is_empty()
+            || !full && recorder.written.elapsed() < recorder.interval
+        {
+            return;
+        }
+        recorder.written = Instant::now();
+
+        let stmts = std::mem::take(&mut recorder.buffer);
+        let pool = database.pool.clone();
+        let task = AsyncComputeTaskPool::get().spawn(write(stmts, pool));
+        recorder.task = Some(task);
+    }
+}
+
+impl<DB> fmt::Debug for SqlxRecorder<DB> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SqlxRecorder")
+            .field("interval", &self.interval)
+            .field("batch_size", &self.batch_size)
+            .field("buffered", &self.len())
+            .field("writing", &self.is_writing())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Execute `stmts` in one transaction, returning how many were executed
+async fn write<DB>(
+    stmts: Vec<SqlxStatement>,
+    pool: Pool<DB>,
+) -> Result<u64, Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+{
+    let mut tx = pool.begin().await?;
+    for stmt in &stmts {
+        let sql = stmt.sql::<DB>();
+        let mut query = sqlx::query(&sql);
+        for value in stmt.binds() {
+            query = query.bind(value);
+        }
+        query.execute(&mut *tx).await?;
+    }
+    tx.commit().await?;
+    Ok(stmts.len() as u64)
+}
+
+/// Send a recorded event, deserialized from its payload
+type SqlxReplayFn = fn(&mut World, &str) -> Result<(), serde_json::Error>;
+
+/// A recorded event: its name, tick and payload
+type SqlxRecordedEvent = (String, i64, String);
+
+/// A [`Plugin`] replaying the events of the selected types recorded by a
+/// [`SqlxRecorderPlugin`]
+///
+/// Each event type is selected with [`Self::with`], and must be added to
+/// the app. Nothing is replayed until [`SqlxReplay::play`] is called. The
+/// events are sent in [`First`], so they're read in [`Update`] on the frame
+/// they're due.
+///
+/// See the [`recorder`](crate::recorder) module for more information.
+pub struct SqlxReplayPlugin<DB> {
+    events: Vec<(&'static str, SqlxReplayFn)>,
+    _db: PhantomData<fn() -> DB>,
+}
+
+impl<DB> Default for SqlxReplayPlugin<DB> {
+    fn default() -> Self {
+        SqlxReplayPlugin { events: Vec::new(), _db: PhantomData }
+    }
+}
+
+impl<DB> SqlxReplayPlugin<DB> {
+    /// Replay the events `E`
+    pub fn with<E: Event + DeserializeOwned>(mut self) -> Self {
+        self.events.push((std::any::type_name::<E>(), |world, payload| {
+            world.send_event(serde_json::from_str::<E>(payload)?);
+            Ok(())
+        }));
+        self
+    }
+}
+
+impl<DB> Plugin for SqlxReplayPlugin<DB>
+where
+    DB: Database + Sync,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+    for<'r> String: Decode<'r, DB> + Type<DB>,
+    for<'r> i64: Decode<'r, DB> + Type<DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SqlxReplay::<DB> {
+            events: self.events.clone(),
+            from: None,
+            speed: 1.,
+            loading: None,
+            recorded: VecDeque::new(),
+            start: 0,
+            elapsed: 0.,
+            _db: PhantomData,
+        });
+        app.add_systems(First, SqlxReplay::<DB>::handle_replay);
+    }
+}
+
+/// A [`Resource`] controlling the [`SqlxReplayPlugin`] of `DB`
+#[derive(Resource)]
+pub struct SqlxReplay<DB> {
+    events: Vec<(&'static str, SqlxReplayFn)>,
+    /// The tick to start loading from, once play was requested
+    from: Option<i64>,
+    speed: f32,
+    loading: Option<Task<Result<Vec<SqlxRecordedEvent>, Error>>>,
+    recorded: VecDeque<SqlxRecordedEvent>,
+    /// The tick of the first replayed event
+    start: i64,
+    /// The ticks replayed so far, scaled by the speed
+    elapsed: f32,
+    _db: PhantomData<fn() -> DB>,
+}
+
+impl<DB> SqlxReplay<DB> {
+    /// Replay every recorded event, `speed` times faster than they were
+    /// recorded
+    pub fn play(&mut self, speed: f32) {
+        self.play_from(0, speed);
+    }
+
+    /// Replay the events recorded since `tick`, `speed` times faster than
+    /// they were recorded
+    ///
+    /// A running replay is stopped first.
+    pub fn play_from(&mut self, tick: u32, speed: f32) {
+        self.stop();
+        self.from = Some(tick.into());
+        self.speed = speed;
+    }
+
+    /// Stop replaying, dropping the events which weren't sent yet
+    pub fn stop(&mut self) {
+        self.from = None;
+        self.loading = None;
+        self.recorded.clear();
+    }
+
+    /// Return true until every loaded event was sent
+    pub fn is_playing(&self) -> bool {
+        self.from.is_some()
+    }
+
+    /// Return true while the recorded events are being loaded
+    pub fn is_loading(&self) -> bool {
+        self.loading.is_some()
+    }
+
+    /// The number of loaded events which weren't sent yet
+    pub fn remaining(&self) -> usize {
+        self.recorded.len()
+    }
+}
+
+impl<DB> SqlxReplay<DB>
+where
+    DB: Database + Sync,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+    for<'r> String: Decode<'r, DB> + Type<DB>,
+    for<'r> i64: Decode<'r, DB> + Type<DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    /// An exclusive [`System`] loading the recorded events once play was
+    /// requested, and sending the ones which are due each frame
+    pub fn handle_replay(world: &mut World) {
+        world.resource_scope(|world, mut replay: Mut<Self>| {
+            let Some(from) = replay.from else {
+                return;
+            };
+            if replay.loading.is_none() && replay.recorded.is_empty() {
+                let pool = world.resource::<SqlxDatabase<DB>>().pool.clone();
+                let names = replay.events.iter().map(|(name, _)| *name);
+                let loading = load_events(from, names.collect(), pool);
+                replay.loading =
+                    Some(AsyncComputeTaskPool::get().spawn(loading));
+            }
+            if let Some(loading) = &mut replay.loading {
+                let Some(result) = block_on(future::poll_once(loading)) else {
+                    return;
+                };
+                replay.loading = None;
+                match result {
+                    Ok(recorded) if !recorded.is_empty() => {
+                        replay.start = recorded[0].1;
+                        replay.elapsed = 0.;
+                        replay.recorded = recorded.into();
+                    }
+                    Ok(_) => {
+                        replay.from = None;
+                        return;
+                    }
+                    Err(err) => {
+                        warn!("failed to load recorded events: {err}");
+                        replay.from = None;
+                        return;
+                    }
+                }
+            } else {
+                replay.elapsed += replay.speed;
+            }
+
+            let due = replay.start + replay.elapsed as i64;
+            while replay.recorded.front().is_some_and(|(_, t, _)| *t <= due) {
+                let (name, _, payload) = replay.recorded.pop_front().unwrap();
+                let send = replay.events.iter().find(|(n, _)| *n == name);
+                if let Some((_, send)) = send {
+                    if let Err(err) = send(world, &payload) {
+                        warn!("failed to replay {name}: {err}");
+                    }
+                }
+            }
+            if replay.recorded.is_empty() {
+                replay.from = None;
+            }
+        });
+    }
+}
+
+impl<DB> fmt::Debug for SqlxReplay<DB> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SqlxReplay")
+            .field("from", &self.from)
+            .field("speed", &self.speed)
+            .field("loading", &self.is_loading())
+            .field("remaining", &self.remaining())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The events named `names` recorded since the tick `from`, in order
+async fn load_events<DB>(
+    from: i64,
+    names: Vec<&'static str>,
+    pool: Pool<DB>,
+) -> Result<Vec<SqlxRecordedEvent>, Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+    for<'r> String: Decode<'r, DB> + Type<DB>,
+    for<'r> i64: Decode<'r, DB> + Type<DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    if names.is_empty() {
+        return Ok(Vec::new());
+    }
+    let placeholders: Vec<_> =
+        (2..names.len() + 2).map(placeholder::<DB>).collect();
+    let sql = format!(
+        "SELECT name, tick, payload FROM {EVENT_TABLE}
+            WHERE tick >= {} AND name IN ({})
+            ORDER BY tick, recorded_at",
+        placeholder::<DB>(1),
+        placeholders.join(", "),
+    );
+    let mut query = sqlx::query_as(&sql).bind(SqlxValue::from(from));
+    for name in names {
+        query = query.bind(SqlxValue::from(name));
+    }
+    query.fetch_all(&pool).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::tasks::TaskPool;
+    use serde::Deserialize;
+    use sqlx::Sqlite;
+
+    #[derive(Event, Serialize)]
+    struct Scored {
+        points: u32,
+    }
+
+    #[test]
+    fn test_recorder() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let mut app = App::new();
+        app.add_plugins(SqlxPlugin::<Sqlite, SqlxDummy>::from_url(url));
+        app.add_event::<Scored>();
+        app.add_plugins(
+            SqlxRecorderPlugin::<Sqlite>::every(Duration::from_secs(3600))
+                .with::<Scored>()
+                .with_batch_size(2),
+        );
+        let pool = app.world().resource::<SqlxDatabase<Sqlite>>().pool.clone();
+        let name = std::any::type_name::<Scored>();
+        let sql = format!("DELETE FROM {EVENT_TABLE} WHERE name = ?");
+        block_on(sqlx::query(&sql).bind(name).execute(&pool)).unwrap();
+
+        app.world_mut().send_event(Scored { points: 10 });
+        app.world_mut().send_event(Scored { points: 20 });
+        app.update();
+        let mut tries = 0;
+        while !app.world().resource::<SqlxRecorder<Sqlite>>().is_empty()
+            && tries < 1000
+        {
+            app.update();
+            tries += 1;
+        }
+
+        let sql = format!(
+            "SELECT payload FROM {EVENT_TABLE} WHERE name = ? ORDER BY payload"
+        );
+        let payloads: Vec<String> =
+            block_on(sqlx::query_scalar(&sql).bind(name).fetch_all(&pool))
+                .unwrap();
+        assert_eq!(vec![r#"{"points":10}"#, r#"{"points":20}"#], payloads);
+    }
+
+    #[derive(Event, Deserialize)]
+    struct Moved {
+        step: u32,
+    }
+
+    /// The steps received, and the frame they were received on
+    #[derive(Resource, Default)]
+    struct Received(Vec<(u32, u32)>);
+
+    fn receive(
+        mut frame: Local<u32>,
+        mut received: ResMut<Received>,
+        mut moves: EventReader<Moved>,
+    ) {
+        *frame += 1;
+        received.0.extend(moves.read().map(|moved| (moved.step, *frame)));
+    }
+
+    #[test]
+    fn test_replay() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let mut app = App::new();
+        app.add_plugins(SqlxPlugin::<Sqlite, SqlxDummy>::from_url(url));
+        app.add_plugins(SqlxRecorderPlugin::<Sqlite>::every(Duration::ZERO));
+        app.add_event::<Moved>();
+        app.add_plugins(SqlxReplayPlugin::<Sqlite>::default().with::<Moved>());
+        app.init_resource::<Received>();
+        app.add_systems(Update, receive);
+
+        let pool = app.world().resource::<SqlxDatabase<Sqlite>>().pool.clone();
+        let name = std::any::type_name::<Moved>();
+        block_on(async {
+            let sql = format!("DELETE FROM {EVENT_TABLE} WHERE name = ?");
+            sqlx::query(&sql).bind(name).execute(&pool).await.unwrap();
+            let sql = format!(
+                "INSERT INTO {EVENT_TABLE} VALUES \
+                 (?, 100, '{{\"step\":1}}', '2024-01-01T00:00:00Z'), \
+                 (?, 100, '{{\"step\":2}}', '2024-01-01T00:00:01Z'), \
+                 (?, 104, '{{\"step\":3}}', '2024-01-01T00:00:02Z')"
+            );
+            let query = sqlx::query(&sql).bind(name).bind(name).bind(name);
+            query.execute(&pool).await.unwrap();
+        });
+
+        app.world_mut().resource_mut::<SqlxReplay<Sqlite>>().play_from(50, 2.);
+        let mut tries = 0;
+        while app.world().resource::<Received>().0.len() < 3 && tries < 1000 {
+            app.update();
+            tries += 1;
+        }
+        assert!(!app.world().resource::<SqlxReplay<Sqlite>>().is_playing());
+        let received = &app.world().resource::<Received>().0;
+        let steps: Vec<_> = received.iter().map(|(step, _)| *step).collect();
+        assert_eq!(vec![1, 2, 3], steps);
+        assert_eq!(received[0].1, received[1].1);
+        assert_eq!(received[0].1 + 2, received[2].1);
+    }
+}
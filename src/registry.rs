@@ -0,0 +1,295 @@
+use crate::*;
+use bevy::ecs::system::BoxedSystem;
+use bevy::prelude::*;
+use sqlx::Type;
+use sqlx::{ColumnIndex, Database, Decode, Encode, Executor, IntoArguments};
+use std::fmt;
+use std::marker::PhantomData;
+
+/// A [`Resource`] holding the systems of every [`SqlxPlugin`] using `DB`
+///
+/// Instead of adding each component's systems to the schedule, a plugin
+/// registers them here, and a single [`Self::handle_events`] and
+/// [`Self::handle_tasks`] pair runs them for every component type of the
+/// database, along with a single [`Self::flush_on_exit`] in [`Last`]. Order
+/// your own systems against these:
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::Sqlite;
+/// # use bevy_sqlx::SqlxRegistry;
+/// # fn my_system() {}
+/// # let mut app = App::new();
+/// app.add_systems(
+///     Update,
+///     my_system.before(SqlxRegistry::<Sqlite>::handle_events),
+/// );
+/// ```
+#[derive(Resource)]
+pub struct SqlxRegistry<DB> {
+    components: Vec<&'static str>,
+    events: Vec<BoxedSystem>,
+    tasks: Vec<BoxedSystem>,
+    exits: Vec<BoxedSystem>,
+    counts: Vec<fn(&World) -> usize>,
+    resyncs: Vec<BoxedSystem>,
+    _db: PhantomData<fn() -> DB>,
+}
+
+impl<DB> Default for SqlxRegistry<DB> {
+    fn default() -> Self {
+        SqlxRegistry {
+            components: Vec::new(),
+            events: Vec::new(),
+            tasks: Vec::new(),
+            exits: Vec::new(),
+            counts: Vec::new(),
+            resyncs: Vec::new(),
+            _db: PhantomData,
+        }
+    }
+}
+
+impl<DB> fmt::Debug for SqlxRegistry<DB> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SqlxRegistry")
+            .field("components", &self.components)
+            .finish()
+    }
+}
+
+impl<DB: Database + Sync> SqlxRegistry<DB> {
+    /// Register the systems of `C`, adding this registry and its systems to
+    /// the app for the first component type of `DB`
+    pub(crate) fn register<C>(app: &mut App)
+    where
+        C: SqlxComponent<DB::Row>,
+        for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+        for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+        for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+        for<'r> SqlxValue: Decode<'r, DB>,
+        usize: ColumnIndex<DB::Row>,
+    {
+        if !app.world().contains_resource::<Self>() {
+            app.init_resource::<Self>();
+            app.init_resource::<SqlxSwap<DB>>();
+            app.add_event::<SqlxSwapDatabase<DB>>();
+            app.add_event::<SqlxSwapStatus<DB>>();
+            app.add_systems(
+                Update,
+                SqlxSwap::<DB>::handle_swap.before(Self::handle_events),
+            );
+            app.init_resource::<SqlxSnapshots<DB>>();
+            app.add_event::<SqlxSnapshotLoaded<DB>>();
+            app.add_systems(
+                Update,
+                SqlxSnapshots::<DB>::handle_snapshots
+                    .before(Self::handle_events),
+            );
+            app.add_systems(Update, Self::handle_events);
+            app.add_systems(Update, Self::handle_tasks);
+            app.add_systems(Last, Self::flush_on_exit);
+            app.init_resource::<SqlxConnectionState<DB>>();
+            app.add_event::<SqlxConnected<DB>>();
+            app.add_event::<SqlxDisconnected<DB>>();
+            app.add_systems(
+                Update,
+                SqlxConnectionState::<DB>::handle_connection
+                    .after(Self::handle_tasks),
+            );
+        }
+
+        let reads = SqlxSyncMode::of::<DB, C>(app.world()).reads();
+        let world = app.world_mut();
+        let mut events = Vec::new();
+        let mut resyncs = Vec::new();
+        if reads {
+            events.push(boxed(
+                world,
+                SqlxSubscription::<DB, C>::handle_subscriptions,
+            ));
+            resyncs.push(boxed(world, SqlxSubscription::<DB, C>::resync));
+        }
+        events.push(boxed(world, SqlxSchedules::<DB, C>::handle_schedules));
+        events.push(boxed(world, SqlxEvent::<DB, C>::handle_events));
+        let tasks = [
+            boxed(world, SqlxTasks::<DB, C>::handle_tasks),
+            boxed(world, SqlxTasks::<DB, C>::handle_reconnect),
+            boxed(world, SqlxErrorHandler::handle_errors::<DB, C>),
+            boxed(world, SqlxActivity::handle_activity::<DB, C>),
+            boxed(world, SqlxEventRegistry::<DB, C>::handle_registry),
+        ];
+        let exit = boxed(world, SqlxTasks::<DB, C>::flush_on_exit);
+        let mut registry = world.resource_mut::<Self>();
+        registry.components.push(std::any::type_name::<C>());
+        registry.events.extend(events);
+        registry.tasks.extend(tasks);
+        registry.exits.push(exit);
+        registry
+            .counts
+            .push(|world| world.resource::<SqlxTasks<DB, C>>().count());
+        registry.resyncs.extend(resyncs);
+    }
+
+    /// An exclusive [`System`] running [`SqlxEvent::handle_events`] for each
+    /// registered component type
+    pub fn handle_events(world: &mut World) {
+        world.resource_scope(|world, mut registry: Mut<Self>| {
+            for system in &mut registry.events {
+                system.run((), world);
+            }
+        });
+    }
+
+    /// An exclusive [`System`] running [`SqlxTasks::handle_tasks`] for each
+    /// registered component type
+    pub fn handle_tasks(world: &mut World) {
+        world.resource_scope(|world, mut registry: Mut<Self>| {
+            for system in &mut registry.tasks {
+                system.run((), world);
+            }
+        });
+    }
+
+    /// An exclusive [`System`] running [`SqlxTasks::flush_on_exit`] for each
+    /// registered component type
+    pub fn flush_on_exit(world: &mut World) {
+        world.resource_scope(|world, mut registry: Mut<Self>| {
+            for system in &mut registry.exits {
+                system.run((), world);
+            }
+        });
+    }
+
+    /// Return true if no registered component type has a running task
+    pub(crate) fn is_idle(&self, world: &World) -> bool {
+        self.counts.iter().all(|count| count(world) == 0)
+    }
+
+    /// The number of running tasks of each registered component type, by
+    /// its type name
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use sqlx::Sqlite;
+    /// # use bevy_sqlx::SqlxRegistry;
+    /// fn loading(world: &World) -> bool {
+    ///     let registry = world.resource::<SqlxRegistry<Sqlite>>();
+    ///     registry.counts(world).any(|(_, count)| count > 0)
+    /// }
+    /// ```
+    pub fn counts<'w>(
+        &'w self,
+        world: &'w World,
+    ) -> impl Iterator<Item = (&'static str, usize)> + 'w {
+        let counts = self.counts.iter().map(|count| count(world));
+        self.components.iter().copied().zip(counts)
+    }
+
+    /// Send the query of every [`SqlxSubscription`] again
+    pub(crate) fn resync(&mut self, world: &mut World) {
+        for system in &mut self.resyncs {
+            system.run((), world);
+        }
+    }
+
+    /// The number of component types registered
+    pub fn len(&self) -> usize {
+        self.components.len()
+    }
+
+    /// Return true if no component types are registered
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+}
+
+fn boxed<M>(
+    world: &mut World,
+    system: impl IntoSystem<(), (), M>,
+) -> BoxedSystem {
+    let mut system = Box::new(IntoSystem::into_system(system));
+    system.initialize(world);
+    system
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::prelude::*;
+    use bevy::tasks::{AsyncComputeTaskPool, TaskPool};
+    use sqlx::{FromRow, Sqlite};
+
+    #[derive(Component, FromRow, Debug)]
+    struct Foo {
+        id: u32,
+    }
+
+    impl PrimaryKey for Foo {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    #[test]
+    fn test_registry() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let mut app = App::new();
+        app.add_plugins(SqlxPlugin::<Sqlite, Foo>::from_url(url));
+        app.add_plugins(SqlxPlugin::<Sqlite, SqlxDummy>::from_url(url));
+        assert_eq!(2, app.world().resource::<SqlxRegistry<Sqlite>>().len());
+
+        let schedule = app.get_schedule(Update).unwrap();
+        let systems = schedule
+            .graph()
+            .systems()
+            .filter(|(_, system, _)| system.name().contains("SqlxRegistry"));
+        assert_eq!(2, systems.count());
+
+        let select = SqlxEvent::<Sqlite, Foo>::query("SELECT 1 AS id")
+            .with_label("select");
+        let aggregate = SqlxEvent::<Sqlite, SqlxDummy>::aggregate("SELECT 2");
+        app.world_mut().send_event(select);
+        app.world_mut().send_event(aggregate);
+
+        // Spawn the tasks without handling them yet.
+        let world = app.world_mut();
+        world.run_system_once(SqlxRegistry::<Sqlite>::handle_events);
+        let registry = world.resource::<SqlxRegistry<Sqlite>>();
+        let counts: Vec<_> = registry.counts(world).collect();
+        assert_eq!(2, counts.len());
+        assert!(counts.iter().all(|(_, count)| *count == 1));
+        let tasks = world.resource::<SqlxTasks<Sqlite, Foo>>();
+        let pending: Vec<_> = tasks.pending().collect();
+        assert_eq!(1, pending.len());
+        assert_eq!(Some("select"), pending[0].label.as_deref());
+        assert!(!pending[0].will_sync);
+
+        let (mut foos, mut dummies) = (None, None);
+        let mut tries = 0;
+        while (foos.is_none() || dummies.is_none()) && tries < 1000 {
+            app.update();
+            let events =
+                app.world().resource::<Events<SqlxEventStatus<Sqlite, Foo>>>();
+            for status in events.iter_current_update_events() {
+                if let SqlxEventStatus::Return(_, components) = status {
+                    foos = Some(components.len());
+                }
+            }
+            let events = app
+                .world()
+                .resource::<Events<SqlxEventStatus<Sqlite, SqlxDummy>>>();
+            for status in events.iter_current_update_events() {
+                if let SqlxEventStatus::Scalar(_, value) = status {
+                    dummies = Some(value.clone());
+                }
+            }
+            tries += 1;
+        }
+        assert_eq!(Some(1), foos);
+        assert_eq!(Some(SqlxValue::Int(2)), dummies);
+    }
+}
@@ -0,0 +1,457 @@
+use crate::*;
+use bevy::ecs::world::Command;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use sqlx::{Database, Executor, IntoArguments};
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+/// A component whose rows reference a row of `P` with a foreign key
+///
+/// ### Example
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::FromRow;
+/// # use bevy_sqlx::*;
+/// #[derive(Component, FromRow)]
+/// struct Foo { id: u32 }
+/// # impl PrimaryKey for Foo {
+/// #     type Column = u32;
+/// #     fn primary_key(&self) -> Self::Column { self.id }
+/// # }
+/// #[derive(Component, FromRow)]
+/// struct Bar { id: u32, foo_id: u32 }
+/// # impl PrimaryKey for Bar {
+/// #     type Column = u32;
+/// #     fn primary_key(&self) -> Self::Column { self.id }
+/// # }
+///
+/// impl SqlxBelongsTo<Foo> for Bar {
+///     fn foreign_key_name() -> &'static str {
+///         "foo_id"
+///     }
+///
+///     fn foreign_key(&self) -> u32 {
+///         self.foo_id
+///     }
+/// }
+/// ```
+pub trait SqlxBelongsTo<P: PrimaryKey> {
+    /// The name of the column referencing `P`'s primary key
+    fn foreign_key_name() -> &'static str;
+
+    /// The primary key of this row's parent
+    fn foreign_key(&self) -> P::Column;
+}
+
+/// An [`Event`] sent when a synced `C` references a `P` which isn't loaded
+///
+/// The entity is parented once a `P` with the key is spawned.
+#[derive(Event)]
+pub struct SqlxOrphanRow<C: SqlxBelongsTo<P>, P: PrimaryKey> {
+    pub entity: Entity,
+    pub parent_key: P::Column,
+    _c: PhantomData<fn() -> C>,
+}
+
+/// A [`Plugin`] making each `C` entity a child of the `P` entity its row
+/// belongs to, see [`SqlxBelongsTo`]
+///
+/// Whenever a `C` is spawned or changes, the `P` with its
+/// [`SqlxBelongsTo::foreign_key`] is looked up by primary key, and set as
+/// its [`Parent`]. When there is none, a [`SqlxOrphanRow`] is sent instead,
+/// and it's parented once that `P` is spawned.
+///
+/// There's no `#[sqlx(belongs_to(...))]` attribute, [`SqlxBelongsTo`] is
+/// implemented by hand.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::{FromRow, Sqlite};
+/// # use bevy_sqlx::*;
+/// # #[derive(Component, FromRow)]
+/// # struct Foo { id: u32 }
+/// # impl PrimaryKey for Foo {
+/// #     type Column = u32;
+/// #     fn primary_key(&self) -> Self::Column { self.id }
+/// # }
+/// # #[derive(Component, FromRow)]
+/// # struct Bar { id: u32, foo_id: u32 }
+/// # impl PrimaryKey for Bar {
+/// #     type Column = u32;
+/// #     fn primary_key(&self) -> Self::Column { self.id }
+/// # }
+/// # impl SqlxBelongsTo<Foo> for Bar {
+/// #     fn foreign_key_name() -> &'static str { "foo_id" }
+/// #     fn foreign_key(&self) -> u32 { self.foo_id }
+/// # }
+/// let url = "sqlite:db/sqlite.db";
+/// App::new()
+///     .add_plugins(SqlxPlugin::<Sqlite, Foo>::from_url(url))
+///     .add_plugins(SqlxPlugin::<Sqlite, Bar>::from_url(url))
+///     .add_plugins(SqlxBelongsToPlugin::<Sqlite, Bar, Foo>::default());
+/// ```
+pub struct SqlxBelongsToPlugin<DB, C, P> {
+    _db: PhantomData<DB>,
+    _c: PhantomData<C>,
+    _p: PhantomData<P>,
+}
+
+impl<DB, C, P> Default for SqlxBelongsToPlugin<DB, C, P> {
+    fn default() -> Self {
+        SqlxBelongsToPlugin {
+            _db: PhantomData,
+            _c: PhantomData,
+            _p: PhantomData,
+        }
+    }
+}
+
+impl<DB, C, P> Plugin for SqlxBelongsToPlugin<DB, C, P>
+where
+    DB: Database + Sync,
+    C: SqlxComponent<DB::Row> + SqlxBelongsTo<P>,
+    P: SqlxComponent<DB::Row>,
+    P::Column: Eq + Hash + 'static,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    fn build(&self, app: &mut App) {
+        app.add_event::<SqlxOrphanRow<C, P>>();
+        app.add_systems(
+            Update,
+            handle_relations::<C, P>.after(SqlxRegistry::<DB>::handle_tasks),
+        );
+    }
+}
+
+/// A [`Command`] loading the `C` rows which belong to a `P` entity, from
+/// [`SqlxEvent::load_children`]
+pub struct SqlxLoadChildren<DB, C, P> {
+    parent: Entity,
+    _db: PhantomData<fn() -> DB>,
+    _c: PhantomData<fn() -> C>,
+    _p: PhantomData<fn() -> P>,
+}
+
+impl<DB, C, P> Command for SqlxLoadChildren<DB, C, P>
+where
+    DB: Database + Sync,
+    C: SqlxComponent<DB::Row> + SqlxBelongsTo<P> + ToRow,
+    P: Component + PrimaryKey,
+    P::Column: Into<SqlxValue>,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    fn apply(self, world: &mut World) {
+        let Some(parent) = world.get::<P>(self.parent) else {
+            warn!("{} has no {}", self.parent, std::any::type_name::<P>());
+            return;
+        };
+        let stmt = SqlxStatement::select(C::table())
+            .filter(C::foreign_key_name(), parent.primary_key());
+        world.send_event(SqlxEvent::<DB, C>::statement_sync(stmt));
+    }
+}
+
+impl<DB, C> SqlxEvent<DB, C>
+where
+    DB: Database + Sync,
+    C: SqlxComponent<DB::Row> + ToRow,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    /// Construct a [`Command`] selecting the rows of `C` which belong to the
+    /// `P` component of `parent`, and syncing them
+    ///
+    /// Relations can be loaded once they're needed, instead of with their
+    /// parents. With a [`SqlxBelongsToPlugin`], the synced components are
+    /// spawned as children of `parent`. Nothing is loaded if `parent` has
+    /// no `P`.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use sqlx::{FromRow, Sqlite};
+    /// # use bevy_sqlx::*;
+    /// # #[derive(Component, FromRow)]
+    /// # struct Foo { id: u32 }
+    /// # impl PrimaryKey for Foo {
+    /// #     type Column = u32;
+    /// #     fn primary_key(&self) -> Self::Column { self.id }
+    /// # }
+    /// # #[derive(Component, FromRow)]
+    /// # struct Bar { id: u32, foo_id: u32 }
+    /// # impl PrimaryKey for Bar {
+    /// #     type Column = u32;
+    /// #     fn primary_key(&self) -> Self::Column { self.id }
+    /// # }
+    /// # impl ToRow for Bar {
+    /// #     fn table() -> &'static str { "bars" }
+    /// #     fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+    /// #         vec![("foo_id", self.foo_id.into())]
+    /// #     }
+    /// # }
+    /// # impl SqlxBelongsTo<Foo> for Bar {
+    /// #     fn foreign_key_name() -> &'static str { "foo_id" }
+    /// #     fn foreign_key(&self) -> u32 { self.foo_id }
+    /// # }
+    /// fn open(mut commands: Commands, chests: Query<Entity, Added<Foo>>) {
+    ///     for chest in &chests {
+    ///         commands.add(
+    ///             SqlxEvent::<Sqlite, Bar>::load_children::<Foo>(chest),
+    ///         );
+    ///     }
+    /// }
+    /// ```
+    pub fn load_children<P>(parent: Entity) -> SqlxLoadChildren<DB, C, P>
+    where
+        C: SqlxBelongsTo<P>,
+        P: PrimaryKey,
+    {
+        SqlxLoadChildren {
+            parent,
+            _db: PhantomData,
+            _c: PhantomData,
+            _p: PhantomData,
+        }
+    }
+}
+
+/// The `P` entities by primary key, and the orphaned `C`s waiting for them
+struct SqlxRelationIndex<C, P: PrimaryKey> {
+    parents: HashMap<P::Column, Entity>,
+    keys: HashMap<Entity, P::Column>,
+    orphans: HashMap<P::Column, Vec<Entity>>,
+    _c: PhantomData<fn() -> C>,
+}
+
+impl<C, P: PrimaryKey> Default for SqlxRelationIndex<C, P> {
+    fn default() -> Self {
+        SqlxRelationIndex {
+            parents: HashMap::new(),
+            keys: HashMap::new(),
+            orphans: HashMap::new(),
+            _c: PhantomData,
+        }
+    }
+}
+
+impl<C, P> SqlxRelationIndex<C, P>
+where
+    P: PrimaryKey,
+    P::Column: Eq + Hash,
+{
+    /// Index `entity` by `key`, returning true if it wasn't already
+    fn insert(&mut self, entity: Entity, key: P::Column) -> bool {
+        match self.keys.insert(entity, key.clone()) {
+            Some(previous) if previous == key => return false,
+            Some(previous) => self.remove_key(entity, &previous),
+            None => {}
+        }
+        self.parents.insert(key, entity);
+        true
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        if let Some(key) = self.keys.remove(&entity) {
+            self.remove_key(entity, &key);
+        }
+    }
+
+    fn remove_key(&mut self, entity: Entity, key: &P::Column) {
+        if self.parents.get(key) == Some(&entity) {
+            self.parents.remove(key);
+        }
+    }
+}
+
+/// Parent changed `C`s, and the orphans of each spawned `P`
+fn handle_relations<C, P>(
+    mut commands: Commands,
+    mut index: Local<SqlxRelationIndex<C, P>>,
+    changed: Query<(Entity, &C, Option<&Parent>), Changed<C>>,
+    children: Query<(&C, Option<&Parent>)>,
+    parents: Query<(Entity, &P), Changed<P>>,
+    mut removed: RemovedComponents<P>,
+    mut orphans: EventWriter<SqlxOrphanRow<C, P>>,
+) where
+    C: Component + SqlxBelongsTo<P>,
+    P: Component + PrimaryKey,
+    P::Column: Eq + Hash + 'static,
+{
+    for entity in removed.read() {
+        index.remove(entity);
+    }
+    let mut spawned = Vec::new();
+    for (entity, parent) in &parents {
+        let key = parent.primary_key();
+        if index.insert(entity, key.clone()) {
+            spawned.push(key);
+        }
+    }
+
+    for (entity, child, current) in &changed {
+        let key = child.foreign_key();
+        match index.parents.get(&key) {
+            Some(&parent) if current.map(Parent::get) != Some(parent) => {
+                commands.entity(entity).set_parent(parent);
+            }
+            Some(_) => {}
+            None => {
+                index.orphans.entry(key.clone()).or_default().push(entity);
+                orphans.send(SqlxOrphanRow {
+                    entity,
+                    parent_key: key,
+                    _c: PhantomData,
+                });
+            }
+        }
+    }
+
+    for key in spawned {
+        let Some(waiting) = index.orphans.remove(&key) else {
+            continue;
+        };
+        let parent = index.parents[&key];
+        for entity in waiting {
+            // The orphan may have been despawned, or changed its key since.
+            let Ok((child, current)) = children.get(entity) else {
+                continue;
+            };
+            if child.foreign_key() == key && current.is_none() {
+                commands.entity(entity).set_parent(parent);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{block_on, AsyncComputeTaskPool, TaskPool};
+    use sqlx::{FromRow, Sqlite};
+
+    #[derive(Component, FromRow, Debug)]
+    struct Foo {
+        id: u32,
+    }
+
+    impl PrimaryKey for Foo {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    #[derive(Component, FromRow, Debug)]
+    struct Bar {
+        id: u32,
+        foo_id: u32,
+    }
+
+    impl PrimaryKey for Bar {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    impl SqlxBelongsTo<Foo> for Bar {
+        fn foreign_key_name() -> &'static str {
+            "foo_id"
+        }
+
+        fn foreign_key(&self) -> u32 {
+            self.foo_id
+        }
+    }
+
+    impl ToRow for Bar {
+        fn table() -> &'static str {
+            "relation_bars"
+        }
+
+        fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+            vec![("id", self.id.into()), ("foo_id", self.foo_id.into())]
+        }
+    }
+
+    fn setup_app() -> App {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let mut app = App::new();
+        app.add_plugins(SqlxPlugin::<Sqlite, Foo>::from_url(url));
+        app.add_plugins(SqlxPlugin::<Sqlite, Bar>::from_url(url));
+        app.add_plugins(SqlxBelongsToPlugin::<Sqlite, Bar, Foo>::default());
+        app
+    }
+
+    #[test]
+    fn test_belongs_to() {
+        let mut app = setup_app();
+
+        let bar = app.world_mut().spawn(Bar { id: 1, foo_id: 7 }).id();
+        app.update();
+        let events = app.world().resource::<Events<SqlxOrphanRow<Bar, Foo>>>();
+        let orphans: Vec<_> = events
+            .iter_current_update_events()
+            .map(|orphan| (orphan.entity, orphan.parent_key))
+            .collect();
+        assert_eq!(vec![(bar, 7)], orphans);
+        assert!(app.world().get::<Parent>(bar).is_none());
+
+        let foo = app.world_mut().spawn(Foo { id: 7 }).id();
+        app.update();
+        assert_eq!(foo, app.world().get::<Parent>(bar).unwrap().get());
+    }
+
+    #[test]
+    fn test_load_children() {
+        let mut app = setup_app();
+        let pool = &app.world().resource::<SqlxDatabase<Sqlite>>().pool;
+        block_on(async {
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS relation_bars \
+                 (id INTEGER PRIMARY KEY, foo_id INTEGER NOT NULL)",
+            )
+            .execute(pool)
+            .await
+            .unwrap();
+            sqlx::query("DELETE FROM relation_bars")
+                .execute(pool)
+                .await
+                .unwrap();
+            sqlx::query(
+                "INSERT INTO relation_bars VALUES (1, 3), (2, 3), (3, 4)",
+            )
+            .execute(pool)
+            .await
+            .unwrap();
+        });
+
+        let foo = app.world_mut().spawn(Foo { id: 3 }).id();
+        let load = SqlxEvent::<Sqlite, Bar>::load_children::<Foo>(foo);
+        app.world_mut().commands().add(load);
+        app.world_mut().flush();
+        let mut tries = 0;
+        let children = |app: &App| {
+            app.world()
+                .get::<Children>(foo)
+                .map_or(0, |children| children.len())
+        };
+        while children(&app) < 2 && tries < 1000 {
+            app.update();
+            tries += 1;
+        }
+
+        let mut bars = app.world_mut().query::<(&Bar, &Parent)>();
+        let mut ids: Vec<_> = bars
+            .iter(app.world())
+            .map(|(bar, parent)| (bar.id, parent.get()))
+            .collect();
+        ids.sort();
+        assert_eq!(vec![(1, foo), (2, foo)], ids);
+    }
+}
@@ -0,0 +1,433 @@
+//! Bevy [`Resource`]s persisted in the database
+//!
+//! The [`SqlxResourcePlugin`] keeps a resource in a single row, written
+//! whenever the resource changes, and reloaded when the app starts. This
+//! covers global game data, like economy totals or world clocks.
+//!
+//! A resource is either stored as JSON in the [`RESOURCE_TABLE`], with
+//! [`SqlxResourcePlugin::json`], or as the row of its own table, with
+//! [`SqlxResourcePlugin::row`].
+//!
+//! | column  | value                                   |
+//! | ------- | --------------------------------------- |
+//! | `name`  | the type name of the resource           |
+//! | `value` | the JSON serialized resource            |
+//!
+//! ### Example
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use serde::{Deserialize, Serialize};
+//! # use sqlx::Sqlite;
+//! # use bevy_sqlx::{SqlxPlugin, SqlxDummy};
+//! # use bevy_sqlx::resource::SqlxResourcePlugin;
+//! #[derive(Resource, Serialize, Deserialize, Default)]
+//! struct WorldClock {
+//!     days: u32,
+//! }
+//!
+//! let url = "sqlite:db/sqlite.db";
+//! App::new()
+//!     .add_plugins(SqlxPlugin::<Sqlite, SqlxDummy>::from_url(url))
+//!     .init_resource::<WorldClock>()
+//!     .add_plugins(SqlxResourcePlugin::<Sqlite, WorldClock>::json());
+//! ```
+use crate::*;
+use bevy::prelude::*;
+use bevy::tasks::futures_lite::future;
+use bevy::tasks::{block_on, AsyncComputeTaskPool, Task};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sqlx::{ColumnIndex, Database, Decode, Encode, Error, Executor, FromRow};
+use sqlx::{IntoArguments, Pool, Type};
+use std::future::Future;
+use std::pin::Pin;
+
+/// The name of the table JSON resources are stored in
+pub const RESOURCE_TABLE: &str = "_bevy_sqlx_resources";
+
+/// A future loading or saving a resource
+type SqlxResourceFuture<T> =
+    Pin<Box<dyn Future<Output = Result<T, Error>> + Send>>;
+
+/// How a resource is stored, given the [`SqlxTablePrefix`]
+struct SqlxResourceFormat<DB: Database, R> {
+    setup: fn(&Pool<DB>) -> Result<(), Error>,
+    load: fn(Pool<DB>, String) -> SqlxResourceFuture<Option<R>>,
+    save: fn(&R, &str) -> Result<SqlxStatement, Error>,
+}
+
+impl<DB: Database, R> Clone for SqlxResourceFormat<DB, R> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<DB: Database, R> Copy for SqlxResourceFormat<DB, R> {}
+
+/// A [`Plugin`] saving the resource `R` whenever it changes, and reloading
+/// it when the app starts
+///
+/// Add it after the [`SqlxPlugin`]. The loaded resource is inserted over
+/// the existing one. See the [`resource`](crate::resource) module for more
+/// information.
+pub struct SqlxResourcePlugin<DB: Database, R> {
+    format: SqlxResourceFormat<DB, R>,
+}
+
+impl<DB, R> SqlxResourcePlugin<DB, R>
+where
+    DB: Database,
+    R: Resource + Serialize + DeserializeOwned,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+    for<'r> String: Decode<'r, DB> + Type<DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    /// Store `R` as JSON in the [`RESOURCE_TABLE`], which is created when
+    /// the plugin is built, if it doesn't exist yet
+    pub fn json() -> Self {
+        let format = SqlxResourceFormat {
+            setup: |pool| block_on(create_resource_table(pool)),
+            load: |pool, _| {
+                Box::pin(async move {
+                    let json = load_json(name::<R>(), pool).await?;
+                    json.map(|json| serde_json::from_str(&json))
+                        .transpose()
+                        .map_err(|err| Error::Decode(err.into()))
+                })
+            },
+            save: |resource, _| {
+                let json = serde_json::to_string(resource)
+                    .map_err(|err| Error::Encode(err.into()))?;
+                let columns =
+                    vec![("name", name::<R>().into()), ("value", json.into())];
+                Ok(SqlxStatement::upsert(RESOURCE_TABLE, "name", columns))
+            },
+        };
+        SqlxResourcePlugin { format }
+    }
+}
+
+impl<DB, R> SqlxResourcePlugin<DB, R>
+where
+    DB: Database,
+    R: Resource + ToRow + PrimaryKey + for<'r> FromRow<'r, DB::Row> + Unpin,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+{
+    /// Store `R` as the single row of its [`ToRow::table`], upserted on its
+    /// primary key
+    ///
+    /// The table is prefixed with the [`SqlxTablePrefix`], if there is one.
+    pub fn row() -> Self {
+        let format = SqlxResourceFormat {
+            setup: |_| Ok(()),
+            load: |pool, prefix| {
+                Box::pin(async move {
+                    let stmt = SqlxStatement::select(R::table())
+                        .prefixed(&prefix)
+                        .limit(1);
+                    let rows = stmt.fetch_all::<DB, R, _>(&pool).await?;
+                    Ok(rows.into_iter().next())
+                })
+            },
+            save: |resource, prefix| {
+                let key = R::primary_key_name();
+                let stmt =
+                    SqlxStatement::upsert(R::table(), key, resource.to_row());
+                Ok(stmt.prefixed(prefix))
+            },
+        };
+        SqlxResourcePlugin { format }
+    }
+}
+
+impl<DB, R> Plugin for SqlxResourcePlugin<DB, R>
+where
+    DB: Database + Sync,
+    R: Resource,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+{
+    fn build(&self, app: &mut App) {
+        let pool = &app.world().resource::<SqlxDatabase<DB>>().pool;
+        (self.format.setup)(pool).unwrap();
+        app.insert_resource(SqlxResourceSync {
+            format: self.format,
+            restored: false,
+            restoring: None,
+            saving: None,
+            pending: None,
+        });
+        app.add_systems(Update, SqlxResourceSync::<DB, R>::handle_restore);
+        // Client builds never write, see `SqlxPlugin::with_client`.
+        if !cfg!(feature = "client") {
+            app.add_systems(
+                Update,
+                SqlxResourceSync::<DB, R>::handle_save
+                    .after(SqlxResourceSync::<DB, R>::handle_restore),
+            );
+        }
+    }
+}
+
+/// Create the [`RESOURCE_TABLE`] unless it already exists
+async fn create_resource_table<DB>(pool: &Pool<DB>) -> Result<(), Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    let sql = format!(
+        "CREATE TABLE IF NOT EXISTS {RESOURCE_TABLE} (
+            name    VARCHAR(255) PRIMARY KEY,
+            value   TEXT         NOT NULL
+        )"
+    );
+    sqlx::query(&sql).execute(pool).await.map(|_| ())
+}
+
+/// The name `R` is stored by
+fn name<R>() -> &'static str {
+    std::any::type_name::<R>()
+}
+
+/// The saved resource named `name`, as JSON
+async fn load_json<DB>(
+    name: &'static str,
+    pool: Pool<DB>,
+) -> Result<Option<String>, Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+    for<'r> String: Decode<'r, DB> + Type<DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    let sql = format!(
+        "SELECT value FROM {RESOURCE_TABLE} WHERE name = {}",
+        placeholder::<DB>(1),
+    );
+    sqlx::query_scalar(&sql)
+        .bind(SqlxValue::from(name))
+        .fetch_optional(&pool)
+        .await
+}
+
+/// Execute `stmt`, saving a resource
+async fn save<DB>(stmt: SqlxStatement, pool: Pool<DB>) -> Result<(), Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+{
+    let sql = stmt.sql::<DB>();
+    let mut query = sqlx::query(&sql);
+    for value in stmt.binds() {
+        query = query.bind(value);
+    }
+    query.execute(&pool).await.map(|_| ())
+}
+
+/// A [`Resource`] holding the tasks of the [`SqlxResourcePlugin`] of `R`
+#[derive(Resource)]
+pub struct SqlxResourceSync<DB: Database, R> {
+    format: SqlxResourceFormat<DB, R>,
+    restored: bool,
+    restoring: Option<Task<Result<Option<R>, Error>>>,
+    saving: Option<Task<Result<(), Error>>>,
+    /// The latest write, waiting for the previous one to be saved
+    pending: Option<SqlxStatement>,
+}
+
+impl<DB: Database, R> SqlxResourceSync<DB, R> {
+    /// Return true until the saved resource was loaded
+    pub fn is_restoring(&self) -> bool {
+        !self.restored
+    }
+
+    /// Return true while the resource is being saved
+    pub fn is_saving(&self) -> bool {
+        self.saving.is_some() || self.pending.is_some()
+    }
+}
+
+impl<DB, R> SqlxResourceSync<DB, R>
+where
+    DB: Database + Sync,
+    R: Resource,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+{
+    /// A [`System`] loading the saved resource when the app starts, and
+    /// inserting it once it's loaded
+    pub fn handle_restore(
+        mut commands: Commands,
+        database: Res<SqlxDatabase<DB>>,
+        prefix: Option<Res<SqlxTablePrefix>>,
+        mut sync: ResMut<Self>,
+    ) {
+        if sync.restored {
+            return;
+        }
+        let load = sync.format.load;
+        let restoring = sync.restoring.get_or_insert_with(|| {
+            let prefix = prefix.map(|prefix| prefix.0.clone());
+            let loading =
+                load(database.pool.clone(), prefix.unwrap_or_default());
+            AsyncComputeTaskPool::get().spawn(loading)
+        });
+        let Some(result) = block_on(future::poll_once(restoring)) else {
+            return;
+        };
+        sync.restoring = None;
+        sync.restored = true;
+        match result {
+            Ok(Some(resource)) => commands.insert_resource(resource),
+            Ok(None) => {}
+            Err(err) => warn!("failed to restore {}: {err}", name::<R>()),
+        }
+    }
+
+    /// A [`System`] saving the resource whenever it changed, once the saved
+    /// resource was restored
+    pub fn handle_save(
+        database: Res<SqlxDatabase<DB>>,
+        prefix: Option<Res<SqlxTablePrefix>>,
+        resource: Option<Res<R>>,
+        mut sync: ResMut<Self>,
+    ) {
+        if let Some(resource) = resource.filter(|r| r.is_changed()) {
+            if sync.restored {
+                let prefix = prefix.as_ref().map_or("", |prefix| &prefix.0);
+                match (sync.format.save)(&resource, prefix) {
+                    Ok(stmt) => sync.pending = Some(stmt),
+                    Err(err) => warn!("failed to save {}: {err}", name::<R>()),
+                }
+            }
+        }
+
+        if let Some(saving) = &mut sync.saving {
+            let Some(result) = block_on(future::poll_once(saving)) else {
+                return;
+            };
+            sync.saving = None;
+            if let Err(err) = result {
+                warn!("failed to save {}: {err}", name::<R>());
+            }
+        }
+        if let Some(stmt) = sync.pending.take() {
+            let saving = save(stmt, database.pool.clone());
+            sync.saving = Some(AsyncComputeTaskPool::get().spawn(saving));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::tasks::TaskPool;
+    use serde::Deserialize;
+    use sqlx::Sqlite;
+
+    #[derive(Resource, Serialize, Deserialize, Default, Debug)]
+    struct Clock {
+        days: u32,
+    }
+
+    #[derive(Resource, FromRow, Default, Debug)]
+    struct Economy {
+        id: u32,
+        gold: i64,
+    }
+
+    impl PrimaryKey for Economy {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    impl ToRow for Economy {
+        fn table() -> &'static str {
+            "resource_economies"
+        }
+
+        fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+            vec![("id", self.id.into()), ("gold", self.gold.into())]
+        }
+    }
+
+    fn setup_app() -> App {
+        let url = "sqlite:db/sqlite.db";
+        let mut app = App::new();
+        app.add_plugins(SqlxPlugin::<Sqlite, SqlxDummy>::from_url(url));
+        app.init_resource::<Clock>();
+        app.init_resource::<Economy>();
+        app.add_plugins(SqlxResourcePlugin::<Sqlite, Clock>::json());
+        app.add_plugins(SqlxResourcePlugin::<Sqlite, Economy>::row());
+        app
+    }
+
+    fn synced(app: &App) -> bool {
+        let clock = app.world().resource::<SqlxResourceSync<Sqlite, Clock>>();
+        let economy =
+            app.world().resource::<SqlxResourceSync<Sqlite, Economy>>();
+        [clock.is_restoring(), clock.is_saving()]
+            .into_iter()
+            .chain([economy.is_restoring(), economy.is_saving()])
+            .all(|busy| !busy)
+    }
+
+    fn update_until(app: &mut App, done: impl Fn(&App) -> bool) {
+        app.update();
+        let mut tries = 0;
+        while !done(app) && tries < 1000 {
+            app.update();
+            tries += 1;
+        }
+    }
+
+    #[test]
+    fn test_resource_sync() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let pool = block_on(Pool::<Sqlite>::connect("sqlite:db/sqlite.db"));
+        block_on(async {
+            let pool = pool.unwrap();
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS resource_economies \
+                 (id INTEGER PRIMARY KEY, gold INTEGER NOT NULL)",
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+            sqlx::query("DELETE FROM resource_economies")
+                .execute(&pool)
+                .await
+                .unwrap();
+            let sql = format!("DELETE FROM {RESOURCE_TABLE} WHERE name = ?");
+            let _ =
+                sqlx::query(&sql).bind(name::<Clock>()).execute(&pool).await;
+        });
+
+        let mut app = setup_app();
+        update_until(&mut app, synced);
+        app.world_mut().resource_mut::<Clock>().days = 3;
+        app.world_mut().resource_mut::<Economy>().gold = 250;
+        update_until(&mut app, synced);
+
+        let mut app = setup_app();
+        update_until(&mut app, |app| {
+            synced(app) && app.world().resource::<Clock>().days == 3
+        });
+        assert_eq!(3, app.world().resource::<Clock>().days);
+        assert_eq!(250, app.world().resource::<Economy>().gold);
+    }
+}
@@ -0,0 +1,273 @@
+use crate::*;
+use bevy::prelude::*;
+use bevy::utils::{Duration, Instant};
+use sqlx::Database;
+use std::fmt;
+use std::sync::Arc;
+
+/// Builds the event a [`SqlxSchedule`] sends each time it's due
+pub type SqlxEventFactory<DB, C> =
+    Arc<dyn Fn() -> SqlxEvent<DB, C> + Send + Sync + 'static>;
+
+/// An event sent every `interval`, like a cleanup job or the refresh of an
+/// aggregate
+///
+/// Add it with [`SqlxPlugin::with_schedule`], or at runtime with
+/// [`SqlxSchedules::insert`], under a name it's enabled and disabled by.
+/// The first event is sent once `interval` elapsed, and each one is sent
+/// without waiting for the previous one to end.
+///
+/// ```
+/// use bevy::utils::Duration;
+/// use sqlx::Sqlite;
+/// use bevy_sqlx::{SqlxEvent, SqlxDummy, SqlxPlugin, SqlxSchedule};
+///
+/// let cleanup = SqlxSchedule::every(Duration::from_secs(60), || {
+///     SqlxEvent::query("DELETE FROM sessions WHERE closed")
+/// });
+/// SqlxPlugin::<Sqlite, SqlxDummy>::from_url("sqlite:db/sqlite.db")
+///     .with_schedule("cleanup", cleanup);
+/// ```
+pub struct SqlxSchedule<DB: Database, C: SqlxComponent<DB::Row>> {
+    interval: Duration,
+    factory: SqlxEventFactory<DB, C>,
+    enabled: bool,
+    next: Option<Instant>,
+}
+
+impl<DB: Database, C: SqlxComponent<DB::Row>> SqlxSchedule<DB, C> {
+    /// Send the event built by `factory` every `interval`
+    pub fn every(
+        interval: Duration,
+        factory: impl Fn() -> SqlxEvent<DB, C> + Send + Sync + 'static,
+    ) -> Self {
+        SqlxSchedule {
+            interval,
+            factory: Arc::new(factory),
+            enabled: true,
+            next: None,
+        }
+    }
+
+    /// Start disabled, until [`SqlxSchedules::enable`] is called
+    pub fn disabled(mut self) -> Self {
+        self.enabled = false;
+        self
+    }
+
+    /// How often the event is sent
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Return true if the event is being sent
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+impl<DB: Database, C: SqlxComponent<DB::Row>> Clone for SqlxSchedule<DB, C> {
+    fn clone(&self) -> Self {
+        SqlxSchedule {
+            interval: self.interval,
+            factory: self.factory.clone(),
+            enabled: self.enabled,
+            next: self.next,
+        }
+    }
+}
+
+impl<DB: Database, C: SqlxComponent<DB::Row>> fmt::Debug
+    for SqlxSchedule<DB, C>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SqlxSchedule")
+            .field("interval", &self.interval)
+            .field("enabled", &self.enabled)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A [`Resource`] holding the [`SqlxSchedule`]s of a [`SqlxPlugin`], by
+/// name
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::Sqlite;
+/// # use bevy_sqlx::{SqlxDummy, SqlxSchedules};
+/// fn pause_cleanup(mut schedules: ResMut<SqlxSchedules<Sqlite, SqlxDummy>>) {
+///     schedules.disable("cleanup");
+/// }
+/// ```
+#[derive(Resource, Debug)]
+pub struct SqlxSchedules<DB: Database, C: SqlxComponent<DB::Row>> {
+    schedules: Vec<(&'static str, SqlxSchedule<DB, C>)>,
+}
+
+impl<DB: Database, C: SqlxComponent<DB::Row>> Default for SqlxSchedules<DB, C> {
+    fn default() -> Self {
+        SqlxSchedules { schedules: Vec::new() }
+    }
+}
+
+impl<DB: Database, C: SqlxComponent<DB::Row>> SqlxSchedules<DB, C> {
+    /// Add `schedule` as `name`, replacing the schedule with that name
+    pub fn insert(
+        &mut self,
+        name: &'static str,
+        schedule: SqlxSchedule<DB, C>,
+    ) {
+        self.remove(name);
+        self.schedules.push((name, schedule));
+    }
+
+    /// Remove the schedule `name`, returning true if there was one
+    pub fn remove(&mut self, name: &str) -> bool {
+        let len = self.schedules.len();
+        self.schedules.retain(|(other, _)| *other != name);
+        self.schedules.len() != len
+    }
+
+    /// The schedule `name`, if there's one
+    pub fn get(&self, name: &str) -> Option<&SqlxSchedule<DB, C>> {
+        self.schedules
+            .iter()
+            .find(|(other, _)| *other == name)
+            .map(|(_, schedule)| schedule)
+    }
+
+    /// The names of every schedule, in the order they were added
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.schedules.iter().map(|(name, _)| *name)
+    }
+
+    /// Send the event of the schedule `name` again, one interval from now,
+    /// returning false if there's no such schedule
+    pub fn enable(&mut self, name: &str) -> bool {
+        self.update(name, |schedule| {
+            if !schedule.enabled {
+                schedule.enabled = true;
+                schedule.next = None;
+            }
+        })
+    }
+
+    /// Stop sending the event of the schedule `name`, returning false if
+    /// there's no such schedule
+    pub fn disable(&mut self, name: &str) -> bool {
+        self.update(name, |schedule| schedule.enabled = false)
+    }
+
+    /// Send the event of the schedule `name` on the next frame, instead of
+    /// waiting for its interval, returning false if there's no such schedule
+    ///
+    /// Disabled schedules aren't sent.
+    pub fn run_now(&mut self, name: &str) -> bool {
+        self.update(name, |schedule| schedule.next = Some(Instant::now()))
+    }
+
+    fn update(
+        &mut self,
+        name: &str,
+        f: impl FnOnce(&mut SqlxSchedule<DB, C>),
+    ) -> bool {
+        let schedule =
+            self.schedules.iter_mut().find(|(other, _)| *other == name);
+        schedule.map(|(_, schedule)| f(schedule)).is_some()
+    }
+}
+
+impl<DB: Database + Sync, C: SqlxComponent<DB::Row>> SqlxSchedules<DB, C> {
+    /// A [`System`] sending the events of the enabled schedules which are
+    /// due
+    pub fn handle_schedules(
+        mut schedules: ResMut<Self>,
+        mut events: EventWriter<SqlxEvent<DB, C>>,
+    ) {
+        let now = Instant::now();
+        for (name, schedule) in &mut schedules.schedules {
+            if !schedule.enabled {
+                continue;
+            }
+            let next = *schedule.next.get_or_insert(now + schedule.interval);
+            if next > now {
+                continue;
+            }
+            schedule.next = Some(now + schedule.interval);
+            let event = (schedule.factory)();
+            if event.label().is_none() {
+                events.send(event.with_label(*name));
+            } else {
+                events.send(event);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{AsyncComputeTaskPool, TaskPool};
+    use bevy::utils::Duration;
+    use sqlx::Sqlite;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_schedule() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let sent = Arc::new(AtomicUsize::new(0));
+        let counter = sent.clone();
+        let schedule = SqlxSchedule::every(Duration::ZERO, move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            SqlxEvent::<Sqlite, SqlxDummy>::query("SELECT 1 WHERE 0")
+        });
+        let hourly = SqlxSchedule::every(Duration::from_secs(3600), || {
+            SqlxEvent::<Sqlite, SqlxDummy>::query("SELECT 1 WHERE 0")
+        });
+        let mut app = App::new();
+        app.add_plugins(
+            SqlxPlugin::<Sqlite, SqlxDummy>::from_url(url)
+                .with_schedule("refresh", schedule)
+                .with_schedule("hourly", hourly),
+        );
+
+        app.update();
+        app.update();
+        assert_eq!(2, sent.load(Ordering::SeqCst));
+        let events =
+            app.world().resource::<Events<SqlxEvent<Sqlite, SqlxDummy>>>();
+        let labels: Vec<_> = events
+            .get_reader()
+            .read(events)
+            .map(|event| event.label().map(|label| label.to_string()))
+            .collect();
+        assert_eq!(vec![Some("refresh".to_string()); 2], labels);
+
+        let mut schedules =
+            app.world_mut().resource_mut::<SqlxSchedules<Sqlite, SqlxDummy>>();
+        assert!(schedules.disable("refresh"));
+        assert!(!schedules.disable("missing"));
+        app.update();
+        assert_eq!(2, sent.load(Ordering::SeqCst));
+
+        let mut schedules =
+            app.world_mut().resource_mut::<SqlxSchedules<Sqlite, SqlxDummy>>();
+        assert!(schedules.enable("refresh"));
+        assert!(schedules.run_now("hourly"));
+        app.update();
+        assert_eq!(3, sent.load(Ordering::SeqCst));
+        let events =
+            app.world().resource::<Events<SqlxEvent<Sqlite, SqlxDummy>>>();
+        let labels: Vec<_> = events
+            .iter_current_update_events()
+            .map(|event| event.label().map(|label| label.to_string()))
+            .collect();
+        assert_eq!(
+            vec![Some("refresh".to_string()), Some("hourly".to_string())],
+            labels
+        );
+    }
+}
@@ -0,0 +1,311 @@
+//! Player sessions stored in the database
+//!
+//! The [`SqlxSessionPlugin`] keeps sessions in the [`SESSION_TABLE`], and
+//! systems log players in and out with the [`SqlxSessions`] system param.
+//! Results are sent as [`SqlxSessionEvent`]s.
+//!
+//! | column       | value                                             |
+//! | ------------ | ------------------------------------------------- |
+//! | `token`      | a random 128-bit token, hex encoded               |
+//! | `player_id`  | the id of the player the session belongs to       |
+//! | `expires_at` | the expiry of the session, in seconds since epoch |
+//!
+//! ### Example
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use bevy::utils::Duration;
+//! # use sqlx::Sqlite;
+//! # use bevy_sqlx::session::*;
+//! fn login(mut sessions: SqlxSessions<Sqlite>) {
+//!     sessions.login(42, Duration::from_secs(60 * 60));
+//! }
+//!
+//! fn logged_in(mut events: EventReader<SqlxSessionEvent>) {
+//!     for event in events.read() {
+//!         if let SqlxSessionEvent::LoggedIn { player_id, token, .. } = event {
+//!             println!("player {player_id} has token {token}");
+//!         }
+//!     }
+//! }
+//! ```
+use crate::*;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::tasks::futures_lite::future;
+use bevy::tasks::{block_on, AsyncComputeTaskPool, Task};
+use bevy::utils::Duration;
+use rand::RngCore;
+use sqlx::{ColumnIndex, Database, Decode, Encode, Error, Executor};
+use sqlx::{IntoArguments, Pool, Type};
+use std::fmt::Write;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::time::SystemTime;
+
+/// The name of the table sessions are stored in
+pub const SESSION_TABLE: &str = "_bevy_sqlx_sessions";
+
+/// A [`Plugin`] managing player sessions in the [`SESSION_TABLE`]
+///
+/// The table is created when the plugin is built, if it doesn't exist yet.
+/// See the [`session`](crate::session) module for more information.
+pub struct SqlxSessionPlugin<DB: Database> {
+    _db: PhantomData<DB>,
+}
+
+impl<DB: Database> Default for SqlxSessionPlugin<DB> {
+    fn default() -> Self {
+        SqlxSessionPlugin { _db: PhantomData }
+    }
+}
+
+impl<DB: Database + Sync> Plugin for SqlxSessionPlugin<DB>
+where
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    fn build(&self, app: &mut App) {
+        let pool = &app.world().resource::<SqlxDatabase<DB>>().pool;
+        block_on(create_session_table(pool)).unwrap();
+        app.init_resource::<SqlxSessionTasks>();
+        app.add_event::<SqlxSessionEvent>();
+        app.add_systems(Update, SqlxSessionTasks::handle_tasks);
+    }
+}
+
+/// Create the [`SESSION_TABLE`] unless it already exists
+async fn create_session_table<DB>(pool: &Pool<DB>) -> Result<(), Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    let sql = format!(
+        "CREATE TABLE IF NOT EXISTS {SESSION_TABLE} (
+            token       VARCHAR(32) PRIMARY KEY,
+            player_id   BIGINT      NOT NULL,
+            expires_at  BIGINT      NOT NULL
+        )"
+    );
+    sqlx::query(&sql).execute(pool).await.map(|_| ())
+}
+
+/// An [`Event`] sent once a [`SqlxSessions`] operation finishes
+#[derive(Event, Debug)]
+pub enum SqlxSessionEvent {
+    /// A new session was created by [`SqlxSessions::login`]
+    LoggedIn {
+        player_id: i64,
+        token: String,
+        expires_at: i64,
+    },
+    /// The token given to [`SqlxSessions::validate`] has an unexpired
+    /// session
+    Valid {
+        player_id: i64,
+        token: String,
+    },
+    /// The token given to [`SqlxSessions::validate`] has no session, or it's
+    /// expired
+    Invalid {
+        token: String,
+    },
+    /// The session was removed by [`SqlxSessions::logout`]
+    LoggedOut {
+        token: String,
+    },
+    Error(Error),
+}
+
+/// A [`Resource`] of running session tasks
+#[derive(Resource, Default, Debug)]
+pub struct SqlxSessionTasks {
+    tasks: Vec<Task<SqlxSessionEvent>>,
+}
+
+impl SqlxSessionTasks {
+    /// A [`System`] sending the [`SqlxSessionEvent`]s of finished tasks
+    pub fn handle_tasks(
+        mut tasks: ResMut<Self>,
+        mut events: EventWriter<SqlxSessionEvent>,
+    ) {
+        tasks.tasks.retain_mut(|task| {
+            block_on(future::poll_once(task))
+                .map(|event| {
+                    events.send(event);
+                })
+                .is_none()
+        });
+    }
+}
+
+/// A [`SystemParam`] for logging players in and out
+///
+/// See the [`session`](crate::session) module for more information.
+#[derive(SystemParam)]
+pub struct SqlxSessions<'w, DB: Database> {
+    database: Res<'w, SqlxDatabase<DB>>,
+    tasks: ResMut<'w, SqlxSessionTasks>,
+}
+
+impl<DB: Database> SqlxSessions<'_, DB>
+where
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+    for<'r> i64: Decode<'r, DB> + Type<DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    /// Create a session for `player_id` lasting `ttl`
+    pub fn login(&mut self, player_id: i64, ttl: Duration) {
+        let mut bytes = [0; 16];
+        rand::rngs::OsRng.fill_bytes(&mut bytes);
+        let token = bytes.iter().fold(String::new(), |mut token, byte| {
+            write!(token, "{byte:02x}").unwrap();
+            token
+        });
+        let expires_at = now() + ttl.as_secs() as i64;
+
+        let sql = format!(
+            "INSERT INTO {SESSION_TABLE} (token, player_id, expires_at)
+                VALUES ({}, {}, {})",
+            placeholder::<DB>(1),
+            placeholder::<DB>(2),
+            placeholder::<DB>(3),
+        );
+        let pool = self.database.pool.clone();
+        self.spawn(async move {
+            let result = sqlx::query(&sql)
+                .bind(SqlxValue::from(token.as_str()))
+                .bind(SqlxValue::from(player_id))
+                .bind(SqlxValue::from(expires_at))
+                .execute(&pool)
+                .await;
+            match result {
+                Ok(_) => {
+                    SqlxSessionEvent::LoggedIn { player_id, token, expires_at }
+                }
+                Err(err) => SqlxSessionEvent::Error(err),
+            }
+        });
+    }
+
+    /// Check whether `token` has an unexpired session
+    pub fn validate(&mut self, token: &str) {
+        let sql = format!(
+            "SELECT player_id FROM {SESSION_TABLE}
+                WHERE token = {} AND expires_at > {}",
+            placeholder::<DB>(1),
+            placeholder::<DB>(2),
+        );
+        let token = token.to_string();
+        let pool = self.database.pool.clone();
+        self.spawn(async move {
+            let result = sqlx::query_scalar(&sql)
+                .bind(SqlxValue::from(token.as_str()))
+                .bind(SqlxValue::from(now()))
+                .fetch_optional(&pool)
+                .await;
+            match result {
+                Ok(Some(player_id)) => {
+                    SqlxSessionEvent::Valid { player_id, token }
+                }
+                Ok(None) => SqlxSessionEvent::Invalid { token },
+                Err(err) => SqlxSessionEvent::Error(err),
+            }
+        });
+    }
+
+    /// Remove the session of `token`
+    pub fn logout(&mut self, token: &str) {
+        let sql = format!(
+            "DELETE FROM {SESSION_TABLE} WHERE token = {}",
+            placeholder::<DB>(1),
+        );
+        let token = token.to_string();
+        let pool = self.database.pool.clone();
+        self.spawn(async move {
+            let result = sqlx::query(&sql)
+                .bind(SqlxValue::from(token.as_str()))
+                .execute(&pool)
+                .await;
+            match result {
+                Ok(_) => SqlxSessionEvent::LoggedOut { token },
+                Err(err) => SqlxSessionEvent::Error(err),
+            }
+        });
+    }
+
+    fn spawn(
+        &mut self,
+        future: impl Future<Output = SqlxSessionEvent> + Send + 'static,
+    ) {
+        let task = AsyncComputeTaskPool::get().spawn(future);
+        self.tasks.tasks.push(task);
+    }
+}
+
+/// The current time, in seconds since the Unix epoch
+fn now() -> i64 {
+    let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH);
+    now.map_or(0, |now| now.as_secs() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::SystemState;
+    use bevy::tasks::TaskPool;
+    use sqlx::Sqlite;
+
+    fn wait_for_session_event(app: &mut App) -> SqlxSessionEvent {
+        let mut tries = 0;
+        loop {
+            app.update();
+            let mut events =
+                app.world_mut().resource_mut::<Events<SqlxSessionEvent>>();
+            if let Some(event) = events.drain().next() {
+                return event;
+            }
+            tries += 1;
+            assert!(tries < 1000);
+        }
+    }
+
+    #[test]
+    fn test_sessions() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let mut app = App::new();
+        app.add_plugins(SqlxPlugin::<Sqlite, SqlxDummy>::from_url(url));
+        app.add_plugins(SqlxSessionPlugin::<Sqlite>::default());
+        let mut sessions: SystemState<SqlxSessions<Sqlite>> =
+            SystemState::new(app.world_mut());
+
+        sessions.get_mut(app.world_mut()).login(7, Duration::from_secs(60));
+        let token = match wait_for_session_event(&mut app) {
+            SqlxSessionEvent::LoggedIn { player_id: 7, token, .. } => token,
+            event => panic!("unexpected {event:?}"),
+        };
+        assert_eq!(32, token.len());
+
+        sessions.get_mut(app.world_mut()).validate(&token);
+        assert!(matches!(
+            wait_for_session_event(&mut app),
+            SqlxSessionEvent::Valid { player_id: 7, .. }
+        ));
+
+        sessions.get_mut(app.world_mut()).logout(&token);
+        assert!(matches!(
+            wait_for_session_event(&mut app),
+            SqlxSessionEvent::LoggedOut { .. }
+        ));
+
+        sessions.get_mut(app.world_mut()).validate(&token);
+        assert!(matches!(
+            wait_for_session_event(&mut app),
+            SqlxSessionEvent::Invalid { .. }
+        ));
+    }
+}
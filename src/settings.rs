@@ -0,0 +1,257 @@
+use crate::*;
+use bevy::asset::io::file::FileAssetReader;
+use bevy::asset::io::{AssetReader, AssetReaderError};
+use bevy::asset::{Asset, AssetLoader, AsyncReadExt, LoadContext};
+use bevy::reflect::TypePath;
+use bevy::tasks::block_on;
+use bevy::utils::{get_short_name, Duration, HashMap};
+use serde::Deserialize;
+use sqlx::pool::PoolOptions;
+use sqlx::Database;
+use std::fmt;
+use std::path::Path;
+
+/// An [`Asset`] describing how to connect to a database, for
+/// [`SqlxPlugin::from_asset`]
+///
+/// Settings are written in RON, so shipping builds read their URL from a
+/// file next to their other assets instead of hardcoding it. Components are
+/// configured by their type name, without its module path.
+///
+/// ```ron
+/// (
+///     url: "postgres://game@localhost/game",
+///     max_connections: Some(8),
+///     acquire_timeout: Some((secs: 5, nanos: 0)),
+///     components: {
+///         "Foo": (table_prefix: Some("staging_"), sync_budget: Some(100)),
+///         "Bar": (read_only: true),
+///     },
+/// )
+/// ```
+#[derive(Asset, TypePath, Deserialize, Clone, Debug)]
+pub struct SqlxSettings {
+    /// The URL of the database
+    pub url: String,
+    /// The most connections the pool opens
+    #[serde(default)]
+    pub max_connections: Option<u32>,
+    /// The connections the pool keeps open
+    #[serde(default)]
+    pub min_connections: Option<u32>,
+    /// How long to wait for a connection
+    #[serde(default)]
+    pub acquire_timeout: Option<Duration>,
+    /// The settings of each component, by its type name
+    #[serde(default)]
+    pub components: HashMap<String, SqlxComponentSettings>,
+}
+
+/// The settings of one component in [`SqlxSettings`]
+#[derive(Deserialize, Clone, Default, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct SqlxComponentSettings {
+    /// See [`SqlxPlugin::with_table_prefix`]
+    pub table_prefix: Option<String>,
+    /// See [`SqlxPlugin::with_read_only`]
+    pub read_only: bool,
+    /// See [`SqlxPlugin::with_audit`]
+    pub audit: bool,
+    /// See [`SqlxPlugin::with_sync_budget`]
+    pub sync_budget: Option<usize>,
+    /// See [`SqlxPlugin::with_detailed_statuses`]
+    pub detailed_statuses: bool,
+}
+
+impl SqlxSettings {
+    /// Parse settings from RON
+    pub fn from_ron(bytes: &[u8]) -> Result<Self, SqlxSettingsError> {
+        ron::de::from_bytes(bytes).map_err(SqlxSettingsError::Ron)
+    }
+
+    /// Read the settings at `path` from the `assets` directory, like the
+    /// default [`AssetServer`](bevy::asset::AssetServer) source does
+    pub async fn read(path: &Path) -> Result<Self, SqlxSettingsError> {
+        let reader = FileAssetReader::new("assets");
+        let mut bytes = Vec::new();
+        let mut file = reader.read(path).await?;
+        file.read_to_end(&mut bytes).await?;
+        Self::from_ron(&bytes)
+    }
+
+    /// The settings of the component `C`, or the defaults
+    pub fn component<C>(&self) -> SqlxComponentSettings {
+        let name = get_short_name(std::any::type_name::<C>());
+        self.components.get(&name).cloned().unwrap_or_default()
+    }
+
+    /// The options of the pool connecting to `DB`
+    pub fn pool_options<DB: Database>(&self) -> PoolOptions<DB> {
+        let mut options = PoolOptions::new();
+        if let Some(max) = self.max_connections {
+            options = options.max_connections(max);
+        }
+        if let Some(min) = self.min_connections {
+            options = options.min_connections(min);
+        }
+        if let Some(timeout) = self.acquire_timeout {
+            options = options.acquire_timeout(timeout);
+        }
+        options
+    }
+}
+
+/// An error reading [`SqlxSettings`]
+#[derive(Debug)]
+pub enum SqlxSettingsError {
+    /// The settings couldn't be read
+    Io(std::io::Error),
+    /// The settings asset couldn't be found or read
+    Reader(AssetReaderError),
+    /// The settings aren't valid RON
+    Ron(ron::error::SpannedError),
+}
+
+impl fmt::Display for SqlxSettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SqlxSettingsError::Io(err) => write!(f, "{err}"),
+            SqlxSettingsError::Reader(err) => write!(f, "{err}"),
+            SqlxSettingsError::Ron(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for SqlxSettingsError {}
+
+impl From<std::io::Error> for SqlxSettingsError {
+    fn from(err: std::io::Error) -> Self {
+        SqlxSettingsError::Io(err)
+    }
+}
+
+impl From<AssetReaderError> for SqlxSettingsError {
+    fn from(err: AssetReaderError) -> Self {
+        SqlxSettingsError::Reader(err)
+    }
+}
+
+/// An [`AssetLoader`] of [`SqlxSettings`], for loading them with the
+/// [`AssetServer`](bevy::asset::AssetServer) at runtime, e.g. before a
+/// [`SqlxSwapDatabase`]
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_sqlx::{SqlxSettings, SqlxSettingsLoader};
+/// App::new()
+///     .add_plugins((MinimalPlugins, AssetPlugin::default()))
+///     .init_asset::<SqlxSettings>()
+///     .register_asset_loader(SqlxSettingsLoader);
+/// ```
+#[derive(Default)]
+pub struct SqlxSettingsLoader;
+
+impl AssetLoader for SqlxSettingsLoader {
+    type Asset = SqlxSettings;
+    type Settings = ();
+    type Error = SqlxSettingsError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut bevy::asset::io::Reader<'_>,
+        _: &'a (),
+        _: &'a mut LoadContext<'_>,
+    ) -> Result<SqlxSettings, SqlxSettingsError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        SqlxSettings::from_ron(&bytes)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
+
+impl<DB: Database, C: SqlxComponent<DB::Row>> SqlxPlugin<DB, C> {
+    /// Build a plugin with a new connection described by the
+    /// [`SqlxSettings`] at `path` in the `assets` directory
+    ///
+    /// The settings are read before connecting, with the pool options
+    /// they describe, and the settings of `C` are applied to the plugin.
+    ///
+    /// ```no_run
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxPlugin, SqlxDummy};
+    ///
+    /// SqlxPlugin::<Sqlite, SqlxDummy>::from_asset("db.ron");
+    /// ```
+    pub fn from_asset(path: impl AsRef<Path>) -> Self {
+        let settings = block_on(SqlxSettings::read(path.as_ref())).unwrap();
+        let options = settings.pool_options::<DB>();
+        let pool = block_on(options.connect(&settings.url)).unwrap();
+        let component = settings.component::<C>();
+        let mut plugin = Self::from_pool(pool);
+        if let Some(prefix) = component.table_prefix {
+            plugin = plugin.with_table_prefix(prefix);
+        }
+        if component.read_only {
+            plugin = plugin.with_read_only();
+        }
+        if component.audit {
+            plugin = plugin.with_audit();
+        }
+        if let Some(budget) = component.sync_budget {
+            plugin = plugin.with_sync_budget(budget);
+        }
+        if component.detailed_statuses {
+            plugin = plugin.with_detailed_statuses();
+        }
+        plugin
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use sqlx::{FromRow, Sqlite};
+
+    #[derive(Component, FromRow, Debug)]
+    struct Foo(u32);
+
+    impl PrimaryKey for Foo {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_from_asset() {
+        let path = std::env::temp_dir().join("bevy_sqlx_test_settings.ron");
+        let ron = r#"(
+            url: "sqlite:db/sqlite.db",
+            max_connections: Some(2),
+            components: {
+                "Foo": (table_prefix: Some("dev_"), read_only: true),
+            },
+        )"#;
+        std::fs::write(&path, ron).unwrap();
+
+        let mut app = App::new();
+        app.add_plugins(SqlxPlugin::<Sqlite, Foo>::from_asset(&path));
+        let config = app.world().resource::<SqlxConfig<Sqlite, Foo>>();
+        assert_eq!(Some("dev_".into()), config.table_prefix);
+        assert!(config.read_only);
+        assert!(!config.audit);
+        let db = app.world().resource::<SqlxDatabase<Sqlite>>();
+        assert_eq!(2, db.pool.options().get_max_connections());
+
+        let settings = SqlxSettings::from_ron(b"(url: \"sqlite::memory:\")");
+        assert_eq!(
+            SqlxComponentSettings::default(),
+            settings.unwrap().component::<Foo>()
+        );
+        assert!(SqlxSettings::from_ron(b"(max_connections: 2)").is_err());
+    }
+}
@@ -0,0 +1,377 @@
+use crate::*;
+use bevy::ecs::world::Command;
+use bevy::prelude::*;
+use bevy::tasks::futures_lite::future;
+use bevy::tasks::{block_on, AsyncComputeTaskPool, Task};
+use sqlx::{Database, Error, Executor, IntoArguments, Pool};
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
+
+/// The isolation level of a [`SqlxSnapshot`]'s transaction
+///
+/// SQLite transactions are always serializable, so both levels begin the
+/// same plain transaction there.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SqlxIsolation {
+    /// Every query sees the rows committed before the first one
+    #[default]
+    RepeatableRead,
+    /// Like [`Self::RepeatableRead`], and the snapshot is one some serial
+    /// order of the concurrent transactions could have produced
+    Serializable,
+}
+
+impl SqlxIsolation {
+    /// The statements beginning a read only transaction at this level on
+    /// the database named `database`
+    fn begin(self, database: &str) -> &'static [&'static str] {
+        use SqlxIsolation::*;
+        match (database, self) {
+            ("PostgreSQL", RepeatableRead) => {
+                &["BEGIN ISOLATION LEVEL REPEATABLE READ READ ONLY"]
+            }
+            ("PostgreSQL", Serializable) => {
+                &["BEGIN ISOLATION LEVEL SERIALIZABLE READ ONLY DEFERRABLE"]
+            }
+            ("MySQL", RepeatableRead) => &[
+                "SET TRANSACTION ISOLATION LEVEL REPEATABLE READ",
+                "START TRANSACTION WITH CONSISTENT SNAPSHOT, READ ONLY",
+            ],
+            ("MySQL", Serializable) => &[
+                "SET TRANSACTION ISOLATION LEVEL SERIALIZABLE",
+                "START TRANSACTION WITH CONSISTENT SNAPSHOT, READ ONLY",
+            ],
+            _ => &["BEGIN"],
+        }
+    }
+}
+
+/// Send one component type's rows of a snapshot, returning the id of its
+/// event
+type SqlxSnapshotPart<DB> =
+    fn(Vec<<DB as Database>::Row>, &mut World) -> SqlxEventId;
+
+/// A [`Command`] running the SELECTs of several component types in one
+/// read only transaction, so the synced world is consistent even while
+/// another process writes
+///
+/// Each query's rows are synced with an [`SqlxEvent::call_sync`] of its
+/// component type, labelled `"snapshot"`, so they're applied like any other
+/// synced rows, with the [`SqlxEventStatus`]es of the type. Once the
+/// transaction ends, a [`SqlxSnapshotLoaded<DB>`] is sent with the ids of
+/// those events, or the error. Nothing is synced when any query fails.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::{FromRow, Sqlite};
+/// # use bevy_sqlx::*;
+/// # #[derive(Component, FromRow)]
+/// # struct Foo { id: u32 }
+/// # impl PrimaryKey for Foo {
+/// #     type Column = u32;
+/// #     fn primary_key(&self) -> Self::Column { self.id }
+/// # }
+/// # #[derive(Component, FromRow)]
+/// # struct Bar { id: u32, foo_id: u32 }
+/// # impl PrimaryKey for Bar {
+/// #     type Column = u32;
+/// #     fn primary_key(&self) -> Self::Column { self.id }
+/// # }
+/// fn load(mut commands: Commands) {
+///     commands.add(
+///         SqlxSnapshot::<Sqlite>::new()
+///             .with::<Foo>("SELECT * FROM foos")
+///             .with::<Bar>("SELECT * FROM bars"),
+///     );
+/// }
+/// ```
+pub struct SqlxSnapshot<DB: Database> {
+    isolation: SqlxIsolation,
+    queries: Vec<(Arc<str>, SqlxSnapshotPart<DB>)>,
+}
+
+impl<DB: Database> Default for SqlxSnapshot<DB> {
+    fn default() -> Self {
+        SqlxSnapshot {
+            isolation: SqlxIsolation::default(),
+            queries: Vec::new(),
+        }
+    }
+}
+
+impl<DB> SqlxSnapshot<DB>
+where
+    DB: Database + Sync,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    /// A snapshot without any queries, at [`SqlxIsolation::RepeatableRead`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run the transaction at `isolation`
+    pub fn with_isolation(mut self, isolation: SqlxIsolation) -> Self {
+        self.isolation = isolation;
+        self
+    }
+
+    /// Also sync the `C` rows of `sql`
+    ///
+    /// `C` needs a [`SqlxPlugin<DB, C>`].
+    pub fn with<C: SqlxComponent<DB::Row>>(mut self, sql: &str) -> Self {
+        self.queries.push((sql.into(), send_part::<DB, C>));
+        self
+    }
+}
+
+impl<DB> Command for SqlxSnapshot<DB>
+where
+    DB: Database + Sync,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    fn apply(self, world: &mut World) {
+        let pool = world.resource::<SqlxDatabase<DB>>().pool.clone();
+        let (sqls, parts) = self.queries.into_iter().unzip();
+        let fetch = snapshot(pool, self.isolation, sqls);
+        let task = AsyncComputeTaskPool::get().spawn(fetch);
+        let mut snapshots = world.resource_mut::<SqlxSnapshots<DB>>();
+        snapshots.running.push((parts, task));
+    }
+}
+
+/// A [`Resource`] holding the running [`SqlxSnapshot`]s of `DB`
+#[derive(Resource)]
+pub struct SqlxSnapshots<DB: Database> {
+    #[allow(clippy::type_complexity)]
+    running: Vec<(
+        Vec<SqlxSnapshotPart<DB>>,
+        Task<Result<Vec<Vec<DB::Row>>, Error>>,
+    )>,
+}
+
+impl<DB: Database> Default for SqlxSnapshots<DB> {
+    fn default() -> Self {
+        SqlxSnapshots { running: Vec::new() }
+    }
+}
+
+impl<DB: Database> SqlxSnapshots<DB> {
+    /// The number of running snapshots
+    pub fn count(&self) -> usize {
+        self.running.len()
+    }
+
+    /// An exclusive [`System`] sending the events of every finished
+    /// snapshot
+    pub fn handle_snapshots(world: &mut World) {
+        let mut finished = Vec::new();
+        world.resource_mut::<Self>().running.retain_mut(|(parts, task)| {
+            match block_on(future::poll_once(task)) {
+                Some(result) => {
+                    finished.push((std::mem::take(parts), result));
+                    false
+                }
+                None => true,
+            }
+        });
+        for (parts, result) in finished {
+            let result = result.map(|fetched| {
+                parts
+                    .into_iter()
+                    .zip(fetched)
+                    .map(|(part, rows)| part(rows, world))
+                    .collect()
+            });
+            world.send_event(SqlxSnapshotLoaded::<DB> {
+                result,
+                _db: PhantomData,
+            });
+        }
+    }
+}
+
+/// An [`Event`] sent when the transaction of a [`SqlxSnapshot`] of `DB`
+/// ends
+///
+/// The result holds the id of each query's event, in the order they were
+/// added, or the error which rolled the transaction back.
+#[derive(Event, Debug)]
+pub struct SqlxSnapshotLoaded<DB> {
+    pub result: Result<Vec<SqlxEventId>, Error>,
+    _db: PhantomData<fn() -> DB>,
+}
+
+/// Send an event syncing `rows` as `C`s
+fn send_part<DB, C>(rows: Vec<DB::Row>, world: &mut World) -> SqlxEventId
+where
+    DB: Database + Sync,
+    C: SqlxComponent<DB::Row>,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    let id = world.resource::<SqlxEventIds>().next();
+    let rows = Mutex::new(Some(rows));
+    let event = SqlxEvent::<DB, C>::call_sync(move |_| {
+        let rows = rows.lock().unwrap().take().unwrap_or_default();
+        async move { rows.iter().map(C::from_row).collect() }
+    });
+    world.send_event(event.with_id(id).with_label("snapshot"));
+    id
+}
+
+/// Fetch the rows of each of `sqls` in one transaction at `isolation`
+async fn snapshot<DB>(
+    pool: Pool<DB>,
+    isolation: SqlxIsolation,
+    sqls: Vec<Arc<str>>,
+) -> Result<Vec<Vec<DB::Row>>, Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    let mut conn = pool.acquire().await?;
+    for sql in isolation.begin(DB::NAME) {
+        if let Err(err) = conn.execute(*sql).await {
+            conn.close_on_drop();
+            return Err(err);
+        }
+    }
+    let mut fetched = Vec::new();
+    let mut error = None;
+    for sql in &sqls {
+        match sqlx::query(sql).fetch_all(&mut *conn).await {
+            Ok(rows) => fetched.push(rows),
+            Err(err) => {
+                error = Some(err);
+                break;
+            }
+        }
+    }
+    let end = if error.is_some() { "ROLLBACK" } else { "COMMIT" };
+    if let Err(err) = conn.execute(end).await {
+        // Don't return a connection which may still be in the transaction.
+        conn.close_on_drop();
+        error.get_or_insert(err);
+    }
+    match error {
+        Some(err) => Err(err),
+        None => Ok(fetched),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{block_on, AsyncComputeTaskPool, TaskPool};
+    use sqlx::{FromRow, Sqlite};
+
+    #[derive(Component, FromRow, Debug)]
+    struct Foo {
+        id: u32,
+        text: String,
+    }
+
+    impl PrimaryKey for Foo {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    #[derive(Component, FromRow, Debug)]
+    struct Bar {
+        id: u32,
+        foo_id: u32,
+    }
+
+    impl PrimaryKey for Bar {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    fn update_until_loaded(
+        app: &mut App,
+    ) -> Result<Vec<SqlxEventId>, sqlx::Error> {
+        let mut tries = 0;
+        loop {
+            app.update();
+            let mut events = app
+                .world_mut()
+                .resource_mut::<Events<SqlxSnapshotLoaded<Sqlite>>>();
+            if let Some(loaded) = events.drain().next() {
+                return loaded.result;
+            }
+            tries += 1;
+            assert!(tries < 1000);
+        }
+    }
+
+    #[test]
+    fn test_snapshot() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let mut app = App::new();
+        app.add_plugins(SqlxPlugin::<Sqlite, Foo>::from_url(url));
+        app.add_plugins(SqlxPlugin::<Sqlite, Bar>::from_url(url));
+
+        let pool = app.world().resource::<SqlxDatabase<Sqlite>>().pool.clone();
+        let (foo_id, bar_id): (u32, u32) = block_on(async {
+            let foo = sqlx::query_scalar(
+                "INSERT INTO foos (text) VALUES ('snapshot') RETURNING id",
+            )
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+            let bar = sqlx::query_scalar(
+                "INSERT INTO bars (foo_id) VALUES (?) RETURNING id",
+            )
+            .bind(foo)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+            (foo, bar)
+        });
+
+        let snapshot = SqlxSnapshot::<Sqlite>::new()
+            .with_isolation(SqlxIsolation::Serializable)
+            .with::<Foo>(&format!("SELECT * FROM foos WHERE id = {foo_id}"))
+            .with::<Bar>(&format!("SELECT * FROM bars WHERE id = {bar_id}"));
+        app.world_mut().commands().add(snapshot);
+        app.world_mut().flush();
+        let ids = update_until_loaded(&mut app).unwrap();
+        assert_eq!(2, ids.len());
+
+        let mut tries = 0;
+        let mut foos = app.world_mut().query::<&Foo>();
+        let mut bars = app.world_mut().query::<&Bar>();
+        while (foos.iter(app.world()).len() == 0
+            || bars.iter(app.world()).len() == 0)
+            && tries < 1000
+        {
+            app.update();
+            tries += 1;
+        }
+        assert_eq!("snapshot", foos.single(app.world()).text);
+        assert_eq!(foo_id, bars.single(app.world()).foo_id);
+
+        // A failing query rolls back, and syncs nothing.
+        let snapshot = SqlxSnapshot::<Sqlite>::new()
+            .with::<Foo>("SELECT * FROM foos")
+            .with::<Bar>("SELECT * FROM missing_table");
+        app.world_mut().commands().add(snapshot);
+        app.world_mut().flush();
+        assert!(update_until_loaded(&mut app).is_err());
+        for _ in 0..10 {
+            app.update();
+        }
+        assert_eq!(1, foos.iter(app.world()).len());
+        let busy = app.world().resource::<SqlxSnapshots<Sqlite>>().count();
+        assert_eq!(0, busy);
+    }
+}
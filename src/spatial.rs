@@ -0,0 +1,311 @@
+use crate::*;
+use bevy::math::Vec2;
+use bevy::prelude::*;
+use bevy::tasks::block_on;
+use sqlx::{Database, Encode, Error, Executor, IntoArguments, Pool, Type};
+use std::marker::PhantomData;
+
+/// A [`ToRow`] component with a 2D position, for regional loads with
+/// [`SqlxEvent::select_in_aabb`]
+///
+/// ### Example
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::FromRow;
+/// # use bevy_sqlx::*;
+/// #[derive(Component, FromRow)]
+/// struct Tree { id: u32, x: f64, y: f64 }
+/// # impl PrimaryKey for Tree {
+/// #     type Column = u32;
+/// #     fn primary_key(&self) -> Self::Column { self.id }
+/// # }
+/// # impl ToRow for Tree {
+/// #     fn table() -> &'static str { "trees" }
+/// #     fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+/// #         vec![("x", self.x.into()), ("y", self.y.into())]
+/// #     }
+/// # }
+///
+/// impl SqlxSpatial for Tree {
+///     fn position_columns() -> (&'static str, &'static str) {
+///         ("x", "y")
+///     }
+/// }
+/// ```
+pub trait SqlxSpatial: ToRow {
+    /// The names of the x and y columns of each row's position
+    fn position_columns() -> (&'static str, &'static str);
+}
+
+/// A [`Plugin`] creating a spatial index of `C`'s positions
+///
+/// The index depends on the database:
+///
+/// - SQLite: an [R*Tree](https://www.sqlite.org/rtree.html) virtual table
+///   named `{table}_rtree`, kept up to date by triggers on `C`'s table.
+/// - PostgreSQL: a GiST index of each row's `ST_MakePoint(x, y)`. The
+///   [PostGIS](https://postgis.net) extension must already be installed.
+///
+/// Other databases don't support [`SqlxEvent::select_in_aabb`], and the
+/// plugin panics when built with them.
+pub struct SqlxSpatialPlugin<DB, C> {
+    _db: PhantomData<DB>,
+    _c: PhantomData<C>,
+}
+
+impl<DB, C> Default for SqlxSpatialPlugin<DB, C> {
+    fn default() -> Self {
+        SqlxSpatialPlugin { _db: PhantomData, _c: PhantomData }
+    }
+}
+
+impl<DB, C> Plugin for SqlxSpatialPlugin<DB, C>
+where
+    DB: Database + Sync,
+    C: SqlxComponent<DB::Row> + SqlxSpatial,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    fn build(&self, app: &mut App) {
+        let pool = &app.world().resource::<SqlxDatabase<DB>>().pool;
+        block_on(create_spatial_index::<DB, C>(pool)).unwrap();
+    }
+}
+
+/// Create the spatial index of `C`'s table unless it already exists
+async fn create_spatial_index<DB, C>(pool: &Pool<DB>) -> Result<(), Error>
+where
+    DB: Database,
+    C: PrimaryKey + SqlxSpatial,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    let table = C::table();
+    let key = C::primary_key_name();
+    let (x, y) = C::position_columns();
+    let statements = match DB::NAME {
+        "SQLite" => vec![
+            format!(
+                "CREATE VIRTUAL TABLE IF NOT EXISTS {table}_rtree
+                    USING rtree(id, min_x, max_x, min_y, max_y)"
+            ),
+            format!(
+                "CREATE TRIGGER IF NOT EXISTS {table}_rtree_insert
+                    AFTER INSERT ON {table} BEGIN
+                        INSERT INTO {table}_rtree
+                            VALUES (NEW.{key}, NEW.{x}, NEW.{x}, NEW.{y}, NEW.{y});
+                    END"
+            ),
+            format!(
+                "CREATE TRIGGER IF NOT EXISTS {table}_rtree_update
+                    AFTER UPDATE OF {x}, {y} ON {table} BEGIN
+                        UPDATE {table}_rtree
+                            SET min_x = NEW.{x}, max_x = NEW.{x},
+                                min_y = NEW.{y}, max_y = NEW.{y}
+                            WHERE id = NEW.{key};
+                    END"
+            ),
+            format!(
+                "CREATE TRIGGER IF NOT EXISTS {table}_rtree_delete
+                    AFTER DELETE ON {table} BEGIN
+                        DELETE FROM {table}_rtree WHERE id = OLD.{key};
+                    END"
+            ),
+            format!(
+                "INSERT OR IGNORE INTO {table}_rtree
+                    SELECT {key}, {x}, {x}, {y}, {y} FROM {table}"
+            ),
+        ],
+        "PostgreSQL" => vec![format!(
+            "CREATE INDEX IF NOT EXISTS {table}_position_gist
+                ON {table} USING GIST (ST_MakePoint({x}, {y}))"
+        )],
+        name => return Err(unsupported(name)),
+    };
+    for sql in statements {
+        sqlx::query(&sql).execute(pool).await?;
+    }
+    Ok(())
+}
+
+fn unsupported(name: &str) -> Error {
+    Error::Configuration(format!("{name} has no spatial index").into())
+}
+
+impl<DB, C> SqlxEvent<DB, C>
+where
+    DB: Database + Sync,
+    C: SqlxComponent<DB::Row> + SqlxSpatial,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+{
+    /// Construct a new [`SqlxEvent`] selecting the rows of `C` positioned
+    /// inside the axis-aligned box from `min` to `max`, inclusive
+    ///
+    /// The query uses the index created by [`SqlxSpatialPlugin`], which must
+    /// be added too. Upon a successful DB interaction, a
+    /// [`SqlxEventStatus::Return`] event will be sent.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use sqlx::{FromRow, Sqlite};
+    /// # use bevy_sqlx::*;
+    /// # #[derive(Component, FromRow)]
+    /// # struct Tree { id: u32, x: f64, y: f64 }
+    /// # impl PrimaryKey for Tree {
+    /// #     type Column = u32;
+    /// #     fn primary_key(&self) -> Self::Column { self.id }
+    /// # }
+    /// # impl ToRow for Tree {
+    /// #     fn table() -> &'static str { "trees" }
+    /// #     fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+    /// #         vec![("x", self.x.into()), ("y", self.y.into())]
+    /// #     }
+    /// # }
+    /// # impl SqlxSpatial for Tree {
+    /// #     fn position_columns() -> (&'static str, &'static str) { ("x", "y") }
+    /// # }
+    /// let player = Vec2::new(100., 250.);
+    /// let view = Vec2::splat(64.);
+    /// SqlxEvent::<Sqlite, Tree>::select_in_aabb(player - view, player + view);
+    /// ```
+    pub fn select_in_aabb(min: Vec2, max: Vec2) -> Self {
+        let table = C::table();
+        let key = C::primary_key_name();
+        let (x, y) = C::position_columns();
+        let sql = match DB::NAME {
+            "SQLite" => Ok(format!(
+                "SELECT {table}.* FROM {table}
+                    JOIN {table}_rtree AS r ON r.id = {table}.{key}
+                    WHERE r.min_x >= ? AND r.max_x <= ?
+                        AND r.min_y >= ? AND r.max_y <= ?"
+            )),
+            "PostgreSQL" => Ok(format!(
+                "SELECT * FROM {table}
+                    WHERE ST_MakePoint({x}, {y}) && ST_MakeEnvelope($1, $3, $2, $4)"
+            )),
+            name => Err(name),
+        };
+        let binds = [min.x, max.x, min.y, max.y].map(SqlxValue::from);
+        Self::call(move |db| {
+            let (sql, binds) = (sql.clone(), binds.clone());
+            async move {
+                let sql = sql.map_err(unsupported)?;
+                let mut query = sqlx::query_as(&sql);
+                for value in binds {
+                    query = query.bind(value);
+                }
+                query.fetch_all(&db).await
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{block_on, AsyncComputeTaskPool, TaskPool};
+    use sqlx::{FromRow, Sqlite, SqlitePool};
+
+    #[derive(Component, FromRow, Debug)]
+    struct Tree {
+        id: u32,
+        x: f64,
+        y: f64,
+    }
+
+    impl PrimaryKey for Tree {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    impl ToRow for Tree {
+        fn table() -> &'static str {
+            "spatial_trees"
+        }
+
+        fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+            vec![("x", self.x.into()), ("y", self.y.into())]
+        }
+    }
+
+    impl SqlxSpatial for Tree {
+        fn position_columns() -> (&'static str, &'static str) {
+            ("x", "y")
+        }
+    }
+
+    #[test]
+    fn test_select_in_aabb() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let pool = block_on(SqlitePool::connect(url)).unwrap();
+        block_on(async {
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS spatial_trees (
+                    id  INTEGER  PRIMARY KEY,
+                    x   REAL     NOT NULL,
+                    y   REAL     NOT NULL
+                )",
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+        });
+
+        let mut app = App::new();
+        app.add_plugins(SqlxPlugin::<Sqlite, Tree>::from_url(url));
+        app.add_plugins(SqlxSpatialPlugin::<Sqlite, Tree>::default());
+
+        let (inside, outside): (Tree, Tree) = block_on(async {
+            let insert = "INSERT INTO spatial_trees (x, y) VALUES (?, ?)
+                              RETURNING *";
+            let inside = sqlx::query_as(insert).bind(1001.).bind(-999.5);
+            let outside = sqlx::query_as(insert).bind(1001.).bind(-990.);
+            (
+                inside.fetch_one(&pool).await.unwrap(),
+                outside.fetch_one(&pool).await.unwrap(),
+            )
+        });
+
+        let select = SqlxEvent::<Sqlite, Tree>::select_in_aabb(
+            Vec2::new(1000., -1000.),
+            Vec2::new(1002., -999.),
+        );
+        let id = app.world().resource::<SqlxEventIds>().next();
+        let select = select.with_id(id);
+        app.world_mut().send_event(select);
+
+        let mut found = None;
+        let mut tries = 0;
+        while found.is_none() && tries < 1000 {
+            app.update();
+            let events =
+                app.world().resource::<Events<SqlxEventStatus<Sqlite, Tree>>>();
+            found = events.iter_current_update_events().find_map(|status| {
+                match status {
+                    SqlxEventStatus::Return(status_id, trees)
+                        if *status_id == id =>
+                    {
+                        Some(
+                            trees
+                                .iter()
+                                .map(|tree| tree.id)
+                                .collect::<Vec<_>>(),
+                        )
+                    }
+                    _ => None,
+                }
+            });
+            tries += 1;
+        }
+        let found = found.unwrap();
+        assert!(found.contains(&inside.id));
+        assert!(!found.contains(&outside.id));
+    }
+}
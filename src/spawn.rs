@@ -0,0 +1,134 @@
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+use std::fmt;
+use std::sync::Arc;
+
+/// Where a [`SqlxPlugin`](crate::SqlxPlugin) spawns the entities of newly
+/// synced components
+///
+/// By default they're spawned at the root of the world, with only the
+/// component. Built with [`SqlxPlugin::with_spawn_parent`],
+/// [`SqlxPlugin::with_spawn_anchor`] or [`SqlxPlugin::with_spawn_markers`]
+/// they slot into an existing scene hierarchy instead. Components which
+/// update an existing entity are unaffected.
+///
+/// [`SqlxPlugin::with_spawn_parent`]: crate::SqlxPlugin::with_spawn_parent
+/// [`SqlxPlugin::with_spawn_anchor`]: crate::SqlxPlugin::with_spawn_anchor
+/// [`SqlxPlugin::with_spawn_markers`]: crate::SqlxPlugin::with_spawn_markers
+#[derive(Clone, Default)]
+pub struct SqlxSpawnTarget {
+    pub parent: Option<SqlxSpawnParent>,
+    markers: Vec<SqlxSpawnMarker>,
+}
+
+/// Inserts a marker bundle on a spawned entity
+type SqlxSpawnMarker = Arc<dyn Fn(&mut EntityCommands) + Send + Sync>;
+
+/// The parent of the entities spawned by a [`SqlxSpawnTarget`]
+#[derive(Clone, Copy, Debug)]
+pub enum SqlxSpawnParent {
+    /// A given entity
+    Entity(Entity),
+    /// The first entity with a marker component, looked up when spawning
+    Anchor(fn(&mut World) -> Option<Entity>),
+}
+
+impl SqlxSpawnTarget {
+    /// Insert a clone of `bundle` on every spawned entity
+    pub fn insert(&mut self, bundle: impl Bundle + Clone) {
+        self.markers.push(Arc::new(move |entity| {
+            entity.insert(bundle.clone());
+        }));
+    }
+
+    /// The entity spawned entities are children of, if it still exists
+    pub fn parent(&self, world: &mut World) -> Option<Entity> {
+        let parent = match self.parent? {
+            SqlxSpawnParent::Entity(entity) => entity,
+            SqlxSpawnParent::Anchor(anchor) => anchor(world)?,
+        };
+        world.get_entity(parent).map(|_| parent)
+    }
+
+    /// Spawn an entity with `component`, as a child of `parent`
+    pub(crate) fn spawn(
+        &self,
+        commands: &mut Commands,
+        parent: Option<Entity>,
+        component: impl Component,
+    ) {
+        let mut entity = commands.spawn(component);
+        for marker in &self.markers {
+            marker(&mut entity);
+        }
+        if let Some(parent) = parent {
+            entity.set_parent(parent);
+        }
+    }
+}
+
+impl fmt::Debug for SqlxSpawnTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SqlxSpawnTarget")
+            .field("parent", &self.parent)
+            .field("markers", &self.markers.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{AsyncComputeTaskPool, TaskPool};
+    use sqlx::{FromRow, Sqlite};
+
+    #[derive(Component, FromRow, Debug)]
+    struct Foo {
+        id: u32,
+    }
+
+    impl PrimaryKey for Foo {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    #[derive(Component)]
+    struct Level;
+
+    #[derive(Component, Clone)]
+    struct Loaded;
+
+    #[test]
+    fn test_spawn_target() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let mut app = App::new();
+        app.add_plugins(
+            SqlxPlugin::<Sqlite, Foo>::from_url(url)
+                .with_spawn_anchor::<Level>()
+                .with_spawn_markers(Loaded),
+        );
+        let level = app.world_mut().spawn(Level).id();
+
+        let sql = "SELECT 1 AS id UNION SELECT 2 AS id";
+        app.world_mut().send_event(SqlxEvent::<Sqlite, Foo>::query_sync(sql));
+        let mut tries = 0;
+        while app.world_mut().query::<&Foo>().iter(app.world()).len() < 2
+            && tries < 1000
+        {
+            app.update();
+            tries += 1;
+        }
+
+        let mut foos = app
+            .world_mut()
+            .query_filtered::<&Parent, (With<Foo>, With<Loaded>)>();
+        let parents: Vec<_> =
+            foos.iter(app.world()).map(|parent| parent.get()).collect();
+        assert_eq!(vec![level, level], parents);
+        assert_eq!(2, app.world().get::<Children>(level).unwrap().len());
+    }
+}
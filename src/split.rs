@@ -0,0 +1,191 @@
+use crate::*;
+use bevy::prelude::*;
+use std::marker::PhantomData;
+
+/// A row joining the columns of several components, which are inserted
+/// onto the same entity instead of the row itself
+///
+/// Denormalized reads, like a `JOIN` of `foos` and `bars` with the `bars`
+/// columns prefixed `bar_`, map to ECS composition this way. The row is
+/// synced by a [`SqlxPlugin<DB, Self>`] built [`SqlxPlugin::with_apply`] a
+/// [`SqlxSplitApply<Self>`].
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::{FromRow, Sqlite};
+/// # use bevy_sqlx::*;
+/// #[derive(Component, FromRow)]
+/// struct Foo {
+///     id: u32,
+///     text: String,
+/// }
+///
+/// #[derive(Component)]
+/// struct Bar {
+///     id: u32,
+///     score: i32,
+/// }
+///
+/// #[derive(Component, FromRow)]
+/// struct FooWithBar {
+///     #[sqlx(flatten)]
+///     foo: Foo,
+///     bar_id: u32,
+///     bar_score: i32,
+/// }
+/// # impl PrimaryKey for FooWithBar {
+/// #     type Column = u32;
+/// #     fn primary_key(&self) -> Self::Column { self.foo.id }
+/// # }
+///
+/// impl SqlxSplit for FooWithBar {
+///     type Bundle = (Foo, Bar);
+///
+///     fn split(self) -> (Foo, Bar) {
+///         (self.foo, Bar { id: self.bar_id, score: self.bar_score })
+///     }
+/// }
+///
+/// let url = "sqlite:db/sqlite.db";
+/// App::new().add_plugins(
+///     SqlxPlugin::<Sqlite, FooWithBar>::from_url(url)
+///         .with_apply(SqlxSplitApply::<FooWithBar>::default()),
+/// );
+/// ```
+pub trait SqlxSplit: Component + PrimaryKey + Sized {
+    /// The components this row is split into
+    type Bundle: Bundle;
+
+    /// Split this row into its components
+    fn split(self) -> Self::Bundle;
+}
+
+/// A [`SqlxApply`] inserting the [`SqlxSplit`] components of synced rows
+/// onto one entity, instead of spawning the rows
+///
+/// Each entity keeps a [`SqlxKey`] with the primary key of its row, so the
+/// row's components are inserted over the same entity when it's synced
+/// again. The rows are split once commands are applied, so each one is
+/// reported with a [`SqlxEventStatus::Update`].
+pub struct SqlxSplitApply<J> {
+    _j: PhantomData<fn() -> J>,
+}
+
+impl<J> Default for SqlxSplitApply<J> {
+    fn default() -> Self {
+        SqlxSplitApply { _j: PhantomData }
+    }
+}
+
+impl<J: SqlxSplit> SqlxApply<J> for SqlxSplitApply<J> {
+    fn apply(&self, row: J, ctx: &mut SqlxApplyContext<J>) -> SqlxApplied {
+        ctx.commands.add(move |world: &mut World| {
+            let pk = row.primary_key();
+            let mut keys = world.query::<(Entity, &SqlxKey<J>)>();
+            let entity = keys
+                .iter(world)
+                .find(|(_, key)| key.key == pk)
+                .map(|(entity, _)| entity);
+            match entity {
+                Some(entity) => {
+                    world.entity_mut(entity).insert(row.split());
+                }
+                None => {
+                    world.spawn((SqlxKey::<J>::new(pk), row.split()));
+                }
+            }
+        });
+        SqlxApplied::Updated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{AsyncComputeTaskPool, TaskPool};
+    use sqlx::{FromRow, Sqlite};
+
+    #[derive(Component, FromRow, Debug)]
+    struct Foo {
+        id: u32,
+        text: String,
+    }
+
+    #[derive(Component, Debug)]
+    struct Bar {
+        id: u32,
+        score: i32,
+    }
+
+    #[derive(Component, FromRow, Debug)]
+    struct FooWithBar {
+        #[sqlx(flatten)]
+        foo: Foo,
+        bar_id: u32,
+        bar_score: i32,
+    }
+
+    impl PrimaryKey for FooWithBar {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.foo.id
+        }
+    }
+
+    impl SqlxSplit for FooWithBar {
+        type Bundle = (Foo, Bar);
+
+        fn split(self) -> (Foo, Bar) {
+            (self.foo, Bar { id: self.bar_id, score: self.bar_score })
+        }
+    }
+
+    fn split(app: &mut App) -> Vec<(u32, String, u32, i32)> {
+        let mut entities = app.world_mut().query::<(&Foo, &Bar)>();
+        let mut split: Vec<_> = entities
+            .iter(app.world())
+            .map(|(foo, bar)| (foo.id, foo.text.clone(), bar.id, bar.score))
+            .collect();
+        split.sort();
+        split
+    }
+
+    fn sync(app: &mut App, sql: &'static str, done: impl Fn(&mut App) -> bool) {
+        let event = SqlxEvent::<Sqlite, FooWithBar>::query_sync(sql);
+        app.world_mut().send_event(event);
+        let mut tries = 0;
+        while !done(app) && tries < 1000 {
+            app.update();
+            tries += 1;
+        }
+    }
+
+    #[test]
+    fn test_split() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let mut app = App::new();
+        app.add_plugins(
+            SqlxPlugin::<Sqlite, FooWithBar>::from_url(url)
+                .with_apply(SqlxSplitApply::<FooWithBar>::default()),
+        );
+
+        let sql = "SELECT 1 AS id, 'one' AS text, 10 AS bar_id, \
+                   1 AS bar_score UNION ALL SELECT 2, 'two', 20, 2";
+        sync(&mut app, sql, |app| split(app).len() == 2);
+        assert_eq!(
+            vec![(1, "one".into(), 10, 1), (2, "two".into(), 20, 2)],
+            split(&mut app)
+        );
+
+        let sql = "SELECT 1 AS id, 'uno' AS text, 10 AS bar_id, 5 AS bar_score";
+        sync(&mut app, sql, |app| split(app)[0].3 == 5);
+        assert_eq!(
+            vec![(1, "uno".into(), 10, 5), (2, "two".into(), 20, 2)],
+            split(&mut app)
+        );
+        let mut rows = app.world_mut().query::<&FooWithBar>();
+        assert_eq!(0, rows.iter(app.world()).len());
+    }
+}
@@ -0,0 +1,257 @@
+use crate::*;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteRow};
+use sqlx::{Error, Pool, Sqlite};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// How to open a SQLite database file, for
+/// [`SqlxPlugin::from_sqlite`]
+///
+/// By default the file is opened for reading and writing, and has to exist
+/// already. A game can ship a read-only content database alongside a save
+/// database, which is created on first launch.
+///
+/// ```
+/// use sqlx::Sqlite;
+/// use bevy_sqlx::{SqlxPlugin, SqlxDummy, SqlxSqliteFile};
+///
+/// SqlxPlugin::<Sqlite, SqlxDummy>::from_sqlite(
+///     SqlxSqliteFile::new("db/sqlite.db").read_only(),
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct SqlxSqliteFile {
+    path: PathBuf,
+    read_only: bool,
+    create: bool,
+}
+
+impl SqlxSqliteFile {
+    /// Open the database file at `path`
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        SqlxSqliteFile {
+            path: path.as_ref().into(),
+            read_only: false,
+            create: false,
+        }
+    }
+
+    /// Open the file without write access, so any write fails
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    /// Create the file, and the directories it's in, if it doesn't exist
+    pub fn create_if_missing(mut self) -> Self {
+        self.create = true;
+        self
+    }
+
+    /// The path of the database file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Return true if the file is opened without write access
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Connect to the database file
+    pub async fn connect(&self) -> Result<Pool<Sqlite>, Error> {
+        if self.create {
+            if let Some(dir) = self.path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+        }
+        let options = SqliteConnectOptions::new()
+            .filename(&self.path)
+            .read_only(self.read_only)
+            .create_if_missing(self.create);
+        Pool::connect_with(options).await
+    }
+}
+
+impl<C: SqlxComponent<SqliteRow>> SqlxPlugin<Sqlite, C> {
+    /// Build a plugin with a new connection to a SQLite database file
+    ///
+    /// Read-only files also reject every event which isn't a `SELECT`, see
+    /// [`Self::with_read_only`].
+    pub fn from_sqlite(file: SqlxSqliteFile) -> Self {
+        let pool = bevy::tasks::block_on(file.connect()).unwrap();
+        let plugin = Self::from_pool(pool);
+        if file.is_read_only() {
+            plugin.with_read_only()
+        } else {
+            plugin
+        }
+    }
+
+    /// Attach the SQLite database file at `path` to every connection, as
+    /// the schema `schema`
+    ///
+    /// Its tables are then named `schema.table`, so queries can join a
+    /// shipped content database with the save database. The pool is
+    /// reconnected, and files attached before stay attached.
+    ///
+    /// ```
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxPlugin, SqlxDummy};
+    ///
+    /// SqlxPlugin::<Sqlite, SqlxDummy>::from_url("sqlite:db/sqlite.db")
+    ///     .attach("db/sqlite.db", "content");
+    /// ```
+    pub fn attach(mut self, path: impl AsRef<Path>, schema: &str) -> Self {
+        let pool = &self.pool;
+        let mut attached = bevy::tasks::block_on(attached(pool)).unwrap();
+        attached.push((path.as_ref().to_string_lossy().into(), schema.into()));
+        let attached = Arc::new(attached);
+        let options = pool.options().clone().after_connect(move |conn, _| {
+            let attached = attached.clone();
+            Box::pin(async move {
+                for (path, schema) in attached.iter() {
+                    let schema = schema.replace('"', "\"\"");
+                    let sql = format!("ATTACH DATABASE ? AS \"{schema}\"");
+                    sqlx::query(&sql).bind(path).execute(&mut *conn).await?;
+                }
+                Ok(())
+            })
+        });
+        let connect = (*pool.connect_options()).clone();
+        let pool = bevy::tasks::block_on(options.connect_with(connect));
+        bevy::tasks::block_on(self.pool.close());
+        self.pool = pool.unwrap();
+        self
+    }
+}
+
+/// The files attached to the connections of `pool`, with their schema
+async fn attached(pool: &Pool<Sqlite>) -> Result<Vec<(String, String)>, Error> {
+    let rows: Vec<(i64, String, String)> =
+        sqlx::query_as("PRAGMA database_list").fetch_all(pool).await?;
+    Ok(rows
+        .into_iter()
+        .filter(|(_, name, _)| name != "main" && name != "temp")
+        .map(|(_, name, file)| (file, name))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{block_on, AsyncComputeTaskPool, TaskPool};
+    use sqlx::{FromRow, Sqlite};
+
+    #[test]
+    fn test_sqlite_file() {
+        let dir = std::env::temp_dir().join("bevy_sqlx_test_sqlite");
+        let _ = std::fs::remove_dir_all(&dir);
+        let file = SqlxSqliteFile::new(dir.join("saves/save.db"));
+        assert!(block_on(file.connect()).is_err());
+
+        let pool =
+            block_on(file.clone().create_if_missing().connect()).unwrap();
+        let sql = "CREATE TABLE saves (id INTEGER PRIMARY KEY)";
+        block_on(sqlx::query(sql).execute(&pool)).unwrap();
+        assert!(file.path().exists());
+
+        let pool = block_on(file.read_only().connect()).unwrap();
+        let sql = "SELECT COUNT(*) FROM saves";
+        let count: i64 =
+            block_on(sqlx::query_scalar(sql).fetch_one(&pool)).unwrap();
+        assert_eq!(0, count);
+        let sql = "INSERT INTO saves (id) VALUES (1)";
+        assert!(block_on(sqlx::query(sql).execute(&pool)).is_err());
+    }
+
+    #[derive(Component, FromRow, Debug)]
+    struct Item {
+        id: u32,
+        name: String,
+    }
+
+    impl PrimaryKey for Item {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    impl ToRow for Item {
+        fn table() -> &'static str {
+            "items"
+        }
+
+        fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+            vec![("id", self.id.into()), ("name", self.name.clone().into())]
+        }
+    }
+
+    fn returned_name(app: &mut App) -> Option<String> {
+        let mut tries = 0;
+        while tries < 1000 {
+            app.update();
+            let events =
+                app.world().resource::<Events<SqlxEventStatus<Sqlite, Item>>>();
+            for status in events.iter_current_update_events() {
+                match status {
+                    SqlxEventStatus::Return(_, returned) => {
+                        return Some(returned[0].name.clone());
+                    }
+                    SqlxEventStatus::Error(_, err, _) => panic!("{err}"),
+                    _ => {}
+                }
+            }
+            tries += 1;
+        }
+        None
+    }
+
+    #[test]
+    fn test_attach() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let dir = std::env::temp_dir().join("bevy_sqlx_test_attach");
+        let _ = std::fs::remove_dir_all(&dir);
+        let (content, extra) = (dir.join("content.db"), dir.join("extra.db"));
+        let file = SqlxSqliteFile::new(&content).create_if_missing();
+        let pool = block_on(file.connect()).unwrap();
+        let sql = "CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT);
+                   INSERT INTO items VALUES (1, 'sword')";
+        block_on(sqlx::query(sql).execute(&pool)).unwrap();
+        let file = SqlxSqliteFile::new(&extra).create_if_missing();
+        block_on(file.connect()).unwrap();
+
+        let url = "sqlite:db/sqlite.db";
+        let plugin = SqlxPlugin::<Sqlite, Item>::from_url(url)
+            .attach(&content, "content")
+            .attach(&extra, "extra")
+            .with_schema("content");
+        let pool = plugin.pool.clone();
+        let mut app = App::new();
+        app.add_plugins(plugin);
+
+        // Every connection has both files attached.
+        let mut conns = Vec::new();
+        for _ in 0..2 {
+            conns.push(block_on(pool.acquire()).unwrap());
+        }
+        for conn in &mut conns {
+            let sql = "SELECT COUNT(*) FROM pragma_database_list \
+                       WHERE name IN ('content', 'extra')";
+            let attached: i64 =
+                block_on(sqlx::query_scalar(sql).fetch_one(&mut **conn))
+                    .unwrap();
+            assert_eq!(2, attached);
+        }
+        drop(conns);
+
+        // Raw SQL names the schema, generated statements are qualified.
+        let sql = "SELECT id, name FROM content.items";
+        app.world_mut().send_event(SqlxEvent::<Sqlite, Item>::query(sql));
+        assert_eq!(Some("sword".into()), returned_name(&mut app));
+        app.world_mut().send_event(SqlxEvent::<Sqlite, Item>::select(1));
+        assert_eq!(Some("sword".into()), returned_name(&mut app));
+    }
+}
@@ -0,0 +1,342 @@
+//! Bevy [`States`] persisted in the database
+//!
+//! The [`SqlxStatePlugin`] keeps the current state of `S` in the
+//! [`STATE_TABLE`], written on every transition. When the app starts, the
+//! saved state is entered, so games resume where the player left off.
+//!
+//! | column  | value                                   |
+//! | ------- | --------------------------------------- |
+//! | `name`  | the type name of the state              |
+//! | `state` | the JSON serialized state               |
+//!
+//! ### Example
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use bevy::state::app::StatesPlugin;
+//! # use serde::{Deserialize, Serialize};
+//! # use sqlx::Sqlite;
+//! # use bevy_sqlx::{SqlxPlugin, SqlxDummy};
+//! # use bevy_sqlx::state::SqlxStatePlugin;
+//! #[derive(States, Serialize, Deserialize, Default, Clone, Debug)]
+//! #[derive(PartialEq, Eq, Hash)]
+//! enum Chapter {
+//!     #[default]
+//!     Intro,
+//!     Forest,
+//!     Castle,
+//! }
+//!
+//! let url = "sqlite:db/sqlite.db";
+//! App::new()
+//!     .add_plugins(StatesPlugin)
+//!     .add_plugins(SqlxPlugin::<Sqlite, SqlxDummy>::from_url(url))
+//!     .init_state::<Chapter>()
+//!     .add_plugins(SqlxStatePlugin::<Sqlite, Chapter>::default());
+//! ```
+use crate::*;
+use bevy::prelude::*;
+use bevy::state::state::FreelyMutableState;
+use bevy::tasks::futures_lite::future;
+use bevy::tasks::{block_on, AsyncComputeTaskPool, Task};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sqlx::{ColumnIndex, Database, Decode, Encode, Error, Executor};
+use sqlx::{IntoArguments, Pool, Type};
+use std::marker::PhantomData;
+
+/// The name of the table states are stored in
+pub const STATE_TABLE: &str = "_bevy_sqlx_states";
+
+/// A [`Plugin`] saving the state of `S` in the [`STATE_TABLE`], and
+/// restoring it when the app starts
+///
+/// The table is created when the plugin is built, if it doesn't exist yet.
+/// Add it after the [`SqlxPlugin`] and the state. See the
+/// [`state`](crate::state) module for more information.
+pub struct SqlxStatePlugin<DB, S> {
+    _db: PhantomData<DB>,
+    _s: PhantomData<S>,
+}
+
+impl<DB, S> Default for SqlxStatePlugin<DB, S> {
+    fn default() -> Self {
+        SqlxStatePlugin { _db: PhantomData, _s: PhantomData }
+    }
+}
+
+impl<DB, S> Plugin for SqlxStatePlugin<DB, S>
+where
+    DB: Database + Sync,
+    S: FreelyMutableState + Serialize + DeserializeOwned,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+    for<'r> String: Decode<'r, DB> + Type<DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    fn build(&self, app: &mut App) {
+        let pool = &app.world().resource::<SqlxDatabase<DB>>().pool;
+        block_on(create_state_table(pool)).unwrap();
+        app.insert_resource(SqlxStateSync::<DB, S> {
+            restored: false,
+            restoring: None,
+            saving: None,
+            pending: None,
+            _db: PhantomData,
+            _s: PhantomData,
+        });
+        app.add_systems(Update, SqlxStateSync::<DB, S>::handle_restore);
+        // Client builds never write, see `SqlxPlugin::with_client`.
+        if !cfg!(feature = "client") {
+            app.add_systems(
+                Update,
+                SqlxStateSync::<DB, S>::handle_save
+                    .after(SqlxStateSync::<DB, S>::handle_restore),
+            );
+        }
+    }
+}
+
+/// Create the [`STATE_TABLE`] unless it already exists
+async fn create_state_table<DB>(pool: &Pool<DB>) -> Result<(), Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    let sql = format!(
+        "CREATE TABLE IF NOT EXISTS {STATE_TABLE} (
+            name    VARCHAR(255) PRIMARY KEY,
+            state   TEXT         NOT NULL
+        )"
+    );
+    sqlx::query(&sql).execute(pool).await.map(|_| ())
+}
+
+/// The name `S` is stored by
+fn name<S>() -> &'static str {
+    std::any::type_name::<S>()
+}
+
+/// The saved state named `name`, as JSON
+async fn load_state<DB>(
+    name: &'static str,
+    pool: Pool<DB>,
+) -> Result<Option<String>, Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+    for<'r> String: Decode<'r, DB> + Type<DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    let sql = format!(
+        "SELECT state FROM {STATE_TABLE} WHERE name = {}",
+        placeholder::<DB>(1),
+    );
+    sqlx::query_scalar(&sql)
+        .bind(SqlxValue::from(name))
+        .fetch_optional(&pool)
+        .await
+}
+
+/// Save `state` as the state named `name`
+async fn save_state<DB>(
+    name: &'static str,
+    state: String,
+    pool: Pool<DB>,
+) -> Result<(), Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+{
+    let sql = format!(
+        "INSERT INTO {STATE_TABLE} (name, state) VALUES ({}, {})
+            ON CONFLICT (name) DO UPDATE SET state = excluded.state",
+        placeholder::<DB>(1),
+        placeholder::<DB>(2),
+    );
+    sqlx::query(&sql)
+        .bind(SqlxValue::from(name))
+        .bind(SqlxValue::from(state))
+        .execute(&pool)
+        .await
+        .map(|_| ())
+}
+
+/// A [`Resource`] holding the tasks of the [`SqlxStatePlugin`] of `S`
+#[derive(Resource)]
+pub struct SqlxStateSync<DB, S> {
+    restored: bool,
+    restoring: Option<Task<Result<Option<String>, Error>>>,
+    saving: Option<Task<Result<(), Error>>>,
+    /// The latest state, waiting for the previous one to be saved
+    pending: Option<String>,
+    _db: PhantomData<fn() -> DB>,
+    _s: PhantomData<fn() -> S>,
+}
+
+impl<DB, S> SqlxStateSync<DB, S> {
+    /// Return true while the saved state is being loaded
+    pub fn is_restoring(&self) -> bool {
+        !self.restored
+    }
+
+    /// Return true while a state is being saved
+    pub fn is_saving(&self) -> bool {
+        self.saving.is_some() || self.pending.is_some()
+    }
+}
+
+impl<DB, S> SqlxStateSync<DB, S>
+where
+    DB: Database + Sync,
+    S: FreelyMutableState + Serialize + DeserializeOwned,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+    for<'r> String: Decode<'r, DB> + Type<DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    /// A [`System`] loading the saved state when the app starts, and
+    /// entering it once it's loaded
+    pub fn handle_restore(
+        database: Res<SqlxDatabase<DB>>,
+        mut sync: ResMut<Self>,
+        state: Res<State<S>>,
+        mut next: ResMut<NextState<S>>,
+    ) {
+        if sync.restored {
+            return;
+        }
+        let restoring = sync.restoring.get_or_insert_with(|| {
+            let loading = load_state(name::<S>(), database.pool.clone());
+            AsyncComputeTaskPool::get().spawn(loading)
+        });
+        let Some(result) = block_on(future::poll_once(restoring)) else {
+            return;
+        };
+        sync.restoring = None;
+        sync.restored = true;
+
+        let saved = result.map_err(|err| err.to_string()).and_then(|json| {
+            json.map(|json| serde_json::from_str::<S>(&json))
+                .transpose()
+                .map_err(|err| err.to_string())
+        });
+        match saved {
+            Ok(Some(saved)) if saved != *state.get() => next.set(saved),
+            Ok(_) => {}
+            Err(err) => warn!("failed to restore {}: {err}", name::<S>()),
+        }
+    }
+
+    /// A [`System`] saving each state entered, once the saved state was
+    /// restored
+    pub fn handle_save(
+        database: Res<SqlxDatabase<DB>>,
+        mut sync: ResMut<Self>,
+        mut transitions: EventReader<StateTransitionEvent<S>>,
+    ) {
+        let entered = transitions.read().filter_map(|t| t.entered.clone());
+        if let Some(entered) = entered.last() {
+            if !sync.is_restoring() {
+                match serde_json::to_string(&entered) {
+                    Ok(json) => sync.pending = Some(json),
+                    Err(err) => warn!("failed to save {}: {err}", name::<S>()),
+                }
+            }
+        }
+
+        if let Some(saving) = &mut sync.saving {
+            let Some(result) = block_on(future::poll_once(saving)) else {
+                return;
+            };
+            sync.saving = None;
+            if let Err(err) = result {
+                warn!("failed to save {}: {err}", name::<S>());
+            }
+        }
+        if let Some(json) = sync.pending.take() {
+            let pool = database.pool.clone();
+            let saving = save_state(name::<S>(), json, pool);
+            sync.saving = Some(AsyncComputeTaskPool::get().spawn(saving));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::state::app::StatesPlugin;
+    use bevy::tasks::TaskPool;
+    use serde::Deserialize;
+    use sqlx::Sqlite;
+
+    #[derive(
+        States,
+        Serialize,
+        Deserialize,
+        Default,
+        Clone,
+        Debug,
+        PartialEq,
+        Eq,
+        Hash,
+    )]
+    enum Chapter {
+        #[default]
+        Intro,
+        Castle,
+    }
+
+    fn setup_app() -> App {
+        let url = "sqlite:db/sqlite.db";
+        let mut app = App::new();
+        app.add_plugins(StatesPlugin);
+        app.add_plugins(SqlxPlugin::<Sqlite, SqlxDummy>::from_url(url));
+        app.init_state::<Chapter>();
+        app.add_plugins(SqlxStatePlugin::<Sqlite, Chapter>::default());
+        app
+    }
+
+    fn update_until(app: &mut App, done: impl Fn(&App) -> bool) {
+        let mut tries = 0;
+        while !done(app) && tries < 1000 {
+            app.update();
+            tries += 1;
+        }
+    }
+
+    #[test]
+    fn test_state() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let pool = block_on(Pool::<Sqlite>::connect("sqlite:db/sqlite.db"));
+        let sql = format!("DELETE FROM {STATE_TABLE} WHERE name = ?");
+        let delete = sqlx::query(&sql).bind(name::<Chapter>());
+        let _ = block_on(delete.execute(&pool.unwrap()));
+
+        let sync = |app: &App| {
+            let sync = app.world().resource::<SqlxStateSync<Sqlite, Chapter>>();
+            !sync.is_restoring() && !sync.is_saving()
+        };
+        let mut app = setup_app();
+        update_until(&mut app, sync);
+        app.world_mut()
+            .resource_mut::<NextState<Chapter>>()
+            .set(Chapter::Castle);
+        app.update();
+        update_until(&mut app, sync);
+
+        let mut app = setup_app();
+        let chapter = |app: &App| {
+            *app.world().resource::<State<Chapter>>().get() == Chapter::Castle
+        };
+        update_until(&mut app, chapter);
+        assert!(chapter(&app));
+    }
+}
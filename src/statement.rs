@@ -0,0 +1,1340 @@
+//! SQL statements generated from a component's [`ToRow`](crate::ToRow)
+//! implementation
+//!
+//! Rather than writing SQL by hand for every table, a [`SqlxStatement`] can
+//! be built for common operations and sent with one of the generated
+//! [`SqlxEvent`](crate::SqlxEvent) constructors, e.g.
+//! [`SqlxEvent::insert`](crate::SqlxEvent::insert).
+//!
+//! Values are bound as [`SqlxValue`]s, and placeholders are rendered for the
+//! target [`Database`] (`?` for SQLite and MySQL, `$N` for PostgreSQL).
+//!
+//! `Vec` columns are bound as [`SqlxValue::Array`]s, for PostgreSQL array
+//! columns like `tags TEXT[]`. They're decoded back by the component's
+//! `FromRow` as usual. Other databases have no arrays, and binding one fails
+//! with an [`Error::Encode`].
+//!
+//! Timestamps are bound as [`SqlxValue::Timestamp`]s, in UTC. They're
+//! converted from [`SystemTime`], and from `chrono` and `time` types with the
+//! crate's features of the same names, which also enable SQLx's support for
+//! decoding them in `FromRow`.
+use serde::{Deserialize, Serialize};
+use sqlx::encode::IsNull;
+use sqlx::error::BoxDynError;
+use sqlx::{ColumnIndex, Database, Decode, Encode, Error, Executor, FromRow};
+use sqlx::{IntoArguments, Type, ValueRef};
+use std::fmt::Write;
+use std::time::SystemTime;
+
+/// A dynamically typed value bound to a generated [`SqlxStatement`]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SqlxValue {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Text(String),
+    Bytes(Vec<u8>),
+    Array(SqlxArray),
+    /// Microseconds since the Unix epoch, in UTC
+    Timestamp(i64),
+}
+
+impl SqlxValue {
+    /// The type PostgreSQL placeholders for this value are cast to, for
+    /// values bound as text
+    fn pg_cast(&self) -> Option<&'static str> {
+        match self {
+            SqlxValue::Array(array) => Some(array.pg_type()),
+            SqlxValue::Timestamp(_) => Some("TIMESTAMPTZ"),
+            _ => None,
+        }
+    }
+}
+
+/// The elements of a [`SqlxValue::Array`]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SqlxArray {
+    Bool(Vec<bool>),
+    Int(Vec<i64>),
+    Float(Vec<f64>),
+    Text(Vec<String>),
+}
+
+impl SqlxArray {
+    /// The PostgreSQL type of this array
+    fn pg_type(&self) -> &'static str {
+        match self {
+            SqlxArray::Bool(_) => "BOOLEAN[]",
+            SqlxArray::Int(_) => "BIGINT[]",
+            SqlxArray::Float(_) => "DOUBLE PRECISION[]",
+            SqlxArray::Text(_) => "TEXT[]",
+        }
+    }
+
+    /// The elements of this array as [`SqlxValue`]s
+    pub fn values(&self) -> Vec<SqlxValue> {
+        match self {
+            SqlxArray::Bool(values) => {
+                values.iter().map(|v| (*v).into()).collect()
+            }
+            SqlxArray::Int(values) => {
+                values.iter().map(|v| (*v).into()).collect()
+            }
+            SqlxArray::Float(values) => {
+                values.iter().map(|v| (*v).into()).collect()
+            }
+            SqlxArray::Text(values) => {
+                values.iter().map(|v| v.as_str().into()).collect()
+            }
+        }
+    }
+
+    /// The array of the given elements, if they're all non-null values of
+    /// the same type, and there's at least one
+    pub fn from_values(values: &[SqlxValue]) -> Option<Self> {
+        macro_rules! collect {
+            ($variant:ident) => {
+                values
+                    .iter()
+                    .map(|value| match value {
+                        SqlxValue::$variant(v) => Some(v.clone()),
+                        _ => None,
+                    })
+                    .collect::<Option<_>>()
+                    .map(SqlxArray::$variant)
+            };
+        }
+        match values.first()? {
+            SqlxValue::Bool(_) => collect!(Bool),
+            SqlxValue::Int(_) => collect!(Int),
+            SqlxValue::Float(_) => collect!(Float),
+            SqlxValue::Text(_) => collect!(Text),
+            _ => None,
+        }
+    }
+
+    /// Render this array as a PostgreSQL array literal, e.g. `{"a","b"}`
+    pub(crate) fn literal(&self) -> String {
+        let elements: Vec<_> = match self {
+            SqlxArray::Bool(values) => values
+                .iter()
+                .map(|v| if *v { "t" } else { "f" }.into())
+                .collect(),
+            SqlxArray::Int(values) => {
+                values.iter().map(i64::to_string).collect()
+            }
+            SqlxArray::Float(values) => values
+                .iter()
+                .map(|v| match *v {
+                    f64::INFINITY => "Infinity".into(),
+                    f64::NEG_INFINITY => "-Infinity".into(),
+                    v => v.to_string(),
+                })
+                .collect(),
+            SqlxArray::Text(values) => values
+                .iter()
+                .map(|v| {
+                    format!(
+                        "\"{}\"",
+                        v.replace('\\', "\\\\").replace('"', "\\\"")
+                    )
+                })
+                .collect(),
+        };
+        format!("{{{}}}", elements.join(","))
+    }
+}
+
+macro_rules! impl_from_value {
+    ($variant:ident: $($ty:ty),*) => {
+        $(impl From<$ty> for SqlxValue {
+            fn from(value: $ty) -> Self {
+                SqlxValue::$variant(value.into())
+            }
+        })*
+    };
+}
+
+impl_from_value!(Bool: bool);
+impl_from_value!(Int: i8, i16, i32, i64, u8, u16, u32);
+impl_from_value!(Float: f32, f64);
+impl_from_value!(Text: String, &str);
+impl_from_value!(Bytes: Vec<u8>, &[u8]);
+
+macro_rules! impl_from_array {
+    ($variant:ident: $($ty:ty),*) => {
+        $(impl From<Vec<$ty>> for SqlxValue {
+            fn from(values: Vec<$ty>) -> Self {
+                let values = values.into_iter().map(Into::into).collect();
+                SqlxValue::Array(SqlxArray::$variant(values))
+            }
+        })*
+    };
+}
+
+impl_from_array!(Bool: bool);
+impl_from_array!(Int: i16, i32, i64, u16, u32);
+impl_from_array!(Float: f32, f64);
+impl_from_array!(Text: String, &str);
+
+impl From<SystemTime> for SqlxValue {
+    fn from(value: SystemTime) -> Self {
+        let micros = match value.duration_since(SystemTime::UNIX_EPOCH) {
+            Ok(since) => since.as_micros() as i64,
+            Err(err) => -(err.duration().as_micros() as i64),
+        };
+        SqlxValue::Timestamp(micros)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> From<chrono::DateTime<Tz>> for SqlxValue {
+    fn from(value: chrono::DateTime<Tz>) -> Self {
+        SqlxValue::Timestamp(value.timestamp_micros())
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<chrono::NaiveDateTime> for SqlxValue {
+    fn from(value: chrono::NaiveDateTime) -> Self {
+        value.and_utc().into()
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<time::OffsetDateTime> for SqlxValue {
+    fn from(value: time::OffsetDateTime) -> Self {
+        SqlxValue::Timestamp((value.unix_timestamp_nanos() / 1000) as i64)
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<time::PrimitiveDateTime> for SqlxValue {
+    fn from(value: time::PrimitiveDateTime) -> Self {
+        value.assume_utc().into()
+    }
+}
+
+impl<T: Into<SqlxValue>> From<Option<T>> for SqlxValue {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(SqlxValue::Null, Into::into)
+    }
+}
+
+impl<DB: Database> Type<DB> for SqlxValue
+where
+    String: Type<DB>,
+{
+    fn type_info() -> DB::TypeInfo {
+        <String as Type<DB>>::type_info()
+    }
+
+    fn compatible(_ty: &DB::TypeInfo) -> bool {
+        true
+    }
+}
+
+impl<'q, DB: Database> Encode<'q, DB> for SqlxValue
+where
+    bool: Encode<'q, DB> + Type<DB>,
+    i64: Encode<'q, DB> + Type<DB>,
+    f64: Encode<'q, DB> + Type<DB>,
+    String: Encode<'q, DB> + Type<DB>,
+    Vec<u8>: Encode<'q, DB> + Type<DB>,
+{
+    fn encode_by_ref(
+        &self,
+        buf: &mut <DB as Database>::ArgumentBuffer<'q>,
+    ) -> Result<IsNull, BoxDynError> {
+        match self {
+            SqlxValue::Null => Ok(IsNull::Yes),
+            SqlxValue::Bool(value) => value.encode_by_ref(buf),
+            SqlxValue::Int(value) => value.encode_by_ref(buf),
+            SqlxValue::Float(value) => value.encode_by_ref(buf),
+            SqlxValue::Text(value) => value.encode_by_ref(buf),
+            SqlxValue::Bytes(value) => value.encode_by_ref(buf),
+            SqlxValue::Array(array) if DB::NAME == "PostgreSQL" => {
+                array.literal().encode_by_ref(buf)
+            }
+            SqlxValue::Array(_) => {
+                Err(format!("{} doesn't support array columns", DB::NAME)
+                    .into())
+            }
+            SqlxValue::Timestamp(micros) => rfc3339(*micros).encode_by_ref(buf),
+        }
+    }
+
+    fn produces(&self) -> Option<DB::TypeInfo> {
+        match self {
+            SqlxValue::Null => None,
+            SqlxValue::Bool(_) => Some(<bool as Type<DB>>::type_info()),
+            SqlxValue::Int(_) => Some(<i64 as Type<DB>>::type_info()),
+            SqlxValue::Float(_) => Some(<f64 as Type<DB>>::type_info()),
+            SqlxValue::Text(_) => Some(<String as Type<DB>>::type_info()),
+            SqlxValue::Bytes(_) => Some(<Vec<u8> as Type<DB>>::type_info()),
+            // Bound as text, and cast by the placeholder.
+            SqlxValue::Array(_) | SqlxValue::Timestamp(_) => {
+                Some(<String as Type<DB>>::type_info())
+            }
+        }
+    }
+}
+
+impl<'r, DB: Database> Decode<'r, DB> for SqlxValue
+where
+    bool: Decode<'r, DB> + Type<DB>,
+    i16: Decode<'r, DB> + Type<DB>,
+    i32: Decode<'r, DB> + Type<DB>,
+    i64: Decode<'r, DB> + Type<DB>,
+    f32: Decode<'r, DB> + Type<DB>,
+    f64: Decode<'r, DB> + Type<DB>,
+    String: Decode<'r, DB> + Type<DB>,
+    Vec<u8>: Decode<'r, DB> + Type<DB>,
+{
+    fn decode(value: DB::ValueRef<'r>) -> Result<Self, BoxDynError> {
+        if value.is_null() {
+            return Ok(SqlxValue::Null);
+        }
+
+        // Integers are checked before booleans, SQLite can't tell them apart.
+        let ty = value.type_info().into_owned();
+        if <i64 as Type<DB>>::compatible(&ty) {
+            Ok(<i64 as Decode<DB>>::decode(value)?.into())
+        } else if <i32 as Type<DB>>::compatible(&ty) {
+            Ok(<i32 as Decode<DB>>::decode(value)?.into())
+        } else if <i16 as Type<DB>>::compatible(&ty) {
+            Ok(<i16 as Decode<DB>>::decode(value)?.into())
+        } else if <bool as Type<DB>>::compatible(&ty) {
+            Ok(<bool as Decode<DB>>::decode(value)?.into())
+        } else if <f64 as Type<DB>>::compatible(&ty) {
+            Ok(<f64 as Decode<DB>>::decode(value)?.into())
+        } else if <f32 as Type<DB>>::compatible(&ty) {
+            Ok(<f32 as Decode<DB>>::decode(value)?.into())
+        } else if <String as Type<DB>>::compatible(&ty) {
+            Ok(<String as Decode<DB>>::decode(value)?.into())
+        } else if <Vec<u8> as Type<DB>>::compatible(&ty) {
+            Ok(<Vec<u8> as Decode<DB>>::decode(value)?.into())
+        } else {
+            Err(format!("unsupported type {ty} for SqlxValue").into())
+        }
+    }
+}
+
+/// The kind of operation a [`SqlxStatement`] performs
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SqlxStatementKind {
+    Select,
+    Insert,
+    Update,
+    Upsert,
+    Delete,
+}
+
+impl SqlxStatementKind {
+    /// Classify a SQL string by its leading keyword, returning `None` for
+    /// other statements, like `CREATE` or `PRAGMA`, and for more than one
+    /// statement
+    ///
+    /// A `WITH` query is classified by the first write in it, if there is
+    /// one.
+    ///
+    /// ```
+    /// use bevy_sqlx::SqlxStatementKind;
+    ///
+    /// let sql = "WITH gone AS (DELETE FROM foos RETURNING *) SELECT * FROM gone";
+    /// assert_eq!(Some(SqlxStatementKind::Delete), SqlxStatementKind::of(sql));
+    /// assert_eq!(None, SqlxStatementKind::of("SELECT 1; DROP TABLE foos"));
+    /// ```
+    pub fn of(sql: &str) -> Option<Self> {
+        let words = sql_words(sql);
+        if let Some(end) = words.iter().position(|word| word == ";") {
+            if end + 1 < words.len() {
+                return None;
+            }
+        }
+        let kind = |word: &str| match word {
+            "SELECT" | "VALUES" => Some(SqlxStatementKind::Select),
+            "INSERT" | "REPLACE" => Some(SqlxStatementKind::Insert),
+            "UPDATE" => Some(SqlxStatementKind::Update),
+            "DELETE" => Some(SqlxStatementKind::Delete),
+            _ => None,
+        };
+        match words.first().map(String::as_str) {
+            Some("WITH") => {
+                let kinds: Vec<_> =
+                    words.iter().filter_map(|word| kind(word)).collect();
+                let write = kinds.iter().find(|kind| **kind != Self::Select);
+                write.or(kinds.first()).copied()
+            }
+            Some(word) => kind(word),
+            None => None,
+        }
+    }
+}
+
+/// Return false for a single `INSERT`, `UPDATE` or `DELETE` without a
+/// `RETURNING` clause, which has no rows to fetch
+pub(crate) fn returns_rows(sql: &str) -> bool {
+    let words = sql_words(sql);
+    let write = matches!(
+        words.first().map(String::as_str),
+        Some("INSERT" | "REPLACE" | "UPDATE" | "DELETE")
+    );
+    !write
+        || SqlxStatementKind::of(sql).is_none()
+        || words.iter().any(|word| word == "RETURNING")
+}
+
+/// The upper cased words of `sql`, and its `;`s, skipping comments and
+/// quoted strings and identifiers
+pub(crate) fn sql_words(sql: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut chars = sql.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_alphanumeric() || c == '_' {
+            word.extend(c.to_uppercase());
+            continue;
+        }
+        if !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        match c {
+            ';' => words.push(";".into()),
+            '\'' | '"' | '`' => {
+                for next in chars.by_ref() {
+                    if next == c {
+                        break;
+                    }
+                }
+            }
+            '-' if chars.peek() == Some(&'-') => {
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut star = false;
+                for next in chars.by_ref() {
+                    if star && next == '/' {
+                        break;
+                    }
+                    star = next == '*';
+                }
+            }
+            _ => {}
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+/// A SQL statement generated for a component's table
+///
+/// Every generated statement ends in `RETURNING *`, so the affected rows are
+/// decoded back into components.
+///
+/// ```
+/// use sqlx::Sqlite;
+/// use bevy_sqlx::{SqlxStatement, SqlxValue};
+///
+/// let stmt = SqlxStatement::select("foos").filter("id", 1);
+/// assert_eq!(
+///     "SELECT * FROM foos WHERE id = ?",
+///     stmt.sql::<Sqlite>(),
+/// );
+/// assert_eq!(vec![SqlxValue::Int(1)], stmt.binds());
+/// ```
+#[derive(Clone, Debug)]
+pub struct SqlxStatement {
+    kind: SqlxStatementKind,
+    table: &'static str,
+    prefix: String,
+    schema: Option<String>,
+    key: Option<&'static str>,
+    columns: Vec<(&'static str, SqlxValue)>,
+    filters: Vec<(&'static str, SqlxValue)>,
+    lists: Vec<(&'static str, Vec<SqlxValue>)>,
+    any: Vec<(&'static str, SqlxArray)>,
+    order: Vec<(&'static str, bool)>,
+    limit: Option<u64>,
+    returning: bool,
+}
+
+impl SqlxStatement {
+    fn new(kind: SqlxStatementKind, table: &'static str) -> Self {
+        SqlxStatement {
+            kind,
+            table,
+            prefix: String::new(),
+            schema: None,
+            key: None,
+            columns: Vec::new(),
+            filters: Vec::new(),
+            lists: Vec::new(),
+            any: Vec::new(),
+            order: Vec::new(),
+            limit: None,
+            returning: true,
+        }
+    }
+
+    /// Select all rows of `table`
+    pub fn select(table: &'static str) -> Self {
+        Self::new(SqlxStatementKind::Select, table)
+    }
+
+    /// Insert a row with the given columns into `table`
+    pub fn insert(
+        table: &'static str,
+        columns: Vec<(&'static str, SqlxValue)>,
+    ) -> Self {
+        SqlxStatement { columns, ..Self::new(SqlxStatementKind::Insert, table) }
+    }
+
+    /// Update the given columns of every row in `table`
+    ///
+    /// Use [`Self::filter`] to restrict which rows are updated.
+    pub fn update(
+        table: &'static str,
+        columns: Vec<(&'static str, SqlxValue)>,
+    ) -> Self {
+        SqlxStatement { columns, ..Self::new(SqlxStatementKind::Update, table) }
+    }
+
+    /// Insert a row, or update it when the `key` column conflicts
+    ///
+    /// On MySQL, the row is updated when any unique key conflicts, and the
+    /// update can't be filtered, so filtered upserts aren't sent, see
+    /// [`SqlxEvent::upsert`](crate::SqlxEvent::upsert).
+    pub fn upsert(
+        table: &'static str,
+        key: &'static str,
+        columns: Vec<(&'static str, SqlxValue)>,
+    ) -> Self {
+        SqlxStatement {
+            key: Some(key),
+            columns,
+            ..Self::new(SqlxStatementKind::Upsert, table)
+        }
+    }
+
+    /// Delete every row in `table`
+    ///
+    /// Use [`Self::filter`] to restrict which rows are deleted.
+    pub fn delete(table: &'static str) -> Self {
+        Self::new(SqlxStatementKind::Delete, table)
+    }
+
+    /// Restrict the statement to rows where `column` equals `value`
+    ///
+    /// Filters on inserts have no `WHERE` clause to join, so they are
+    /// ignored.
+    pub fn filter(
+        mut self,
+        column: &'static str,
+        value: impl Into<SqlxValue>,
+    ) -> Self {
+        self.filters.push((column, value.into()));
+        self
+    }
+
+    /// Restrict the statement to rows where `column` is one of `values`
+    ///
+    /// Each value is bound to its own placeholder, as in
+    /// `WHERE id IN (?, ?, ?)`. No rows match an empty list. Like
+    /// [`Self::filter`], it's ignored on inserts.
+    pub fn filter_in(
+        mut self,
+        column: &'static str,
+        values: impl IntoIterator<Item = impl Into<SqlxValue>>,
+    ) -> Self {
+        self.lists.push((column, values.into_iter().map(Into::into).collect()));
+        self
+    }
+
+    /// Restrict the statement to rows where `column` is one of the elements
+    /// of `array`
+    ///
+    /// The whole array is bound to a single placeholder, as in
+    /// `WHERE id = ANY($1::BIGINT[])`, so the statement is the same for any
+    /// number of values. Only PostgreSQL supports it, other databases fail
+    /// to bind the array, see [`Self::filter_in`] instead.
+    pub fn filter_any(
+        mut self,
+        column: &'static str,
+        array: SqlxArray,
+    ) -> Self {
+        self.any.push((column, array));
+        self
+    }
+
+    /// Order selected rows by `column`, ascending
+    ///
+    /// Ordering only applies to selects, it's ignored otherwise.
+    pub fn order_by(mut self, column: &'static str) -> Self {
+        self.order.push((column, false));
+        self
+    }
+
+    /// Order selected rows by `column`, descending
+    ///
+    /// Ordering only applies to selects, it's ignored otherwise.
+    pub fn order_by_desc(mut self, column: &'static str) -> Self {
+        self.order.push((column, true));
+        self
+    }
+
+    /// Select at most `limit` rows
+    ///
+    /// Limits only apply to selects, they're ignored otherwise.
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Leave out the `RETURNING *` clause of writes
+    ///
+    /// For databases without `RETURNING`. Generated inserts are selected
+    /// back by the key the database generated for them instead, see
+    /// [`SqlxEvent::insert`](crate::SqlxEvent::insert). Those inserts
+    /// aren't written to the [`AUDIT_TABLE`](crate::AUDIT_TABLE).
+    pub fn without_returning(mut self) -> Self {
+        self.returning = false;
+        self
+    }
+
+    /// Return true if writes end in a `RETURNING *` clause
+    pub fn is_returning(&self) -> bool {
+        self.returning
+    }
+
+    /// Scope the statement to the rows of a single tenant
+    ///
+    /// Inserts get `column` as an additional value, selects, updates and
+    /// deletes get it as an additional filter. Upserts get both, so they can
+    /// never take over another tenant's row.
+    pub fn tenant(mut self, column: &'static str, value: SqlxValue) -> Self {
+        match self.kind {
+            SqlxStatementKind::Insert => self.columns.push((column, value)),
+            SqlxStatementKind::Upsert => {
+                self.columns.push((column, value.clone()));
+                self.filters.push((column, value));
+            }
+            _ => self.filters.push((column, value)),
+        }
+        self
+    }
+
+    /// The kind of operation this statement performs
+    pub fn kind(&self) -> SqlxStatementKind {
+        self.kind
+    }
+
+    /// The table this statement operates on
+    pub fn table(&self) -> &'static str {
+        self.table
+    }
+
+    /// Prefix the name of this statement's table with `prefix`, e.g. `dev_`
+    ///
+    /// ```
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::SqlxStatement;
+    ///
+    /// let stmt = SqlxStatement::select("foos").prefixed("dev_");
+    /// assert_eq!("SELECT * FROM dev_foos", stmt.sql::<Sqlite>());
+    /// ```
+    pub fn prefixed(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// The prefix of this statement's table, see [`Self::prefixed`]
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Qualify this statement's table with `schema`, e.g. a Postgres schema
+    /// or an attached SQLite database
+    ///
+    /// ```
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::SqlxStatement;
+    ///
+    /// let stmt = SqlxStatement::select("foos").in_schema("staging");
+    /// assert_eq!("SELECT * FROM staging.foos", stmt.sql::<Sqlite>());
+    /// ```
+    pub fn in_schema(mut self, schema: &str) -> Self {
+        self.schema = Some(schema.into());
+        self
+    }
+
+    /// The schema of this statement's table, see [`Self::in_schema`]
+    pub fn schema(&self) -> Option<&str> {
+        self.schema.as_deref()
+    }
+
+    /// The name of the table this statement operates on, with its schema
+    /// and prefix
+    pub fn table_name(&self) -> String {
+        match &self.schema {
+            Some(schema) => format!("{schema}.{}{}", self.prefix, self.table),
+            None => format!("{}{}", self.prefix, self.table),
+        }
+    }
+
+    /// The columns and values this statement writes
+    pub fn columns(&self) -> &[(&'static str, SqlxValue)] {
+        &self.columns
+    }
+
+    /// The columns and values this statement is filtered on
+    pub fn filters(&self) -> &[(&'static str, SqlxValue)] {
+        &self.filters
+    }
+
+    /// Render the SQL of this statement for the given database
+    pub fn sql<DB: Database>(&self) -> String {
+        let table = self.table_name();
+        let mut sql = String::new();
+        let mut binds = 0;
+        let mut next = |value: &SqlxValue| {
+            binds += 1;
+            typed_placeholder::<DB>(binds, value)
+        };
+
+        let names =
+            || self.columns.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+
+        match self.kind {
+            SqlxStatementKind::Select => {
+                write!(sql, "SELECT * FROM {table}").unwrap();
+            }
+            SqlxStatementKind::Insert | SqlxStatementKind::Upsert => {
+                let values: Vec<_> =
+                    self.columns.iter().map(|(_, value)| next(value)).collect();
+                write!(
+                    sql,
+                    "INSERT INTO {table} ({}) VALUES ({})",
+                    names().join(", "),
+                    values.join(", "),
+                )
+                .unwrap();
+            }
+            SqlxStatementKind::Update => {
+                let sets: Vec<_> = self
+                    .columns
+                    .iter()
+                    .map(|(name, value)| format!("{} = {}", name, next(value)))
+                    .collect();
+                write!(sql, "UPDATE {table} SET {}", sets.join(", ")).unwrap();
+            }
+            SqlxStatementKind::Delete => {
+                write!(sql, "DELETE FROM {table}").unwrap();
+            }
+        }
+
+        if let Some(key) = self.key {
+            sql.push_str(&on_conflict::<DB>(key, &names()));
+        }
+
+        if self.kind != SqlxStatementKind::Insert {
+            // Qualify upsert filters, `excluded` has the same columns.
+            let qualified = |name: &str| match self.kind {
+                SqlxStatementKind::Upsert => format!("{table}.{name}"),
+                _ => name.into(),
+            };
+            let mut conditions: Vec<_> = self
+                .filters
+                .iter()
+                .map(|(name, value)| {
+                    format!("{} = {}", qualified(name), next(value))
+                })
+                .collect();
+            for (name, values) in &self.lists {
+                if values.is_empty() {
+                    conditions.push("1 = 0".into());
+                    continue;
+                }
+                let values: Vec<_> = values.iter().map(&mut next).collect();
+                conditions.push(format!(
+                    "{} IN ({})",
+                    qualified(name),
+                    values.join(", ")
+                ));
+            }
+            for (name, array) in &self.any {
+                let value = SqlxValue::Array(array.clone());
+                conditions.push(format!(
+                    "{} = ANY({})",
+                    qualified(name),
+                    next(&value)
+                ));
+            }
+            if !conditions.is_empty() {
+                write!(sql, " WHERE {}", conditions.join(" AND ")).unwrap();
+            }
+        }
+
+        if self.kind == SqlxStatementKind::Select {
+            if !self.order.is_empty() {
+                let order: Vec<_> = self
+                    .order
+                    .iter()
+                    .map(|(name, desc)| {
+                        format!("{name} {}", if *desc { "DESC" } else { "ASC" })
+                    })
+                    .collect();
+                write!(sql, " ORDER BY {}", order.join(", ")).unwrap();
+            }
+            if let Some(limit) = self.limit {
+                write!(sql, " LIMIT {limit}").unwrap();
+            }
+        } else if self.returning {
+            sql.push_str(" RETURNING *");
+        }
+
+        sql
+    }
+
+    /// The values bound to this statement's placeholders, in order
+    pub fn binds(&self) -> Vec<SqlxValue> {
+        let columns = self.columns.iter().map(|(_, value)| value.clone());
+        match self.kind {
+            SqlxStatementKind::Insert => columns.collect(),
+            _ => columns
+                .chain(self.filters.iter().map(|(_, value)| value.clone()))
+                .chain(self.lists.iter().flat_map(|(_, values)| values.clone()))
+                .chain(
+                    self.any
+                        .iter()
+                        .map(|(_, array)| SqlxValue::Array(array.clone())),
+                )
+                .collect(),
+        }
+    }
+
+    /// The select of the rows this statement is about to change
+    ///
+    /// Inserts and selects don't change existing rows, so they have none.
+    pub fn selection(&self) -> Option<SqlxStatement> {
+        let mut select = SqlxStatement::select(self.table);
+        select.prefix = self.prefix.clone();
+        select.schema = self.schema.clone();
+        select.returning = self.returning;
+        match self.kind {
+            SqlxStatementKind::Select | SqlxStatementKind::Insert => None,
+            SqlxStatementKind::Update | SqlxStatementKind::Delete => {
+                select.filters = self.filters.clone();
+                select.lists = self.lists.clone();
+                select.any = self.any.clone();
+                Some(select)
+            }
+            SqlxStatementKind::Upsert => {
+                let key = self.key?;
+                let (_, value) =
+                    self.columns.iter().find(|(name, _)| *name == key)?;
+                select.filters = self.filters.clone();
+                select.filters.push((key, value.clone()));
+                Some(select)
+            }
+        }
+    }
+
+    /// Execute this statement, decoding the returned rows into components
+    pub(crate) async fn fetch_all<'c, DB, C, E>(
+        self,
+        executor: E,
+    ) -> Result<Vec<C>, Error>
+    where
+        DB: Database,
+        C: for<'r> FromRow<'r, DB::Row> + Send + Unpin,
+        E: Executor<'c, Database = DB>,
+        for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+        for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+    {
+        let sql = self.sql::<DB>();
+        let mut query = sqlx::query_as(&sql);
+        for value in self.binds() {
+            query = query.bind(value);
+        }
+        query.fetch_all(executor).await
+    }
+}
+
+/// Execute the insert `sql`, returning the number of rows inserted and the
+/// key generated for the last one
+///
+/// The key is read on the same connection, with `last_insert_rowid()` on
+/// SQLite and `LAST_INSERT_ID()` on MySQL. Other databases have `RETURNING`
+/// instead, and return `None`.
+pub(crate) async fn execute_insert<DB>(
+    sql: &str,
+    binds: Vec<SqlxValue>,
+    conn: &mut DB::Connection,
+) -> Result<Option<(u64, i64)>, Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+    for<'r> SqlxValue: Decode<'r, DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    let generated = match DB::NAME {
+        "SQLite" => "SELECT changes(), last_insert_rowid()",
+        "MySQL" => "SELECT ROW_COUNT(), LAST_INSERT_ID()",
+        _ => return Ok(None),
+    };
+    let mut query = sqlx::query(sql);
+    for value in binds {
+        query = query.bind(value);
+    }
+    query.execute(&mut *conn).await?;
+    let (rows, key): (SqlxValue, SqlxValue) =
+        sqlx::query_as(generated).fetch_one(&mut *conn).await?;
+    match (rows, key) {
+        (SqlxValue::Int(rows), SqlxValue::Int(key)) => {
+            Ok(Some((rows as u64, key)))
+        }
+        (rows, key) => {
+            let err = format!("unexpected generated key {rows:?}, {key:?}");
+            Err(Error::Decode(err.into()))
+        }
+    }
+}
+
+/// Execute `sql`, returning the number of rows it changed
+///
+/// The count is read on the same connection, with `changes()` on SQLite and
+/// `ROW_COUNT()` on MySQL. PostgreSQL only reports it in the command tag of
+/// the statement, which is read with the `postgres` feature.
+pub(crate) async fn execute_counted<DB>(
+    sql: &str,
+    binds: Vec<SqlxValue>,
+    conn: &mut DB::Connection,
+) -> Result<u64, Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+    for<'r> SqlxValue: Decode<'r, DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    let mut query = sqlx::query(sql);
+    for value in binds {
+        query = query.bind(value);
+    }
+    let result = query.execute(&mut *conn).await?;
+    let counted = match DB::NAME {
+        "SQLite" => "SELECT changes()",
+        "MySQL" => "SELECT ROW_COUNT()",
+        _ => {
+            return command_tag_rows::<DB>(&result).ok_or_else(|| {
+                let err = format!(
+                    "counting the rows of {} needs the crate's feature",
+                    DB::NAME
+                );
+                Error::Configuration(err.into())
+            })
+        }
+    };
+    let (rows,): (SqlxValue,) =
+        sqlx::query_as(counted).fetch_one(&mut *conn).await?;
+    match rows {
+        SqlxValue::Int(rows) => Ok(rows.max(0) as u64),
+        rows => {
+            let err = format!("unexpected row count {rows:?}");
+            Err(Error::Decode(err.into()))
+        }
+    }
+}
+
+/// The number of rows in the command tag of a PostgreSQL `result`
+fn command_tag_rows<DB: Database>(result: &DB::QueryResult) -> Option<u64> {
+    #[cfg(feature = "postgres")]
+    {
+        let result: &dyn std::any::Any = result;
+        if let Some(result) =
+            result.downcast_ref::<sqlx::postgres::PgQueryResult>()
+        {
+            return Some(result.rows_affected());
+        }
+    }
+    #[cfg(not(feature = "postgres"))]
+    let _ = result;
+    None
+}
+
+/// Render microseconds since the Unix epoch as an RFC 3339 timestamp in UTC,
+/// like SQLx encodes `chrono` timestamps for SQLite
+pub(crate) fn rfc3339(micros: i64) -> String {
+    let (secs, micros) =
+        (micros.div_euclid(1_000_000), micros.rem_euclid(1_000_000));
+    let (days, secs) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+
+    // Howard Hinnant's `civil_from_days`, with years starting in March.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    let mut rfc3339 = format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60,
+    );
+    if micros % 1000 != 0 {
+        write!(rfc3339, ".{micros:06}").unwrap();
+    } else if micros != 0 {
+        write!(rfc3339, ".{:03}", micros / 1000).unwrap();
+    }
+    rfc3339.push_str("+00:00");
+    rfc3339
+}
+
+/// The `n`th (1-indexed) bind placeholder for the given database
+pub(crate) fn placeholder<DB: Database>(n: usize) -> String {
+    if DB::NAME == "PostgreSQL" {
+        format!("${n}")
+    } else {
+        "?".into()
+    }
+}
+
+/// The `n`th (1-indexed) bind placeholder for `value`, cast for PostgreSQL
+/// when it's bound as text
+pub(crate) fn typed_placeholder<DB: Database>(
+    n: usize,
+    value: &SqlxValue,
+) -> String {
+    match value.pg_cast() {
+        Some(ty) if DB::NAME == "PostgreSQL" => {
+            format!("{}::{ty}", placeholder::<DB>(n))
+        }
+        _ => placeholder::<DB>(n),
+    }
+}
+
+/// The conflict clause of an upsert on `key` for the given database,
+/// updating the other `names` to their inserted values
+///
+/// MySQL has no `ON CONFLICT`, and gets an `ON DUPLICATE KEY UPDATE`
+/// instead, which conflicts on any unique key.
+pub(crate) fn on_conflict<DB: Database>(key: &str, names: &[&str]) -> String {
+    let mysql = DB::NAME == "MySQL";
+    let sets: Vec<_> = names
+        .iter()
+        .filter(|name| **name != key)
+        .map(|name| match mysql {
+            true => format!("{name} = VALUES({name})"),
+            false => format!("{name} = excluded.{name}"),
+        })
+        .collect();
+    match (mysql, sets.is_empty()) {
+        (true, true) => format!(" ON DUPLICATE KEY UPDATE {key} = {key}"),
+        (true, false) => {
+            format!(" ON DUPLICATE KEY UPDATE {}", sets.join(", "))
+        }
+        (false, true) => format!(" ON CONFLICT ({key}) DO NOTHING"),
+        (false, false) => {
+            format!(" ON CONFLICT ({key}) DO UPDATE SET {}", sets.join(", "))
+        }
+    }
+}
+
+/// The most placeholders a statement can bind for the given database
+pub(crate) fn max_binds<DB: Database>() -> usize {
+    match DB::NAME {
+        "PostgreSQL" | "MySQL" => u16::MAX as usize,
+        "SQLite" => 32766,
+        _ => 999,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use sqlx::{Encode, Sqlite};
+
+    #[test]
+    fn test_kind_of() {
+        use SqlxStatementKind::*;
+        assert_eq!(Some(Select), SqlxStatementKind::of(" select * from foos;"));
+        assert_eq!(Some(Select), SqlxStatementKind::of("(SELECT 1)"));
+        assert_eq!(Some(Delete), SqlxStatementKind::of("DELETE FROM foos"));
+        assert_eq!(
+            Some(Insert),
+            SqlxStatementKind::of("-- select\nINSERT INTO foos VALUES ('x')")
+        );
+        assert_eq!(
+            Some(Select),
+            SqlxStatementKind::of("SELECT 'DELETE', /* UPDATE */ \"INSERT\"")
+        );
+        assert_eq!(
+            Some(Update),
+            SqlxStatementKind::of(
+                "WITH x AS (SELECT 1) UPDATE foos SET flag = TRUE"
+            )
+        );
+        assert_eq!(None, SqlxStatementKind::of("CREATE TABLE bars (id INT)"));
+        assert_eq!(None, SqlxStatementKind::of("SELECT 1; DELETE FROM foos"));
+        assert_eq!(None, SqlxStatementKind::of(""));
+        assert!(returns_rows("SELECT * FROM foos"));
+        assert!(returns_rows("PRAGMA table_info(foos)"));
+        assert!(returns_rows("DELETE FROM foos RETURNING *"));
+        assert!(!returns_rows("DELETE FROM foos WHERE text = 'returning'"));
+        assert!(returns_rows("WITH x AS (SELECT 1) DELETE FROM foos"));
+        assert!(returns_rows("DELETE FROM foos; SELECT 1"));
+    }
+
+    #[test]
+    fn test_insert() {
+        let columns = vec![("id", 1.into()), ("text", "insert".into())];
+        let stmt = SqlxStatement::insert("foos", columns);
+        assert_eq!(
+            "INSERT INTO foos (id, text) VALUES (?, ?) RETURNING *",
+            stmt.sql::<Sqlite>()
+        );
+        assert_eq!(
+            vec![SqlxValue::Int(1), SqlxValue::Text("insert".into())],
+            stmt.binds()
+        );
+    }
+
+    #[test]
+    fn test_update() {
+        let stmt = SqlxStatement::update("foos", vec![("flag", true.into())])
+            .filter("id", 1);
+        assert_eq!(
+            "UPDATE foos SET flag = ? WHERE id = ? RETURNING *",
+            stmt.sql::<Sqlite>()
+        );
+        assert_eq!(
+            vec![SqlxValue::Bool(true), SqlxValue::Int(1)],
+            stmt.binds()
+        );
+    }
+
+    #[test]
+    fn test_upsert() {
+        let columns = vec![("id", 1.into()), ("text", "upsert".into())];
+        let stmt = SqlxStatement::upsert("foos", "id", columns);
+        assert_eq!(
+            "INSERT INTO foos (id, text) VALUES (?, ?) \
+             ON CONFLICT (id) DO UPDATE SET text = excluded.text \
+             RETURNING *",
+            stmt.sql::<Sqlite>()
+        );
+    }
+
+    #[cfg(feature = "mysql")]
+    #[test]
+    fn test_upsert_mysql() {
+        let columns = vec![("id", 1.into()), ("text", "upsert".into())];
+        let stmt =
+            SqlxStatement::upsert("foos", "id", columns).without_returning();
+        assert_eq!(
+            "INSERT INTO foos (id, text) VALUES (?, ?) \
+             ON DUPLICATE KEY UPDATE text = VALUES(text)",
+            stmt.sql::<sqlx::MySql>()
+        );
+        let stmt = SqlxStatement::upsert("foos", "id", vec![("id", 1.into())])
+            .without_returning();
+        assert_eq!(
+            "INSERT INTO foos (id) VALUES (?) ON DUPLICATE KEY UPDATE id = id",
+            stmt.sql::<sqlx::MySql>()
+        );
+    }
+
+    #[test]
+    fn test_order_by_limit() {
+        let stmt = SqlxStatement::select("foos")
+            .filter("flag", true)
+            .order_by_desc("score")
+            .order_by("id")
+            .limit(10);
+        assert_eq!(
+            "SELECT * FROM foos WHERE flag = ? \
+             ORDER BY score DESC, id ASC LIMIT 10",
+            stmt.sql::<Sqlite>()
+        );
+    }
+
+    #[test]
+    fn test_filter_in() {
+        let stmt = SqlxStatement::select("foos")
+            .filter("flag", true)
+            .filter_in("id", [1, 2, 3]);
+        assert_eq!(
+            "SELECT * FROM foos WHERE flag = ? AND id IN (?, ?, ?)",
+            stmt.sql::<Sqlite>()
+        );
+        assert_eq!(
+            vec![
+                SqlxValue::Bool(true),
+                SqlxValue::Int(1),
+                SqlxValue::Int(2),
+                SqlxValue::Int(3),
+            ],
+            stmt.binds()
+        );
+
+        let empty = SqlxStatement::delete("foos")
+            .filter_in("id", Vec::<SqlxValue>::new());
+        assert_eq!(
+            "DELETE FROM foos WHERE 1 = 0 RETURNING *",
+            empty.sql::<Sqlite>()
+        );
+
+        let values = [1.into(), 2.into()];
+        let array = SqlxArray::from_values(&values).unwrap();
+        assert_eq!(SqlxArray::Int(vec![1, 2]), array);
+        assert_eq!(None, SqlxArray::from_values(&[1.into(), "2".into()]));
+        assert_eq!(None, SqlxArray::from_values(&[]));
+        let any = SqlxStatement::select("foos").filter_any("id", array);
+        assert_eq!("SELECT * FROM foos WHERE id = ANY(?)", any.sql::<Sqlite>());
+    }
+
+    #[test]
+    fn test_without_returning() {
+        let stmt = SqlxStatement::insert("foos", vec![("text", "a".into())])
+            .without_returning();
+        assert!(!stmt.is_returning());
+        assert_eq!("INSERT INTO foos (text) VALUES (?)", stmt.sql::<Sqlite>());
+    }
+
+    #[test]
+    fn test_tenant() {
+        let select =
+            SqlxStatement::select("foos").tenant("tenant_id", 7.into());
+        assert_eq!(
+            "SELECT * FROM foos WHERE tenant_id = ?",
+            select.sql::<Sqlite>()
+        );
+        assert_eq!(vec![SqlxValue::Int(7)], select.binds());
+
+        let delete = SqlxStatement::delete("foos")
+            .filter("id", 1)
+            .tenant("tenant_id", 7.into());
+        assert_eq!(
+            "DELETE FROM foos WHERE id = ? AND tenant_id = ? RETURNING *",
+            delete.sql::<Sqlite>()
+        );
+
+        let insert = SqlxStatement::insert("foos", vec![("text", "a".into())])
+            .tenant("tenant_id", 7.into());
+        assert_eq!(
+            "INSERT INTO foos (text, tenant_id) VALUES (?, ?) RETURNING *",
+            insert.sql::<Sqlite>()
+        );
+        assert_eq!(
+            vec![SqlxValue::Text("a".into()), SqlxValue::Int(7)],
+            insert.binds()
+        );
+
+        let upsert =
+            SqlxStatement::upsert("foos", "id", vec![("id", 1.into())])
+                .tenant("tenant_id", 7.into());
+        assert_eq!(
+            "INSERT INTO foos (id, tenant_id) VALUES (?, ?) \
+             ON CONFLICT (id) DO UPDATE SET tenant_id = excluded.tenant_id \
+             WHERE foos.tenant_id = ? RETURNING *",
+            upsert.sql::<Sqlite>()
+        );
+        assert_eq!(
+            vec![SqlxValue::Int(1), SqlxValue::Int(7), SqlxValue::Int(7)],
+            upsert.binds()
+        );
+    }
+
+    #[test]
+    fn test_array() {
+        let tags = SqlxValue::from(vec!["a", "b \"c\"", "d\\e"]);
+        let SqlxValue::Array(array) = &tags else {
+            panic!("{tags:?} isn't an array");
+        };
+        assert_eq!(r#"{"a","b \"c\"","d\\e"}"#, array.literal());
+        let scores = SqlxArray::Float(vec![1.5, f64::NEG_INFINITY]);
+        assert_eq!("{1.5,-Infinity}", scores.literal());
+
+        let stmt = SqlxStatement::update("foos", vec![("tags", tags.clone())]);
+        assert_eq!(
+            "UPDATE foos SET tags = ? RETURNING *",
+            stmt.sql::<Sqlite>()
+        );
+        let Err(err) = Encode::<Sqlite>::encode_by_ref(&tags, &mut Vec::new())
+        else {
+            panic!("{tags:?} was encoded for SQLite");
+        };
+        assert_eq!("SQLite doesn't support array columns", err.to_string());
+    }
+
+    #[test]
+    fn test_timestamp() {
+        use std::time::{Duration, SystemTime};
+
+        let epoch = SqlxValue::from(SystemTime::UNIX_EPOCH);
+        assert_eq!(SqlxValue::Timestamp(0), epoch);
+        assert_eq!("1970-01-01T00:00:00+00:00", rfc3339(0));
+        assert_eq!("1969-12-31T23:59:59.999+00:00", rfc3339(-1000));
+
+        let leap_day = SystemTime::UNIX_EPOCH
+            + Duration::from_secs(951_782_400)
+            + Duration::from_micros(5);
+        let SqlxValue::Timestamp(micros) = leap_day.into() else {
+            panic!("{leap_day:?} isn't a timestamp");
+        };
+        assert_eq!("2000-02-29T00:00:00.000005+00:00", rfc3339(micros));
+
+        let stmt = SqlxStatement::insert(
+            "foos",
+            vec![("at", SqlxValue::Timestamp(0))],
+        );
+        assert_eq!(
+            "INSERT INTO foos (at) VALUES (?) RETURNING *",
+            stmt.sql::<Sqlite>()
+        );
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_chrono() {
+        use chrono::{DateTime, FixedOffset};
+
+        let at = DateTime::<FixedOffset>::parse_from_rfc3339(
+            "2000-02-29T01:00:00.000005+01:00",
+        )
+        .unwrap();
+        assert_eq!(SqlxValue::Timestamp(951_782_400_000_005), at.into());
+        assert_eq!(
+            SqlxValue::Timestamp(951_782_400_000_005),
+            at.naive_utc().into()
+        );
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn test_time() {
+        use time::OffsetDateTime;
+
+        let at =
+            OffsetDateTime::from_unix_timestamp_nanos(951_782_400_000_005_000)
+                .unwrap();
+        assert_eq!(SqlxValue::Timestamp(951_782_400_000_005), at.into());
+    }
+}
@@ -0,0 +1,501 @@
+use crate::*;
+use bevy::math::{IVec2, Vec2};
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use sqlx::{Database, Encode, Executor, IntoArguments, Type};
+use std::marker::PhantomData;
+
+/// A marker [`Component`] for the entities, like cameras or players, which
+/// the regions of a [`SqlxStreamingPlugin`] are loaded around
+#[derive(Component, Clone, Copy, Default, Debug)]
+pub struct SqlxStreamAnchor;
+
+/// A [`Plugin`] streaming the rows of `C` in and out of an open world, one
+/// square region at a time
+///
+/// Every region within `radius` of a [`SqlxStreamAnchor`]'s
+/// [`GlobalTransform`], on either axis, is loaded with a synchronizing
+/// [`SqlxEvent::select_in_aabb`], so a [`SqlxSpatialPlugin<DB, C>`] is
+/// needed too. Once no anchor is near a region anymore, every `C` whose
+/// `position` is in it is despawned, and upserted first when built
+/// [`Self::with_persist`]. A [`SqlxRegionStatus<C>`] is sent as each region
+/// is loaded and unloaded. See [`SqlxStreaming<DB, C>`] for the state of
+/// the regions.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::{FromRow, Sqlite};
+/// # use bevy_sqlx::*;
+/// # #[derive(Component, FromRow)]
+/// # struct Tree { id: u32, x: f64, y: f64 }
+/// # impl PrimaryKey for Tree {
+/// #     type Column = u32;
+/// #     fn primary_key(&self) -> Self::Column { self.id }
+/// # }
+/// # impl ToRow for Tree {
+/// #     fn table() -> &'static str { "trees" }
+/// #     fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+/// #         vec![("x", self.x.into()), ("y", self.y.into())]
+/// #     }
+/// # }
+/// # impl SqlxSpatial for Tree {
+/// #     fn position_columns() -> (&'static str, &'static str) { ("x", "y") }
+/// # }
+/// let position = |tree: &Tree| Vec2::new(tree.x as f32, tree.y as f32);
+/// # let mut app = App::new();
+/// # let url = "sqlite:db/sqlite.db";
+/// # app.add_plugins(SqlxPlugin::<Sqlite, Tree>::from_url(url));
+/// app.add_plugins(
+///     SqlxStreamingPlugin::<Sqlite, Tree>::new(256., 512., position)
+///         .with_persist(),
+/// );
+/// app.world_mut().spawn((TransformBundle::default(), SqlxStreamAnchor));
+/// ```
+pub struct SqlxStreamingPlugin<DB, C> {
+    region_size: f32,
+    radius: f32,
+    position: fn(&C) -> Vec2,
+    persist: bool,
+    _db: PhantomData<fn() -> DB>,
+}
+
+impl<DB, C> SqlxStreamingPlugin<DB, C> {
+    /// Load the regions of `region_size` within `radius` of an anchor, with
+    /// components positioned by `position`
+    pub fn new(
+        region_size: f32,
+        radius: f32,
+        position: fn(&C) -> Vec2,
+    ) -> Self {
+        SqlxStreamingPlugin {
+            region_size,
+            radius,
+            position,
+            persist: false,
+            _db: PhantomData,
+        }
+    }
+
+    /// Upsert the components of a region before despawning them
+    pub fn with_persist(mut self) -> Self {
+        self.persist = true;
+        self
+    }
+}
+
+impl<DB, C> Plugin for SqlxStreamingPlugin<DB, C>
+where
+    DB: Database + Sync,
+    C: SqlxComponent<DB::Row> + SqlxSpatial,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+{
+    fn build(&self, app: &mut App) {
+        let mode = SqlxSyncMode::of::<DB, C>(app.world());
+        app.insert_resource(SqlxStreaming::<DB, C> {
+            region_size: self.region_size,
+            radius: self.radius,
+            position: self.position,
+            persist: self.persist && mode.writes(),
+            regions: HashMap::new(),
+            loads: HashMap::new(),
+            _db: PhantomData,
+        });
+        app.add_event::<SqlxRegionStatus<C>>();
+        if !mode.reads() {
+            return;
+        }
+        app.add_systems(
+            Update,
+            (
+                SqlxStreaming::<DB, C>::handle_regions
+                    .before(SqlxRegistry::<DB>::handle_events),
+                SqlxStreaming::<DB, C>::handle_loads
+                    .after(SqlxRegistry::<DB>::handle_tasks),
+            ),
+        );
+    }
+}
+
+/// The state of a region of a [`SqlxStreamingPlugin`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SqlxRegionState {
+    /// The region's rows are being selected
+    Loading,
+    /// The region's rows were synced
+    Loaded,
+    /// Selecting the region's rows failed, see its
+    /// [`SqlxEventStatus::Error`]
+    ///
+    /// The region is loaded again once it's left and re-entered.
+    Failed,
+}
+
+/// What happened to a region, see [`SqlxRegionStatus`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SqlxRegionChange {
+    /// An anchor came near, and the region's rows are being selected
+    Loading,
+    /// The region's rows were synced
+    Loaded,
+    /// Selecting the region's rows failed
+    Failed,
+    /// No anchor is near anymore, and this many components were despawned
+    Unloaded(usize),
+}
+
+/// An [`Event`] sent when a region of the [`SqlxStreamingPlugin`] of `C`
+/// changes
+#[derive(Event, Debug)]
+pub struct SqlxRegionStatus<C> {
+    pub region: IVec2,
+    pub change: SqlxRegionChange,
+    _c: PhantomData<fn() -> C>,
+}
+
+impl<C> SqlxRegionStatus<C> {
+    fn new(region: IVec2, change: SqlxRegionChange) -> Self {
+        SqlxRegionStatus { region, change, _c: PhantomData }
+    }
+}
+
+/// A [`Resource`] holding the regions of the [`SqlxStreamingPlugin`] of `C`
+#[derive(Resource)]
+pub struct SqlxStreaming<DB, C> {
+    region_size: f32,
+    radius: f32,
+    position: fn(&C) -> Vec2,
+    persist: bool,
+    regions: HashMap<IVec2, SqlxRegionState>,
+    /// The region each running select loads
+    loads: HashMap<SqlxEventId, IVec2>,
+    _db: PhantomData<fn() -> DB>,
+}
+
+impl<DB, C> SqlxStreaming<DB, C> {
+    /// The region containing `position`
+    pub fn region_of(&self, position: Vec2) -> IVec2 {
+        (position / self.region_size).floor().as_ivec2()
+    }
+
+    /// The corners of `region`, from its minimum to its maximum
+    pub fn bounds(&self, region: IVec2) -> (Vec2, Vec2) {
+        let min = region.as_vec2() * self.region_size;
+        (min, min + Vec2::splat(self.region_size))
+    }
+
+    /// The state of `region`, or `None` if no anchor is near it
+    pub fn state(&self, region: IVec2) -> Option<SqlxRegionState> {
+        self.regions.get(&region).copied()
+    }
+
+    /// Every region near an anchor, with its state
+    pub fn regions(
+        &self,
+    ) -> impl Iterator<Item = (IVec2, SqlxRegionState)> + '_ {
+        self.regions.iter().map(|(region, state)| (*region, *state))
+    }
+
+    /// Return true while a region is being loaded
+    pub fn is_loading(&self) -> bool {
+        !self.loads.is_empty()
+    }
+
+    /// The regions within the radius of `anchor`
+    fn near(&self, anchor: Vec2) -> impl Iterator<Item = IVec2> {
+        let min = self.region_of(anchor - self.radius);
+        let max = self.region_of(anchor + self.radius);
+        (min.x..=max.x)
+            .flat_map(move |x| (min.y..=max.y).map(move |y| IVec2::new(x, y)))
+    }
+}
+
+impl<DB, C> SqlxStreaming<DB, C>
+where
+    DB: Database + Sync,
+    C: SqlxComponent<DB::Row> + SqlxSpatial,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+{
+    /// A [`System`] loading the regions anchors came near, and unloading
+    /// the regions they left
+    pub fn handle_regions(
+        mut commands: Commands,
+        anchors: Query<&GlobalTransform, With<SqlxStreamAnchor>>,
+        components: Query<(Entity, &C)>,
+        ids: Res<SqlxEventIds>,
+        mut streaming: ResMut<Self>,
+        mut events: EventWriter<SqlxEvent<DB, C>>,
+        mut statuses: EventWriter<SqlxRegionStatus<C>>,
+    ) {
+        let near: HashSet<_> = anchors
+            .iter()
+            .flat_map(|anchor| streaming.near(anchor.translation().truncate()))
+            .collect();
+
+        let left: Vec<_> = streaming
+            .regions
+            .keys()
+            .filter(|region| !near.contains(*region))
+            .copied()
+            .collect();
+        for region in left {
+            // A region left while loading is unloaded once it's loaded.
+            let state = streaming.regions.remove(&region);
+            if state != Some(SqlxRegionState::Loading) {
+                let count = streaming.unload(
+                    region,
+                    &components,
+                    &mut commands,
+                    &mut events,
+                );
+                statuses.send(SqlxRegionStatus::new(
+                    region,
+                    SqlxRegionChange::Unloaded(count),
+                ));
+            }
+        }
+
+        for region in near {
+            if streaming.regions.contains_key(&region) {
+                continue;
+            }
+            streaming.regions.insert(region, SqlxRegionState::Loading);
+            statuses
+                .send(SqlxRegionStatus::new(region, SqlxRegionChange::Loading));
+            // A region re-entered while loading keeps its running select.
+            if streaming.loads.values().any(|loading| *loading == region) {
+                continue;
+            }
+            let (min, max) = streaming.bounds(region);
+            let id = ids.next();
+            let event = SqlxEvent::select_in_aabb(min, max)
+                .sync(true)
+                .with_id(id)
+                .with_label(format!("region {} {}", region.x, region.y));
+            streaming.loads.insert(id, region);
+            events.send(event);
+        }
+    }
+
+    /// A [`System`] marking regions loaded once their selects are synced
+    pub fn handle_loads(
+        mut commands: Commands,
+        components: Query<(Entity, &C)>,
+        mut streaming: ResMut<Self>,
+        mut loads: EventReader<SqlxEventStatus<DB, C>>,
+        mut events: EventWriter<SqlxEvent<DB, C>>,
+        mut statuses: EventWriter<SqlxRegionStatus<C>>,
+    ) {
+        for status in loads.read() {
+            let (id, state) = match status {
+                SqlxEventStatus::Complete(id, _) => {
+                    (id, SqlxRegionState::Loaded)
+                }
+                SqlxEventStatus::Error(id, ..) => (id, SqlxRegionState::Failed),
+                _ => continue,
+            };
+            let Some(region) = streaming.loads.remove(id) else {
+                continue;
+            };
+            let change = match streaming.regions.get_mut(&region) {
+                Some(current) => {
+                    *current = state;
+                    match state {
+                        SqlxRegionState::Failed => SqlxRegionChange::Failed,
+                        _ => SqlxRegionChange::Loaded,
+                    }
+                }
+                None => {
+                    let count = streaming.unload(
+                        region,
+                        &components,
+                        &mut commands,
+                        &mut events,
+                    );
+                    SqlxRegionChange::Unloaded(count)
+                }
+            };
+            statuses.send(SqlxRegionStatus::new(region, change));
+        }
+    }
+
+    /// Despawn the components in `region`, upserting them first if the
+    /// plugin persists them, and return how many there were
+    fn unload(
+        &self,
+        region: IVec2,
+        components: &Query<(Entity, &C)>,
+        commands: &mut Commands,
+        events: &mut EventWriter<SqlxEvent<DB, C>>,
+    ) -> usize {
+        let mut count = 0;
+        for (entity, component) in components {
+            if self.region_of((self.position)(component)) != region {
+                continue;
+            }
+            if self.persist {
+                events.send(SqlxEvent::upsert(component));
+            }
+            commands.entity(entity).despawn_recursive();
+            count += 1;
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{block_on, AsyncComputeTaskPool, TaskPool};
+    use sqlx::{FromRow, Sqlite};
+
+    #[derive(Component, FromRow, Debug)]
+    struct Tree {
+        id: u32,
+        x: f64,
+        y: f64,
+        height: f64,
+    }
+
+    impl PrimaryKey for Tree {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    impl ToRow for Tree {
+        fn table() -> &'static str {
+            "streaming_trees"
+        }
+
+        fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+            vec![
+                ("id", self.id.into()),
+                ("x", self.x.into()),
+                ("y", self.y.into()),
+                ("height", self.height.into()),
+            ]
+        }
+    }
+
+    impl SqlxSpatial for Tree {
+        fn position_columns() -> (&'static str, &'static str) {
+            ("x", "y")
+        }
+    }
+
+    fn update_until_status(
+        app: &mut App,
+        change: SqlxRegionChange,
+    ) -> Vec<IVec2> {
+        let mut tries = 0;
+        loop {
+            app.update();
+            let events =
+                app.world().resource::<Events<SqlxRegionStatus<Tree>>>();
+            let regions: Vec<_> = events
+                .iter_current_update_events()
+                .filter(|status| status.change == change)
+                .map(|status| status.region)
+                .collect();
+            if !regions.is_empty() {
+                return regions;
+            }
+            tries += 1;
+            assert!(tries < 1000);
+        }
+    }
+
+    #[test]
+    fn test_streaming() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let mut app = App::new();
+        app.add_plugins(SqlxPlugin::<Sqlite, Tree>::from_url(url));
+        let pool = app.world().resource::<SqlxDatabase<Sqlite>>().pool.clone();
+        block_on(async {
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS streaming_trees (
+                    id      INTEGER  PRIMARY KEY,
+                    x       REAL     NOT NULL,
+                    y       REAL     NOT NULL,
+                    height  REAL     NOT NULL
+                )",
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+            sqlx::query("DELETE FROM streaming_trees")
+                .execute(&pool)
+                .await
+                .unwrap();
+            sqlx::query(
+                "INSERT INTO streaming_trees VALUES
+                    (1, 5, 5, 1), (2, 205, 5, 1)",
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+        });
+        app.add_plugins(SqlxSpatialPlugin::<Sqlite, Tree>::default());
+        let position = |tree: &Tree| Vec2::new(tree.x as f32, tree.y as f32);
+        app.add_plugins(
+            SqlxStreamingPlugin::<Sqlite, Tree>::new(100., 50., position)
+                .with_persist(),
+        );
+
+        let at = |x, y| GlobalTransform::from_xyz(x, y, 0.);
+        let anchor =
+            app.world_mut().spawn((SqlxStreamAnchor, at(10., 10.))).id();
+        let mut loaded = Vec::new();
+        while loaded.len() < 4 {
+            loaded.extend(update_until_status(
+                &mut app,
+                SqlxRegionChange::Loaded,
+            ));
+        }
+        let streaming = app.world().resource::<SqlxStreaming<Sqlite, Tree>>();
+        assert_eq!(
+            Some(SqlxRegionState::Loaded),
+            streaming.state(IVec2::new(-1, -1))
+        );
+        assert_eq!(None, streaming.state(IVec2::new(2, 0)));
+        let mut trees = app.world_mut().query::<&mut Tree>();
+        let ids: Vec<_> = trees.iter(app.world()).map(|tree| tree.id).collect();
+        assert_eq!(vec![1], ids);
+        trees.single_mut(app.world_mut()).height = 2.;
+
+        app.world_mut().entity_mut(anchor).insert(at(250., 10.));
+        let unloaded =
+            update_until_status(&mut app, SqlxRegionChange::Unloaded(1));
+        assert_eq!(vec![IVec2::new(0, 0)], unloaded);
+        update_until_status(&mut app, SqlxRegionChange::Loaded);
+        let mut tries = 0;
+        while trees.iter(app.world()).all(|tree| tree.id != 2) && tries < 1000 {
+            app.update();
+            tries += 1;
+        }
+        let ids: Vec<_> = trees.iter(app.world()).map(|tree| tree.id).collect();
+        assert_eq!(vec![2], ids);
+
+        let mut height = 1.;
+        let mut tries = 0;
+        while height != 2. && tries < 1000 {
+            app.update();
+            height = block_on(
+                sqlx::query_scalar(
+                    "SELECT height FROM streaming_trees WHERE id = 1",
+                )
+                .fetch_one(&pool),
+            )
+            .unwrap();
+            tries += 1;
+        }
+        assert_eq!(2., height);
+    }
+}
@@ -0,0 +1,204 @@
+use crate::*;
+use bevy::prelude::*;
+use sqlx::{Database, Executor, IntoArguments};
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// An [`Event`] reporting that rows of a table have changed
+///
+/// The plugin sends one whenever a generated write (see [`SqlxStatement`])
+/// finishes. Changes made any other way, e.g. with [`SqlxEvent::query`] or by
+/// another process, can be reported by sending this event directly, for
+/// example from a PostgreSQL `LISTEN` connection.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_sqlx::SqlxTableChanged;
+/// fn notify(mut changes: EventWriter<SqlxTableChanged>) {
+///     changes.send(SqlxTableChanged::new("foos"));
+/// }
+/// ```
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct SqlxTableChanged {
+    pub table: Arc<str>,
+}
+
+impl SqlxTableChanged {
+    pub fn new(table: impl Into<Arc<str>>) -> Self {
+        SqlxTableChanged { table: table.into() }
+    }
+}
+
+/// A [`Component`] holding a live, synchronizing query
+///
+/// When spawned, the subscription's SQL is sent with
+/// [`SqlxEvent::query_sync`], and it's sent again whenever a
+/// [`SqlxTableChanged`] is read for one of the tables it watches. The tables
+/// following `FROM` and `JOIN` in the SQL are watched by default, more can be
+/// added with [`Self::watch`].
+///
+/// Rows which no longer match the query are not despawned.
+///
+/// ### Example
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::{FromRow, Sqlite};
+/// # use bevy_sqlx::{PrimaryKey, SqlxSubscription};
+/// # #[derive(Component, FromRow)]
+/// # struct Foo { id: u32 }
+/// # impl PrimaryKey for Foo {
+/// #     type Column = u32;
+/// #     fn primary_key(&self) -> Self::Column { self.id }
+/// # }
+/// fn subscribe(mut commands: Commands) {
+///     let sql = "SELECT * FROM foos WHERE flag = TRUE";
+///     commands.spawn(SqlxSubscription::<Sqlite, Foo>::new(sql));
+/// }
+/// ```
+#[derive(Component)]
+pub struct SqlxSubscription<DB: Database + Sync, C: SqlxComponent<DB::Row>> {
+    sql: Arc<str>,
+    tables: Vec<Arc<str>>,
+    _db: PhantomData<DB>,
+    _c: PhantomData<C>,
+}
+
+impl<DB: Database + Sync, C: SqlxComponent<DB::Row>> SqlxSubscription<DB, C> {
+    /// Subscribe to the given SELECT statement
+    ///
+    /// ```
+    /// use sqlx::Sqlite;
+    /// use bevy_sqlx::{SqlxSubscription, SqlxDummy};
+    ///
+    /// let sql = "SELECT foos.* FROM foos JOIN bars ON bars.foo_id = foos.id";
+    /// let subscription = SqlxSubscription::<Sqlite, SqlxDummy>::new(sql);
+    /// assert!(subscription.watches("foos"));
+    /// assert!(subscription.watches("bars"));
+    /// ```
+    pub fn new(sql: &str) -> Self {
+        let words: Vec<_> = sql.split_whitespace().collect();
+        let tables = words
+            .windows(2)
+            .filter(|pair| {
+                pair[0].eq_ignore_ascii_case("FROM")
+                    || pair[0].eq_ignore_ascii_case("JOIN")
+            })
+            .map(|pair| pair[1].trim_matches(|c: char| "\"`();,".contains(c)))
+            .filter(|table| !table.is_empty())
+            .map(Into::into)
+            .collect();
+
+        SqlxSubscription {
+            sql: sql.into(),
+            tables,
+            _db: PhantomData,
+            _c: PhantomData,
+        }
+    }
+
+    /// Also re-execute the query when `table` changes
+    pub fn watch(mut self, table: impl Into<Arc<str>>) -> Self {
+        self.tables.push(table.into());
+        self
+    }
+
+    /// Return true if changes to `table` re-execute the query
+    pub fn watches(&self, table: &str) -> bool {
+        self.tables.iter().any(|watched| &**watched == table)
+    }
+}
+
+impl<DB: Database + Sync, C: SqlxComponent<DB::Row>> SqlxSubscription<DB, C>
+where
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    /// A [`System`] sending each subscription's query when it's added, or
+    /// when a table it watches changes
+    pub fn handle_subscriptions(
+        subscriptions: Query<Ref<Self>>,
+        mut changes: EventReader<SqlxTableChanged>,
+        mut events: EventWriter<SqlxEvent<DB, C>>,
+    ) {
+        let changes: Vec<_> = changes.read().collect();
+        for subscription in &subscriptions {
+            if subscription.is_added()
+                || changes.iter().any(|c| subscription.watches(&c.table))
+            {
+                events
+                    .send(SqlxEvent::query_sync(subscription.sql.to_string()));
+            }
+        }
+    }
+
+    /// A [`System`] sending every subscription's query, after the
+    /// [`SqlxDatabase`] was swapped with [`SqlxSwapDatabase::with_resync`]
+    pub fn resync(
+        subscriptions: Query<&Self>,
+        mut events: EventWriter<SqlxEvent<DB, C>>,
+    ) {
+        for subscription in &subscriptions {
+            events.send(SqlxEvent::query_sync(subscription.sql.to_string()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{AsyncComputeTaskPool, TaskPool};
+    use sqlx::{FromRow, Sqlite};
+    use std::time::SystemTime;
+
+    #[derive(Component, FromRow, Debug)]
+    struct Foo {
+        id: u32,
+        text: String,
+    }
+
+    impl PrimaryKey for Foo {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    impl ToRow for Foo {
+        fn table() -> &'static str {
+            "foos"
+        }
+
+        fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+            vec![("text", self.text.clone().into())]
+        }
+    }
+
+    #[test]
+    fn test_subscription() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let mut app = App::new();
+        app.add_plugins(SqlxPlugin::<Sqlite, Foo>::from_url(url));
+
+        let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH);
+        let text = format!("subscription {}", now.unwrap().as_nanos());
+        let sql = format!("SELECT * FROM foos WHERE text = '{text}'");
+        app.world_mut().spawn(SqlxSubscription::<Sqlite, Foo>::new(&sql));
+        app.update();
+        app.update();
+
+        let foo = Foo { id: 0, text: text.clone() };
+        app.world_mut().send_event(SqlxEvent::<Sqlite, Foo>::insert(&foo));
+
+        let mut tries = 0;
+        let mut query = app.world_mut().query::<&Foo>();
+        while query.iter(app.world()).len() == 0 && tries < 1000 {
+            app.update();
+            tries += 1;
+        }
+
+        assert_eq!(text, query.single(app.world()).text);
+    }
+}
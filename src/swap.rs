@@ -0,0 +1,211 @@
+use crate::*;
+use bevy::ecs::event::ManualEventReader;
+use bevy::prelude::*;
+use bevy::tasks::AsyncComputeTaskPool;
+use sqlx::{Database, Pool};
+use std::marker::PhantomData;
+
+/// An [`Event`] replacing the pool of the [`SqlxDatabase`] at runtime, e.g.
+/// when switching between save slot files
+///
+/// Once sent, events of every [`SqlxPlugin`] using `DB` are held until the
+/// running ones have finished, then the pool is swapped, and the held events
+/// are dispatched on the new one. The old pool is closed. Each step sends a
+/// [`SqlxSwapStatus`], so dependent systems can react.
+///
+/// With [`Self::with_resync`], the query of every [`SqlxSubscription`] is
+/// sent again on the new pool.
+///
+/// ### Example
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy::tasks::block_on;
+/// # use sqlx::Sqlite;
+/// # use bevy_sqlx::*;
+/// fn load_slot(mut swaps: EventWriter<SqlxSwapDatabase<Sqlite>>) {
+///     let file = SqlxSqliteFile::new("saves/slot2.db").create_if_missing();
+///     let pool = block_on(file.connect()).unwrap();
+///     swaps.send(SqlxSwapDatabase::new(pool).with_resync());
+/// }
+/// ```
+#[derive(Event, Debug)]
+pub struct SqlxSwapDatabase<DB: Database> {
+    pub pool: Pool<DB>,
+    pub resync: bool,
+}
+
+impl<DB: Database> Clone for SqlxSwapDatabase<DB> {
+    fn clone(&self) -> Self {
+        SqlxSwapDatabase { pool: self.pool.clone(), resync: self.resync }
+    }
+}
+
+impl<DB: Database> SqlxSwapDatabase<DB> {
+    /// Swap to `pool`
+    pub fn new(pool: Pool<DB>) -> Self {
+        SqlxSwapDatabase { pool, resync: false }
+    }
+
+    /// Send every [`SqlxSubscription`]'s query again once swapped
+    pub fn with_resync(mut self) -> Self {
+        self.resync = true;
+        self
+    }
+}
+
+/// An [`Event`] sent while handling a [`SqlxSwapDatabase`]
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::Sqlite;
+/// # use bevy_sqlx::SqlxSwapStatus;
+/// fn swap(mut statuses: EventReader<SqlxSwapStatus<Sqlite>>) {
+///     for status in statuses.read() {
+///         match status {
+///             SqlxSwapStatus::Draining(_) => {},
+///             SqlxSwapStatus::Swapped(_) => {},
+///         }
+///     }
+/// }
+/// ```
+#[derive(Event, Debug)]
+pub enum SqlxSwapStatus<DB: Database> {
+    /// New events are held until the running ones finish
+    Draining(PhantomData<DB>),
+    /// The [`SqlxDatabase`] holds the new pool, and the held events were
+    /// dispatched on it
+    Swapped(PhantomData<DB>),
+}
+
+/// A [`Resource`] holding the [`SqlxSwapDatabase`] of `DB` waiting for
+/// running events to finish
+#[derive(Resource)]
+pub struct SqlxSwap<DB: Database> {
+    requests: ManualEventReader<SqlxSwapDatabase<DB>>,
+    pending: Option<SqlxSwapDatabase<DB>>,
+}
+
+impl<DB: Database> Default for SqlxSwap<DB> {
+    fn default() -> Self {
+        SqlxSwap { requests: ManualEventReader::default(), pending: None }
+    }
+}
+
+impl<DB: Database> SqlxSwap<DB> {
+    /// Return true while events are held for a swap
+    pub fn is_swapping(&self) -> bool {
+        self.pending.is_some()
+    }
+}
+
+impl<DB: Database + Sync> SqlxSwap<DB> {
+    /// An exclusive [`System`] starting the last [`SqlxSwapDatabase`] sent,
+    /// and swapping the pool once every registered component type's tasks
+    /// are finished
+    ///
+    /// It runs before [`SqlxRegistry::handle_events`], so held events are
+    /// dispatched on the new pool in the same frame.
+    pub fn handle_swap(world: &mut World) {
+        world.resource_scope(|world, mut swap: Mut<Self>| {
+            let requests = world.resource::<Events<SqlxSwapDatabase<DB>>>();
+            let request = swap.requests.read(requests).last().cloned();
+            if let Some(request) = request {
+                if swap.pending.replace(request).is_none() {
+                    world.send_event(SqlxSwapStatus::<DB>::Draining(
+                        PhantomData,
+                    ));
+                }
+            }
+            if swap.pending.is_none()
+                || !world.resource::<SqlxRegistry<DB>>().is_idle(world)
+            {
+                return;
+            }
+
+            let request = swap.pending.take().unwrap();
+            let mut database = world.resource_mut::<SqlxDatabase<DB>>();
+            let old = std::mem::replace(&mut database.pool, request.pool);
+            AsyncComputeTaskPool::get()
+                .spawn(async move { old.close().await })
+                .detach();
+            if request.resync {
+                world.resource_scope(
+                    |world, mut registry: Mut<SqlxRegistry<DB>>| {
+                        registry.resync(world);
+                    },
+                );
+            }
+            world.send_event(SqlxSwapStatus::<DB>::Swapped(PhantomData));
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{block_on, AsyncComputeTaskPool, TaskPool};
+    use sqlx::{FromRow, Sqlite};
+
+    #[derive(Component, FromRow, Debug)]
+    struct Foo {
+        id: u32,
+        text: String,
+    }
+
+    impl PrimaryKey for Foo {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    #[test]
+    fn test_swap() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let mut app = App::new();
+        app.add_plugins(SqlxPlugin::<Sqlite, Foo>::from_url(url));
+        let sql = "SELECT * FROM slot_foos";
+        app.world_mut().spawn(SqlxSubscription::<Sqlite, Foo>::new(sql));
+        app.update();
+
+        let path = std::env::temp_dir().join("bevy_sqlx_test_swap/slot.db");
+        let _ = std::fs::remove_file(&path);
+        let file = SqlxSqliteFile::new(&path).create_if_missing();
+        let pool = block_on(async {
+            let pool = file.connect().await.unwrap();
+            sqlx::query("CREATE TABLE slot_foos (id INTEGER, text TEXT)")
+                .execute(&pool)
+                .await
+                .unwrap();
+            sqlx::query("INSERT INTO slot_foos VALUES (1, 'slot')")
+                .execute(&pool)
+                .await
+                .unwrap();
+            pool
+        });
+        let swap = SqlxSwapDatabase::new(pool).with_resync();
+        app.world_mut().send_event(swap);
+
+        let mut statuses = Vec::new();
+        let mut tries = 0;
+        let mut foos = app.world_mut().query::<&Foo>();
+        while foos.iter(app.world()).len() == 0 && tries < 1000 {
+            app.update();
+            let events =
+                app.world().resource::<Events<SqlxSwapStatus<Sqlite>>>();
+            statuses.extend(
+                events
+                    .iter_current_update_events()
+                    .map(|s| matches!(s, SqlxSwapStatus::Swapped(_))),
+            );
+            tries += 1;
+        }
+
+        assert_eq!(vec![false, true], statuses);
+        assert_eq!("slot", foos.single(app.world()).text);
+        assert!(!app.world().resource::<SqlxSwap<Sqlite>>().is_swapping());
+    }
+}
@@ -0,0 +1,50 @@
+use bevy::tasks::{AsyncComputeTaskPool, IoTaskPool, Task, TaskPool};
+use std::future::Future;
+use std::sync::Arc;
+
+/// The Bevy [`TaskPool`] running the database interaction of
+/// [`SqlxEvent`](crate::SqlxEvent)s
+///
+/// By default events run on the [`AsyncComputeTaskPool`]. Database IO can be
+/// moved off it, so it doesn't compete with compute-heavy async work, with
+/// [`SqlxPlugin::with_task_pool`](crate::SqlxPlugin::with_task_pool) for
+/// every event of a plugin, or
+/// [`SqlxEvent::with_task_pool`](crate::SqlxEvent::with_task_pool) for a
+/// single event.
+///
+/// ```
+/// use std::sync::Arc;
+/// use bevy::tasks::TaskPoolBuilder;
+/// use sqlx::Sqlite;
+/// use bevy_sqlx::{SqlxPlugin, SqlxDummy, SqlxTaskPool};
+///
+/// let pool = TaskPoolBuilder::new().num_threads(2).build();
+/// SqlxPlugin::<Sqlite, SqlxDummy>::from_url("sqlite:db/sqlite.db")
+///     .with_task_pool(SqlxTaskPool::Custom(Arc::new(pool)));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub enum SqlxTaskPool {
+    /// The [`AsyncComputeTaskPool`]
+    #[default]
+    AsyncCompute,
+    /// The [`IoTaskPool`]
+    Io,
+    /// A pool owned by the app
+    Custom(Arc<TaskPool>),
+}
+
+impl SqlxTaskPool {
+    /// Spawn `future` on this pool
+    pub fn spawn<T: Send + 'static>(
+        &self,
+        future: impl Future<Output = T> + Send + 'static,
+    ) -> Task<T> {
+        match self {
+            SqlxTaskPool::AsyncCompute => {
+                AsyncComputeTaskPool::get().spawn(future)
+            }
+            SqlxTaskPool::Io => IoTaskPool::get().spawn(future),
+            SqlxTaskPool::Custom(pool) => pool.spawn(future),
+        }
+    }
+}
@@ -1,10 +1,45 @@
 use crate::*;
 use bevy::ecs::system::SystemState;
 use bevy::prelude::*;
-use bevy::tasks::futures_lite::future;
-use bevy::tasks::{block_on, Task};
-use sqlx::{Database, Error, Executor, IntoArguments};
+use bevy::tasks::Task;
+use bevy::utils::{Duration, Instant};
+use crossbeam_channel::{Receiver, Sender};
+use sqlx::{ColumnIndex, Database, Decode, Encode, Error, Executor};
+use sqlx::{IntoArguments, Type};
+use std::collections::VecDeque;
+use std::fmt;
 use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// The result of an [`SqlxEvent`]'s task
+#[derive(Debug)]
+pub(crate) enum SqlxTaskOutput<C> {
+    Components(Vec<C>),
+    Optional(Option<C>),
+    Scalar(SqlxValue),
+    Done(u64),
+    SchemaChanged,
+    /// The key generated for an inserted row, and the insert's output
+    Inserted(i64, Box<SqlxTaskOutput<C>>),
+}
+
+/// What a task of [`SqlxTasks`] sends once its event is finished
+pub(crate) type SqlxTaskResult<C> =
+    (SqlxEventId, Result<SqlxTaskOutput<C>, Error>);
+
+/// A running task of [`SqlxTasks`], see [`SqlxTasks::pending`]
+#[derive(Clone, Debug)]
+pub struct SqlxPendingTask {
+    /// The id of the task's event
+    pub id: SqlxEventId,
+    /// The label of the task's event, if it has one
+    pub label: Option<Arc<str>>,
+    /// How long ago the task was spawned
+    pub elapsed: Duration,
+    /// Whether the task's components will be synced
+    pub will_sync: bool,
+}
 
 /// A [`Resource`](bevy::prelude::Resource) of tasks with the resulting
 /// components from the database
@@ -21,15 +56,67 @@ use std::marker::PhantomData;
 ///     }
 /// }
 /// ```
-#[derive(Resource, Debug)]
+#[allow(clippy::type_complexity)]
+#[derive(Resource)]
 pub struct SqlxTasks<DB: Database, C: SqlxComponent<DB::Row>> {
-    pub(crate) components: Vec<(SqlxEventId, bool, Task<Result<Vec<C>, Error>>)>,
+    pub(crate) components: Vec<(SqlxEvent<DB, C>, Instant, Task<()>)>,
+    /// Where tasks send their results, see [`Self::handle_tasks`]
+    pub(crate) sender: Sender<SqlxTaskResult<C>>,
+    receiver: Receiver<SqlxTaskResult<C>>,
+    /// Results received, but left for the next frame by the frame budget
+    finished: VecDeque<SqlxTaskResult<C>>,
+    pub(crate) throttled: VecDeque<SqlxEvent<DB, C>>,
+    pub(crate) bucket: Option<SqlxTokenBucket>,
+    pub(crate) lost: VecDeque<SqlxEvent<DB, C>>,
+    pub(crate) reconnect: Option<SqlxReconnect>,
+    pub(crate) syncing: VecDeque<(SqlxEventId, SqlxSyncSummary, VecDeque<C>)>,
+    pub(crate) queued: Vec<(SqlxEventId, Arc<AtomicBool>)>,
     _r: PhantomData<DB::Row>,
 }
 
 impl<DB: Database, C: SqlxComponent<DB::Row>> Default for SqlxTasks<DB, C> {
     fn default() -> Self {
-        SqlxTasks { components: Vec::new(), _r: PhantomData::<DB::Row> }
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        SqlxTasks {
+            components: Vec::new(),
+            sender,
+            receiver,
+            finished: VecDeque::new(),
+            throttled: VecDeque::new(),
+            bucket: None,
+            lost: VecDeque::new(),
+            reconnect: None,
+            syncing: VecDeque::new(),
+            queued: Vec::new(),
+            _r: PhantomData::<DB::Row>,
+        }
+    }
+}
+
+impl<DB: Database, C: SqlxComponent<DB::Row>> fmt::Debug for SqlxTasks<DB, C>
+where
+    C: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let components: Vec<_> = self
+            .components
+            .iter()
+            .map(|(e, _, task)| (e.assigned_id(), task))
+            .collect();
+        let finished: Vec<_> = self.finished.iter().map(|(id, _)| id).collect();
+        let throttled: Vec<_> =
+            self.throttled.iter().map(|e| e.assigned_id()).collect();
+        let lost: Vec<_> = self.lost.iter().map(|e| e.assigned_id()).collect();
+        let syncing: Vec<_> =
+            self.syncing.iter().map(|(id, _, c)| (id, c.len())).collect();
+        f.debug_struct("SqlxTasks")
+            .field("components", &components)
+            .field("finished", &finished)
+            .field("throttled", &throttled)
+            .field("lost", &lost)
+            .field("reconnect", &self.reconnect)
+            .field("syncing", &syncing)
+            .finish()
     }
 }
 
@@ -38,97 +125,478 @@ where
     for<'c> &'c mut <DB as Database>::Connection: Executor<'c, Database = DB>,
     for<'q> <DB as Database>::Arguments<'q>: IntoArguments<'q, DB>,
 {
-    /// An exclusive [`System`] which polls [`Task`]s for `Result<C, Error>`
+    /// An exclusive [`System`] which handles the results of finished
+    /// [`Task`]s
     ///
-    /// Tasks are spawned in [`SqlxEvent::handle_events`].
+    /// Tasks are spawned in [`SqlxEvent::handle_events`], and send their
+    /// results over a channel, which is drained without blocking, so the
+    /// running tasks are never polled.
     ///
     /// If [`SqlxEvent::will_sync`] was `true`:
     ///
     /// When a task is finished, we check if the component of type `C` is
     /// already spawned:
     /// - If it is, we just `insert` the new component over the existing one
-    /// and send an [`SqlxEventStatus::Update`]
+    ///   and send an [`SqlxEventStatus::Update`]
     /// - If it isn't, we `spawn` a new entity with the new component and send
-    /// an [`SqlxEventStatus::Spawn`]
+    ///   an [`SqlxEventStatus::Spawn`]
     ///
     /// If [`SqlxEvent::will_sync`] was `false`:
     ///
     /// - We send an [`SqlxEventStatus::Return`] with the component itself.
+    ///
+    /// This is what the default [`SqlxApply`] does, which the plugin can
+    /// replace [`SqlxPlugin::with_apply`]. New entities are spawned where the
+    /// plugin's [`SqlxSpawnTarget`] says.
+    ///
+    /// If [`SqlxEvent::target`] is an entity, the first component is
+    /// inserted onto it instead, and an [`SqlxEventStatus::Update`] is sent.
+    /// With no component an [`SqlxEventStatus::Error`] is sent, and when the
+    /// entity is gone the plugin's [`SqlxDespawnedTarget`] is followed.
+    ///
+    /// If the plugin was built [`SqlxPlugin::with_sync_budget`], synced
+    /// components are queued instead, and at most that many are spawned or
+    /// updated each frame, and the event's [`SqlxEventStatus::Complete`] is
+    /// sent once all of its components are synced.
+    ///
+    /// Every synchronizing event which finishes, or fails, ends with an
+    /// [`SqlxEventStatus::Complete`], summarizing what was synced.
+    ///
+    /// If the plugin was built [`SqlxPlugin::with_detailed_statuses`], an
+    /// [`SqlxEventStatus::Executing`] is sent once a task starts running,
+    /// and an [`SqlxEventStatus::Decoded`] before its components are synced
+    /// or returned.
+    ///
+    /// If the plugin was built [`SqlxPlugin::with_frame_budget`], finished
+    /// tasks and queued components left once the budget is spent are handled
+    /// in the next frame. At least one of each is handled every frame.
+    ///
+    /// Events which fail are kept in the [`SqlxDeadLetters`], if the plugin
+    /// was built [`SqlxPlugin::with_dead_letters`]. Writes rejected by a
+    /// unique or foreign key constraint send an
+    /// [`SqlxEventStatus::Constraint`] before their error.
+    ///
+    /// Aggregates always send an [`SqlxEventStatus::Scalar`] with their
+    /// value instead, and exports send [`SqlxEventStatus::Progress`] while
+    /// running, then an [`SqlxEventStatus::Done`].
+    ///
+    /// Successful generated writes also send a [`SqlxTableChanged`].
+    ///
+    /// Inserts which don't return their row send an
+    /// [`SqlxEventStatus::Inserted`] with the key generated for it first.
+    ///
+    /// Events which fail because the connection to the database was lost
+    /// send no status, and are held until it's restored, see
+    /// [`Self::handle_reconnect`].
+    #[allow(clippy::type_complexity)]
     pub fn handle_tasks(
         world: &mut World,
         params: &mut SystemState<(
-            Query<(Entity, Ref<C>)>,
+            (
+                Query<(Entity, Ref<C>), Without<SqlxSkipSync>>,
+                Query<(Entity, &SqlxKey<C>), Without<SqlxSkipSync>>,
+            ),
             Commands,
+            Res<SqlxConfig<DB, C>>,
             ResMut<Self>,
             EventWriter<SqlxEventStatus<DB, C>>,
+            EventWriter<SqlxTableChanged>,
+            EventWriter<SqlxConnectionStatus<DB>>,
+            Option<ResMut<SqlxDeadLetters<DB, C>>>,
         )>,
     ) {
-        let (mut query, mut commands, mut tasks, mut status) =
-            params.get_mut(world);
+        let config = world.resource::<SqlxConfig<DB, C>>();
+        let parent = config.spawn_target.clone().parent(world);
+        let (
+            (query, keys),
+            mut commands,
+            config,
+            mut tasks,
+            mut status,
+            mut changes,
+            mut connection,
+            mut letters,
+        ) = params.get_mut(world);
 
-        tasks.components.retain_mut(|(id, sync, task)| {
-            block_on(future::poll_once(task))
-                .map(|result| {
-                    match result {
-                        Ok(task_components) => {
-                            if *sync {
-                                for task_component in task_components {
-                                    // Check if the task's component is already spawned.
-                                    let mut existing_entity = None;
-                                    for (entity, spawned_component) in
-                                        &mut query
-                                    {
-                                        if task_component.primary_key()
-                                            == spawned_component.primary_key()
-                                        {
-                                            existing_entity = Some(entity);
-                                            break;
-                                        }
-                                    }
-
-                                    if let Some(entity) = existing_entity {
-                                        status.send(SqlxEventStatus::Update(
-                                            *id,
-                                            task_component.primary_key(),
-                                            PhantomData,
-                                        ));
-                                        commands
-                                            .entity(entity)
-                                            .insert(task_component);
-                                    } else {
-                                        status.send(SqlxEventStatus::Spawn(
-                                            *id,
-                                            task_component.primary_key(),
-                                            PhantomData,
-                                        ));
-                                        // TODO: Look into world.spawn_batch
-                                        // after taking set disjunction of ids.
-                                        commands.spawn(task_component);
-                                    }
-                                }
-                            } else {
-                                status.send(SqlxEventStatus::Return(
-                                    *id,
-                                    task_components,
-                                ));
-                            }
-                        }
-                        Err(err) => {
-                            status.send(SqlxEventStatus::Error(*id, err));
+        let start = Instant::now();
+        let spent = |handled| {
+            handled > 0
+                && config
+                    .frame_budget
+                    .is_some_and(|budget| start.elapsed() >= budget)
+        };
+        tasks.queued.retain(|(id, started)| {
+            let started = started.load(Ordering::Relaxed);
+            if started {
+                status.send(SqlxEventStatus::Executing(*id));
+            }
+            !started
+        });
+
+        for (event, _, _) in &tasks.components {
+            if let Some(rows) = event.progress() {
+                status
+                    .send(SqlxEventStatus::Progress(event.assigned_id(), rows));
+            }
+        }
+
+        let mut lost = Vec::new();
+        let mut syncing = Vec::new();
+        let mut handled = 0;
+        let tasks = &mut *tasks;
+        tasks.finished.extend(tasks.receiver.try_iter());
+        while !spent(handled) {
+            let Some((id, result)) = tasks.finished.pop_front() else {
+                break;
+            };
+            let Some(index) = tasks
+                .components
+                .iter()
+                .position(|(e, ..)| e.assigned_id() == id)
+            else {
+                continue;
+            };
+            let (event, _, _) = tasks.components.remove(index);
+            let (event, sync) = (&event, event.will_sync());
+            handled += 1;
+            if let (Ok(_), Some(table)) = (&result, event.written_table()) {
+                changes.send(SqlxTableChanged::new(table));
+            }
+            if let Some(letters) = &mut letters {
+                match &result {
+                    Err(err) if is_connection_error(err) => {}
+                    Err(err) => letters.fail(event.clone(), err),
+                    Ok(_) => letters.succeed(id),
+                }
+            }
+            let mut complete = sync.then(SqlxSyncSummary::default);
+            let result = result.map(|output| match output {
+                SqlxTaskOutput::Inserted(key, output) => {
+                    status.send(SqlxEventStatus::Inserted(id, key));
+                    *output
+                }
+                output => output,
+            });
+            match result {
+                Err(err) if is_connection_error(&err) => {
+                    complete = None;
+                    lost.push(event.clone());
+                }
+                Ok(SqlxTaskOutput::Scalar(value)) => {
+                    status.send(SqlxEventStatus::Scalar(id, value));
+                }
+                Ok(SqlxTaskOutput::Done(rows)) => {
+                    status.send(SqlxEventStatus::Done(id, rows));
+                }
+                Ok(SqlxTaskOutput::SchemaChanged) => {
+                    status.send(SqlxEventStatus::SchemaChanged(id));
+                }
+                Ok(SqlxTaskOutput::Optional(component)) => {
+                    status.send(SqlxEventStatus::Optional(id, component));
+                }
+                Ok(SqlxTaskOutput::Inserted(..)) => {
+                    unreachable!("inserted outputs are unwrapped")
+                }
+                Ok(SqlxTaskOutput::Components(task_components)) => {
+                    if config.detailed_statuses {
+                        status.send(SqlxEventStatus::Decoded(
+                            id,
+                            task_components.len() as u64,
+                        ));
+                    }
+                    if let Some(entity) = event.target() {
+                        let component = task_components.into_iter().next();
+                        status.send(Self::load_into_target(
+                            event,
+                            entity,
+                            component,
+                            &mut commands,
+                            (&config, parent),
+                        ));
+                    } else if sync && !config.sync_mode.reads() {
+                        status
+                            .send(SqlxEventStatus::Return(id, task_components));
+                    } else if sync && config.sync_budget.is_some() {
+                        complete = None;
+                        syncing.push((
+                            id,
+                            SqlxSyncSummary::default(),
+                            task_components.into(),
+                        ));
+                    } else if let Some(summary) = &mut complete {
+                        for task_component in task_components {
+                            summary.add(Self::sync(
+                                id,
+                                task_component,
+                                (&query, &keys),
+                                &mut commands,
+                                (&config, parent),
+                                &mut status,
+                            ));
                         }
+                    } else {
+                        status
+                            .send(SqlxEventStatus::Return(id, task_components));
                     }
-                })
-                .is_none()
-        });
+                }
+                Err(err) => {
+                    if let Some(summary) = &mut complete {
+                        summary.errors += 1;
+                    }
+                    if let Some(violation) = SqlxConstraintViolation::of(&err) {
+                        status.send(SqlxEventStatus::Constraint(id, violation));
+                    }
+                    status.send(SqlxEventStatus::Error(id, err, event.clone()));
+                }
+            }
+            if let Some(summary) = complete {
+                status.send(SqlxEventStatus::Complete(id, summary));
+            }
+        }
+
+        if !lost.is_empty() && tasks.reconnect.is_none() {
+            tasks.reconnect = Some(SqlxReconnect::new());
+            connection.send(SqlxConnectionStatus::Lost(PhantomData));
+        }
+        tasks.lost.extend(lost);
 
+        tasks.syncing.extend(syncing);
+        let mut budget = config.sync_budget.unwrap_or(0);
+        let mut handled = 0;
+        while let Some((id, summary, components)) = tasks.syncing.front_mut() {
+            while budget > 0 && !spent(handled) {
+                let Some(component) = components.pop_front() else {
+                    break;
+                };
+                summary.add(Self::sync(
+                    *id,
+                    component,
+                    (&query, &keys),
+                    &mut commands,
+                    (&config, parent),
+                    &mut status,
+                ));
+                budget -= 1;
+                handled += 1;
+            }
+            if !components.is_empty() {
+                break;
+            }
+            status.send(SqlxEventStatus::Complete(*id, *summary));
+            tasks.syncing.pop_front();
+        }
+
+        world.init_resource::<SqlxApplyingSync<C>>();
         params.apply(world);
+        world.remove_resource::<SqlxApplyingSync<C>>();
+    }
+
+    /// Insert the `component` loaded by `event` onto its target `entity`,
+    /// handling a despawned entity as the plugin's [`SqlxDespawnedTarget`]
+    /// says
+    fn load_into_target(
+        event: &SqlxEvent<DB, C>,
+        entity: Entity,
+        component: Option<C>,
+        commands: &mut Commands,
+        (config, parent): (&SqlxConfig<DB, C>, Option<Entity>),
+    ) -> SqlxEventStatus<DB, C> {
+        let id = event.assigned_id();
+        let Some(component) = component else {
+            return SqlxEventStatus::Error(
+                id,
+                Error::RowNotFound,
+                event.clone(),
+            );
+        };
+        let pk = component.primary_key();
+        if let Some(mut target) = commands.get_entity(entity) {
+            // The entity may still be despawned by a command queued before
+            // this one, which `try_insert` ignores.
+            target.try_insert(component);
+            return SqlxEventStatus::Update(id, pk, PhantomData);
+        }
+        match config.despawned_target {
+            SqlxDespawnedTarget::Discard => SqlxEventStatus::Done(id, 0),
+            SqlxDespawnedTarget::Respawn => {
+                config.spawn_target.spawn(commands, parent, component);
+                SqlxEventStatus::Spawn(id, pk, PhantomData)
+            }
+            SqlxDespawnedTarget::Error => {
+                let err = format!("{entity} is gone");
+                SqlxEventStatus::Error(
+                    id,
+                    Error::Configuration(err.into()),
+                    event.clone(),
+                )
+            }
+        }
     }
 
+    /// Apply `component` with the plugin's [`SqlxApply`], spawning new
+    /// entities as children of `parent`
+    #[allow(clippy::type_complexity)]
+    fn sync<'w, 's>(
+        id: SqlxEventId,
+        component: C,
+        (query, keys): (
+            &Query<'w, 's, (Entity, Ref<'static, C>), Without<SqlxSkipSync>>,
+            &Query<
+                'w,
+                's,
+                (Entity, &'static SqlxKey<C>),
+                Without<SqlxSkipSync>,
+            >,
+        ),
+        commands: &mut Commands<'w, 's>,
+        (config, parent): (&SqlxConfig<DB, C>, Option<Entity>),
+        status: &mut EventWriter<SqlxEventStatus<DB, C>>,
+    ) -> SqlxApplied {
+        let pk = component.primary_key();
+        let mut ctx = SqlxApplyContext {
+            commands,
+            spawned: query,
+            keys,
+            matcher: &*config.matcher,
+            target: &config.spawn_target,
+            parent,
+        };
+        let applied = config.apply.apply(component, &mut ctx);
+        match applied {
+            SqlxApplied::Spawned => {
+                status.send(SqlxEventStatus::Spawn(id, pk, PhantomData));
+            }
+            SqlxApplied::Updated => {
+                status.send(SqlxEventStatus::Update(id, pk, PhantomData));
+            }
+            SqlxApplied::Ignored => {}
+        }
+        applied
+    }
+
+    /// The number of running tasks
     pub fn count(&self) -> usize {
         self.components.len()
     }
 
+    /// What each running task is doing, in the order they were spawned
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use sqlx::Sqlite;
+    /// # use bevy_sqlx::*;
+    /// fn overlay(tasks: Res<SqlxTasks<Sqlite, SqlxDummy>>) {
+    ///     for task in tasks.pending() {
+    ///         let label = task.label.as_deref().unwrap_or("query");
+    ///         info!("{label} running for {:?}", task.elapsed);
+    ///     }
+    /// }
+    /// ```
+    pub fn pending(&self) -> impl Iterator<Item = SqlxPendingTask> + '_ {
+        self.components.iter().map(|(event, spawned, _)| SqlxPendingTask {
+            id: event.assigned_id(),
+            label: event.label().map(Arc::from),
+            elapsed: spawned.elapsed(),
+            will_sync: event.will_sync(),
+        })
+    }
+
     pub fn is_empty(&self) -> bool {
-        self.components.is_empty()
+        self.components.is_empty() && self.syncing.is_empty()
+    }
+
+    /// Return true while the event `id` is throttled or its task is running
+    pub fn is_pending(&self, id: SqlxEventId) -> bool {
+        self.components.iter().any(|(event, _, _)| event.id() == Some(id))
+            || self.throttled.iter().any(|event| event.id() == Some(id))
+            || self.lost.iter().any(|event| event.id() == Some(id))
+            || self.syncing.iter().any(|(event, _, _)| *event == id)
+    }
+
+    /// Return true while the connection to the database is lost
+    pub fn is_connection_lost(&self) -> bool {
+        self.reconnect.is_some()
+    }
+
+    /// A [`System`] which tries to reconnect to the database while the
+    /// connection is lost, with exponential backoff
+    ///
+    /// Once a connection is acquired a [`SqlxConnectionStatus::Restored`]
+    /// event is sent and the held events are dispatched again, before any
+    /// other.
+    pub fn handle_reconnect(
+        database: Res<SqlxDatabase<DB>>,
+        credentials: Option<Res<SqlxCredentials<DB>>>,
+        mut tasks: ResMut<Self>,
+        mut connection: EventWriter<SqlxConnectionStatus<DB>>,
+    ) {
+        let Some(reconnect) = &mut tasks.reconnect else {
+            return;
+        };
+        let provider = credentials.as_ref().map(|c| &c.provider);
+        if !reconnect.poll(&database.pool, provider) {
+            return;
+        }
+
+        tasks.reconnect = None;
+        let mut lost = std::mem::take(&mut tasks.lost);
+        lost.append(&mut tasks.throttled);
+        tasks.throttled = lost;
+        connection.send(SqlxConnectionStatus::Restored(PhantomData));
+    }
+
+    /// The number of events waiting on the plugin's [`SqlxRateLimit`]
+    pub fn throttled(&self) -> usize {
+        self.throttled.len()
+    }
+}
+
+impl<DB: Database + Sync, C: SqlxComponent<DB::Row>> SqlxTasks<DB, C>
+where
+    for<'c> &'c mut <DB as Database>::Connection: Executor<'c, Database = DB>,
+    for<'q> <DB as Database>::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+    for<'r> SqlxValue: Decode<'r, DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    /// An exclusive [`System`] which blocks until every pending event is
+    /// finished once [`AppExit`] is sent
+    ///
+    /// Throttled events are dispatched right away, ignoring the plugin's
+    /// [`SqlxRateLimit`], then [`Self::handle_tasks`] is run until no tasks
+    /// remain, or [`SqlxConfig::flush_timeout`] has passed.
+    #[allow(clippy::type_complexity)]
+    pub fn flush_on_exit(
+        world: &mut World,
+        params: &mut SystemState<(
+            EventReader<AppExit>,
+            Res<SqlxDatabase<DB>>,
+            Res<SqlxConfig<DB, C>>,
+            Option<Res<TenantId>>,
+            ResMut<Self>,
+            EventWriter<SqlxEventStatus<DB, C>>,
+        )>,
+    ) {
+        let (mut exits, database, config, tenant, mut tasks, mut status) =
+            params.get_mut(world);
+        if exits.is_empty() {
+            return;
+        }
+        exits.clear();
+
+        let deadline = Instant::now() + config.flush_timeout;
+        for event in std::mem::take(&mut tasks.throttled) {
+            let db = database.pool.clone();
+            let tenant = tenant.as_deref();
+            event.dispatch(db, &config, tenant, &mut tasks, &mut status);
+        }
+        params.apply(world);
+
+        let mut handle_tasks = SystemState::new(world);
+        while !world.resource::<Self>().is_empty() && Instant::now() < deadline
+        {
+            Self::handle_tasks(world, &mut handle_tasks);
+            std::thread::sleep(Duration::from_millis(1));
+        }
     }
 }
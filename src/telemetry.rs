@@ -0,0 +1,349 @@
+use crate::*;
+use bevy::prelude::*;
+use bevy::tasks::futures_lite::future;
+use bevy::tasks::{block_on, AsyncComputeTaskPool, Task};
+use bevy::utils::{Duration, Instant};
+use sqlx::{Database, Encode, Error, Executor, IntoArguments, Pool, Type};
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// The buffered rows of one table
+type SqlxTelemetryRows = Vec<Vec<(&'static str, SqlxValue)>>;
+
+/// A [`Plugin`] adding a [`SqlxTelemetry`] sink, flushing the datapoints
+/// pushed to it in batched inserts
+///
+/// The buffered datapoints are flushed every `interval`, or once
+/// [`Self::with_batch_size`] of them are buffered, with
+/// [`SqlxEvent::copy_in`]'s multi-row `INSERT`s, or `COPY` on Postgres. If
+/// the last flush hasn't finished yet, the next one waits for it. Once
+/// [`Self::with_capacity`] datapoints are buffered, new ones are dropped,
+/// and reported by the next [`SqlxTelemetryFlushed`].
+///
+/// The inserts bypass the events of the [`SqlxPlugin`], so they aren't
+/// throttled, audited or scoped to a tenant.
+///
+/// ### Example
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy::utils::Duration;
+/// # use sqlx::Sqlite;
+/// # use bevy_sqlx::*;
+/// struct FrameTime {
+///     millis: f64,
+/// }
+///
+/// impl ToRow for FrameTime {
+///     fn table() -> &'static str { "frame_times" }
+///     fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+///         vec![("millis", self.millis.into())]
+///     }
+/// }
+///
+/// fn measure(
+///     time: Res<Time>,
+///     mut telemetry: ResMut<SqlxTelemetry<Sqlite>>,
+/// ) {
+///     let millis = time.delta_seconds_f64() * 1000.;
+///     telemetry.push(&FrameTime { millis });
+/// }
+///
+/// let url = "sqlite:db/sqlite.db";
+/// App::new()
+///     .add_plugins(SqlxPlugin::<Sqlite, SqlxDummy>::from_url(url))
+///     .add_plugins(
+///         SqlxTelemetryPlugin::<Sqlite>::every(Duration::from_secs(10)),
+///     )
+///     .add_systems(Update, measure);
+/// ```
+pub struct SqlxTelemetryPlugin<DB> {
+    interval: Duration,
+    batch_size: usize,
+    capacity: usize,
+    _db: PhantomData<fn() -> DB>,
+}
+
+impl<DB> SqlxTelemetryPlugin<DB> {
+    /// Flush every `interval`
+    pub fn every(interval: Duration) -> Self {
+        SqlxTelemetryPlugin {
+            interval,
+            batch_size: 1000,
+            capacity: 100_000,
+            _db: PhantomData,
+        }
+    }
+
+    /// Flush as soon as `batch_size` datapoints are buffered, 1000 by
+    /// default
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Drop the datapoints pushed while `capacity` of them are buffered,
+    /// 100 000 by default
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+}
+
+impl<DB> Plugin for SqlxTelemetryPlugin<DB>
+where
+    DB: Database + Sync,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+{
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SqlxTelemetry::<DB> {
+            interval: self.interval,
+            batch_size: self.batch_size,
+            capacity: self.capacity,
+            flushed: Instant::now(),
+            tables: Vec::new(),
+            len: 0,
+            dropped: 0,
+            task: None,
+            _db: PhantomData,
+        });
+        app.add_event::<SqlxTelemetryFlushed>();
+        // Client builds never write, see `SqlxPlugin::with_client`.
+        if cfg!(feature = "client") {
+            return;
+        }
+        app.add_systems(Update, SqlxTelemetry::<DB>::handle_telemetry);
+    }
+}
+
+/// A [`Resource`] buffering datapoints until the [`SqlxTelemetryPlugin`]
+/// of `DB` flushes them
+#[derive(Resource)]
+pub struct SqlxTelemetry<DB> {
+    interval: Duration,
+    batch_size: usize,
+    capacity: usize,
+    flushed: Instant,
+    tables: Vec<(&'static str, SqlxTelemetryRows)>,
+    len: usize,
+    dropped: u64,
+    task: Option<Task<Result<u64, Error>>>,
+    _db: PhantomData<fn() -> DB>,
+}
+
+impl<DB> SqlxTelemetry<DB> {
+    /// Buffer the row of `datapoint`, returning false if it was dropped
+    /// because the buffer is full
+    pub fn push<T: ToRow>(&mut self, datapoint: &T) -> bool {
+        if self.is_full() {
+            self.dropped += 1;
+            return false;
+        }
+        let row = datapoint.to_row();
+        match self.tables.iter_mut().find(|(table, _)| *table == T::table()) {
+            Some((_, rows)) => rows.push(row),
+            None => self.tables.push((T::table(), vec![row])),
+        }
+        self.len += 1;
+        true
+    }
+
+    /// The number of datapoints which haven't been flushed yet
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return true if no datapoint is waiting to be flushed
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Return true while new datapoints are dropped
+    pub fn is_full(&self) -> bool {
+        self.len >= self.capacity
+    }
+
+    /// The number of datapoints dropped since the last flush finished
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Return true while a flush is running
+    pub fn is_flushing(&self) -> bool {
+        self.task.is_some()
+    }
+
+    /// Flush on the next frame, instead of waiting for the interval
+    pub fn flush_now(&mut self) {
+        self.flushed = Instant::now() - self.interval;
+    }
+}
+
+impl<DB> SqlxTelemetry<DB>
+where
+    DB: Database + Sync,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+{
+    /// A [`System`] finishing the running flush, and starting the next one
+    /// once the interval elapsed or the batch is full
+    pub fn handle_telemetry(
+        database: Res<SqlxDatabase<DB>>,
+        mut telemetry: ResMut<Self>,
+        mut flushed: EventWriter<SqlxTelemetryFlushed>,
+    ) {
+        if let Some(task) = &mut telemetry.task {
+            let Some(result) = block_on(future::poll_once(task)) else {
+                return;
+            };
+            telemetry.task = None;
+            flushed.send(SqlxTelemetryFlushed {
+                database: DB::NAME,
+                result,
+                dropped: std::mem::take(&mut telemetry.dropped),
+            });
+        }
+        let full = telemetry.len >= telemetry.batch_size;
+        if telemetry.is_empty()
+            || !full && telemetry.flushed.elapsed() < telemetry.interval
+        {
+            return;
+        }
+        telemetry.flushed = Instant::now();
+
+        telemetry.len = 0;
+        let tables = std::mem::take(&mut telemetry.tables);
+        let pool = database.pool.clone();
+        let task = AsyncComputeTaskPool::get().spawn(flush(tables, pool));
+        telemetry.task = Some(task);
+    }
+}
+
+impl<DB> fmt::Debug for SqlxTelemetry<DB> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SqlxTelemetry")
+            .field("interval", &self.interval)
+            .field("batch_size", &self.batch_size)
+            .field("capacity", &self.capacity)
+            .field("buffered", &self.len)
+            .field("dropped", &self.dropped)
+            .field("flushing", &self.is_flushing())
+            .finish_non_exhaustive()
+    }
+}
+
+/// An [`Event`] sent when a flush of a [`SqlxTelemetryPlugin`] finishes
+///
+/// The result is the number of datapoints flushed, or the first error. A
+/// failed table's datapoints are lost, while the other tables are still
+/// flushed.
+#[derive(Event, Debug)]
+pub struct SqlxTelemetryFlushed {
+    /// The [`Database::NAME`] of the flushed database
+    pub database: &'static str,
+    pub result: Result<u64, Error>,
+    /// The number of datapoints dropped because the buffer was full, since
+    /// the previous flush
+    pub dropped: u64,
+}
+
+/// Insert the rows of each table, returning how many were inserted
+async fn flush<DB>(
+    tables: Vec<(&'static str, SqlxTelemetryRows)>,
+    pool: Pool<DB>,
+) -> Result<u64, Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'q> SqlxValue: Encode<'q, DB> + Type<DB>,
+{
+    let mut result = Ok(0);
+    for (table, rows) in tables {
+        let progress = Arc::new(SqlxProgress::default());
+        let copied = copy_in(table, rows.into(), progress, pool.clone()).await;
+        result = match (result, copied) {
+            (Ok(total), Ok(rows)) => Ok(total + rows),
+            (Err(err), _) | (Ok(_), Err(err)) => Err(err),
+        };
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{block_on, AsyncComputeTaskPool, TaskPool};
+    use bevy::utils::Duration;
+    use sqlx::Sqlite;
+
+    struct Sample {
+        value: u32,
+    }
+
+    impl ToRow for Sample {
+        fn table() -> &'static str {
+            "telemetry_samples"
+        }
+
+        fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+            vec![("value", self.value.into())]
+        }
+    }
+
+    #[test]
+    fn test_telemetry() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let mut app = App::new();
+        app.add_plugins(SqlxPlugin::<Sqlite, SqlxDummy>::from_url(url));
+        app.add_plugins(
+            SqlxTelemetryPlugin::<Sqlite>::every(Duration::from_secs(3600))
+                .with_batch_size(10)
+                .with_capacity(15),
+        );
+        let pool = app.world().resource::<SqlxDatabase<Sqlite>>().pool.clone();
+        block_on(async {
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS telemetry_samples \
+                 (value INTEGER NOT NULL)",
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+            sqlx::query("DELETE FROM telemetry_samples")
+                .execute(&pool)
+                .await
+                .unwrap();
+        });
+
+        let mut telemetry =
+            app.world_mut().resource_mut::<SqlxTelemetry<Sqlite>>();
+        let pushed = (0..20)
+            .filter(|value| telemetry.push(&Sample { value: *value }))
+            .count();
+        assert_eq!(15, pushed);
+        assert!(telemetry.is_full());
+
+        let mut reports = Vec::new();
+        let mut tries = 0;
+        while reports.is_empty() && tries < 1000 {
+            app.update();
+            let mut events =
+                app.world_mut().resource_mut::<Events<SqlxTelemetryFlushed>>();
+            reports
+                .extend(events.drain().map(|f| (f.result.unwrap(), f.dropped)));
+            tries += 1;
+        }
+        assert_eq!(vec![(15, 5)], reports);
+        let sql = "SELECT COUNT(*) FROM telemetry_samples";
+        let count: i64 =
+            block_on(sqlx::query_scalar(sql).fetch_one(&pool)).unwrap();
+        assert_eq!(15, count);
+    }
+}
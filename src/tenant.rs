@@ -0,0 +1,33 @@
+use crate::*;
+use bevy::prelude::*;
+
+/// A [`Resource`] identifying the tenant generated statements are scoped to
+///
+/// When a [`SqlxPlugin`] is configured with [`SqlxPlugin::with_tenant`],
+/// every generated [`SqlxStatement`] it dispatches is scoped to this tenant
+/// (see [`SqlxStatement::tenant`]). Raw SQL from [`SqlxEvent::query`] and
+/// [`SqlxEvent::call`] is never rewritten.
+///
+/// ### Example
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::{FromRow, Sqlite};
+/// # use bevy_sqlx::{SqlxPlugin, PrimaryKey, TenantId};
+/// # #[derive(Component, FromRow)]
+/// # struct Foo(u32);
+/// # impl PrimaryKey for Foo {
+/// #     type Column = u32;
+/// #     fn primary_key(&self) -> Self::Column { self.0 }
+/// # }
+/// let url = "sqlite:db/sqlite.db";
+/// App::new()
+///     .add_plugins(DefaultPlugins)
+///     .add_plugins(
+///         SqlxPlugin::<Sqlite, Foo>::from_url(&url).with_tenant("tenant_id"),
+///     )
+///     .insert_resource(TenantId(42.into()))
+///     .run();
+/// ```
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct TenantId(pub SqlxValue);
@@ -0,0 +1,94 @@
+use bevy::utils::Instant;
+
+/// A limit on how many [`SqlxEvent`](crate::SqlxEvent)s are dispatched per
+/// second
+///
+/// Limits are enforced with a token bucket: up to `burst` events can be
+/// dispatched at once, after which `per_second` events are dispatched each
+/// second. Events over the limit are queued in order, and a
+/// [`SqlxEventStatus::Throttled`](crate::SqlxEventStatus::Throttled) is sent
+/// for each of them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SqlxRateLimit {
+    per_second: f32,
+    burst: u32,
+}
+
+impl SqlxRateLimit {
+    /// Dispatch `per_second` events each second, after an initial `burst`
+    ///
+    /// # Panics
+    ///
+    /// If `per_second` isn't positive, or `burst` is 0, since no event
+    /// would ever be dispatched.
+    pub fn new(per_second: f32, burst: u32) -> Self {
+        assert!(per_second > 0., "rate limit must be positive");
+        assert!(burst >= 1, "rate limit burst must be at least 1");
+        SqlxRateLimit { per_second, burst }
+    }
+
+    /// The number of events dispatched each second
+    pub fn per_second(&self) -> f32 {
+        self.per_second
+    }
+
+    /// The number of events dispatched at once
+    pub fn burst(&self) -> u32 {
+        self.burst
+    }
+}
+
+/// The token bucket enforcing a [`SqlxRateLimit`]
+#[derive(Debug)]
+pub(crate) struct SqlxTokenBucket {
+    tokens: f32,
+    refilled: Instant,
+}
+
+impl SqlxTokenBucket {
+    pub(crate) fn new(limit: &SqlxRateLimit) -> Self {
+        SqlxTokenBucket { tokens: limit.burst as f32, refilled: Instant::now() }
+    }
+
+    /// Take a token from the bucket, returning false if it's empty
+    pub(crate) fn try_acquire(&mut self, limit: &SqlxRateLimit) -> bool {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.refilled).as_secs_f32();
+        self.tokens =
+            (self.tokens + elapsed * limit.per_second).min(limit.burst as f32);
+        self.refilled = now;
+
+        if self.tokens >= 1. {
+            self.tokens -= 1.;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_token_bucket() {
+        let limit = SqlxRateLimit::new(1., 2);
+        let mut bucket = SqlxTokenBucket::new(&limit);
+        assert!(bucket.try_acquire(&limit));
+        assert!(bucket.try_acquire(&limit));
+        assert!(!bucket.try_acquire(&limit));
+    }
+
+    #[test]
+    #[should_panic(expected = "burst must be at least 1")]
+    fn test_rate_limit_no_burst() {
+        SqlxRateLimit::new(10., 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "rate limit must be positive")]
+    fn test_rate_limit_not_positive() {
+        SqlxRateLimit::new(0., 10);
+    }
+}
@@ -0,0 +1,206 @@
+use crate::*;
+use bevy::utils::Duration;
+use sqlx::pool::PoolConnection;
+use sqlx::{ColumnIndex, Database, Decode, Error, Executor, IntoArguments};
+use sqlx::{Pool, Type};
+use std::future::Future;
+use std::pin::Pin;
+
+/// A connection whose statement timeout is set for one event, see
+/// [`SqlxPlugin::with_statement_timeout`]
+///
+/// The timeout is enforced by the database itself:
+/// - Postgres sets `statement_timeout`, cancelling statements which run
+///   longer
+/// - SQLite sets `busy_timeout`, failing statements which wait longer for a
+///   lock
+/// - MySQL sets `max_execution_time`, which only applies to `SELECT`s
+///
+/// Other databases ignore the timeout.
+pub(crate) struct SqlxTimedConnection<DB: Database> {
+    conn: PoolConnection<DB>,
+    /// The SQL restoring the connection's previous timeout
+    reset: Option<String>,
+}
+
+impl<DB: Database> SqlxTimedConnection<DB>
+where
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'r> SqlxValue: Decode<'r, DB> + Type<DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    /// Acquire a connection of `pool`, with its statement timeout set to
+    /// `timeout`, if any
+    pub(crate) async fn acquire(
+        pool: &Pool<DB>,
+        timeout: Option<Duration>,
+    ) -> Result<Self, Error> {
+        let mut conn = pool.acquire().await?;
+        let Some(timeout) = timeout else {
+            return Ok(SqlxTimedConnection { conn, reset: None });
+        };
+        let millis = timeout.as_millis();
+        let (set, reset) = match DB::NAME {
+            "PostgreSQL" => (
+                format!("SET statement_timeout = {millis}"),
+                "RESET statement_timeout".into(),
+            ),
+            "SQLite" => {
+                let previous: SqlxValue =
+                    sqlx::query_scalar("PRAGMA busy_timeout")
+                        .fetch_one(&mut *conn)
+                        .await?;
+                let SqlxValue::Int(previous) = previous else {
+                    let err = format!("unexpected busy_timeout {previous:?}");
+                    return Err(Error::Decode(err.into()));
+                };
+                (
+                    format!("PRAGMA busy_timeout = {millis}"),
+                    format!("PRAGMA busy_timeout = {previous}"),
+                )
+            }
+            "MySQL" => (
+                format!("SET SESSION max_execution_time = {millis}"),
+                "SET SESSION max_execution_time = DEFAULT".into(),
+            ),
+            _ => return Ok(SqlxTimedConnection { conn, reset: None }),
+        };
+        sqlx::query(&set).execute(&mut *conn).await?;
+        Ok(SqlxTimedConnection { conn, reset: Some(reset) })
+    }
+
+    /// Restore the connection's previous timeout, returning `result`
+    pub(crate) async fn release<T>(
+        mut self,
+        result: Result<T, Error>,
+    ) -> Result<T, Error> {
+        if let Some(reset) = &self.reset {
+            let reset = sqlx::query(reset).execute(&mut *self.conn).await;
+            if reset.is_err() {
+                // Don't return a connection with the wrong timeout.
+                self.conn.close_on_drop();
+            }
+        }
+        result
+    }
+}
+
+/// The future of an event's statements, on one connection
+pub(crate) type SqlxConnFuture<'c, C> =
+    Pin<Box<dyn Future<Output = Result<SqlxTaskOutput<C>, Error>> + Send + 'c>>;
+
+/// Run `run` on a connection of `pool` whose statement timeout is `timeout`,
+/// if any
+pub(crate) fn timed<DB, C, F>(
+    pool: Pool<DB>,
+    timeout: Option<Duration>,
+    run: F,
+) -> SqlxEventFuture<C>
+where
+    DB: Database,
+    C: Send + 'static,
+    F: for<'c> FnOnce(&'c mut DB::Connection) -> SqlxConnFuture<'c, C>
+        + Send
+        + 'static,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'r> SqlxValue: Decode<'r, DB> + Type<DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    Box::pin(async move {
+        let mut timed = SqlxTimedConnection::acquire(&pool, timeout).await?;
+        let result = run(&mut timed.conn).await;
+        timed.release(result).await
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{block_on, AsyncComputeTaskPool, TaskPool};
+    use bevy::utils::{Duration, Instant};
+    use sqlx::pool::PoolOptions;
+    use sqlx::{FromRow, Pool, Sqlite};
+
+    #[derive(Component, FromRow, Debug)]
+    struct Foo {
+        id: u32,
+    }
+
+    impl PrimaryKey for Foo {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    fn update_until_ended(app: &mut App) -> Option<bool> {
+        let mut tries = 0;
+        while tries < 1000 {
+            app.update();
+            let events =
+                app.world().resource::<Events<SqlxEventStatus<Sqlite, Foo>>>();
+            for status in events.iter_current_update_events() {
+                match status {
+                    SqlxEventStatus::Return(..) => return Some(true),
+                    SqlxEventStatus::Error(..) => return Some(false),
+                    _ => {}
+                }
+            }
+            tries += 1;
+        }
+        None
+    }
+
+    #[test]
+    fn test_statement_timeout() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let options = PoolOptions::<Sqlite>::new().max_connections(1);
+        let pool = block_on(options.connect(url)).unwrap();
+        block_on(
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS timeouts \
+                 (id INTEGER PRIMARY KEY, text TEXT NOT NULL)",
+            )
+            .execute(&pool),
+        )
+        .unwrap();
+
+        let mut app = App::new();
+        let plugin = SqlxPlugin::<Sqlite, Foo>::from_pool(pool.clone())
+            .with_statement_timeout(Duration::from_secs(2));
+        app.add_plugins(plugin);
+        let config = app.world().resource::<SqlxConfig<Sqlite, Foo>>();
+        assert_eq!(Some(Duration::from_secs(2)), config.statement_timeout);
+
+        // While another connection holds the write lock, the insert gives up
+        // after its own timeout instead of the plugin's.
+        let locker: Pool<Sqlite> = block_on(Pool::connect(url)).unwrap();
+        let mut lock = block_on(locker.acquire()).unwrap();
+        block_on(sqlx::query("BEGIN IMMEDIATE").execute(&mut *lock)).unwrap();
+        let sql = "INSERT INTO timeouts (text) VALUES ('locked') RETURNING id";
+        let insert = SqlxEvent::<Sqlite, Foo>::query(sql)
+            .with_statement_timeout(Duration::from_millis(10));
+        let start = Instant::now();
+        app.world_mut().send_event(insert);
+        assert_eq!(Some(false), update_until_ended(&mut app));
+        assert!(start.elapsed() < Duration::from_secs(2));
+        block_on(sqlx::query("ROLLBACK").execute(&mut *lock)).unwrap();
+        drop(lock);
+
+        // The connection's own busy timeout is restored afterwards.
+        let busy: i64 = block_on(
+            sqlx::query_scalar("PRAGMA busy_timeout").fetch_one(&pool),
+        )
+        .unwrap();
+        assert_eq!(5000, busy);
+
+        let sql = "INSERT INTO timeouts (text) VALUES ('free') RETURNING id";
+        let insert = SqlxEvent::<Sqlite, Foo>::query(sql);
+        app.world_mut().send_event(insert);
+        assert_eq!(Some(true), update_until_ended(&mut app));
+    }
+}
@@ -0,0 +1,273 @@
+use crate::*;
+use bevy::tasks::block_on;
+use sqlx::{ConnectOptions, Connection, Database};
+use std::fmt;
+use std::path::PathBuf;
+
+/// How a connection negotiates TLS, see [`SqlxTls`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SqlxTlsMode {
+    /// Never use TLS
+    Disable,
+    /// Use TLS if the server supports it, the default of Postgres and MySQL
+    #[default]
+    Prefer,
+    /// Fail to connect without TLS, but don't verify the server's
+    /// certificate
+    Require,
+    /// Fail to connect without TLS, or when the server's certificate isn't
+    /// signed by a trusted root certificate
+    VerifyCa,
+    /// Like [`Self::VerifyCa`], but the certificate must also be issued to
+    /// the host being connected to
+    VerifyFull,
+}
+
+/// A certificate or key, either in a file or in memory
+#[derive(Clone)]
+pub enum SqlxTlsCert {
+    /// A PEM file, read when connecting
+    Path(PathBuf),
+    /// PEM encoded bytes, like a secret from the environment
+    Pem(Vec<u8>),
+}
+
+impl fmt::Debug for SqlxTlsCert {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SqlxTlsCert::Path(path) => {
+                f.debug_tuple("Path").field(path).finish()
+            }
+            // Never print key material.
+            SqlxTlsCert::Pem(pem) => {
+                write!(f, "Pem({} bytes)", pem.len())
+            }
+        }
+    }
+}
+
+impl From<PathBuf> for SqlxTlsCert {
+    fn from(path: PathBuf) -> Self {
+        SqlxTlsCert::Path(path)
+    }
+}
+
+impl From<&str> for SqlxTlsCert {
+    fn from(path: &str) -> Self {
+        SqlxTlsCert::Path(path.into())
+    }
+}
+
+impl From<Vec<u8>> for SqlxTlsCert {
+    fn from(pem: Vec<u8>) -> Self {
+        SqlxTlsCert::Pem(pem)
+    }
+}
+
+/// The TLS settings of a connection, for [`SqlxPlugin::with_tls`]
+///
+/// A URL can set the mode, but not certificates held in memory, so
+/// production databases requiring client certificates are configured here
+/// instead. Connecting with TLS needs one of sqlx's TLS features, like
+/// `sqlx/tls-rustls`, to be enabled.
+///
+/// ```
+/// use bevy_sqlx::{SqlxTls, SqlxTlsMode};
+///
+/// # std::env::set_var("DATABASE_CLIENT_KEY", "...");
+/// let key = std::env::var("DATABASE_CLIENT_KEY").unwrap();
+/// SqlxTls::new(SqlxTlsMode::VerifyFull)
+///     .with_root_cert("certs/root.pem")
+///     .with_client_cert("certs/client.pem")
+///     .with_client_key(key.into_bytes());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SqlxTls {
+    pub mode: SqlxTlsMode,
+    /// The certificate of the authority which signed the server's
+    pub root_cert: Option<SqlxTlsCert>,
+    /// The certificate presented to the server
+    pub client_cert: Option<SqlxTlsCert>,
+    /// The private key of the client certificate
+    pub client_key: Option<SqlxTlsCert>,
+}
+
+impl SqlxTls {
+    /// Connect with `mode`, without any certificates
+    pub fn new(mode: SqlxTlsMode) -> Self {
+        SqlxTls { mode, ..Default::default() }
+    }
+
+    /// Verify the server's certificate with `cert`
+    pub fn with_root_cert(mut self, cert: impl Into<SqlxTlsCert>) -> Self {
+        self.root_cert = Some(cert.into());
+        self
+    }
+
+    /// Present `cert` to the server, with [`Self::with_client_key`]
+    pub fn with_client_cert(mut self, cert: impl Into<SqlxTlsCert>) -> Self {
+        self.client_cert = Some(cert.into());
+        self
+    }
+
+    /// Sign with `key`, for the certificate of [`Self::with_client_cert`]
+    pub fn with_client_key(mut self, key: impl Into<SqlxTlsCert>) -> Self {
+        self.client_key = Some(key.into());
+        self
+    }
+}
+
+/// Connect options which [`SqlxTls`] settings can be applied to
+///
+/// This is implemented for `PgConnectOptions` and `MySqlConnectOptions`,
+/// with the `postgres` and `mysql` features.
+pub trait SqlxTlsOptions: ConnectOptions {
+    /// Use `tls` for new connections
+    fn with_tls(self, tls: &SqlxTls) -> Self;
+}
+
+#[cfg(feature = "postgres")]
+impl SqlxTlsOptions for sqlx::postgres::PgConnectOptions {
+    fn with_tls(mut self, tls: &SqlxTls) -> Self {
+        use sqlx::postgres::PgSslMode;
+
+        self = self.ssl_mode(match tls.mode {
+            SqlxTlsMode::Disable => PgSslMode::Disable,
+            SqlxTlsMode::Prefer => PgSslMode::Prefer,
+            SqlxTlsMode::Require => PgSslMode::Require,
+            SqlxTlsMode::VerifyCa => PgSslMode::VerifyCa,
+            SqlxTlsMode::VerifyFull => PgSslMode::VerifyFull,
+        });
+        self = match &tls.root_cert {
+            Some(SqlxTlsCert::Path(path)) => self.ssl_root_cert(path),
+            Some(SqlxTlsCert::Pem(pem)) => {
+                self.ssl_root_cert_from_pem(pem.clone())
+            }
+            None => self,
+        };
+        self = match &tls.client_cert {
+            Some(SqlxTlsCert::Path(path)) => self.ssl_client_cert(path),
+            Some(SqlxTlsCert::Pem(pem)) => self.ssl_client_cert_from_pem(pem),
+            None => self,
+        };
+        match &tls.client_key {
+            Some(SqlxTlsCert::Path(path)) => self.ssl_client_key(path),
+            Some(SqlxTlsCert::Pem(pem)) => self.ssl_client_key_from_pem(pem),
+            None => self,
+        }
+    }
+}
+
+#[cfg(feature = "mysql")]
+impl SqlxTlsOptions for sqlx::mysql::MySqlConnectOptions {
+    fn with_tls(mut self, tls: &SqlxTls) -> Self {
+        use sqlx::mysql::MySqlSslMode;
+
+        self = self.ssl_mode(match tls.mode {
+            SqlxTlsMode::Disable => MySqlSslMode::Disabled,
+            SqlxTlsMode::Prefer => MySqlSslMode::Preferred,
+            SqlxTlsMode::Require => MySqlSslMode::Required,
+            SqlxTlsMode::VerifyCa => MySqlSslMode::VerifyCa,
+            SqlxTlsMode::VerifyFull => MySqlSslMode::VerifyIdentity,
+        });
+        self = match &tls.root_cert {
+            Some(SqlxTlsCert::Path(path)) => self.ssl_ca(path),
+            Some(SqlxTlsCert::Pem(pem)) => self.ssl_ca_from_pem(pem.clone()),
+            None => self,
+        };
+        self = match &tls.client_cert {
+            Some(SqlxTlsCert::Path(path)) => self.ssl_client_cert(path),
+            Some(SqlxTlsCert::Pem(pem)) => self.ssl_client_cert_from_pem(pem),
+            None => self,
+        };
+        match &tls.client_key {
+            Some(SqlxTlsCert::Path(path)) => self.ssl_client_key(path),
+            Some(SqlxTlsCert::Pem(pem)) => self.ssl_client_key_from_pem(pem),
+            None => self,
+        }
+    }
+}
+
+impl<DB, C> SqlxPlugin<DB, C>
+where
+    DB: Database,
+    C: SqlxComponent<DB::Row>,
+    <DB::Connection as Connection>::Options: SqlxTlsOptions,
+{
+    /// Build a plugin connecting to `url` with `tls`
+    ///
+    /// Unlike [`Self::with_tls`], no connection is made without TLS first,
+    /// so this works with servers which only accept client certificates.
+    ///
+    #[cfg_attr(feature = "postgres", doc = "```no_run")]
+    #[cfg_attr(not(feature = "postgres"), doc = "```ignore")]
+    /// use sqlx::Postgres;
+    /// use bevy_sqlx::{SqlxPlugin, SqlxDummy, SqlxTls, SqlxTlsMode};
+    ///
+    /// let url = "postgres://db.example.com/game";
+    /// let tls = SqlxTls::new(SqlxTlsMode::VerifyFull)
+    ///     .with_root_cert("certs/root.pem");
+    /// SqlxPlugin::<Postgres, SqlxDummy>::from_url_with_tls(url, &tls);
+    /// ```
+    pub fn from_url_with_tls(url: &str, tls: &SqlxTls) -> Self {
+        let options = url
+            .parse::<<DB::Connection as Connection>::Options>()
+            .unwrap()
+            .with_tls(tls);
+        Self::from_options(options)
+    }
+
+    /// Reconnect the pool with `tls`
+    ///
+    /// Credentials read by a [`SqlxCredentialsProvider`] replace the
+    /// connect options, and with them these settings, so use
+    /// `sslmode` and certificate paths in the provided URL instead.
+    ///
+    #[cfg_attr(feature = "postgres", doc = "```no_run")]
+    #[cfg_attr(not(feature = "postgres"), doc = "```ignore")]
+    /// use sqlx::Postgres;
+    /// use bevy_sqlx::{SqlxPlugin, SqlxDummy, SqlxTls, SqlxTlsMode};
+    ///
+    /// let url = "postgres://db.example.com/game";
+    /// SqlxPlugin::<Postgres, SqlxDummy>::from_url(url)
+    ///     .with_tls(&SqlxTls::new(SqlxTlsMode::Require));
+    /// ```
+    pub fn with_tls(mut self, tls: &SqlxTls) -> Self {
+        let connect = (*self.pool.connect_options()).clone().with_tls(tls);
+        let options = self.pool.options().clone();
+        let pool = block_on(options.connect_with(connect));
+        block_on(self.pool.close());
+        self.pool = pool.unwrap();
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_tls() {
+        let tls = SqlxTls::new(SqlxTlsMode::VerifyCa)
+            .with_root_cert("certs/root.pem")
+            .with_client_key(b"secret".to_vec());
+        assert_eq!(SqlxTlsMode::VerifyCa, tls.mode);
+        assert!(tls.client_cert.is_none());
+        let debug = format!("{tls:?}");
+        assert!(debug.contains("root.pem"));
+        assert!(debug.contains("Pem(6 bytes)"));
+        assert!(!debug.contains("secret"));
+    }
+
+    #[cfg(feature = "postgres")]
+    #[test]
+    fn test_pg_tls() {
+        use sqlx::postgres::{PgConnectOptions, PgSslMode};
+
+        let url = "postgres://localhost/game?sslmode=disable";
+        let options: PgConnectOptions = url.parse().unwrap();
+        let tls = SqlxTls::new(SqlxTlsMode::VerifyFull);
+        let options = options.with_tls(&tls);
+        assert!(matches!(options.get_ssl_mode(), PgSslMode::VerifyFull));
+    }
+}
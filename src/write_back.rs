@@ -0,0 +1,234 @@
+use crate::*;
+use bevy::prelude::*;
+use sqlx::{Database, Executor, IntoArguments};
+use std::marker::PhantomData;
+
+/// A [`Plugin`] writing `C` back to the database from observers of its
+/// lifecycle, instead of a system scanning for changes every frame
+///
+/// - When `C` is inserted, including over an existing `C`, it's upserted.
+/// - When `C` is removed, or its entity despawned, its row is deleted, if
+///   the plugin was built [`Self::with_deletes`].
+///
+/// Only inserts are observed, so a `C` mutated in place isn't written.
+/// Insert the new value instead, e.g. `commands.entity(e).insert(foo)`.
+/// Components synced from the database by [`SqlxTasks::handle_tasks`]
+/// aren't written back, nor are those of entities marked [`SqlxSkipSync`],
+/// and nothing is written for a plugin which doesn't
+/// [`SqlxSyncMode::writes`].
+///
+/// ### Example
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use sqlx::{FromRow, Sqlite};
+/// # use bevy_sqlx::*;
+/// # #[derive(Component, FromRow)]
+/// # struct Foo { id: u32, text: String }
+/// # impl PrimaryKey for Foo {
+/// #     type Column = u32;
+/// #     fn primary_key(&self) -> Self::Column { self.id }
+/// # }
+/// # impl ToRow for Foo {
+/// #     fn table() -> &'static str { "foos" }
+/// #     fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+/// #         vec![("id", self.id.into()), ("text", self.text.clone().into())]
+/// #     }
+/// # }
+/// let url = "sqlite:db/sqlite.db";
+/// App::new()
+///     .add_plugins(SqlxPlugin::<Sqlite, Foo>::from_url(url))
+///     .add_plugins(SqlxWriteBackPlugin::<Sqlite, Foo>::default());
+/// ```
+pub struct SqlxWriteBackPlugin<DB, C> {
+    deletes: bool,
+    _db: PhantomData<DB>,
+    _c: PhantomData<C>,
+}
+
+impl<DB, C> Default for SqlxWriteBackPlugin<DB, C> {
+    fn default() -> Self {
+        SqlxWriteBackPlugin {
+            deletes: false,
+            _db: PhantomData,
+            _c: PhantomData,
+        }
+    }
+}
+
+impl<DB, C> SqlxWriteBackPlugin<DB, C> {
+    /// Delete the rows of removed components too
+    pub fn with_deletes(mut self) -> Self {
+        self.deletes = true;
+        self
+    }
+}
+
+impl<DB, C> Plugin for SqlxWriteBackPlugin<DB, C>
+where
+    DB: Database + Sync,
+    C: SqlxComponent<DB::Row> + ToRow,
+    C::Column: Into<SqlxValue>,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    fn build(&self, app: &mut App) {
+        if !SqlxSyncMode::of::<DB, C>(app.world()).writes() {
+            return;
+        }
+        app.observe(upsert_inserted::<DB, C>);
+        if self.deletes {
+            app.observe(delete_removed::<DB, C>);
+        }
+    }
+}
+
+/// A [`Resource`] present while [`SqlxTasks::handle_tasks`] applies the
+/// components it synced, so they aren't written back
+#[derive(Resource)]
+pub(crate) struct SqlxApplyingSync<C>(PhantomData<fn() -> C>);
+
+impl<C> Default for SqlxApplyingSync<C> {
+    fn default() -> Self {
+        SqlxApplyingSync(PhantomData)
+    }
+}
+
+fn upsert_inserted<DB, C>(
+    trigger: Trigger<OnInsert, C>,
+    syncing: Option<Res<SqlxApplyingSync<C>>>,
+    components: Query<&C, Without<SqlxSkipSync>>,
+    mut events: EventWriter<SqlxEvent<DB, C>>,
+) where
+    DB: Database + Sync,
+    C: SqlxComponent<DB::Row> + ToRow,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    if syncing.is_some() {
+        return;
+    }
+    if let Ok(component) = components.get(trigger.entity()) {
+        events.send(SqlxEvent::upsert(component));
+    }
+}
+
+fn delete_removed<DB, C>(
+    trigger: Trigger<OnRemove, C>,
+    components: Query<&C, Without<SqlxSkipSync>>,
+    mut events: EventWriter<SqlxEvent<DB, C>>,
+) where
+    DB: Database + Sync,
+    C: SqlxComponent<DB::Row> + ToRow,
+    C::Column: Into<SqlxValue>,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    if let Ok(component) = components.get(trigger.entity()) {
+        events.send(SqlxEvent::delete(component));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bevy::prelude::*;
+    use bevy::tasks::{block_on, AsyncComputeTaskPool, TaskPool};
+    use sqlx::{FromRow, Pool, Sqlite};
+
+    #[derive(Component, FromRow, Debug)]
+    struct Foo {
+        id: u32,
+        text: String,
+    }
+
+    impl PrimaryKey for Foo {
+        type Column = u32;
+        fn primary_key(&self) -> Self::Column {
+            self.id
+        }
+    }
+
+    impl ToRow for Foo {
+        fn table() -> &'static str {
+            "write_backs"
+        }
+
+        fn to_row(&self) -> Vec<(&'static str, SqlxValue)> {
+            vec![("id", self.id.into()), ("text", self.text.clone().into())]
+        }
+    }
+
+    fn text(pool: &Pool<Sqlite>, id: u32) -> Option<String> {
+        let sql = "SELECT text FROM write_backs WHERE id = ?";
+        let query = sqlx::query_scalar(sql).bind(id);
+        block_on(query.fetch_optional(pool)).unwrap()
+    }
+
+    fn update_until(app: &mut App, mut done: impl FnMut(&mut App) -> bool) {
+        let mut tries = 0;
+        while !done(app) && tries < 1000 {
+            app.update();
+            tries += 1;
+        }
+    }
+
+    #[test]
+    fn test_write_back() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::new);
+        let url = "sqlite:db/sqlite.db";
+        let pool: Pool<Sqlite> = block_on(Pool::connect(url)).unwrap();
+        block_on(async {
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS write_backs \
+                 (id INTEGER PRIMARY KEY, text TEXT NOT NULL)",
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+            sqlx::query("DELETE FROM write_backs")
+                .execute(&pool)
+                .await
+                .unwrap();
+        });
+
+        let mut app = App::new();
+        app.add_plugins(SqlxPlugin::<Sqlite, Foo>::from_url(url));
+        app.add_plugins(
+            SqlxWriteBackPlugin::<Sqlite, Foo>::default().with_deletes(),
+        );
+
+        let entity = app.world_mut().spawn(Foo { id: 1, text: "new".into() });
+        let entity = entity.id();
+        update_until(&mut app, |_| text(&pool, 1).is_some());
+        assert_eq!(Some("new".into()), text(&pool, 1));
+
+        let foo = Foo { id: 1, text: "replaced".into() };
+        app.world_mut().entity_mut(entity).insert(foo);
+        update_until(&mut app, |_| text(&pool, 1).as_deref() != Some("new"));
+        assert_eq!(Some("replaced".into()), text(&pool, 1));
+
+        // Neither ghosts nor synced rows are written back, and synced rows
+        // are spawned apart from ghosts with the same primary key.
+        let ghost = (Foo { id: 2, text: "ghost".into() }, SqlxSkipSync);
+        let ghost = app.world_mut().spawn(ghost).id();
+        let sql = "SELECT 2 AS id, 'synced' AS text";
+        let event = SqlxEvent::<Sqlite, Foo>::query_sync(sql);
+        app.world_mut().send_event(event);
+        update_until(&mut app, |app| {
+            let mut foos = app.world_mut().query::<&Foo>();
+            foos.iter(app.world()).len() == 3
+        });
+        for _ in 0..10 {
+            app.update();
+        }
+        assert_eq!(None, text(&pool, 2));
+        let foo = app.world().get::<Foo>(ghost).unwrap();
+        assert_eq!("ghost", foo.text);
+        app.world_mut().despawn(ghost);
+
+        app.world_mut().despawn(entity);
+        update_until(&mut app, |_| text(&pool, 1).is_none());
+        assert_eq!(None, text(&pool, 1));
+    }
+}
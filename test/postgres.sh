@@ -1,3 +1,3 @@
 DATABASE_URL=postgres://localhost/bevy_sqlx cargo sqlx database setup
-cargo build --examples --features sqlx/postgres,bevy/bevy_winit,bevy/wayland
-cargo test --features sqlx/postgres
+cargo build --examples --features postgres,bevy/bevy_winit,bevy/wayland
+cargo test --features postgres
@@ -1,5 +1,5 @@
 #!/bin/sh
 cargo sqlx database setup
 cargo build &&
-cargo build --examples --features sqlx/sqlite,bevy/bevy_winit,bevy/wayland &&
-cargo test --features sqlx/sqlite
+cargo build --examples --features sqlite,bevy/bevy_winit,bevy/wayland &&
+cargo test --features sqlite